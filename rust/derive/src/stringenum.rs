@@ -92,6 +92,9 @@ pub fn derive_enum_string<T: std::str::FromStr + quote::ToTokens>(input: TokenSt
                     _ => None
                 }
             }
+            fn values() -> Vec<(&'static str, #utype_str)> {
+                vec![ #( (#names, #values) ,)* ]
+            }
         }
     };
 
@@ -136,6 +136,10 @@ pub struct AppLayerTxData {
     /// not using application layer keywords
     pub guessed_applayer_logged: u8,
 
+    /// Time in seconds the transaction was first seen, set by the
+    /// app-layer transaction housekeeping. 0 until then.
+    pub first_seen: u64,
+
     /// detection engine progress tracking for use by detection engine
     /// Reflects the "progress" of prefilter engines into this TX, where
     /// the value is offset by 1. So if for progress state 0 the engines
@@ -193,6 +197,7 @@ impl AppLayerTxData {
             file_flags: 0,
             file_tx: 0,
             guessed_applayer_logged: 0,
+            first_seen: 0,
             updated_tc: true,
             updated_ts: true,
             flags: 0,
@@ -220,6 +225,7 @@ impl AppLayerTxData {
             file_flags: 0,
             file_tx: 0,
             guessed_applayer_logged: 0,
+            first_seen: 0,
             updated_tc,
             updated_ts,
             detect_progress_ts: 0,
@@ -535,6 +541,7 @@ extern "C" {
     pub fn AppLayerProtoDetectConfProtoDetectionEnabled(ipproto: *const c_char, proto: *const c_char) -> c_int;
     pub fn AppLayerProtoDetectConfProtoDetectionEnabledDefault(ipproto: *const c_char, proto: *const c_char, default: bool) -> c_int;
     pub fn AppLayerRequestProtocolTLSUpgrade(flow: *const Flow) -> bool;
+    pub fn AppLayerSetTLSUpgrade(flow: *const Flow, flags: u8);
 }
 
 // Defined in app-layer-parser.h
@@ -561,8 +568,14 @@ extern "C" {
     pub fn AppLayerParserConfParserEnabled(ipproto: *const c_char, proto: *const c_char) -> c_int;
     pub fn AppLayerParserRegisterLogger(pproto: u8, alproto: AppProto);
     pub fn AppLayerParserRegisterParserAcceptableDataDirection(ipproto: u8, alproto: AppProto, dir: u8);
+    pub fn SCAppLayerParserRegisterFlowLogFunc(
+        ipproto: u8, alproto: AppProto, log_flow: Option<LogFlowFn>,
+    );
 }
 
+/// Log the state of a flow in its flow record
+pub type LogFlowFn = unsafe extern "C" fn(state: *mut c_void, jb: *mut c_void) -> bool;
+
 #[repr(C)]
 pub struct AppLayerGetTxIterTuple {
     tx_ptr: *mut std::os::raw::c_void,
@@ -0,0 +1,190 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! Per protocol counters of the app-layer parsers.
+//!
+//! A parser declares a static [`AppLayerParserStats`], registers it with
+//! [`applayer_stats_register`] when the parser gets registered, and updates
+//! it with the `applayer_stats_*` macros. The counters are only meant for
+//! monitoring, through the `app-layer-parser-stats` unix socket command, so
+//! they are updated with relaxed atomics.
+
+use crate::jsonbuilder::{JsonBuilder, JsonError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Increment a counter of an [`AppLayerParserStats`].
+#[macro_export]
+macro_rules! applayer_stats_incr {
+    ($stats:expr, $counter:ident) => {
+        $crate::applayer_stats_add!($stats, $counter, 1)
+    };
+}
+
+/// Add a value to a counter of an [`AppLayerParserStats`].
+#[macro_export]
+macro_rules! applayer_stats_add {
+    ($stats:expr, $counter:ident, $value:expr) => {{
+        $stats
+            .$counter
+            .fetch_add($value as u64, std::sync::atomic::Ordering::Relaxed);
+    }};
+}
+
+/// Subtract a value from a counter of an [`AppLayerParserStats`], like the
+/// live states when one is freed.
+#[macro_export]
+macro_rules! applayer_stats_sub {
+    ($stats:expr, $counter:ident, $value:expr) => {{
+        $stats
+            .$counter
+            .fetch_sub($value as u64, std::sync::atomic::Ordering::Relaxed);
+    }};
+}
+
+/// Account for a transaction freed by the engine, or with the parser state
+/// at the end of the flow.
+#[macro_export]
+macro_rules! applayer_stats_tx_freed {
+    ($stats:expr, $completed:expr) => {{
+        $crate::applayer_stats_incr!($stats, tx_freed);
+        if $completed {
+            $crate::applayer_stats_incr!($stats, tx_completed);
+        }
+    }};
+}
+
+/// Count the parse error a parser returns to the engine, if any, and pass
+/// the result on.
+#[macro_export]
+macro_rules! applayer_stats_result {
+    ($stats:expr, $result:expr) => {{
+        let result: $crate::applayer::AppLayerResult = $result;
+        if result.status < 0 {
+            $crate::applayer_stats_incr!($stats, parse_errors);
+        }
+        result
+    }};
+}
+
+pub struct AppLayerParserStats {
+    name: &'static str,
+    /// Transactions created by the parser.
+    pub tx_created: AtomicU64,
+    /// Transactions which were complete when freed. The other freed
+    /// transactions were cut short, by the end of the flow or a max-tx
+    /// limit.
+    pub tx_completed: AtomicU64,
+    /// Transactions freed, by the engine or with the parser state.
+    pub tx_freed: AtomicU64,
+    /// Parse errors returned to the engine. Parsers not returning errors
+    /// for malformed messages, like the udp ones, do not count them.
+    pub parse_errors: AtomicU64,
+    /// App-layer events set.
+    pub events: AtomicU64,
+    /// Parser states currently allocated, so flows of the protocol with a
+    /// parser state. The memory the states use is not tracked.
+    pub states_live: AtomicU64,
+}
+
+impl AppLayerParserStats {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            tx_created: AtomicU64::new(0),
+            tx_completed: AtomicU64::new(0),
+            tx_freed: AtomicU64::new(0),
+            parse_errors: AtomicU64::new(0),
+            events: AtomicU64::new(0),
+            states_live: AtomicU64::new(0),
+        }
+    }
+
+    fn log(&self, js: &mut JsonBuilder) -> Result<(), JsonError> {
+        js.open_object(self.name)?;
+        js.set_uint("tx_created", self.tx_created.load(Ordering::Relaxed))?;
+        js.set_uint("tx_completed", self.tx_completed.load(Ordering::Relaxed))?;
+        js.set_uint("tx_freed", self.tx_freed.load(Ordering::Relaxed))?;
+        js.set_uint("parse_errors", self.parse_errors.load(Ordering::Relaxed))?;
+        js.set_uint("events", self.events.load(Ordering::Relaxed))?;
+        js.set_uint("states_live", self.states_live.load(Ordering::Relaxed))?;
+        js.close()?;
+        Ok(())
+    }
+}
+
+static PARSER_STATS: Mutex<Vec<&'static AppLayerParserStats>> = Mutex::new(Vec::new());
+
+/// Make the counters of a parser available to the unix socket.
+///
+/// Registering the same counters twice, as the udp and tcp parsers of a
+/// protocol may do, is harmless.
+pub fn applayer_stats_register(stats: &'static AppLayerParserStats) {
+    if let Ok(mut registered) = PARSER_STATS.lock() {
+        if !registered.iter().any(|s| std::ptr::eq(*s, stats)) {
+            registered.push(stats);
+        }
+    }
+}
+
+fn applayer_stats_log(js: &mut JsonBuilder) -> Result<(), JsonError> {
+    if let Ok(registered) = PARSER_STATS.lock() {
+        for stats in registered.iter() {
+            stats.log(js)?;
+        }
+    }
+    Ok(())
+}
+
+/// Add the counters of the registered parsers to an open object, one
+/// object per protocol.
+#[no_mangle]
+pub extern "C" fn SCAppLayerParserStatsLog(js: &mut JsonBuilder) -> bool {
+    applayer_stats_log(js).is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::jsonbuilder::{SCJbLen, SCJbPtr};
+
+    static TEST_STATS: AppLayerParserStats = AppLayerParserStats::new("test");
+
+    #[test]
+    fn test_applayer_stats_log() {
+        applayer_stats_register(&TEST_STATS);
+        applayer_stats_register(&TEST_STATS);
+        applayer_stats_incr!(TEST_STATS, tx_created);
+        applayer_stats_incr!(TEST_STATS, tx_created);
+        applayer_stats_tx_freed!(TEST_STATS, true);
+        applayer_stats_tx_freed!(TEST_STATS, false);
+        applayer_stats_incr!(TEST_STATS, states_live);
+        applayer_stats_incr!(TEST_STATS, states_live);
+        applayer_stats_sub!(TEST_STATS, states_live, 1);
+
+        let mut js = JsonBuilder::try_new_object().unwrap();
+        applayer_stats_log(&mut js).unwrap();
+        js.close().unwrap();
+        // other parsers may have registered their counters in other tests
+        let buf = unsafe { std::slice::from_raw_parts(SCJbPtr(&mut js), SCJbLen(&js)) };
+        let out = std::str::from_utf8(buf).unwrap();
+        assert!(out.contains(
+            r#""test":{"tx_created":2,"tx_completed":1,"tx_freed":2,"parse_errors":0,"events":0,"states_live":1}"#
+        ));
+        assert_eq!(out.matches(r#""test":"#).count(), 1);
+    }
+}
@@ -21,9 +21,11 @@ use crate::applayer::{self, *};
 use crate::bittorrent_dht::parser::{
     parse_bittorrent_dht_packet, BitTorrentDHTError, BitTorrentDHTRequest, BitTorrentDHTResponse,
 };
+use crate::conf::conf_get;
 use crate::core::{ALPROTO_UNKNOWN, IPPROTO_UDP};
 use crate::direction::Direction;
 use crate::flow::Flow;
+use std::collections::VecDeque;
 use std::ffi::CString;
 use std::os::raw::c_char;
 
@@ -31,6 +33,13 @@ const BITTORRENT_DHT_PAYLOAD_PREFIX: &[u8] = b"d1:ad2:id20:\0";
 
 static mut ALPROTO_BITTORRENT_DHT: AppProto = ALPROTO_UNKNOWN;
 
+/// Maximum number of requests kept around for pairing with responses.
+static mut BITTORRENT_DHT_MAX_OUTSTANDING: usize = 64;
+
+/// Seconds after which an unanswered request is no longer paired with a
+/// response, 0 to keep it until it is evicted by newer requests.
+static mut BITTORRENT_DHT_REQUEST_TIMEOUT: u64 = 60;
+
 #[derive(AppLayerEvent, Debug, PartialEq, Eq)]
 pub enum BitTorrentDHTEvent {
     MalformedPacket,
@@ -63,10 +72,38 @@ impl BitTorrentDHTTransaction {
     }
 }
 
+/// A request that has not seen a response or error yet.
+struct OutstandingRequest {
+    tx_id: u64,
+    direction: Direction,
+    transaction_id: Vec<u8>,
+    request_type: String,
+    /// time of the request, in seconds
+    ts: u64,
+}
+
+/// Check whether a response could plausibly answer a query of the given
+/// type. Transaction ids are short and get reused quickly, so the id alone
+/// is not enough to pick the right request.
+fn response_matches_request(request_type: &str, response: &BitTorrentDHTResponse) -> bool {
+    let has_nodes = response.nodes.is_some() || response.nodes6.is_some();
+    let has_peers = response.token.is_some() || response.values.is_some();
+    match request_type {
+        "ping" | "announce_peer" => !has_nodes && !has_peers,
+        "find_node" => has_nodes && !has_peers,
+        "get_peers" => has_nodes || has_peers,
+        // unknown or extension queries: rely on the transaction id
+        _ => true,
+    }
+}
+
 #[derive(Default)]
 pub struct BitTorrentDHTState {
     tx_id: u64,
     transactions: Vec<BitTorrentDHTTransaction>,
+    outstanding: VecDeque<OutstandingRequest>,
+    /// time of the flow, in seconds
+    ts: u64,
     state_data: AppLayerStateData,
 }
 
@@ -91,6 +128,22 @@ impl BitTorrentDHTState {
         return tx;
     }
 
+    /// Update the time of the flow and drop the requests that have been
+    /// waiting for a response for more than the configured timeout.
+    fn update_ts(&mut self, ts: u64) {
+        self.ts = ts;
+        self.expire_requests(unsafe { BITTORRENT_DHT_REQUEST_TIMEOUT });
+    }
+
+    fn expire_requests(&mut self, timeout: u64) {
+        if timeout == 0 {
+            return;
+        }
+        let ts = self.ts;
+        self.outstanding
+            .retain(|req| ts.saturating_sub(req.ts) <= timeout);
+    }
+
     fn is_dht(input: &[u8]) -> bool {
         if input.len() > 5 {
             match &input[0..5] {
@@ -115,11 +168,53 @@ impl BitTorrentDHTState {
             SCLogDebug!("BitTorrent DHT Parsing Error: {}", _e);
         }
 
+        if status {
+            self.pair_request(&mut tx, _direction);
+        }
         self.transactions.push(tx);
 
         return status;
     }
 
+    /// Track a new request, or pair a response or error with the most
+    /// recent matching request sent in the opposite direction.
+    fn pair_request(&mut self, tx: &mut BitTorrentDHTTransaction, direction: Direction) {
+        if let Some(request_type) = &tx.request_type {
+            if self.outstanding.len() >= unsafe { BITTORRENT_DHT_MAX_OUTSTANDING } {
+                self.outstanding.pop_front();
+            }
+            if unsafe { BITTORRENT_DHT_MAX_OUTSTANDING } > 0 {
+                self.outstanding.push_back(OutstandingRequest {
+                    tx_id: tx.tx_id,
+                    direction,
+                    transaction_id: tx.transaction_id.clone(),
+                    request_type: request_type.clone(),
+                    ts: self.ts,
+                });
+            }
+            return;
+        }
+
+        let found = self.outstanding.iter().rposition(|req| {
+            req.direction != direction
+                && req.transaction_id == tx.transaction_id
+                && tx
+                    .response
+                    .as_ref()
+                    .map_or(true, |r| response_matches_request(&req.request_type, r))
+        });
+        if let Some(index) = found {
+            if let Some(req) = self.outstanding.remove(index) {
+                SCLogDebug!(
+                    "response tx {} paired with request tx {}",
+                    tx.tx_id,
+                    req.tx_id
+                );
+                tx.request_type = Some(req.request_type);
+            }
+        }
+    }
+
     fn tx_iterator(
         &mut self, min_tx_id: u64, state: &mut u64,
     ) -> Option<(&BitTorrentDHTTransaction, u64, bool)> {
@@ -188,6 +283,7 @@ unsafe extern "C" fn parse(
     direction: Direction,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, BitTorrentDHTState);
+    state.update_ts(cast_pointer!(_flow, Flow).get_last_time().as_secs());
     let buf = stream_slice.as_slice();
     state.parse(buf, direction).into()
 }
@@ -290,6 +386,20 @@ pub unsafe extern "C" fn SCRegisterBittorrentDhtUdpParser() {
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
         }
+        if let Some(val) = conf_get("app-layer.protocols.bittorrent-dht.max-outstanding-requests") {
+            if let Ok(v) = val.parse::<usize>() {
+                BITTORRENT_DHT_MAX_OUTSTANDING = v;
+            } else {
+                SCLogError!("Invalid value for bittorrent-dht.max-outstanding-requests");
+            }
+        }
+        if let Some(val) = conf_get("app-layer.protocols.bittorrent-dht.request-timeout") {
+            if let Ok(v) = val.parse::<u64>() {
+                BITTORRENT_DHT_REQUEST_TIMEOUT = v;
+            } else {
+                SCLogError!("Invalid value for bittorrent-dht.request-timeout");
+            }
+        }
 
         if AppLayerProtoDetectPMRegisterPatternCS(
             IPPROTO_UDP,
@@ -320,3 +430,97 @@ pub unsafe extern "C" fn SCRegisterBittorrentDhtUdpParser() {
         SCLogDebug!("Protocol detector and parser disabled for bittorrent-dht.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PING_REQUEST: &[u8] = b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe";
+    const GET_PEERS_REQUEST: &[u8] = b"d1:ad2:id20:abcdefghij01234567899:info_hash20:mnopqrstuvwxyz123456e1:q9:get_peers1:t2:aa1:y1:qe";
+    const PING_RESPONSE: &[u8] = b"d1:rd2:id20:mnopqrstuvwxyz123456e1:t2:aa1:y1:re";
+    const GET_PEERS_RESPONSE: &[u8] = b"d1:rd2:id20:abcdefghij01234567895:token8:aoeusnth6:valuesl6:axje.u6:idhtnmee1:t2:aa1:y1:re";
+
+    fn request_type(state: &mut BitTorrentDHTState, tx_id: u64) -> Option<String> {
+        state.get_tx(tx_id).unwrap().request_type.clone()
+    }
+
+    #[test]
+    fn test_pair_reused_transaction_id() {
+        let mut state = BitTorrentDHTState::new();
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        assert!(state.parse(GET_PEERS_REQUEST, Direction::ToServer));
+        // get_peers answered first: must not be paired with the older ping
+        assert!(state.parse(GET_PEERS_RESPONSE, Direction::ToClient));
+        assert!(state.parse(PING_RESPONSE, Direction::ToClient));
+
+        assert_eq!(request_type(&mut state, 2), Some("get_peers".to_string()));
+        assert_eq!(request_type(&mut state, 3), Some("ping".to_string()));
+        assert!(state.outstanding.is_empty());
+    }
+
+    #[test]
+    fn test_pair_most_recent_request() {
+        let mut state = BitTorrentDHTState::new();
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        assert!(state.parse(PING_RESPONSE, Direction::ToClient));
+
+        assert_eq!(request_type(&mut state, 2), Some("ping".to_string()));
+        assert_eq!(state.outstanding.len(), 1);
+        assert_eq!(state.outstanding[0].tx_id, 1);
+    }
+
+    #[test]
+    fn test_pair_ignores_same_direction() {
+        let mut state = BitTorrentDHTState::new();
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        assert!(state.parse(PING_RESPONSE, Direction::ToServer));
+
+        assert_eq!(request_type(&mut state, 1), None);
+        assert_eq!(state.outstanding.len(), 1);
+    }
+
+    #[test]
+    fn test_outstanding_requests_expire() {
+        let mut state = BitTorrentDHTState::new();
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        for _ in 0..unsafe { BITTORRENT_DHT_MAX_OUTSTANDING } {
+            assert!(state.parse(GET_PEERS_REQUEST, Direction::ToServer));
+        }
+        // the ping request was evicted, so the response stays unpaired
+        assert!(state.parse(PING_RESPONSE, Direction::ToClient));
+
+        let last = state.tx_id - 1;
+        assert_eq!(request_type(&mut state, last), None);
+        assert_eq!(state.outstanding.len(), unsafe {
+            BITTORRENT_DHT_MAX_OUTSTANDING
+        });
+    }
+
+    #[test]
+    fn test_outstanding_requests_time_out() {
+        let mut state = BitTorrentDHTState::new();
+        state.ts = 100;
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        state.ts = 130;
+        assert!(state.parse(GET_PEERS_REQUEST, Direction::ToServer));
+
+        // the ping request waited for more than the timeout
+        state.ts = 161;
+        state.expire_requests(60);
+        assert_eq!(state.outstanding.len(), 1);
+        assert_eq!(state.outstanding[0].tx_id, 2);
+        assert!(state.parse(PING_RESPONSE, Direction::ToClient));
+        assert_eq!(request_type(&mut state, 2), None);
+
+        // still in time for the get_peers request
+        assert!(state.parse(GET_PEERS_RESPONSE, Direction::ToClient));
+        assert_eq!(request_type(&mut state, 3), Some("get_peers".to_string()));
+
+        // no timeout
+        assert!(state.parse(PING_REQUEST, Direction::ToServer));
+        state.ts = 10000;
+        state.expire_requests(0);
+        assert_eq!(state.outstanding.len(), 1);
+    }
+}
@@ -168,7 +168,7 @@ pub fn get_resp_type_for_req(t: u8) -> u8 {
 
 pub fn get_req_type_for_resp(t: u8) -> u8 {
     match t {
-        DCERPC_TYPE_RESPONSE => DCERPC_TYPE_REQUEST,
+        DCERPC_TYPE_RESPONSE | DCERPC_TYPE_FAULT => DCERPC_TYPE_REQUEST,
         DCERPC_TYPE_BINDACK => DCERPC_TYPE_BIND,
         DCERPC_TYPE_ALTER_CONTEXT_RESP => DCERPC_TYPE_ALTER_CONTEXT,
         _ => DCERPC_TYPE_UNKNOWN,
@@ -250,6 +250,8 @@ pub struct DCERPCUuidEntry {
     pub ctxid: u16,
     pub internal_id: u16,
     pub result: u16,
+    pub ack_reason: u16,
+    pub acked: bool,
     pub uuid: Vec<u8>,
     pub version: u16,
     pub versionminor: u16,
@@ -488,7 +490,10 @@ impl DCERPCState {
                             continue;
                         }
                         let resp_cmd = get_resp_type_for_req(cmd);
-                        if resp_cmd != tx.resp_cmd {
+                        // a request may also come after the fault answering it
+                        if resp_cmd != tx.resp_cmd
+                            && !(cmd == DCERPC_TYPE_REQUEST && tx.resp_cmd == DCERPC_TYPE_FAULT)
+                        {
                             continue;
                         }
                     }
@@ -643,6 +648,14 @@ impl DCERPCState {
         match parser::parse_dcerpc_bind(input) {
             Ok((leftover_bytes, header)) => {
                 let numctxitems = header.numctxitems;
+                // An alter_context adds or redefines contexts, the others
+                // stay in use
+                let mut contexts = Vec::new();
+                if self.get_hdr_type() == Some(DCERPC_TYPE_ALTER_CONTEXT) {
+                    if let Some(bind) = self.bind.take() {
+                        contexts = bind.uuid_list;
+                    }
+                }
                 self.bind = Some(header);
                 for i in 0..numctxitems {
                     retval = self.handle_bindctxitem(&input[idx as usize..], i as u16);
@@ -651,6 +664,11 @@ impl DCERPCState {
                     }
                     idx += retval;
                 }
+                if let Some(ref mut bind) = self.bind {
+                    contexts.retain(|u| !bind.uuid_list.iter().any(|n| n.ctxid == u.ctxid));
+                    contexts.append(&mut bind.uuid_list);
+                    bind.uuid_list = contexts;
+                }
                 let call_id = self.get_hdr_call_id().unwrap_or(0);
                 let mut tx = self.create_tx(call_id);
                 tx.req_cmd = self.get_hdr_type().unwrap_or(0);
@@ -681,13 +699,19 @@ impl DCERPCState {
         match parser::parse_dcerpc_bindack(input) {
             Ok((leftover_bytes, mut back)) => {
                 if let Some(ref mut bind) = self.bind {
+                    // results are in the same order as the context items of the
+                    // bind or alter_context, which come last in the list
                     for (uuid_internal_id, r) in back.ctxitems.iter().enumerate() {
-                        for uuid in bind.uuid_list.iter_mut() {
-                            if uuid.internal_id == uuid_internal_id as u16 {
-                                uuid.result = r.ack_result;
-                                if uuid.result != 0 {
-                                    break;
-                                }
+                        if let Some(uuid) = bind
+                            .uuid_list
+                            .iter_mut()
+                            .rev()
+                            .find(|u| u.internal_id == uuid_internal_id as u16)
+                        {
+                            uuid.result = r.ack_result;
+                            uuid.ack_reason = r.ack_reason;
+                            uuid.acked = true;
+                            if uuid.result == 0 {
                                 back.accepted_uuid_list.push(uuid.clone());
                                 SCLogDebug!("DCERPC BINDACK accepted UUID: {:?}", uuid);
                             }
@@ -983,6 +1007,23 @@ impl DCERPCState {
                         return AppLayerResult::err();
                     }
                 }
+                DCERPC_TYPE_FAULT => {
+                    // the fault ends the call, in place of the response
+                    let tx = if let Some(tx) = self.get_tx_by_call_id(current_call_id, Direction::ToClient) {
+                        tx.resp_cmd = x;
+                        tx
+                    } else {
+                        let mut tx = self.create_tx(current_call_id);
+                        tx.resp_cmd = x;
+                        self.transactions.push_back(tx);
+                        self.transactions.back_mut().unwrap()
+                    };
+                    tx.resp_done = true;
+                    tx.frag_cnt_tc = 1;
+                    if let Some(flow) = self.flow {
+                        sc_app_layer_parser_trigger_raw_stream_reassembly(flow, Direction::ToClient as i32);
+                    }
+                }
                 _ => {
                     SCLogDebug!("Unrecognized packet type: {:?}", x);
                     return AppLayerResult::err();
@@ -1273,7 +1314,9 @@ pub unsafe extern "C" fn SCRegisterDcerpcParser() {
 mod tests {
     use crate::applayer::{AppLayerResult, StreamSlice};
     use crate::core::*;
-    use crate::dcerpc::dcerpc::DCERPCState;
+    use crate::dcerpc::dcerpc::{
+        DCERPCState, DCERPC_HDR_LEN, DCERPC_TYPE_FAULT, DCERPC_TYPE_REQUEST, DCERPC_TYPE_RESPONSE,
+    };
     use crate::direction::Direction;
     use std::cmp;
 
@@ -2173,5 +2216,119 @@ mod tests {
             assert_eq!(1, back.accepted_uuid_list[0].ctxid);
             assert_eq!(expected_uuid2, back.accepted_uuid_list[0].uuid);
         }
+        // the context of the bind is still in use after the alter_context
+        let bind = dcerpc_state.bind.as_ref().unwrap();
+        assert_eq!(2, bind.uuid_list.len());
+        for (uuidentry, (ctxid, uuid)) in bind
+            .uuid_list
+            .iter()
+            .zip([(0, &expected_uuid1), (1, &expected_uuid2)])
+        {
+            assert_eq!(ctxid, uuidentry.ctxid);
+            assert_eq!(uuid, &uuidentry.uuid);
+            assert!(uuidentry.acked);
+            assert_eq!(0, uuidentry.result);
+        }
+    }
+
+    /// Builds a little endian connection oriented PDU.
+    fn build_pdu(hdrtype: u8, call_id: u32, body: &[u8]) -> Vec<u8> {
+        let frag_len = (DCERPC_HDR_LEN as usize + body.len()) as u16;
+        let mut pdu = vec![0x05, 0x00, hdrtype, 0x03, 0x10, 0x00, 0x00, 0x00];
+        pdu.extend_from_slice(&frag_len.to_le_bytes());
+        pdu.extend_from_slice(&[0x00, 0x00]);
+        pdu.extend_from_slice(&call_id.to_le_bytes());
+        pdu.extend_from_slice(body);
+        pdu
+    }
+
+    fn build_request(call_id: u32, opnum: u16, stub: &[u8]) -> Vec<u8> {
+        let mut body = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        body.extend_from_slice(&opnum.to_le_bytes());
+        body.extend_from_slice(stub);
+        build_pdu(DCERPC_TYPE_REQUEST, call_id, &body)
+    }
+
+    fn build_response(call_id: u32, stub: &[u8]) -> Vec<u8> {
+        let mut body = vec![0x00; 8];
+        body.extend_from_slice(stub);
+        build_pdu(DCERPC_TYPE_RESPONSE, call_id, &body)
+    }
+
+    fn parse_pdu(state: &mut DCERPCState, pdu: &[u8], dir: Direction) {
+        let flags = if dir == Direction::ToServer {
+            STREAM_TOSERVER
+        } else {
+            STREAM_TOCLIENT
+        };
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.handle_input_data(StreamSlice::from_slice(pdu, flags, 0), dir)
+        );
+    }
+
+    #[test]
+    pub fn test_out_of_order_responses() {
+        let mut state = DCERPCState::new();
+        parse_pdu(&mut state, &build_request(2, 5, b"req-two"), Direction::ToServer);
+        parse_pdu(&mut state, &build_request(3, 6, b"req-three"), Direction::ToServer);
+        // pipelined calls may be answered in any order
+        parse_pdu(&mut state, &build_response(3, b"resp-three"), Direction::ToClient);
+        parse_pdu(&mut state, &build_response(2, b"resp-two"), Direction::ToClient);
+
+        assert_eq!(2, state.transactions.len());
+        for (call_id, opnum, req, resp) in [
+            (2, 5, &b"req-two"[..], &b"resp-two"[..]),
+            (3, 6, &b"req-three"[..], &b"resp-three"[..]),
+        ] {
+            let tx = state
+                .transactions
+                .iter()
+                .find(|tx| tx.call_id == call_id)
+                .unwrap();
+            assert_eq!(opnum, tx.opnum);
+            assert!(tx.req_done && tx.resp_done);
+            assert!(tx.stub_data_buffer_ts.ends_with(req));
+            assert!(tx.stub_data_buffer_tc.ends_with(resp));
+        }
+    }
+
+    #[test]
+    pub fn test_fault_response() {
+        // nca_s_fault_access_denied
+        let fault_body: &[u8] = &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let mut state = DCERPCState::new();
+        parse_pdu(&mut state, &build_request(4, 7, b"req-four"), Direction::ToServer);
+        parse_pdu(&mut state, &build_request(5, 8, b"req-five"), Direction::ToServer);
+        parse_pdu(
+            &mut state,
+            &build_pdu(DCERPC_TYPE_FAULT, 5, fault_body),
+            Direction::ToClient,
+        );
+        parse_pdu(&mut state, &build_response(4, b"resp-four"), Direction::ToClient);
+
+        assert_eq!(2, state.transactions.len());
+        let tx = &state.transactions[1];
+        assert_eq!(5, tx.call_id);
+        assert_eq!(DCERPC_TYPE_FAULT, tx.resp_cmd);
+        assert!(tx.resp_done);
+        let tx = &state.transactions[0];
+        assert_eq!(4, tx.call_id);
+        assert_eq!(DCERPC_TYPE_RESPONSE, tx.resp_cmd);
+
+        // a fault seen before its request completes the same transaction
+        parse_pdu(
+            &mut state,
+            &build_pdu(DCERPC_TYPE_FAULT, 6, fault_body),
+            Direction::ToClient,
+        );
+        parse_pdu(&mut state, &build_request(6, 9, b"req-six"), Direction::ToServer);
+        assert_eq!(3, state.transactions.len());
+        let tx = &state.transactions[2];
+        assert_eq!(9, tx.opnum);
+        assert!(tx.req_done && tx.resp_done);
     }
 }
@@ -62,31 +62,20 @@ pub struct DCEOpnumData {
 fn match_backuuid(
     tx: &DCERPCTransaction, state: &mut DCERPCState, if_data: &mut DCEIfaceData,
 ) -> u8 {
-    let mut ret = 0;
-    if let Some(ref bindack) = state.bindack {
-        for uuidentry in bindack.accepted_uuid_list.iter() {
-            ret = 1;
+    if let Some(ref bind) = state.bind {
+        let ctxid = tx.get_req_ctxid();
+        for uuidentry in bind.uuid_list.iter() {
             // if any_frag is not enabled, we need to match only against the first fragment
             if if_data.any_frag == 0 && (uuidentry.flags & DCERPC_UUID_ENTRY_FLAG_FF == 0) {
                 SCLogDebug!("any frag not enabled");
                 continue;
             }
-            // if the uuid has been rejected(uuidentry->result == 1), we skip to the next uuid
-            if uuidentry.result != 0 {
+            // only match contexts the server accepted in a bind_ack
+            if !uuidentry.acked || uuidentry.result != 0 {
                 SCLogDebug!("Skipping to next UUID");
                 continue;
             }
-
-            for i in 0..16 {
-                if if_data.if_uuid[i] != uuidentry.uuid[i] {
-                    SCLogDebug!("Iface UUID and BINDACK Accepted UUID does not match");
-                    ret = 0;
-                    break;
-                }
-            }
-            let ctxid = tx.get_req_ctxid();
-            ret &= (uuidentry.ctxid == ctxid) as u8;
-            if ret == 0 {
+            if uuidentry.ctxid != ctxid || if_data.if_uuid != uuidentry.uuid {
                 SCLogDebug!("CTX IDs/UUIDs do not match");
                 continue;
             }
@@ -94,17 +83,14 @@ fn match_backuuid(
             if let Some(x) = &if_data.du16 {
                 if !detect_match_uint(x, uuidentry.version) {
                     SCLogDebug!("Interface version did not match");
-                    ret &= 0;
+                    continue;
                 }
             }
-
-            if ret == 1 {
-                return 1;
-            }
+            return 1;
         }
     }
 
-    return ret;
+    return 0;
 }
 
 fn parse_iface_data(arg: &str) -> Result<DCEIfaceData, ()> {
@@ -118,35 +104,23 @@ fn parse_iface_data(arg: &str) -> Result<DCEIfaceData, ()> {
         }
     };
 
-    match split_args.len() {
-        1 => {}
-        2 => match split_args[1] {
-            "any_frag" => {
+    for (i, opt) in split_args.iter().enumerate().skip(1) {
+        match *opt {
+            "any_frag" if any_frag == 0 => {
                 any_frag = 1;
             }
-            _ => {
-                match detect_parse_uint(split_args[1]) {
+            // the version comparison can only come right after the uuid
+            _ if i == 1 => {
+                match detect_parse_uint(opt) {
                     Ok((_, x)) => du16 = Some(x),
                     _ => {
                         return Err(());
                     }
                 };
             }
-        },
-        3 => {
-            match detect_parse_uint(split_args[1]) {
-                Ok((_, x)) => du16 = Some(x),
-                _ => {
-                    return Err(());
-                }
-            };
-            if split_args[2] != "any_frag" {
+            _ => {
                 return Err(());
             }
-            any_frag = 1;
-        }
-        _ => {
-            return Err(());
         }
     }
 
@@ -424,6 +398,86 @@ mod test {
         let arg = "12345678-1234-1234-1234-123456789ABC,>65535,any_frag";
         let iface_data = parse_iface_data(arg);
         assert!(iface_data.is_err());
+
+        let arg = "12345678-1234-1234-1234-123456789ABC,any_frag,any_frag";
+        let iface_data = parse_iface_data(arg);
+        assert!(iface_data.is_err());
+
+        let arg = "12345678-1234-1234-1234-123456789ABC,any_frag,>1";
+        let iface_data = parse_iface_data(arg);
+        assert!(iface_data.is_err());
+    }
+
+    #[test]
+    fn test_match_backuuid_multiple_contexts() {
+        // bind of lsarpc (ctx 0), srvsvc (ctx 1) and svcctl (ctx 2),
+        // only srvsvc is accepted by the bind_ack
+        let bind: &[u8] = &[
+            0x05, 0x00, 0x0b, 0x03, 0x10, 0x00, 0x00, 0x00, 0xa0, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x00, 0xd0, 0x16, 0xd0, 0x16, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x01, 0x00, 0x78, 0x57, 0x34, 0x12, 0x34, 0x12, 0xcd, 0xab, 0xef, 0x00,
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0x00, 0x00, 0x00, 0x00, 0x04, 0x5d, 0x88, 0x8a,
+            0xeb, 0x1c, 0xc9, 0x11, 0x9f, 0xe8, 0x08, 0x00, 0x2b, 0x10, 0x48, 0x60, 0x02, 0x00,
+            0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0xc8, 0x4f, 0x32, 0x4b, 0x70, 0x16, 0xd3, 0x01,
+            0x12, 0x78, 0x5a, 0x47, 0xbf, 0x6e, 0xe1, 0x88, 0x03, 0x00, 0x00, 0x00, 0x04, 0x5d,
+            0x88, 0x8a, 0xeb, 0x1c, 0xc9, 0x11, 0x9f, 0xe8, 0x08, 0x00, 0x2b, 0x10, 0x48, 0x60,
+            0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x81, 0xbb, 0x7a, 0x36, 0x44, 0x98,
+            0xf1, 0x35, 0xad, 0x32, 0x98, 0xf0, 0x38, 0x00, 0x10, 0x03, 0x02, 0x00, 0x00, 0x00,
+            0x04, 0x5d, 0x88, 0x8a, 0xeb, 0x1c, 0xc9, 0x11, 0x9f, 0xe8, 0x08, 0x00, 0x2b, 0x10,
+            0x48, 0x60, 0x02, 0x00, 0x00, 0x00,
+        ];
+        let bindack: &[u8] = &[
+            0x05, 0x00, 0x0c, 0x03, 0x10, 0x00, 0x00, 0x00, 0x74, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x00, 0xb8, 0x10, 0xb8, 0x10, 0xf0, 0x53, 0x00, 0x00, 0x0d, 0x00, 0x5c, 0x70,
+            0x69, 0x70, 0x65, 0x5c, 0x73, 0x72, 0x76, 0x73, 0x76, 0x63, 0x00, 0x00, 0x03, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x04, 0x5d, 0x88, 0x8a, 0xeb, 0x1c, 0xc9, 0x11, 0x9f, 0xe8, 0x08, 0x00,
+            0x2b, 0x10, 0x48, 0x60, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        let lsarpc = "12345778-1234-abcd-ef00-0123456789ab";
+        let srvsvc = "4b324fc8-1670-01d3-1278-5a47bf6ee188";
+        let svcctl = "367abb81-9844-35f1-ad32-98f038001003";
+
+        let mut state = DCERPCState::new();
+        assert_eq!(16, state.process_header(bind));
+        assert_eq!(144, state.process_bind_pdu(&bind[16..]));
+        let mut tx = DCERPCTransaction::new();
+        tx.ctxid = 1;
+
+        // no bind_ack yet: the context is not accepted
+        let mut if_data = parse_iface_data(srvsvc).unwrap();
+        assert_eq!(0, match_backuuid(&tx, &mut state, &mut if_data));
+
+        assert_eq!(16, state.process_header(bindack));
+        assert_eq!(100, state.process_bindack_pdu(&bindack[16..]));
+        let bind = state.bind.as_ref().unwrap();
+        assert_eq!(3, bind.uuid_list.len());
+        for (uuidentry, result) in bind.uuid_list.iter().zip([2, 0, 2]) {
+            assert!(uuidentry.acked);
+            assert_eq!(result, uuidentry.result);
+            assert_eq!(result, uuidentry.ack_reason);
+        }
+        let back = state.bindack.as_ref().unwrap();
+        assert_eq!(1, back.accepted_uuid_list.len());
+        assert_eq!(1, back.accepted_uuid_list[0].ctxid);
+
+        for arg in [srvsvc, format!("{},=3", srvsvc).as_str()] {
+            let mut if_data = parse_iface_data(arg).unwrap();
+            assert_eq!(1, match_backuuid(&tx, &mut state, &mut if_data));
+        }
+        let mut if_data = parse_iface_data(&format!("{},>3", srvsvc)).unwrap();
+        assert_eq!(0, match_backuuid(&tx, &mut state, &mut if_data));
+        // rejected contexts never match, whatever the request context
+        for ctxid in [0, 2] {
+            tx.ctxid = ctxid;
+            for arg in [lsarpc, svcctl] {
+                let mut if_data = parse_iface_data(arg).unwrap();
+                assert_eq!(0, match_backuuid(&tx, &mut state, &mut if_data));
+            }
+        }
     }
 
     #[test]
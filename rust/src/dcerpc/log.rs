@@ -30,7 +30,10 @@ fn log_bind_interfaces(jsb: &mut JsonBuilder, state: &DCERPCState) -> Result<(),
             jsb.set_string("uuid", &ifstr)?;
             let vstr = format!("{}.{}", uuid.version, uuid.versionminor);
             jsb.set_string("version", &vstr)?;
-            jsb.set_uint("ack_result", uuid.result as u64)?;
+            if uuid.acked {
+                jsb.set_uint("ack_result", uuid.result as u64)?;
+                jsb.set_uint("ack_reason", uuid.ack_reason as u64)?;
+            }
             jsb.close()?;
         }
         jsb.close()?;
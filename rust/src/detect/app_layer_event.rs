@@ -0,0 +1,94 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! Implements the app_layer_event.count keyword, matching on the number of
+//! app-layer events set on a transaction, for any app-layer protocol.
+
+use super::uint::{DetectUintData, SCDetectU8Free, SCDetectU8Match, SCDetectU8Parse};
+use super::{
+    DetectHelperBufferRegister, DetectHelperKeywordRegister, SCSigTableAppLiteElmt,
+    SigMatchAppendSMToList,
+};
+use crate::core::{ALPROTO_UNKNOWN, STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::flow::Flow;
+use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+use std::os::raw::{c_int, c_void};
+
+extern "C" {
+    fn SCAppLayerParserGetTxEventCount(f: *const Flow, tx: *mut c_void) -> u8;
+}
+
+static mut G_APP_LAYER_EVENT_COUNT_KW_ID: c_int = 0;
+static mut G_APP_LAYER_EVENT_COUNT_BUFFER_ID: c_int = 0;
+
+unsafe extern "C" fn app_layer_event_count_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    let ctx = SCDetectU8Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_APP_LAYER_EVENT_COUNT_KW_ID,
+        ctx,
+        G_APP_LAYER_EVENT_COUNT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        app_layer_event_count_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn app_layer_event_count_match(
+    _de: *mut c_void, f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    let count = SCAppLayerParserGetTxEventCount(f as *const Flow, tx);
+    return SCDetectU8Match(count, ctx);
+}
+
+unsafe extern "C" fn app_layer_event_count_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    SCDetectU8Free(ctx);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectAppLayerEventCountRegister() {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"app_layer_event.count\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the number of app-layer events set on the transaction\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/app-layer.html#app-layer-event-count\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(app_layer_event_count_match),
+        Setup: app_layer_event_count_setup,
+        Free: Some(app_layer_event_count_free),
+        flags: 0,
+    };
+    G_APP_LAYER_EVENT_COUNT_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_APP_LAYER_EVENT_COUNT_BUFFER_ID = DetectHelperBufferRegister(
+        b"app_layer_event.count\0".as_ptr() as *const libc::c_char,
+        ALPROTO_UNKNOWN,
+        STREAM_TOSERVER | STREAM_TOCLIENT,
+    );
+}
@@ -0,0 +1,179 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! Implements the app_tx.ordinal and app_tx.flow_offset keywords, matching
+//! on the position of a transaction in its flow, for any app-layer protocol.
+
+use super::uint::{
+    DetectUintData, SCDetectU32Free, SCDetectU32Match, SCDetectU32Parse, SCDetectU64Free,
+    SCDetectU64Match, SCDetectU64Parse,
+};
+use super::{
+    DetectHelperBufferRegister, DetectHelperKeywordRegister, SCSigTableAppLiteElmt,
+    SigMatchAppendSMToList,
+};
+use crate::core::{ALPROTO_UNKNOWN, STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::flow::Flow;
+use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+use std::os::raw::{c_int, c_void};
+
+extern "C" {
+    fn SCAppLayerParserGetTxFirstSeen(f: *const Flow, tx: *mut c_void) -> u64;
+    fn SCDetectHelperGetTxId(det: *const c_void) -> u64;
+}
+
+static mut G_APP_TX_ORDINAL_KW_ID: c_int = 0;
+static mut G_APP_TX_FLOW_OFFSET_KW_ID: c_int = 0;
+static mut G_APP_TX_BUFFER_ID: c_int = 0;
+
+/// Position of the transaction in its flow, the first transaction is 1.
+fn tx_ordinal(tx_id: u64) -> u64 {
+    tx_id.saturating_add(1)
+}
+
+/// Seconds between the start of the flow and the first time the transaction
+/// was seen. A new transaction only gets its time on the housekeeping after
+/// detection, so the time of the current packet is used until then.
+fn tx_flow_offset(first_seen: u64, flow_start: u64, now: u64) -> u32 {
+    let first_seen = if first_seen != 0 { first_seen } else { now };
+    let offset = first_seen.saturating_sub(flow_start);
+    u32::try_from(offset).unwrap_or(u32::MAX)
+}
+
+unsafe extern "C" fn app_tx_ordinal_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    let ctx = SCDetectU64Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(de, s, G_APP_TX_ORDINAL_KW_ID, ctx, G_APP_TX_BUFFER_ID).is_null() {
+        app_tx_ordinal_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn app_tx_ordinal_match(
+    de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, _tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let ctx = cast_pointer!(ctx, DetectUintData<u64>);
+    let ordinal = tx_ordinal(SCDetectHelperGetTxId(de));
+    return SCDetectU64Match(ordinal, ctx);
+}
+
+unsafe extern "C" fn app_tx_ordinal_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u64>);
+    SCDetectU64Free(ctx);
+}
+
+unsafe extern "C" fn app_tx_flow_offset_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(de, s, G_APP_TX_FLOW_OFFSET_KW_ID, ctx, G_APP_TX_BUFFER_ID).is_null()
+    {
+        app_tx_flow_offset_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn app_tx_flow_offset_match(
+    _de: *mut c_void, f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    let flow = cast_pointer!(f, Flow);
+    let first_seen = SCAppLayerParserGetTxFirstSeen(flow, tx);
+    let start = flow.get_start_time().as_secs();
+    let now = flow.get_last_time().as_secs();
+    return SCDetectU32Match(tx_flow_offset(first_seen, start, now), ctx);
+}
+
+unsafe extern "C" fn app_tx_flow_offset_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectAppTxRegister() {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"app_tx.ordinal\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the position of the transaction in the flow, starting at 1\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/app-layer.html#app-tx-ordinal\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(app_tx_ordinal_match),
+        Setup: app_tx_ordinal_setup,
+        Free: Some(app_tx_ordinal_free),
+        flags: 0,
+    };
+    G_APP_TX_ORDINAL_KW_ID = DetectHelperKeywordRegister(&kw);
+    let kw = SCSigTableAppLiteElmt {
+        name: b"app_tx.flow_offset\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the seconds between the flow start and the transaction start\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/app-layer.html#app-tx-flow-offset\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(app_tx_flow_offset_match),
+        Setup: app_tx_flow_offset_setup,
+        Free: Some(app_tx_flow_offset_free),
+        flags: 0,
+    };
+    G_APP_TX_FLOW_OFFSET_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_APP_TX_BUFFER_ID = DetectHelperBufferRegister(
+        b"app_tx\0".as_ptr() as *const libc::c_char,
+        ALPROTO_UNKNOWN,
+        STREAM_TOSERVER | STREAM_TOCLIENT,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect::uint::{detect_match_uint, detect_parse_uint};
+
+    #[test]
+    fn test_app_tx_ordinal() {
+        let (_, ctx) = detect_parse_uint::<u64>(">500").unwrap();
+        // the 501st transaction has id 500
+        assert!(!detect_match_uint(&ctx, tx_ordinal(499)));
+        assert!(detect_match_uint(&ctx, tx_ordinal(500)));
+        assert_eq!(tx_ordinal(0), 1);
+        assert_eq!(tx_ordinal(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_app_tx_flow_offset() {
+        let (_, ctx) = detect_parse_uint::<u32>(">600").unwrap();
+        // seen 10 minutes and 1 second after the flow start
+        let offset = tx_flow_offset(1_000_601, 1_000_000, 1_000_900);
+        assert_eq!(offset, 601);
+        assert!(detect_match_uint(&ctx, offset));
+        // not seen yet by the housekeeping, so first seen now
+        assert_eq!(tx_flow_offset(0, 1_000_000, 1_000_030), 30);
+        // a clock going back does not wrap
+        assert_eq!(tx_flow_offset(999_999, 1_000_000, 1_000_000), 0);
+        assert_eq!(tx_flow_offset(u64::MAX, 0, 0), u32::MAX);
+    }
+}
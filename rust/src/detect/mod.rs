@@ -17,6 +17,8 @@
 
 //! Module for rule parsing.
 
+pub mod app_layer_event;
+pub mod app_tx;
 pub mod byte_extract;
 pub mod byte_math;
 pub mod entropy;
@@ -38,7 +40,7 @@ pub mod datasets;
 use std::os::raw::{c_char, c_int, c_void};
 use std::ffi::CString;
 
-use crate::core::DetectEngineThreadCtx;
+use crate::core::{DetectEngineThreadCtx, ALPROTO_UNKNOWN};
 use suricata_sys::sys::{AppProto, DetectEngineCtx, Signature};
 
 /// EnumString trait that will be implemented on enums that
@@ -55,6 +57,9 @@ pub trait EnumString<T> {
 
     /// Get an enum variant from parsing a string.
     fn from_str(s: &str) -> Option<Self> where Self: Sized;
+
+    /// Return the names and numeric values of all the variants.
+    fn values() -> Vec<(&'static str, T)> where Self: Sized;
 }
 
 /// Rust app-layer light version of SigTableElmt for simple sticky buffer
@@ -93,6 +98,278 @@ pub fn helper_keyword_register_sticky_buffer(kw: &SigTableElmtStickyBuffer) -> c
     }
 }
 
+/// Callback returning the inspection buffer of a transaction, as given to
+/// `DetectHelperBufferMpmRegister`.
+pub type DetectGetDataFn = unsafe extern "C" fn(
+    *mut c_void,
+    *const c_void,
+    *const c_void,
+    u8,
+    *const c_void,
+    c_int,
+) -> *mut c_void;
+
+/// Registration calls made for the sticky buffers generated by
+/// `detect_sticky_buffer!`.
+pub trait StickyBufferRegistrar {
+    unsafe fn sticky_buffer(&mut self, kw: &SigTableElmtStickyBuffer) -> c_int;
+    unsafe fn buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_data: DetectGetDataFn,
+    ) -> c_int;
+}
+
+/// Registers into the detection engine.
+pub struct DetectEngineRegistrar;
+
+impl StickyBufferRegistrar for DetectEngineRegistrar {
+    unsafe fn sticky_buffer(&mut self, kw: &SigTableElmtStickyBuffer) -> c_int {
+        helper_keyword_register_sticky_buffer(kw)
+    }
+
+    unsafe fn buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_data: DetectGetDataFn,
+    ) -> c_int {
+        DetectHelperBufferMpmRegister(name, desc, alproto, dir, get_data)
+    }
+}
+
+/// Generates the plumbing of a sticky buffer holding a single value of a
+/// transaction: the setup and get data callbacks, and their registration.
+///
+/// The items are generated in a module named `$module`:
+/// - `BUFFER_ID`, the id of the buffer once registered
+/// - `get_tx_data`, filling the buffer from the transaction
+/// - `register`, registering the keyword and its buffer, and returning the
+///   keyword id
+///
+/// `get` is a `fn(&Tx) -> Option<&[u8]>`, returning `None` when the
+/// transaction has no such buffer. Paths used by `alproto` and `get` are
+/// resolved in the parent module.
+///
+/// ```ignore
+/// detect_sticky_buffer! {
+///     mod rfb_name {
+///         name: "rfb.name",
+///         desc: "sticky buffer to match on the RFB desktop name",
+///         url: "/rules/rfb-keywords.html#rfb-name",
+///         mpm_desc: "rfb name",
+///         alproto: ALPROTO_RFB,
+///         dir: STREAM_TOCLIENT,
+///         tx: RFBTransaction,
+///         get: |tx| tx.tc_server_init.as_ref().map(|r| r.name.as_slice()),
+///     }
+/// }
+/// ```
+///
+/// A keyword can also apply to several app-layer protocols, each with its
+/// own transaction type. The buffer is then registered for every protocol
+/// listed in `protos`, and the inspection engines of a protocol only get
+/// the buffer from the transactions of that protocol. Each protocol gets
+/// its own `get_tx_data` in a module named after it, e.g.
+/// `krb5_srealm::smb::get_tx_data` below. The rules using the keyword are
+/// limited to the listed protocols.
+///
+/// ```ignore
+/// detect_sticky_buffer! {
+///     mod krb5_srealm {
+///         name: "krb5.srealm",
+///         desc: "sticky buffer to match on Kerberos 5 server realm",
+///         url: "/rules/kerberos-keywords.html#krb5-srealm",
+///         mpm_desc: "Kerberos 5 server realm",
+///         protos: {
+///             krb5 {
+///                 alproto: ALPROTO_KRB5,
+///                 dir: STREAM_TOSERVER | STREAM_TOCLIENT,
+///                 tx: KRB5Transaction,
+///                 get: |tx| tx.srealm.as_ref().map(|r| r.0.as_bytes()),
+///             },
+///             smb {
+///                 alproto: ALPROTO_SMB,
+///                 dir: STREAM_TOSERVER,
+///                 tx: SMBTransaction,
+///                 get: smb_tx_get_krb5_srealm,
+///             },
+///         },
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! detect_sticky_buffer {
+    (
+        mod $module:ident {
+            name: $name:literal,
+            desc: $desc:literal,
+            url: $url:literal,
+            mpm_desc: $mpm_desc:literal,
+            alproto: $alproto:expr,
+            dir: $dir:expr,
+            tx: $tx:ty,
+            get: $get:expr $(,)?
+        }
+    ) => {
+        mod $module {
+            #[allow(unused_imports)]
+            use super::*;
+            use std::os::raw::{c_int, c_void};
+            use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+            pub(super) static mut BUFFER_ID: c_int = 0;
+
+            unsafe extern "C" fn setup(
+                de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+            ) -> c_int {
+                if $crate::detect::DetectSignatureSetAppProto(s, $alproto) != 0 {
+                    return -1;
+                }
+                if suricata_sys::sys::SCDetectBufferSetActiveList(de, s, BUFFER_ID) < 0 {
+                    return -1;
+                }
+                return 0;
+            }
+
+            $crate::detect_sticky_buffer!(@get_data $tx, $get);
+
+            pub(super) unsafe fn register<R: $crate::detect::StickyBufferRegistrar>(
+                reg: &mut R,
+            ) -> c_int {
+                let kw = $crate::detect::SigTableElmtStickyBuffer {
+                    name: String::from($name),
+                    desc: String::from($desc),
+                    url: String::from($url),
+                    setup,
+                };
+                let kw_id = reg.sticky_buffer(&kw);
+                BUFFER_ID = reg.buffer_mpm(
+                    concat!($name, "\0").as_ptr() as *const libc::c_char,
+                    concat!($mpm_desc, "\0").as_ptr() as *const libc::c_char,
+                    $alproto,
+                    $dir,
+                    get_data,
+                );
+                kw_id
+            }
+        }
+    };
+    (
+        mod $module:ident {
+            name: $name:literal,
+            desc: $desc:literal,
+            url: $url:literal,
+            mpm_desc: $mpm_desc:literal,
+            protos: {
+                $(
+                    $proto:ident {
+                        alproto: $alproto:expr,
+                        dir: $dir:expr,
+                        tx: $tx:ty,
+                        get: $get:expr $(,)?
+                    }
+                ),+ $(,)?
+            } $(,)?
+        }
+    ) => {
+        mod $module {
+            #[allow(unused_imports)]
+            use super::*;
+            use std::os::raw::c_int;
+            use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+            pub(super) static mut BUFFER_ID: c_int = 0;
+
+            unsafe extern "C" fn setup(
+                de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+            ) -> c_int {
+                if $crate::detect::detect_set_multi_app_proto(s, &[$($alproto),+]) != 0 {
+                    return -1;
+                }
+                if suricata_sys::sys::SCDetectBufferSetActiveList(de, s, BUFFER_ID) < 0 {
+                    return -1;
+                }
+                return 0;
+            }
+
+            $(
+                pub(crate) mod $proto {
+                    #[allow(unused_imports)]
+                    use super::*;
+                    use std::os::raw::{c_int, c_void};
+
+                    $crate::detect_sticky_buffer!(@get_data $tx, $get);
+                }
+            )+
+
+            pub(super) unsafe fn register<R: $crate::detect::StickyBufferRegistrar>(
+                reg: &mut R,
+            ) -> c_int {
+                let kw = $crate::detect::SigTableElmtStickyBuffer {
+                    name: String::from($name),
+                    desc: String::from($desc),
+                    url: String::from($url),
+                    setup,
+                };
+                let kw_id = reg.sticky_buffer(&kw);
+                $(
+                    // same buffer for all the protocols
+                    BUFFER_ID = reg.buffer_mpm(
+                        concat!($name, "\0").as_ptr() as *const libc::c_char,
+                        concat!($mpm_desc, "\0").as_ptr() as *const libc::c_char,
+                        $alproto,
+                        $dir,
+                        $proto::get_data,
+                    );
+                )+
+                kw_id
+            }
+        }
+    };
+    (@get_data $tx:ty, $get:expr) => {
+        pub(crate) unsafe extern "C" fn get_tx_data(
+            tx: *const c_void, _flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
+        ) -> bool {
+            let tx = $crate::cast_pointer!(tx, $tx);
+            let get: for<'t> fn(&'t $tx) -> Option<&'t [u8]> = $get;
+            if let Some(value) = get(tx) {
+                *buffer = value.as_ptr();
+                *buffer_len = value.len() as u32;
+                return true;
+            }
+            *buffer = std::ptr::null();
+            *buffer_len = 0;
+            return false;
+        }
+
+        pub(super) unsafe extern "C" fn get_data(
+            de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
+            tx: *const c_void, list_id: c_int,
+        ) -> *mut c_void {
+            return $crate::detect::DetectHelperGetData(
+                de,
+                transforms,
+                flow,
+                flow_flags,
+                tx,
+                list_id,
+                get_tx_data,
+            );
+        }
+    };
+}
+
+/// Limits a signature to the given app-layer protocols, or to the ones it
+/// already uses among them.
+///
+/// Returns 0 on success, -1 if the signature uses none of them.
+pub unsafe fn detect_set_multi_app_proto(s: *mut Signature, alprotos: &[AppProto]) -> c_int {
+    let mut list = alprotos.to_vec();
+    list.push(ALPROTO_UNKNOWN);
+    if DetectSignatureSetMultiAppProto(s, list.as_ptr()) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
 #[repr(C)]
 #[allow(non_snake_case)]
 /// Names of SigTableElmt for release by rust
@@ -147,6 +424,7 @@ pub struct SCSigTableAppLiteElmt {
 }
 
 pub const SIGMATCH_NOOPT: u16 = 1; // BIT_U16(0) in detect.h
+pub(crate) const SIGMATCH_OPTIONAL_OPT: u16 = 0x10; // BIT_U16(4) in detect.h
 pub(crate) const SIGMATCH_QUOTES_MANDATORY: u16 = 0x40; // BIT_U16(6) in detect.h
 pub const SIGMATCH_INFO_STICKY_BUFFER: u16 = 0x200; // BIT_U16(9)
 
@@ -175,6 +453,7 @@ extern "C" {
         name: *const libc::c_char, alproto: AppProto, dir: u8,
     ) -> c_int;
     pub fn DetectSignatureSetAppProto(s: *mut Signature, alproto: AppProto) -> c_int;
+    pub fn DetectSignatureSetMultiAppProto(s: *mut Signature, alprotos: *const AppProto) -> c_int;
     pub fn SigMatchAppendSMToList(
         de: *mut DetectEngineCtx, s: *mut Signature, kwid: c_int, ctx: *const c_void, bufid: c_int,
     ) -> *mut c_void;
@@ -242,6 +521,28 @@ fn get_endian_value(value: &str) -> Option<ByteEndian> {
     res
 }
 
+/// Returns the buffer a `detect_sticky_buffer!` gets from a transaction.
+#[cfg(test)]
+pub(crate) fn sticky_buffer_tx_data<'a, T>(
+    get_tx_data: unsafe extern "C" fn(*const c_void, u8, *mut *const u8, *mut u32) -> bool,
+    tx: &'a T,
+) -> Option<&'a [u8]> {
+    let mut buffer = std::ptr::null();
+    let mut buffer_len = 0;
+    unsafe {
+        if get_tx_data(
+            tx as *const T as *const c_void,
+            0,
+            &mut buffer,
+            &mut buffer_len,
+        ) {
+            return Some(std::slice::from_raw_parts(buffer, buffer_len as usize));
+        }
+    }
+    assert!(buffer.is_null() && buffer_len == 0);
+    None
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -266,5 +567,6 @@ mod test {
         assert_eq!(TestEnum::from_str("zero"), Some(TestEnum::Zero));
         assert_eq!(TestEnum::from_str("nope"), None);
         assert_eq!(TestEnum::from_str("best_value_ever"), Some(TestEnum::BestValueEver));
+        assert_eq!(TestEnum::values(), vec![("zero", 0), ("best_value_ever", 42)]);
     }
 }
@@ -25,6 +25,7 @@ pub mod domain;
 pub mod dotprefix;
 pub mod hash;
 pub mod http_headers;
+pub mod punycode;
 pub mod strip_whitespace;
 pub mod urldecode;
 pub mod xor;
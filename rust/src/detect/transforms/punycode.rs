@@ -0,0 +1,250 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+use super::{
+    DetectHelperTransformRegister, DetectSignatureAddTransform, InspectionBufferCheckAndExpand,
+    InspectionBufferLength, InspectionBufferPtr, InspectionBufferTruncate, SCTransformTableElmt,
+};
+use crate::detect::SIGMATCH_NOOPT;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+
+static mut G_TRANSFORM_PUNYCODE_DECODE_ID: c_int = 0;
+
+// RFC 3492 parameters
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+/// Labels are at most 63 bytes, so a valid label cannot decode to more
+/// code points than this.
+const MAX_LABEL_CODE_POINTS: usize = 63;
+
+const ACE_PREFIX: &[u8] = b"xn--";
+
+fn decode_digit(c: u8) -> Option<u32> {
+    match c {
+        b'0'..=b'9' => Some((c - b'0') as u32 + 26),
+        b'a'..=b'z' => Some((c - b'a') as u32),
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        _ => None,
+    }
+}
+
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+    k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+}
+
+/// Decode a punycode string, without the ACE prefix, to its code points.
+fn punycode_decode(input: &[u8]) -> Option<Vec<char>> {
+    let (basic, extended) = match input.iter().rposition(|&c| c == b'-') {
+        Some(pos) => (&input[..pos], &input[pos + 1..]),
+        None => (&input[..0], input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.iter().map(|&c| c as char).collect();
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut pos = 0;
+    while pos < extended.len() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(*extended.get(pos)?)?;
+            pos += 1;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = if k <= bias {
+                TMIN
+            } else if k >= bias + TMAX {
+                TMAX
+            } else {
+                k - bias
+            };
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        if output.len() >= MAX_LABEL_CODE_POINTS {
+            return None;
+        }
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    if output.is_empty() {
+        return None;
+    }
+    Some(output)
+}
+
+/// Decode the `xn--` labels of a domain name to UTF-8, keeping the other
+/// labels and the ones that fail to decode as they are.
+fn punycode_decode_name(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    for (i, label) in input.split(|&c| c == b'.').enumerate() {
+        if i > 0 {
+            output.push(b'.');
+        }
+        let decoded = if label.len() > ACE_PREFIX.len()
+            && label[..ACE_PREFIX.len()].eq_ignore_ascii_case(ACE_PREFIX)
+        {
+            punycode_decode(&label[ACE_PREFIX.len()..])
+        } else {
+            None
+        };
+        if let Some(decoded) = decoded {
+            let mut buf = [0; 4];
+            for c in decoded {
+                output.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        } else {
+            output.extend_from_slice(label);
+        }
+    }
+    output
+}
+
+unsafe extern "C" fn punycode_decode_setup(
+    _de: *mut c_void, s: *mut c_void, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    return DetectSignatureAddTransform(s, G_TRANSFORM_PUNYCODE_DECODE_ID, ptr::null_mut());
+}
+
+unsafe extern "C" fn punycode_decode_transform(
+    _det: *mut c_void, buffer: *mut c_void, _ctx: *mut c_void,
+) {
+    let input = InspectionBufferPtr(buffer);
+    let input_len = InspectionBufferLength(buffer);
+    if input.is_null() || input_len == 0 {
+        return;
+    }
+    let input = build_slice!(input, input_len as usize);
+    if !input
+        .windows(ACE_PREFIX.len())
+        .any(|w| w.eq_ignore_ascii_case(ACE_PREFIX))
+    {
+        return;
+    }
+
+    // decode first, as the input is no longer valid once the buffer grows
+    let decoded = punycode_decode_name(input);
+
+    let output = InspectionBufferCheckAndExpand(buffer, decoded.len() as u32);
+    if output.is_null() {
+        // allocation failure
+        return;
+    }
+    let output = std::slice::from_raw_parts_mut(output, decoded.len());
+    output.copy_from_slice(&decoded);
+
+    InspectionBufferTruncate(buffer, decoded.len() as u32);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectTransformPunycodeDecodeRegister() {
+    let kw = SCTransformTableElmt {
+        name: b"punycode_decode\0".as_ptr() as *const libc::c_char,
+        desc: b"decode the punycode labels of a domain name to UTF-8\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/transforms.html#punycode-decode\0".as_ptr() as *const libc::c_char,
+        Setup: punycode_decode_setup,
+        flags: SIGMATCH_NOOPT,
+        Transform: punycode_decode_transform,
+        Free: None,
+        TransformValidate: None,
+    };
+    unsafe {
+        G_TRANSFORM_PUNYCODE_DECODE_ID = DetectHelperTransformRegister(&kw);
+        if G_TRANSFORM_PUNYCODE_DECODE_ID < 0 {
+            SCLogWarning!("Failed registering transform punycode_decode");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punycode_decode() {
+        let decoded: String = punycode_decode(b"mnchen-3ya")
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(decoded, "münchen");
+        let decoded: String = punycode_decode(b"80ak6aa92e")
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(decoded, "аррӏе");
+        let decoded: String = punycode_decode(b"ihqwcrb4cv8a8dqg056pqjye")
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(decoded, "他们为什么不说中文");
+
+        assert!(punycode_decode(b"").is_none());
+        assert!(punycode_decode(b"abc-!!").is_none());
+        // ends in the middle of a code point
+        assert!(punycode_decode(b"mnchen-3y").is_none());
+        // overflow
+        assert!(punycode_decode(b"99999999999").is_none());
+    }
+
+    #[test]
+    fn test_punycode_decode_name() {
+        assert_eq!(
+            punycode_decode_name(b"www.xn--mnchen-3ya.de"),
+            "www.münchen.de".as_bytes()
+        );
+        assert_eq!(
+            punycode_decode_name(b"XN--80ak6aa92e.com"),
+            "аррӏе.com".as_bytes()
+        );
+        assert_eq!(
+            punycode_decode_name(b"xn--bcher-kva.xn--80ak6aa92e.example"),
+            "bücher.аррӏе.example".as_bytes()
+        );
+        // invalid labels are kept as they are
+        assert_eq!(
+            punycode_decode_name(b"xn--abc-!!.xn--mnchen-3ya.xn--"),
+            "xn--abc-!!.münchen.xn--".as_bytes()
+        );
+        assert_eq!(punycode_decode_name(b"example.com."), b"example.com.");
+    }
+}
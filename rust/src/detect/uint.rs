@@ -24,8 +24,12 @@ use nom7::Err;
 use nom7::IResult;
 
 use super::EnumString;
+use crate::jsonbuilder::{JsonBuilder, JsonError};
 
+use lazy_static::lazy_static;
 use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::sync::Mutex;
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 #[repr(u8)]
@@ -83,6 +87,46 @@ pub fn detect_parse_uint_enum<T1: DetectIntType, T2: EnumString<T1>>(
     return None;
 }
 
+type DetectUintEnumJsonFn = fn(&mut JsonBuilder) -> Result<(), JsonError>;
+
+lazy_static! {
+    /// Callbacks listing the names accepted by keywords parsed with
+    /// `detect_parse_uint_enum`, by keyword id.
+    static ref UINT_ENUM_KEYWORDS: Mutex<Vec<(c_int, DetectUintEnumJsonFn)>> =
+        Mutex::new(Vec::new());
+}
+
+fn detect_uint_enum_to_json<T1: DetectIntType, T2: EnumString<T1>>(
+    js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
+    js.open_object("enum")?;
+    for (name, value) in T2::values() {
+        js.set_uint(name, value.to_u64().unwrap_or(0))?;
+    }
+    js.close()?;
+    Ok(())
+}
+
+/// Register the enumeration accepted by a keyword using
+/// `detect_parse_uint_enum`, so that engine analysis can list its names.
+pub fn detect_register_uint_enum<T1: DetectIntType, T2: EnumString<T1>>(kw_id: c_int) {
+    if let Ok(mut kws) = UINT_ENUM_KEYWORDS.lock() {
+        kws.push((kw_id, detect_uint_enum_to_json::<T1, T2>));
+    }
+}
+
+/// Add the `"enum"` object with the names and values accepted by the
+/// keyword, returns false if the keyword has no registered enumeration.
+#[no_mangle]
+pub extern "C" fn SCDetectUintEnumToJson(kw_id: u16, js: &mut JsonBuilder) -> bool {
+    if let Ok(kws) = UINT_ENUM_KEYWORDS.lock() {
+        if let Some((_, to_json)) = kws.iter().find(|(id, _)| *id == kw_id as c_int) {
+            return to_json(js).is_ok();
+        }
+    }
+    return false;
+}
+
 pub trait DetectIntType:
     std::str::FromStr
     + std::cmp::PartialOrd
@@ -520,6 +564,7 @@ pub unsafe extern "C" fn SCDetectU16Free(ctx: &mut DetectUintData<u16>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::jsonbuilder::{SCJbLen, SCJbPtr};
 
     use suricata_derive::EnumStringU8;
 
@@ -541,6 +586,21 @@ mod tests {
         assert_eq!(ctx.mode, DetectUintMode::DetectUintModeGt);
     }
 
+    #[test]
+    fn test_detect_uint_enum_to_json() {
+        detect_register_uint_enum::<u8, TestEnum>(4242);
+        let mut js = JsonBuilder::try_new_object().unwrap();
+        assert!(SCDetectUintEnumToJson(4242, &mut js));
+        // an unregistered keyword logs nothing
+        assert!(!SCDetectUintEnumToJson(4243, &mut js));
+        js.close().unwrap();
+        let buf = unsafe { std::slice::from_raw_parts(SCJbPtr(&mut js), SCJbLen(&js)) };
+        assert_eq!(
+            std::str::from_utf8(buf).unwrap(),
+            r#"{"enum":{"zero":0,"best_value_ever":42}}"#
+        );
+    }
+
     #[test]
     fn test_parse_uint_bitmask() {
         let (_, val) = detect_parse_uint::<u64>("&0x40!=0").unwrap();
@@ -23,8 +23,8 @@ use super::parser::DHCPOptionWrapper;
 use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
 use crate::detect::uint::{DetectUintData, SCDetectU64Free, SCDetectU64Match, SCDetectU64Parse};
 use crate::detect::{
-    DetectHelperBufferRegister, DetectHelperKeywordRegister, DetectSignatureSetAppProto,
-    SCSigTableAppLiteElmt, SigMatchAppendSMToList,
+    DetectHelperBufferRegister, DetectHelperKeywordAliasRegister, DetectHelperKeywordRegister,
+    DetectSignatureSetAppProto, SCSigTableAppLiteElmt, SigMatchAppendSMToList,
 };
 use suricata_sys::sys::{DetectEngineCtx, Signature};
 use std::os::raw::{c_int, c_void};
@@ -176,6 +176,11 @@ pub unsafe extern "C" fn SCDetectDHCPRegister() {
         flags: 0,
     };
     G_DHCP_LEASE_TIME_KW_ID = DetectHelperKeywordRegister(&kw);
+    // consistent with the other time keywords and the eve field
+    DetectHelperKeywordAliasRegister(
+        G_DHCP_LEASE_TIME_KW_ID,
+        b"dhcp.lease_time\0".as_ptr() as *const libc::c_char,
+    );
     G_DHCP_LEASE_TIME_BUFFER_ID = DetectHelperBufferRegister(
         b"dhcp.leasetime\0".as_ptr() as *const libc::c_char,
         ALPROTO_DHCP,
@@ -17,15 +17,18 @@
 
 use super::dns::{DNSRcode, DNSRecordType, DNSTransaction, ALPROTO_DNS};
 use crate::core::{DetectEngineThreadCtx, STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::detect::float::{
+    detect_match_float, DetectFloatData, SCDetectF64Free, SCDetectF64Parse,
+};
 use crate::detect::uint::{
-    detect_match_uint, detect_parse_uint_enum, DetectUintData, SCDetectU16Free, SCDetectU8Free,
-    SCDetectU8Parse,
+    detect_match_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    SCDetectU16Free, SCDetectU8Free, SCDetectU8Parse,
 };
 use crate::detect::{
     helper_keyword_register_sticky_buffer, DetectHelperBufferRegister,
     DetectHelperKeywordAliasRegister, DetectHelperKeywordRegister,
     DetectHelperMultiBufferProgressMpmRegister, DetectSignatureSetAppProto, SCSigTableAppLiteElmt,
-    SigMatchAppendSMToList, SigTableElmtStickyBuffer,
+    SigMatchAppendSMToList, SigTableElmtStickyBuffer, SIGMATCH_NOOPT,
 };
 use crate::direction::Direction;
 use std::ffi::CStr;
@@ -123,6 +126,32 @@ unsafe extern "C" fn dns_rrtype_match(
     return 0;
 }
 
+/// Perform the DNS query name entropy match on the first label of each
+/// query name.
+unsafe extern "C" fn dns_query_name_entropy_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, DNSTransaction);
+    let ctx = cast_pointer!(ctx, DetectFloatData<f64>);
+
+    for entropy in tx.query_label_entropy() {
+        if detect_match_float(ctx, *entropy) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+/// Match responses for which no query was seen.
+unsafe extern "C" fn dns_response_unsolicited_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, DNSTransaction);
+    return tx.unsolicited as c_int;
+}
+
 static mut G_DNS_ANSWER_NAME_BUFFER_ID: c_int = 0;
 static mut G_DNS_QUERY_NAME_BUFFER_ID: c_int = 0;
 static mut G_DNS_QUERY_BUFFER_ID: c_int = 0;
@@ -132,6 +161,10 @@ static mut G_DNS_RCODE_KW_ID: c_int = 0;
 static mut G_DNS_RCODE_BUFFER_ID: c_int = 0;
 static mut G_DNS_RRTYPE_KW_ID: c_int = 0;
 static mut G_DNS_RRTYPE_BUFFER_ID: c_int = 0;
+static mut G_DNS_QUERY_NAME_ENTROPY_KW_ID: c_int = 0;
+static mut G_DNS_QUERY_NAME_ENTROPY_BUFFER_ID: c_int = 0;
+static mut G_DNS_RESPONSE_UNSOLICITED_KW_ID: c_int = 0;
+static mut G_DNS_RESPONSE_UNSOLICITED_BUFFER_ID: c_int = 0;
 
 unsafe extern "C" fn dns_opcode_setup(
     de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
@@ -226,6 +259,57 @@ unsafe extern "C" fn dns_rrtype_free(_de: *mut c_void, ctx: *mut c_void) {
     SCDetectU16Free(ctx);
 }
 
+unsafe extern "C" fn dns_query_name_entropy_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_DNS) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectF64Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_DNS_QUERY_NAME_ENTROPY_KW_ID,
+        ctx,
+        G_DNS_QUERY_NAME_ENTROPY_BUFFER_ID,
+    )
+    .is_null()
+    {
+        dns_query_name_entropy_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn dns_query_name_entropy_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectFloatData<f64>);
+    SCDetectF64Free(ctx);
+}
+
+unsafe extern "C" fn dns_response_unsolicited_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_DNS) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_DNS_RESPONSE_UNSOLICITED_KW_ID,
+        std::ptr::null_mut(),
+        G_DNS_RESPONSE_UNSOLICITED_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
 unsafe extern "C" fn dns_detect_answer_name_setup(
     de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
 ) -> c_int {
@@ -382,6 +466,7 @@ pub unsafe extern "C" fn SCDetectDNSRegister() {
         flags: 0,
     };
     G_DNS_RCODE_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u16, DNSRcode>(G_DNS_RCODE_KW_ID);
     G_DNS_RCODE_BUFFER_ID = DetectHelperBufferRegister(
         b"dns.rcode\0".as_ptr() as *const libc::c_char,
         ALPROTO_DNS,
@@ -397,6 +482,7 @@ pub unsafe extern "C" fn SCDetectDNSRegister() {
         flags: 0,
     };
     G_DNS_RRTYPE_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u16, DNSRecordType>(G_DNS_RRTYPE_KW_ID);
     G_DNS_RRTYPE_BUFFER_ID = DetectHelperBufferRegister(
         b"dns.rrtype\0".as_ptr() as *const libc::c_char,
         ALPROTO_DNS,
@@ -421,6 +507,37 @@ pub unsafe extern "C" fn SCDetectDNSRegister() {
         dns_tx_get_query, // reuse, will be called only toserver
         1,                // request complete
     );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"dns.query.name.entropy\0".as_ptr() as *const libc::c_char,
+        desc: b"Match the entropy of the first label of the DNS query name.\0".as_ptr()
+            as *const libc::c_char,
+        url: b"rules/dns-keywords.html#dns-query-name-entropy\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(dns_query_name_entropy_match),
+        Setup: dns_query_name_entropy_setup,
+        Free: Some(dns_query_name_entropy_free),
+        flags: 0,
+    };
+    G_DNS_QUERY_NAME_ENTROPY_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_DNS_QUERY_NAME_ENTROPY_BUFFER_ID = DetectHelperBufferRegister(
+        b"dns.query.name.entropy\0".as_ptr() as *const libc::c_char,
+        ALPROTO_DNS,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"dns.response.unsolicited\0".as_ptr() as *const libc::c_char,
+        desc: b"Match DNS responses without a matching query.\0".as_ptr() as *const libc::c_char,
+        url: b"rules/dns-keywords.html#dns-response-unsolicited\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(dns_response_unsolicited_match),
+        Setup: dns_response_unsolicited_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_DNS_RESPONSE_UNSOLICITED_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_DNS_RESPONSE_UNSOLICITED_BUFFER_ID = DetectHelperBufferRegister(
+        b"dns.response.unsolicited\0".as_ptr() as *const libc::c_char,
+        ALPROTO_DNS,
+        STREAM_TOCLIENT,
+    );
 }
 
 #[cfg(test)]
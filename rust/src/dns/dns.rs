@@ -22,7 +22,9 @@ use std::ffi::CString;
 use std::os::raw::c_void;
 
 use crate::applayer::*;
+use crate::applayerstats::{applayer_stats_register, AppLayerParserStats};
 use crate::core::{self, *};
+use crate::detect::entropy::calculate_entropy;
 use crate::direction::Direction;
 use crate::direction::DIR_BOTH;
 use crate::dns::parser;
@@ -126,6 +128,8 @@ pub enum DNSRcode {
 
 pub(super) static mut ALPROTO_DNS: AppProto = ALPROTO_UNKNOWN;
 
+static DNS_STATS: AppLayerParserStats = AppLayerParserStats::new("dns");
+
 #[derive(AppLayerFrameType)]
 enum DnsFrameType {
     /// DNS PDU frame. For UDP DNS this is the complete UDP payload, for TCP
@@ -149,6 +153,8 @@ pub enum DNSEvent {
     TooManyLabels,
     InvalidAdditionals,
     InvalidAuthorities,
+    /// A response was seen for which no query with the same ID was seen.
+    UnsolicitedResponse,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -281,6 +287,14 @@ pub struct DNSTransaction {
     pub request: Option<DNSMessage>,
     pub response: Option<DNSMessage>,
     pub tx_data: AppLayerTxData,
+    /// Entropy of the first label of the request query names, computed
+    /// once when first needed by detection.
+    query_label_entropy: Option<Vec<f64>>,
+    /// Set on a response transaction when no outstanding query with the
+    /// same DNS ID was seen on the flow.
+    pub unsolicited: bool,
+    /// Bits of the DNSEvent set on the transaction
+    events: u32,
 }
 
 impl Transaction for DNSTransaction {
@@ -319,9 +333,31 @@ impl DNSTransaction {
         return 0;
     }
 
+    /// Get the Shannon entropy of the first label of each query name in
+    /// the request.
+    pub fn query_label_entropy(&mut self) -> &[f64] {
+        let request = &self.request;
+        self.query_label_entropy.get_or_insert_with(|| {
+            request.as_ref().map_or_else(Vec::new, |request| {
+                request
+                    .queries
+                    .iter()
+                    .map(|query| {
+                        let name = &query.name.value;
+                        let label = name.split(|c| *c == b'.').next().unwrap_or(name);
+                        calculate_entropy(label)
+                    })
+                    .collect()
+            })
+        })
+    }
+
     /// Set an event. The event is set on the most recent transaction.
     pub fn set_event(&mut self, event: DNSEvent) {
-        self.tx_data.set_event(event as u8);
+        let event = event as u8;
+        self.events |= 1 << event;
+        self.tx_data.set_event(event);
+        applayer_stats_incr!(DNS_STATS, events);
     }
 }
 
@@ -368,6 +404,15 @@ pub struct DNSState {
     config: Option<ConfigTracker>,
 
     gap: bool,
+
+    /// DNS IDs of the queries that have not been answered yet.
+    pending_queries: VecDeque<u16>,
+
+    /// Set once queries may have been missed, either because the flow
+    /// was picked up mid-stream, a gap was seen in the request direction
+    /// or pending queries were evicted. Unmatched responses are then no
+    /// longer flagged as unsolicited.
+    queries_untracked: bool,
 }
 
 impl State<DNSTransaction> for DNSState {
@@ -542,6 +587,8 @@ impl DNSState {
         }
         if found {
             self.transactions.remove(index);
+            // a dns transaction is complete as soon as it is created
+            applayer_stats_tx_freed!(DNS_STATS, true);
         }
     }
 
@@ -549,6 +596,27 @@ impl DNSState {
         return self.transactions.iter().find(|&tx| tx.id == tx_id + 1);
     }
 
+    /// Remember the ID of a query so its response can be matched to it.
+    fn track_query(&mut self, id: u16) {
+        if self.pending_queries.len() >= DNS_MAX_PENDING_QUERIES {
+            self.pending_queries.pop_front();
+            self.queries_untracked = true;
+        }
+        self.pending_queries.push_back(id);
+    }
+
+    /// Match a response ID against the pending queries, removing the
+    /// query if found.
+    ///
+    /// Returns false only if the response is known to be unsolicited.
+    fn match_query(&mut self, id: u16) -> bool {
+        if let Some(index) = self.pending_queries.iter().position(|&q| q == id) {
+            self.pending_queries.remove(index);
+            return true;
+        }
+        self.queries_untracked
+    }
+
     /// Set an event. The event is set on the most recent transaction.
     fn set_event(&mut self, event: DNSEvent) {
         let len = self.transactions.len();
@@ -556,8 +624,7 @@ impl DNSState {
             return;
         }
 
-        let tx = &mut self.transactions[len - 1];
-        tx.tx_data.set_event(event as u8);
+        self.transactions[len - 1].set_event(event);
     }
 
     fn parse_request(
@@ -567,6 +634,10 @@ impl DNSState {
             Ok(mut tx) => {
                 self.tx_id += 1;
                 tx.id = self.tx_id;
+                applayer_stats_incr!(DNS_STATS, tx_created);
+                if let Some(request) = &tx.request {
+                    self.track_query(request.header.tx_id);
+                }
                 if let Some(frame) = frame {
                     frame.set_tx(flow, tx.id);
                 }
@@ -626,6 +697,7 @@ impl DNSState {
             Ok(mut tx) => {
                 self.tx_id += 1;
                 tx.id = self.tx_id;
+                applayer_stats_incr!(DNS_STATS, tx_created);
                 if let Some(ref mut config) = &mut self.config {
                     if let Some(response) = &tx.response {
                         if let Some(config) = config.remove(&response.header.tx_id) {
@@ -633,6 +705,12 @@ impl DNSState {
                         }
                     }
                 }
+                if let Some(response) = &tx.response {
+                    if !self.match_query(response.header.tx_id) {
+                        tx.unsolicited = true;
+                        tx.set_event(DNSEvent::UnsolicitedResponse);
+                    }
+                }
                 if let Some(frame) = frame {
                     frame.set_tx(flow, tx.id);
                 }
@@ -664,7 +742,7 @@ impl DNSState {
             if is_dns || is_incomplete {
                 self.gap = false;
             } else {
-                AppLayerResult::ok();
+                return AppLayerResult::ok();
             }
         }
 
@@ -771,7 +849,7 @@ impl DNSState {
                 SCLogDebug!(
                     "[response]Not enough DNS traffic to parse. Returning {}/{}",
                     consumed as u32,
-                    (cur_i.len() - consumed) as u32
+                    (size + 2) as u32
                 );
                 return AppLayerResult::incomplete(consumed as u32, (size + 2) as u32);
             }
@@ -783,6 +861,7 @@ impl DNSState {
     fn request_gap(&mut self, gap: u32) {
         if gap > 0 {
             self.gap = true;
+            self.queries_untracked = true;
         }
     }
 
@@ -797,6 +876,9 @@ impl DNSState {
 
 const DNS_HEADER_SIZE: usize = 12;
 
+/// Maximum number of unanswered queries tracked per flow.
+const DNS_MAX_PENDING_QUERIES: usize = 256;
+
 fn probe_header_validity(header: &DNSHeader, rlen: usize) -> (bool, bool, bool) {
     let nb_records = header.additional_rr as usize
         + header.answer_rr as usize
@@ -835,12 +917,19 @@ fn probe(input: &[u8], dlen: usize) -> (bool, bool, bool) {
     };
 
     // If input is less than dlen then we know we don't have enough data to
-    // parse a complete message, so perform header validation only.
+    // parse a complete message, so perform header validation only. If the
+    // header itself is not complete yet, report the input as incomplete.
     if input.len() < dlen {
-        if let Ok((_, header)) = parser::dns_parse_header(input) {
-            return probe_header_validity(&header, dlen);
-        } else {
-            return (false, false, false);
+        match parser::dns_parse_header(input) {
+            Ok((_, header)) => {
+                return probe_header_validity(&header, dlen);
+            }
+            Err(Err::Incomplete(_)) => {
+                return (false, false, true);
+            }
+            Err(_) => {
+                return (false, false, false);
+            }
         }
     }
 
@@ -874,14 +963,19 @@ extern "C" fn state_new(
 ) -> *mut std::os::raw::c_void {
     let state = DNSState::new();
     let boxed = Box::new(state);
+    applayer_stats_incr!(DNS_STATS, states_live);
     return Box::into_raw(boxed) as *mut _;
 }
 
 /// Params:
 /// - state: *mut DNSState as void pointer
 extern "C" fn state_free(state: *mut std::os::raw::c_void) {
-    // Just unbox...
-    std::mem::drop(unsafe { Box::from_raw(state as *mut DNSState) });
+    let state = unsafe { Box::from_raw(state as *mut DNSState) };
+    // the transactions left are freed with the state
+    applayer_stats_add!(DNS_STATS, tx_freed, state.transactions.len());
+    applayer_stats_add!(DNS_STATS, tx_completed, state.transactions.len());
+    std::mem::drop(state);
+    applayer_stats_sub!(DNS_STATS, states_live, 1);
 }
 
 unsafe extern "C" fn state_tx_free(state: *mut std::os::raw::c_void, tx_id: u64) {
@@ -914,10 +1008,13 @@ unsafe extern "C" fn parse_request_tcp(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, DNSState);
+    if stream_slice.flags() & STREAM_MIDSTREAM != 0 {
+        state.queries_untracked = true;
+    }
     if stream_slice.is_gap() {
         state.request_gap(stream_slice.gap_size());
     } else if !stream_slice.is_empty() {
-        return state.parse_request_tcp(flow, stream_slice);
+        return applayer_stats_result!(DNS_STATS, state.parse_request_tcp(flow, stream_slice));
     }
     AppLayerResult::ok()
 }
@@ -927,10 +1024,13 @@ unsafe extern "C" fn parse_response_tcp(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, DNSState);
+    if stream_slice.flags() & STREAM_MIDSTREAM != 0 {
+        state.queries_untracked = true;
+    }
     if stream_slice.is_gap() {
         state.response_gap(stream_slice.gap_size());
     } else if !stream_slice.is_empty() {
-        return state.parse_response_tcp(flow, stream_slice);
+        return applayer_stats_result!(DNS_STATS, state.parse_response_tcp(flow, stream_slice));
     }
     AppLayerResult::ok()
 }
@@ -1263,6 +1363,7 @@ pub unsafe extern "C" fn SCRegisterDnsUdpParser() {
         ALPROTO_DNS = alproto;
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
+            applayer_stats_register(&DNS_STATS);
         }
     }
 }
@@ -1310,6 +1411,7 @@ pub unsafe extern "C" fn SCRegisterDnsTcpParser() {
         ALPROTO_DNS = alproto;
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
+            applayer_stats_register(&DNS_STATS);
         }
     }
 }
@@ -1319,6 +1421,79 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_dns_query_label_entropy() {
+        let query = |name: &[u8]| DNSQueryEntry {
+            name: DNSName {
+                value: name.to_vec(),
+                flags: DNSNameFlags::default(),
+            },
+            rrtype: 1,
+            rrclass: 1,
+        };
+        let mut tx = DNSTransaction::new(Direction::ToServer);
+        assert!(tx.query_label_entropy().is_empty());
+
+        let mut tx = DNSTransaction::new(Direction::ToServer);
+        tx.request = Some(DNSMessage {
+            header: DNSHeader {
+                tx_id: 1,
+                flags: 0x0100,
+                questions: 2,
+                answer_rr: 0,
+                authority_rr: 0,
+                additional_rr: 0,
+            },
+            queries: vec![query(b"aaaa.example.com"), query(b"abcd.example.com")],
+            answers: Vec::new(),
+            authorities: Vec::new(),
+            invalid_authorities: false,
+            additionals: Vec::new(),
+            invalid_additionals: false,
+        });
+        assert_eq!(tx.query_label_entropy(), &[0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_dns_unsolicited_response() {
+        fn message(id: u16, flags: u16) -> Vec<u8> {
+            let mut buf = Vec::new();
+            buf.extend_from_slice(&id.to_be_bytes());
+            buf.extend_from_slice(&flags.to_be_bytes());
+            buf.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+            buf.extend_from_slice(b"\x07example\x03com\x00\x00\x01\x00\x01");
+            buf
+        }
+        let flow = std::ptr::null();
+
+        let mut state = DNSState::new();
+        assert!(state.parse_request(&message(1, 0x0100), false, None, flow));
+        assert!(state.parse_request(&message(2, 0x0100), false, None, flow));
+        assert!(state.parse_response(&message(2, 0x8180), false, None, flow));
+        assert!(!state.transactions[2].unsolicited);
+        assert!(state.parse_response(&message(1, 0x8180), false, None, flow));
+        assert!(!state.transactions[3].unsolicited);
+        // Each query is only answered once.
+        assert!(state.parse_response(&message(1, 0x8180), false, None, flow));
+        assert!(state.transactions[4].unsolicited);
+        assert!(state.parse_response(&message(3, 0x8180), false, None, flow));
+        assert!(state.transactions[5].unsolicited);
+
+        // Once queries may have been missed, unmatched responses are
+        // not flagged.
+        let mut state = DNSState::new();
+        state.request_gap(10);
+        assert!(state.parse_response(&message(3, 0x8180), false, None, flow));
+        assert!(!state.transactions[0].unsolicited);
+
+        let mut state = DNSState::new();
+        for id in 0..=DNS_MAX_PENDING_QUERIES as u16 {
+            state.track_query(id);
+        }
+        assert!(state.match_query(DNS_MAX_PENDING_QUERIES as u16));
+        assert!(state.match_query(0));
+    }
+
     #[test]
     fn test_dns_parse_request_tcp_valid() {
         // A UDP DNS request with the DNS payload starting at byte 42.
@@ -1751,6 +1926,146 @@ mod tests {
         );
     }
 
+    // A TCP stream of two copies of the message, each with its length prefix.
+    fn dns_tcp_stream(msg: &[u8]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for _ in 0..2 {
+            stream.extend_from_slice(&(msg.len() as u16).to_be_bytes());
+            stream.extend_from_slice(msg);
+        }
+        stream
+    }
+
+    // Feed the chunks to the parser the way the app-layer does: data that
+    // was not consumed is passed again with the next chunk, and the parser
+    // is not called until it has the number of bytes it asked for.
+    fn dns_tcp_feed(state: &mut DNSState, chunks: &[&[u8]], direction: u8) {
+        let mut buf: Vec<u8> = Vec::new();
+        let mut needed = 0;
+        for chunk in chunks {
+            buf.extend_from_slice(chunk);
+            if buf.len() < needed {
+                continue;
+            }
+            let slice = StreamSlice::from_slice(&buf, direction, 0);
+            let r = if direction == STREAM_TOSERVER {
+                state.parse_request_tcp(std::ptr::null(), slice)
+            } else {
+                state.parse_response_tcp(std::ptr::null(), slice)
+            };
+            assert_ne!(r, AppLayerResult::err());
+            if r.is_incomplete() {
+                assert!(r.consumed as usize + r.needed as usize > buf.len());
+                buf.drain(..r.consumed as usize);
+                needed = r.needed as usize;
+            } else {
+                buf.clear();
+                needed = 0;
+            }
+        }
+        assert!(buf.is_empty());
+    }
+
+    // A new state for the stream, where the queries of the responses of
+    // the stream were seen.
+    fn dns_tcp_state(msg: &[u8], direction: u8) -> DNSState {
+        let mut state = DNSState::new();
+        if direction == STREAM_TOCLIENT {
+            let id = u16::from_be_bytes([msg[0], msg[1]]);
+            state.track_query(id);
+            state.track_query(id);
+        }
+        state
+    }
+
+    // No event was set on the transactions of the stream.
+    fn dns_no_events(state: &DNSState) -> bool {
+        state.transactions.iter().all(|tx| tx.events == 0)
+    }
+
+    #[rustfmt::skip]
+    const DNS_TCP_SPLIT_REQUEST: &[u8] = &[
+        0x10, 0x32, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x06, 0x67, 0x6F, 0x6F,
+        0x67, 0x6C, 0x65, 0x03, 0x63, 0x6F, 0x6D, 0x00,
+        0x00, 0x10, 0x00, 0x01,
+    ];
+
+    #[rustfmt::skip]
+    const DNS_TCP_SPLIT_RESPONSE: &[u8] = &[
+        0x8d, 0x32, 0x81, 0xa0, 0x00, 0x01, 0x00, 0x03,
+        0x00, 0x00, 0x00, 0x00, 0x03, 0x77, 0x77, 0x77,
+        0x0c, 0x73, 0x75, 0x72, 0x69, 0x63, 0x61, 0x74,
+        0x61, 0x2d, 0x69, 0x64, 0x73, 0x03, 0x6f, 0x72,
+        0x67, 0x00, 0x00, 0x01, 0x00, 0x01, 0xc0, 0x0c,
+        0x00, 0x05, 0x00, 0x01, 0x00, 0x00, 0x0d, 0xd8,
+        0x00, 0x12, 0x0c, 0x73, 0x75, 0x72, 0x69, 0x63,
+        0x61, 0x74, 0x61, 0x2d, 0x69, 0x64, 0x73, 0x03,
+        0x6f, 0x72, 0x67, 0x00, 0xc0, 0x32, 0x00, 0x01,
+        0x00, 0x01, 0x00, 0x00, 0x00, 0xf4, 0x00, 0x04,
+        0xc0, 0x00, 0x4e, 0x18, 0xc0, 0x32, 0x00, 0x01,
+        0x00, 0x01, 0x00, 0x00, 0x00, 0xf4, 0x00, 0x04,
+        0xc0, 0x00, 0x4e, 0x19,
+    ];
+
+    #[test]
+    fn test_dns_tcp_parser_every_split() {
+        for (msg, direction) in [
+            (DNS_TCP_SPLIT_REQUEST, STREAM_TOSERVER),
+            (DNS_TCP_SPLIT_RESPONSE, STREAM_TOCLIENT),
+        ] {
+            let stream = dns_tcp_stream(msg);
+            for split in 1..stream.len() {
+                let mut state = dns_tcp_state(msg, direction);
+                dns_tcp_feed(&mut state, &[&stream[..split], &stream[split..]], direction);
+                assert_eq!(state.transactions.len(), 2, "split at {}", split);
+                assert!(dns_no_events(&state), "split at {}", split);
+            }
+
+            // One byte at a time.
+            let mut state = dns_tcp_state(msg, direction);
+            let chunks: Vec<&[u8]> = stream.chunks(1).collect();
+            dns_tcp_feed(&mut state, &chunks, direction);
+            assert_eq!(state.transactions.len(), 2);
+            assert!(dns_no_events(&state));
+        }
+    }
+
+    #[test]
+    fn test_dns_tcp_parser_gap_split_prefix() {
+        for (msg, direction) in [
+            (DNS_TCP_SPLIT_REQUEST, STREAM_TOSERVER),
+            (DNS_TCP_SPLIT_RESPONSE, STREAM_TOCLIENT),
+        ] {
+            let stream = dns_tcp_stream(msg);
+            // After a gap, data resuming on a message boundary with only
+            // the length prefix, or a part of the header, must not be
+            // discarded.
+            for split in 1..(2 + DNS_HEADER_SIZE) {
+                let mut state = dns_tcp_state(msg, direction);
+                let slice = StreamSlice::from_slice(&stream[..split], direction, 0);
+                let r = if direction == STREAM_TOSERVER {
+                    state.request_gap(10);
+                    state.parse_request_tcp(std::ptr::null(), slice)
+                } else {
+                    state.response_gap(10);
+                    state.parse_response_tcp(std::ptr::null(), slice)
+                };
+                let needed = if split == 1 { 2 } else { msg.len() as u32 + 2 };
+                assert_eq!(
+                    r,
+                    AppLayerResult::incomplete(0, needed),
+                    "split at {}",
+                    split
+                );
+
+                dns_tcp_feed(&mut state, &[&stream], direction);
+                assert_eq!(state.transactions.len(), 2, "split at {}", split);
+                assert!(dns_no_events(&state), "split at {}", split);
+            }
+        }
+    }
+
     #[test]
     fn test_dns_event_from_id() {
         assert_eq!(DNSEvent::from_id(0), Some(DNSEvent::MalformedData));
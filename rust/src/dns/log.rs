@@ -788,6 +788,9 @@ fn log_json(tx: &DNSTransaction, flags: u64, jb: &mut JsonBuilder) -> Result<(),
     let opcode = ((header.flags >> 11) & 0xf) as u8;
     jb.set_uint("opcode", opcode as u64)?;
     jb.set_string("rcode", &dns_rcode_string(header.flags))?;
+    if tx.unsolicited {
+        jb.set_bool("unsolicited", true)?;
+    }
 
     if !message.queries.is_empty() {
         jb.open_array("queries")?;
@@ -31,9 +31,9 @@ use super::parser::{
 
 use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
 use crate::detect::uint::{
-    detect_match_uint, detect_parse_uint_enum, DetectUintData, SCDetectU16Free, SCDetectU16Match,
-    SCDetectU16Parse, SCDetectU32Free, SCDetectU32Match, SCDetectU32Parse, SCDetectU8Free,
-    SCDetectU8Match, SCDetectU8Parse,
+    detect_match_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    SCDetectU16Free, SCDetectU16Match, SCDetectU16Parse, SCDetectU32Free, SCDetectU32Match,
+    SCDetectU32Parse, SCDetectU8Free, SCDetectU8Match, SCDetectU8Parse,
 };
 use crate::detect::{
     helper_keyword_register_sticky_buffer, DetectHelperBufferMpmRegister,
@@ -1420,6 +1420,7 @@ pub unsafe extern "C" fn SCDetectEnipRegister() {
         flags: 0,
     };
     G_ENIP_STATUS_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u32, EnipStatus>(G_ENIP_STATUS_KW_ID);
     G_ENIP_STATUS_BUFFER_ID = DetectHelperBufferRegister(
         b"enip.status\0".as_ptr() as *const libc::c_char,
         ALPROTO_ENIP,
@@ -1510,6 +1511,7 @@ pub unsafe extern "C" fn SCDetectEnipRegister() {
         flags: 0,
     };
     G_ENIP_COMMAND_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u16, EnipCommand>(G_ENIP_COMMAND_KW_ID);
     G_ENIP_COMMAND_BUFFER_ID = DetectHelperBufferRegister(
         b"enip.command\0".as_ptr() as *const libc::c_char,
         ALPROTO_ENIP,
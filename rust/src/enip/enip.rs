@@ -50,6 +50,8 @@ pub struct EnipTransaction {
     pub request: Option<parser::EnipPdu>,
     pub response: Option<parser::EnipPdu>,
     pub done: bool,
+    /// Bits of the EnipEvent set on the transaction
+    events: u32,
 
     tx_data: AppLayerTxData,
 }
@@ -60,6 +62,19 @@ impl Transaction for EnipTransaction {
     }
 }
 
+impl EnipTransaction {
+    fn set_event(&mut self, event: EnipEvent) {
+        let event = event as u8;
+        self.events |= 1 << event;
+        self.tx_data.set_event(event);
+    }
+
+    #[cfg(test)]
+    fn has_event(&self, event: EnipEvent) -> bool {
+        self.events & (1 << (event as u32)) != 0
+    }
+}
+
 #[derive(Default)]
 pub struct EnipState {
     state_data: AppLayerStateData,
@@ -109,60 +124,60 @@ fn process_frames(
     );
     let items = parser::enip_pdu_get_items(pdu);
     for item in items.iter() {
-        let _pdu = Frame::new(
-            flow,
-            stream_slice,
-            &input[item.start..],
-            4 + item.item_length as i64,
-            EnipFrameType::EnipItem as u8,
-            tx_id,
-        );
+        if let Some(item_start) = input.get(item.start..) {
+            let _pdu = Frame::new(
+                flow,
+                stream_slice,
+                item_start,
+                4 + item.item_length as i64,
+                EnipFrameType::EnipItem as u8,
+                tx_id,
+            );
+        }
     }
     if let parser::EnipPayload::Cip(c) = &pdu.payload {
         for item in c.items.iter() {
             if let parser::EnipItemPayload::Data(d) = &item.payload {
-                let _pdu = Frame::new(
-                    flow,
-                    stream_slice,
-                    &input[item.cip_offset..],
-                    item.item_length as i64,
-                    EnipFrameType::Cip as u8,
-                    tx_id,
-                );
-                match &d.cip.cipdir {
-                    parser::CipDir::Request(req) => {
-                        if let parser::EnipCipRequestPayload::Multiple(m) = &req.payload {
-                            for i in 0..m.packet_list.len() {
-                                let _pdu = Frame::new(
-                                    flow,
-                                    stream_slice,
-                                    &input[item.cip_offset
-                                        + m.offset_from_cip
-                                        + (m.offset_list[i] as usize)..],
-                                    m.size_list[i] as i64,
-                                    EnipFrameType::Cip as u8,
-                                    tx_id,
-                                );
-                            }
-                        }
-                    }
-                    parser::CipDir::Response(resp) => {
-                        if let parser::EnipCipResponsePayload::Multiple(m) = &resp.payload {
-                            for i in 0..m.packet_list.len() {
-                                let _pdu = Frame::new(
-                                    flow,
-                                    stream_slice,
-                                    &input[item.cip_offset
-                                        + m.offset_from_cip
-                                        + (m.offset_list[i] as usize)..],
-                                    m.size_list[i] as i64,
-                                    EnipFrameType::Cip as u8,
-                                    tx_id,
-                                );
-                            }
+                if let Some(cip_start) = input.get(item.cip_offset..) {
+                    let _pdu = Frame::new(
+                        flow,
+                        stream_slice,
+                        cip_start,
+                        item.item_length as i64,
+                        EnipFrameType::Cip as u8,
+                        tx_id,
+                    );
+                }
+                let multiple = match &d.cip.cipdir {
+                    parser::CipDir::Request(req) => match &req.payload {
+                        parser::EnipCipRequestPayload::Multiple(m) => Some(m),
+                        _ => None,
+                    },
+                    parser::CipDir::Response(resp) => match &resp.payload {
+                        parser::EnipCipResponsePayload::Multiple(m) => Some(m),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(m) = multiple {
+                    for (offset, size) in m.offset_list.iter().zip(m.size_list.iter()) {
+                        // offsets come from the packet, do not trust them
+                        let packet_start = item
+                            .cip_offset
+                            .checked_add(m.offset_from_cip)
+                            .and_then(|o| o.checked_add(*offset as usize))
+                            .and_then(|o| input.get(o..));
+                        if let Some(packet_start) = packet_start {
+                            let _pdu = Frame::new(
+                                flow,
+                                stream_slice,
+                                packet_start,
+                                *size as i64,
+                                EnipFrameType::Cip as u8,
+                                tx_id,
+                            );
                         }
                     }
-                    _ => {}
                 }
             }
         }
@@ -203,17 +218,25 @@ impl EnipState {
         return tx;
     }
 
-    fn purge_tx_flood(&mut self) {
+    /// Close all the transactions when there are too many of them. The
+    /// PDU causing it gets no transaction, so if it is invalid, the event
+    /// is set on the most recent transaction.
+    fn purge_tx_flood(&mut self, invalid_pdu: bool) {
         let mut event_set = false;
         for tx in self.transactions.iter_mut() {
             tx.tx_data.updated_tc = true;
             tx.tx_data.updated_ts = true;
             tx.done = true;
             if !event_set {
-                tx.tx_data.set_event(EnipEvent::TooManyTransactions as u8);
+                tx.set_event(EnipEvent::TooManyTransactions);
                 event_set = true;
             }
         }
+        if invalid_pdu {
+            if let Some(tx) = self.transactions.back_mut() {
+                tx.set_event(EnipEvent::InvalidPdu);
+            }
+        }
     }
 
     fn find_request(&mut self, pdu: &parser::EnipPdu) -> Option<&mut EnipTransaction> {
@@ -242,7 +265,7 @@ impl EnipState {
                     if let Some(tx) = self.find_request(&pdu) {
                         process_frames(&pdu, &stream_slice, flow, input, Some(tx.tx_id - 1));
                         if pdu.invalid {
-                            tx.tx_data.set_event(EnipEvent::InvalidPdu as u8);
+                            tx.set_event(EnipEvent::InvalidPdu);
                         }
                         tx.response = Some(pdu);
                         return AppLayerResult::ok();
@@ -250,11 +273,11 @@ impl EnipState {
                 }
                 if self.transactions.len() >= unsafe { ENIP_MAX_TX } {
                     process_frames(&pdu, &stream_slice, flow, input, None);
-                    self.purge_tx_flood();
+                    self.purge_tx_flood(pdu.invalid);
                 } else {
                     let mut tx = self.new_tx();
                     if pdu.invalid {
-                        tx.tx_data.set_event(EnipEvent::InvalidPdu as u8);
+                        tx.set_event(EnipEvent::InvalidPdu);
                     }
                     process_frames(&pdu, &stream_slice, flow, input, Some(tx.tx_id - 1));
                     if request {
@@ -266,8 +289,18 @@ impl EnipState {
                 }
                 return AppLayerResult::ok();
             }
-            Err(_) => {
-                return AppLayerResult::err();
+            Err(_e) => {
+                // one malformed datagram must not stop parsing of the flow
+                SCLogDebug!("ENIP: invalid datagram {:?}", _e);
+                if self.transactions.len() >= unsafe { ENIP_MAX_TX } {
+                    self.purge_tx_flood(true);
+                } else {
+                    let mut tx = self.new_tx();
+                    tx.done = true;
+                    tx.set_event(EnipEvent::InvalidPdu);
+                    self.transactions.push_back(tx);
+                }
+                return AppLayerResult::ok();
             }
         }
     }
@@ -296,7 +329,7 @@ impl EnipState {
                         if let Some(tx) = self.find_request(&pdu) {
                             process_frames(&pdu, &stream_slice, flow, start, Some(tx.tx_id - 1));
                             if pdu.invalid {
-                                tx.tx_data.set_event(EnipEvent::InvalidPdu as u8);
+                                tx.set_event(EnipEvent::InvalidPdu);
                             }
                             tx.response = Some(pdu);
                             start = rem;
@@ -305,12 +338,12 @@ impl EnipState {
                     }
                     if self.transactions.len() >= unsafe { ENIP_MAX_TX } {
                         process_frames(&pdu, &stream_slice, flow, start, None);
-                        self.purge_tx_flood();
+                        self.purge_tx_flood(pdu.invalid);
                     } else {
                         let mut tx = self.new_tx();
                         process_frames(&pdu, &stream_slice, flow, start, Some(tx.tx_id - 1));
                         if pdu.invalid {
-                            tx.tx_data.set_event(EnipEvent::InvalidPdu as u8);
+                            tx.set_event(EnipEvent::InvalidPdu);
                         }
                         if request {
                             tx.request = Some(pdu);
@@ -676,3 +709,91 @@ pub unsafe extern "C" fn SCEnipRegisterParsers() {
         SCLogDebug!("Protocol detector and parser disabled for ENIP on TCP.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enip_header(cmd: u16, pdulen: u16) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&cmd.to_le_bytes());
+        buf.extend_from_slice(&pdulen.to_le_bytes());
+        buf.extend_from_slice(&[0; 20]); // session, status, context, options
+        buf
+    }
+
+    fn parse_udp(state: &mut EnipState, input: &[u8]) -> AppLayerResult {
+        let stream_slice = StreamSlice::from_slice(input, STREAM_TOSERVER, 0);
+        state.parse_udp(stream_slice, true, std::ptr::null())
+    }
+
+    #[test]
+    fn test_enip_item_length_exceeds_pdu() {
+        let mut input = enip_header(EnipCommand::SendRRData.into_u(), 18);
+        input.extend_from_slice(&[0, 0, 0, 0, 0, 0]); // handle, timeout
+        input.extend_from_slice(&[2, 0]); // two items
+        input.extend_from_slice(&[0, 0, 0, 0]); // null address item
+        input.extend_from_slice(&[0xb2, 0, 0, 1]); // unconnected data, 256 bytes
+        input.extend_from_slice(&[0x0e, 0x03]);
+
+        let mut state = EnipState::new();
+        assert_eq!(parse_udp(&mut state, &input), AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        assert!(state.transactions[0].request.as_ref().unwrap().invalid);
+    }
+
+    #[test]
+    fn test_enip_multiple_service_bad_offset() {
+        let mut input = enip_header(EnipCommand::SendRRData.into_u(), 24);
+        input.extend_from_slice(&[0, 0, 0, 0, 0, 0]); // handle, timeout
+        input.extend_from_slice(&[2, 0]); // two items
+        input.extend_from_slice(&[0, 0, 0, 0]); // null address item
+        input.extend_from_slice(&[0xb2, 0, 8, 0]); // unconnected data
+
+        // multiple service request to the message router, with one
+        // service at an offset past the end of the item
+        input.extend_from_slice(&[0x0a, 0x01, 0x20, 0x02, 0x01, 0x00, 0xff, 0xff]);
+
+        let mut state = EnipState::new();
+        assert_eq!(parse_udp(&mut state, &input), AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        assert!(state.transactions[0].request.as_ref().unwrap().invalid);
+    }
+
+    #[test]
+    fn test_enip_udp_truncated_datagram() {
+        // pdu length larger than the datagram
+        let mut truncated = enip_header(EnipCommand::SendRRData.into_u(), 100);
+        truncated.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 0]);
+        let list_identity = enip_header(EnipCommand::ListIdentity.into_u(), 0);
+
+        let mut state = EnipState::new();
+        assert_eq!(parse_udp(&mut state, &truncated), AppLayerResult::ok());
+        assert_eq!(parse_udp(&mut state, &list_identity), AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].request.is_none());
+        assert!(state.transactions[0].done);
+        assert!(state.transactions[1].request.is_some());
+    }
+
+    #[test]
+    fn test_enip_udp_flood_invalid_pdu() {
+        let mut truncated = enip_header(EnipCommand::SendRRData.into_u(), 100);
+        truncated.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 0]);
+        let list_identity = enip_header(EnipCommand::ListIdentity.into_u(), 0);
+
+        let mut state = EnipState::new();
+        for _ in 0..unsafe { ENIP_MAX_TX } {
+            assert_eq!(parse_udp(&mut state, &list_identity), AppLayerResult::ok());
+        }
+        assert!(state.transactions.iter().all(|tx| tx.events == 0));
+
+        // no room for a transaction for the invalid datagram
+        assert_eq!(parse_udp(&mut state, &truncated), AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), unsafe { ENIP_MAX_TX });
+        assert!(state.transactions[0].has_event(EnipEvent::TooManyTransactions));
+        let last = state.transactions.back().unwrap();
+        assert!(last.done);
+        assert!(last.has_event(EnipEvent::InvalidPdu));
+    }
+}
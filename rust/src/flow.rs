@@ -19,6 +19,7 @@
 /// cbindgen:ignore
 extern "C" {
     fn FlowGetLastTimeAsParts(flow: &Flow, secs: *mut u64, usecs: *mut u64);
+    fn FlowGetStartTimeAsParts(flow: &Flow, secs: *mut u64, usecs: *mut u64);
     fn FlowGetFlags(flow: &Flow) -> u32;
     fn FlowGetSourcePort(flow: &Flow) -> u16;
     fn FlowGetDestinationPort(flow: &Flow) -> u16;
@@ -27,6 +28,11 @@ extern "C" {
 // Flow flags
 pub const FLOW_DIR_REVERSED: u32 = BIT_U32!(26);
 
+// Flow upgrade record flags
+pub const FLOW_UPGRADE_REQUESTED: u8 = BIT_U8!(0);
+pub const FLOW_UPGRADE_ACCEPTED: u8 = BIT_U8!(1);
+pub const FLOW_UPGRADE_REFUSED: u8 = BIT_U8!(2);
+
 /// Opaque flow type (defined in C)
 pub enum Flow {}
 
@@ -43,6 +49,17 @@ impl Flow {
         }
     }
 
+    /// Return the time of the first packet of the flow as a `Duration`
+    /// since the epoch.
+    pub fn get_start_time(&mut self) -> std::time::Duration {
+        unsafe {
+            let mut secs: u64 = 0;
+            let mut usecs: u64 = 0;
+            FlowGetStartTimeAsParts(self, &mut secs, &mut usecs);
+            std::time::Duration::new(secs, usecs as u32 * 1000)
+        }
+    }
+
     /// Return the flow flags.
     pub fn get_flags(&self) -> u32 {
         unsafe { FlowGetFlags(self) }
@@ -73,7 +73,7 @@ fn http2_tx_has_errorcode(
 ) -> std::os::raw::c_int {
     if direction == Direction::ToServer {
         for i in 0..tx.frames_ts.len() {
-            match tx.frames_ts[i].data {
+            match &tx.frames_ts[i].data {
                 HTTP2FrameTypeData::GOAWAY(goaway) => {
                     if goaway.errorcode == code {
                         return 1;
@@ -89,7 +89,7 @@ fn http2_tx_has_errorcode(
         }
     } else {
         for i in 0..tx.frames_tc.len() {
-            match tx.frames_tc[i].data {
+            match &tx.frames_tc[i].data {
                 HTTP2FrameTypeData::GOAWAY(goaway) => {
                     if goaway.errorcode == code {
                         return 1;
@@ -224,6 +224,70 @@ pub unsafe extern "C" fn SCHttp2TxGetNextWindow(
     return http2_tx_get_next_window(tx, direction.into(), nb);
 }
 
+fn http2_tx_goaways(
+    tx: &HTTP2Transaction, direction: Direction,
+) -> impl Iterator<Item = &parser::HTTP2FrameGoAway> {
+    let frames = if direction == Direction::ToServer {
+        &tx.frames_ts
+    } else {
+        &tx.frames_tc
+    };
+    frames.iter().filter_map(|frame| match &frame.data {
+        HTTP2FrameTypeData::GOAWAY(goaway) => Some(goaway),
+        _ => None,
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCHttp2TxGetNextGoAwayLastStreamId(
+    tx: *mut std::os::raw::c_void, direction: u8, nb: u32,
+) -> std::os::raw::c_int {
+    let tx = cast_pointer!(tx, HTTP2Transaction);
+    // the reserved bit is masked out, so the identifier fits
+    match http2_tx_goaways(tx, direction.into()).nth(nb as usize) {
+        Some(goaway) => goaway.last_stream_id as i32,
+        None => -1,
+    }
+}
+
+/// Gets the debug data of the GOAWAY frames carrying some, one per buffer
+#[no_mangle]
+pub unsafe extern "C" fn SCHttp2TxGetGoAwayDebugData(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, direction: u8, nb: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, HTTP2Transaction);
+    if let Some(goaway) = http2_tx_goaways(tx, direction.into())
+        .filter(|goaway| !goaway.debug_data.is_empty())
+        .nth(nb as usize)
+    {
+        *buffer = goaway.debug_data.as_ptr();
+        *buffer_len = goaway.debug_data.len() as u32;
+        return true;
+    }
+    return false;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCHttp2TxGetStreamId(tx: *mut std::os::raw::c_void) -> u32 {
+    let tx = cast_pointer!(tx, HTTP2Transaction);
+    return tx.stream_id;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCHttp2StateGetMaxConcurrentStreams(
+    state: *mut std::os::raw::c_void,
+) -> u32 {
+    let state = cast_pointer!(state, HTTP2State);
+    return state.max_open_streams;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCHttp2StateGetRstCount(state: *mut std::os::raw::c_void) -> u32 {
+    let state = cast_pointer!(state, HTTP2State);
+    return state.rst_count;
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn SCHttp2DetectSettingsCtxParse(
     str: *const std::os::raw::c_char,
@@ -619,29 +683,28 @@ fn http2_lower(value: &[u8]) -> Option<Vec<u8>> {
     return None;
 }
 
-// returns a tuple with the value and its size
+/// Extracts the host from an authority the way HTTP/1 host normalization
+/// does: userinfo and port are stripped, and so are trailing dots. IPv6
+/// literals keep their brackets. Lowercasing is left to the caller.
 fn http2_normalize_host(value: &[u8]) -> &[u8] {
-    match value.iter().position(|&x| x == b'@') {
-        Some(i) => {
-            let value = &value[i + 1..];
-            match value.iter().position(|&x| x == b':') {
-                Some(i) => {
-                    return &value[..i];
-                }
-                None => {
-                    return value;
-                }
-            }
+    // a host cannot contain '@', so it follows the last one
+    let value = match value.iter().rposition(|&x| x == b'@') {
+        Some(i) => &value[i + 1..],
+        None => value,
+    };
+    let end = if value.first() == Some(&b'[') {
+        match value.iter().position(|&x| x == b']') {
+            Some(i) => i + 1,
+            None => value.len(),
         }
-        None => match value.iter().position(|&x| x == b':') {
-            Some(i) => {
-                return &value[..i];
-            }
-            None => {
-                return value;
-            }
-        },
+    } else {
+        value.iter().position(|&x| x == b':').unwrap_or(value.len())
+    };
+    let mut host = &value[..end];
+    while let Some((b'.', rest)) = host.split_last() {
+        host = rest;
     }
+    return host;
 }
 
 #[no_mangle]
@@ -650,8 +713,6 @@ pub unsafe extern "C" fn SCHttp2TxGetHostNorm(
 ) -> u8 {
     if let Ok(value) = http2_frames_get_header_value(tx, Direction::ToServer, ":authority") {
         let r = http2_normalize_host(value);
-        // r is a tuple with the value and its size
-        // this is useful when we only take a substring (before the port)
         match http2_lower(r) {
             Some(normval) => {
                 // In case we needed some normalization,
@@ -1029,6 +1090,7 @@ pub unsafe extern "C" fn SCHttp2TxAddHeader(
 mod tests {
 
     use super::*;
+    use crate::detect::uint::detect_parse_uint;
 
     #[test]
     fn test_http2_normalize_host() {
@@ -1049,6 +1111,123 @@ mod tests {
         assert_eq!(r4, "localhost".as_bytes().to_vec());
     }
 
+    #[test]
+    fn test_http2_normalize_host_bypass() {
+        // each authority must give the host HTTP/1 normalization gives for
+        // the same Host header or URI authority
+        let cases: [(&str, &str); 9] = [
+            ("user@evil.com", "evil.com"),
+            ("user@EVIL.com:443", "EVIL.com"),
+            ("a@b@evil.com", "evil.com"),
+            ("example.com.", "example.com"),
+            ("example.com..:8080", "example.com"),
+            ("user:pass@example.com.:443", "example.com"),
+            ("[::1]:8443", "[::1]"),
+            ("[2001:db8::1]", "[2001:db8::1]"),
+            ("...", ""),
+        ];
+        for (authority, host) in cases {
+            assert_eq!(
+                http2_normalize_host(authority.as_bytes()),
+                host.as_bytes(),
+                "{}",
+                authority
+            );
+        }
+    }
+
+    #[test]
+    fn test_http2_host_norm_buffer() {
+        let mut tx = HTTP2Transaction::new();
+        let head = parser::HTTP2FrameHeaders {
+            padlength: None,
+            priority: None,
+            blocks: vec![parser::HTTP2FrameHeaderBlock {
+                name: b":authority".to_vec().into(),
+                value: b"admin@Example.COM.:443".to_vec().into(),
+                error: parser::HTTP2HeaderDecodeStatus::HTTP2HeaderDecodeSuccess,
+                sizeupdate: 0,
+            }],
+        };
+        tx.frames_ts.push(HTTP2Frame {
+            header: parser::HTTP2FrameHeader {
+                length: 0,
+                ftype: parser::HTTP2FrameType::Headers as u8,
+                flags: 0,
+                reserved: 0,
+                stream_id: 1,
+            },
+            data: HTTP2FrameTypeData::HEADERS(head),
+        });
+
+        let mut buffer: *const u8 = std::ptr::null();
+        let mut buffer_len: u32 = 0;
+        unsafe {
+            assert_eq!(
+                SCHttp2TxGetHostNorm(&mut tx, &mut buffer, &mut buffer_len),
+                1
+            );
+            let norm = std::slice::from_raw_parts(buffer, buffer_len as usize);
+            assert_eq!(norm, b"example.com");
+            // the raw buffer keeps the original authority
+            assert_eq!(SCHttp2TxGetHost(&mut tx, &mut buffer, &mut buffer_len), 1);
+            let raw = std::slice::from_raw_parts(buffer, buffer_len as usize);
+            assert_eq!(raw, b"admin@Example.COM.:443");
+        }
+    }
+
+    #[test]
+    fn test_http2_goaway_getters() {
+        let mut tx = HTTP2Transaction::new();
+        for (last_stream_id, debug_data) in [(3, &b""[..]), (7, &b"too many resets"[..])] {
+            tx.frames_tc.push(HTTP2Frame {
+                header: parser::HTTP2FrameHeader {
+                    length: 8 + debug_data.len() as u32,
+                    ftype: parser::HTTP2FrameType::GoAway as u8,
+                    flags: 0,
+                    reserved: 0,
+                    stream_id: 0,
+                },
+                data: HTTP2FrameTypeData::GOAWAY(parser::HTTP2FrameGoAway {
+                    last_stream_id,
+                    errorcode: 0,
+                    debug_data: debug_data.to_vec(),
+                }),
+            });
+        }
+        let tx_ptr = &mut tx as *mut HTTP2Transaction as *mut c_void;
+        let mut buffer: *const u8 = std::ptr::null();
+        let mut buffer_len: u32 = 0;
+        unsafe {
+            let dir = Direction::ToClient as u8;
+            assert_eq!(SCHttp2TxGetNextGoAwayLastStreamId(tx_ptr, dir, 0), 3);
+            assert_eq!(SCHttp2TxGetNextGoAwayLastStreamId(tx_ptr, dir, 1), 7);
+            assert_eq!(SCHttp2TxGetNextGoAwayLastStreamId(tx_ptr, dir, 2), -1);
+            let to_server = Direction::ToServer as u8;
+            assert_eq!(SCHttp2TxGetNextGoAwayLastStreamId(tx_ptr, to_server, 0), -1);
+
+            // the frame without debug data gives no buffer
+            assert!(SCHttp2TxGetGoAwayDebugData(
+                std::ptr::null_mut(),
+                tx_ptr,
+                dir,
+                0,
+                &mut buffer,
+                &mut buffer_len
+            ));
+            let data = std::slice::from_raw_parts(buffer, buffer_len as usize);
+            assert_eq!(data, b"too many resets");
+            assert!(!SCHttp2TxGetGoAwayDebugData(
+                std::ptr::null_mut(),
+                tx_ptr,
+                dir,
+                1,
+                &mut buffer,
+                &mut buffer_len
+            ));
+        }
+    }
+
     #[test]
     fn test_http2_header_trimspaces() {
         let buf0 = "nospaces".as_bytes();
@@ -1106,4 +1285,20 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_http2_stream_id() {
+        let mut tx = HTTP2Transaction::new();
+        tx.stream_id = 3;
+        let txp = &mut tx as *mut HTTP2Transaction as *mut c_void;
+        let stream_id = unsafe { SCHttp2TxGetStreamId(txp) };
+        let stream_id_match = |rule: &str| {
+            let (_, ctx) = detect_parse_uint::<u32>(rule).unwrap();
+            detect_match_uint(&ctx, stream_id)
+        };
+        assert!(stream_id_match("3"));
+        assert!(stream_id_match(">1"));
+        assert!(!stream_id_match("1"));
+        assert!(!stream_id_match("<3"));
+    }
 }
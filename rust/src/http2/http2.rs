@@ -64,7 +64,7 @@ pub enum HTTP2ConnectionState {
 
 const HTTP2_FRAME_HEADER_LEN: usize = 9;
 const HTTP2_MAGIC_LEN: usize = 24;
-const HTTP2_FRAME_GOAWAY_LEN: usize = 4;
+const HTTP2_FRAME_GOAWAY_LEN: usize = 8;
 const HTTP2_FRAME_RSTSTREAM_LEN: usize = 4;
 const HTTP2_FRAME_PRIORITY_LEN: usize = 5;
 const HTTP2_FRAME_WINDOWUPDATE_LEN: usize = 4;
@@ -152,12 +152,25 @@ pub struct DohHttp2Tx {
     pub dns_response_tx: Option<DNSTransaction>,
 }
 
+/// Stream counters of a connection, logged when the connection ends
+#[derive(Debug, Clone, Copy)]
+pub struct HTTP2ConnStats {
+    /// Highest number of streams open at the same time
+    pub max_concurrent_streams: u32,
+    /// Number of RST_STREAM frames, in both directions
+    pub rst_count: u32,
+}
+
 #[derive(Debug)]
 pub struct HTTP2Transaction {
     tx_id: u64,
     pub stream_id: u32,
     pub state: HTTP2TransactionState,
     child_stream_id: u32,
+    /// the stream is counted in the open streams of the connection
+    stream_open: bool,
+    /// Events set on the transaction, as a bitmask of HTTP2Event
+    events: u32,
 
     pub frames_tc: Vec<HTTP2Frame>,
     pub frames_ts: Vec<HTTP2Frame>,
@@ -176,6 +189,8 @@ pub struct HTTP2Transaction {
     pub resp_line: Vec<u8>,
 
     pub doh: Option<DohHttp2Tx>,
+    /// set on the transaction created at the end of the connection
+    pub conn_stats: Option<HTTP2ConnStats>,
 }
 
 impl Transaction for HTTP2Transaction {
@@ -196,6 +211,8 @@ impl HTTP2Transaction {
             tx_id: 0,
             stream_id: 0,
             child_stream_id: 0,
+            stream_open: false,
+            events: 0,
             state: HTTP2TransactionState::HTTP2StateIdle,
             frames_tc: Vec::new(),
             frames_ts: Vec::new(),
@@ -208,6 +225,7 @@ impl HTTP2Transaction {
             req_line: Vec::new(),
             resp_line: Vec::new(),
             doh: None,
+            conn_stats: None,
         }
     }
 
@@ -232,7 +250,14 @@ impl HTTP2Transaction {
     }
 
     pub fn set_event(&mut self, event: HTTP2Event) {
-        self.tx_data.set_event(event as u8);
+        let event = event as u8;
+        self.events |= 1 << event;
+        self.tx_data.set_event(event);
+    }
+
+    #[cfg(test)]
+    fn has_event(&self, event: HTTP2Event) -> bool {
+        self.events & (1 << (event as u32)) != 0
     }
 
     fn handle_headers(
@@ -468,6 +493,17 @@ impl HTTP2Transaction {
         return r;
     }
 
+    /// Stops counting the stream as open once it is reset or closed in
+    /// both directions. To be called after handle_frame which does the
+    /// state transition for the frame.
+    fn close_stream(&mut self, is_rst: bool) -> bool {
+        if self.stream_open && (is_rst || self.state == HTTP2TransactionState::HTTP2StateClosed) {
+            self.stream_open = false;
+            return true;
+        }
+        return false;
+    }
+
     fn handle_dns_data(&mut self, dir: Direction, flow: *const Flow) {
         if let Some(doh) = &mut self.doh {
             if !doh.data_buf[dir.index()].is_empty() {
@@ -521,6 +557,7 @@ pub enum HTTP2Event {
     ReassemblyLimitReached,
     DnsRequestTooLong,
     DnsResponseTooLong,
+    InvalidStreamIdParity,
 }
 
 pub struct HTTP2DynTable {
@@ -553,6 +590,13 @@ struct HTTP2HeaderReassemblyBuffer {
     stream_id: u32,
 }
 
+/// These frames can be received on a closed stream for a short period.
+fn frame_allowed_on_closed_stream(ftype: u8) -> bool {
+    ftype == parser::HTTP2FrameType::RstStream as u8
+        || ftype == parser::HTTP2FrameType::WindowUpdate as u8
+        || ftype == parser::HTTP2FrameType::Priority as u8
+}
+
 pub struct HTTP2State {
     state_data: AppLayerStateData,
     tx_id: u64,
@@ -562,6 +606,13 @@ pub struct HTTP2State {
     dynamic_headers_tc: HTTP2DynTable,
     transactions: VecDeque<HTTP2Transaction>,
     progress: HTTP2ConnectionState,
+    // highest stream ids seen for client and server initiated streams
+    max_stream_id_client: u32,
+    max_stream_id_server: u32,
+    open_streams: u32,
+    pub max_open_streams: u32,
+    pub rst_count: u32,
+    conn_stats_logged: bool,
 
     c2s_buf: HTTP2HeaderReassemblyBuffer,
     s2c_buf: HTTP2HeaderReassemblyBuffer,
@@ -597,6 +648,12 @@ impl HTTP2State {
             dynamic_headers_tc: HTTP2DynTable::new(),
             transactions: VecDeque::new(),
             progress: HTTP2ConnectionState::Http2StateInit,
+            max_stream_id_client: 0,
+            max_stream_id_server: 0,
+            open_streams: 0,
+            max_open_streams: 0,
+            rst_count: 0,
+            conn_stats_logged: false,
             c2s_buf: HTTP2HeaderReassemblyBuffer::default(),
             s2c_buf: HTTP2HeaderReassemblyBuffer::default(),
         }
@@ -632,7 +689,7 @@ impl HTTP2State {
             return;
         }
         let tx = &mut self.transactions[len - 1];
-        tx.tx_data.set_event(event as u8);
+        tx.set_event(event);
     }
 
     // Free a transaction by ID.
@@ -667,6 +724,10 @@ impl HTTP2State {
             }
         }
         if found {
+            if self.transactions[index].stream_open {
+                // dropped before it was closed
+                self.open_streams -= 1;
+            }
             self.transactions.remove(index);
         }
     }
@@ -721,6 +782,44 @@ impl HTTP2State {
         return self.transactions.back_mut().unwrap();
     }
 
+    /// Counts the stream of a new transaction as open.
+    fn open_stream(&mut self, tx: &mut HTTP2Transaction) {
+        tx.stream_open = true;
+        self.open_streams += 1;
+        self.max_open_streams = self.max_open_streams.max(self.open_streams);
+    }
+
+    fn check_new_stream_id(
+        &mut self, tx: &mut HTTP2Transaction, header: &parser::HTTP2FrameHeader,
+        data: &HTTP2FrameTypeData, dir: Direction,
+    ) {
+        let sid = tx.stream_id;
+        // client initiated streams use odd ids, server pushed ones even ids
+        let client_initiated = sid % 2 == 1;
+        match data {
+            HTTP2FrameTypeData::PUSHPROMISE(_) if client_initiated => {
+                tx.set_event(HTTP2Event::InvalidStreamIdParity);
+            }
+            HTTP2FrameTypeData::HEADERS(_) if dir == Direction::ToServer && !client_initiated => {
+                tx.set_event(HTTP2Event::InvalidStreamIdParity);
+            }
+            _ => {}
+        }
+        let max = if client_initiated {
+            &mut self.max_stream_id_client
+        } else {
+            &mut self.max_stream_id_server
+        };
+        if sid <= *max {
+            // the stream was closed and freed already : it must not be used again
+            if !frame_allowed_on_closed_stream(header.ftype) {
+                tx.set_event(HTTP2Event::StreamIdReuse);
+            }
+        } else {
+            *max = sid;
+        }
+    }
+
     pub fn find_or_create_tx(
         &mut self, header: &parser::HTTP2FrameHeader, data: &HTTP2FrameTypeData, dir: Direction,
     ) -> Option<&mut HTTP2Transaction> {
@@ -754,14 +853,10 @@ impl HTTP2State {
         };
         let index = self.find_tx_index(sid);
         if index > 0 {
-            if self.transactions[index - 1].state == HTTP2TransactionState::HTTP2StateClosed {
-                //these frames can be received in this state for a short period
-                if header.ftype != parser::HTTP2FrameType::RstStream as u8
-                    && header.ftype != parser::HTTP2FrameType::WindowUpdate as u8
-                    && header.ftype != parser::HTTP2FrameType::Priority as u8
-                {
-                    self.set_event(HTTP2Event::StreamIdReuse);
-                }
+            if self.transactions[index - 1].state == HTTP2TransactionState::HTTP2StateClosed
+                && !frame_allowed_on_closed_stream(header.ftype)
+            {
+                self.set_event(HTTP2Event::StreamIdReuse);
             }
 
             let tx = &mut self.transactions[index - 1];
@@ -791,6 +886,10 @@ impl HTTP2State {
             tx.tx_id = self.tx_id;
             tx.stream_id = sid;
             tx.state = HTTP2TransactionState::HTTP2StateOpen;
+            if let HTTP2FrameTypeData::HEADERS(_) | HTTP2FrameTypeData::PUSHPROMISE(_) = data {
+                self.open_stream(&mut tx);
+            }
+            self.check_new_stream_id(&mut tx, header, data, dir);
             tx.tx_data.update_file_flags(self.state_data.file_flags);
             tx.update_file_flags(tx.tx_data.file_flags);
             tx.tx_data.file_tx = STREAM_TOSERVER | STREAM_TOCLIENT; // might hold files in both directions
@@ -1222,6 +1321,8 @@ impl HTTP2State {
                             }
                         }
                     }
+                    let is_rst = head.ftype == parser::HTTP2FrameType::RstStream as u8;
+                    let stream_closed = tx.close_stream(is_rst);
                     if reass_limit_reached {
                         tx.tx_data
                             .set_event(HTTP2Event::ReassemblyLimitReached as u8);
@@ -1241,6 +1342,12 @@ impl HTTP2State {
                             data: txdata,
                         });
                     }
+                    if stream_closed {
+                        self.open_streams -= 1;
+                    }
+                    if is_rst {
+                        self.rst_count = self.rst_count.saturating_add(1);
+                    }
                     if ftype == parser::HTTP2FrameType::Data as u8 {
                         match unsafe { SURICATA_HTTP2_FILE_CONFIG } {
                             Some(sfcm) => {
@@ -1300,6 +1407,25 @@ impl HTTP2State {
         return AppLayerResult::ok();
     }
 
+    /// At the end of the connection, create a global transaction to log the
+    /// stream counters, once for both directions.
+    fn log_conn_stats(&mut self) {
+        let no_streams = self.max_stream_id_client == 0 && self.max_stream_id_server == 0;
+        if self.conn_stats_logged || no_streams {
+            return;
+        }
+        if self.transactions.len() >= unsafe { HTTP2_MAX_STREAMS } {
+            return;
+        }
+        self.conn_stats_logged = true;
+        let stats = HTTP2ConnStats {
+            max_concurrent_streams: self.max_open_streams,
+            rst_count: self.rst_count,
+        };
+        let tx = self.create_global_tx();
+        tx.conn_stats = Some(stats);
+    }
+
     fn parse_ts(&mut self, flow: *const Flow, stream_slice: StreamSlice) -> AppLayerResult {
         //very first : skip magic
         let mut input = stream_slice.as_slice();
@@ -1461,6 +1587,10 @@ unsafe extern "C" fn http2_parse_ts(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, HTTP2State);
+    if stream_slice.is_empty() && stream_slice.flags() & STREAM_EOF != 0 {
+        state.log_conn_stats();
+        return AppLayerResult::ok();
+    }
     return state.parse_ts(flow, stream_slice);
 }
 
@@ -1469,6 +1599,10 @@ unsafe extern "C" fn http2_parse_tc(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, HTTP2State);
+    if stream_slice.is_empty() && stream_slice.flags() & STREAM_EOF != 0 {
+        state.log_conn_stats();
+        return AppLayerResult::ok();
+    }
     return state.parse_tc(flow, stream_slice);
 }
 
@@ -1617,3 +1751,151 @@ pub unsafe extern "C" fn SCRegisterHttp2Parser() {
         SCLogNotice!("Protocol detector and parser disabled for DOH2.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_header(ftype: parser::HTTP2FrameType, sid: u32) -> parser::HTTP2FrameHeader {
+        parser::HTTP2FrameHeader {
+            length: 0,
+            ftype: ftype as u8,
+            flags: 0,
+            reserved: 0,
+            stream_id: sid,
+        }
+    }
+
+    fn headers() -> HTTP2FrameTypeData {
+        HTTP2FrameTypeData::HEADERS(parser::HTTP2FrameHeaders {
+            padlength: None,
+            priority: None,
+            blocks: Vec::new(),
+        })
+    }
+
+    /// Runs the stream id checks on a new transaction for a HEADERS frame.
+    fn new_stream(state: &mut HTTP2State, sid: u32, dir: Direction) -> HTTP2Transaction {
+        let mut tx = HTTP2Transaction::new();
+        tx.stream_id = sid;
+        tx.state = HTTP2TransactionState::HTTP2StateOpen;
+        let header = frame_header(parser::HTTP2FrameType::Headers, sid);
+        state.check_new_stream_id(&mut tx, &header, &headers(), dir);
+        tx
+    }
+
+    #[test]
+    fn test_http2_open_streams() {
+        let mut state = HTTP2State::new();
+        let mut tx1 = HTTP2Transaction::new();
+        let mut tx3 = HTTP2Transaction::new();
+        state.open_stream(&mut tx1);
+        state.open_stream(&mut tx3);
+        assert_eq!(state.open_streams, 2);
+        assert_eq!(state.max_open_streams, 2);
+
+        // half closed streams are still open
+        tx1.state = HTTP2TransactionState::HTTP2StateHalfClosedClient;
+        assert!(!tx1.close_stream(false));
+        tx1.state = HTTP2TransactionState::HTTP2StateClosed;
+        assert!(tx1.close_stream(false));
+        // a closed stream is not closed twice
+        assert!(!tx1.close_stream(false));
+
+        // a stream freed before it was closed
+        tx3.tx_id = 1;
+        state.transactions.push_back(tx3);
+        state.free_tx(0);
+        assert_eq!(state.open_streams, 1);
+
+        let mut tx5 = HTTP2Transaction::new();
+        state.open_stream(&mut tx5);
+        assert_eq!(state.open_streams, 2);
+        assert_eq!(state.max_open_streams, 2);
+    }
+
+    #[test]
+    fn test_http2_rst_stream() {
+        let mut tx = HTTP2Transaction::new();
+        tx.state = HTTP2TransactionState::HTTP2StateHalfClosedClient;
+        // streams which were never counted as open
+        assert!(!tx.close_stream(true));
+
+        let mut state = HTTP2State::new();
+        state.open_stream(&mut tx);
+        assert!(tx.close_stream(true));
+        // a reset stream is not closed twice
+        assert!(!tx.close_stream(true));
+        tx.state = HTTP2TransactionState::HTTP2StateClosed;
+        assert!(!tx.close_stream(false));
+    }
+
+    #[test]
+    fn test_http2_stream_id_parity() {
+        let mut state = HTTP2State::new();
+        let tx = new_stream(&mut state, 1, Direction::ToServer);
+        assert!(!tx.has_event(HTTP2Event::InvalidStreamIdParity));
+        // client initiated streams use odd ids
+        let tx = new_stream(&mut state, 2, Direction::ToServer);
+        assert!(tx.has_event(HTTP2Event::InvalidStreamIdParity));
+        assert!(!tx.has_event(HTTP2Event::StreamIdReuse));
+
+        // server pushed streams use even ids
+        let mut tx = HTTP2Transaction::new();
+        tx.stream_id = 3;
+        let data = HTTP2FrameTypeData::PUSHPROMISE(parser::HTTP2FramePushPromise {
+            padlength: None,
+            reserved: 0,
+            stream_id: 3,
+            blocks: Vec::new(),
+        });
+        let header = frame_header(parser::HTTP2FrameType::PushPromise, 1);
+        state.check_new_stream_id(&mut tx, &header, &data, Direction::ToClient);
+        assert!(tx.has_event(HTTP2Event::InvalidStreamIdParity));
+    }
+
+    #[test]
+    fn test_http2_stream_id_reuse() {
+        let mut state = HTTP2State::new();
+        for sid in [1, 5] {
+            let tx = new_stream(&mut state, sid, Direction::ToServer);
+            assert!(!tx.has_event(HTTP2Event::StreamIdReuse));
+        }
+        // lower than the highest client stream id
+        let tx = new_stream(&mut state, 3, Direction::ToServer);
+        assert!(tx.has_event(HTTP2Event::StreamIdReuse));
+        assert!(!tx.has_event(HTTP2Event::InvalidStreamIdParity));
+        // server stream ids are tracked separately
+        let tx = new_stream(&mut state, 2, Direction::ToClient);
+        assert!(!tx.has_event(HTTP2Event::StreamIdReuse));
+
+        // window updates are allowed on a freed stream
+        let mut tx = HTTP2Transaction::new();
+        tx.stream_id = 1;
+        let header = frame_header(parser::HTTP2FrameType::WindowUpdate, 1);
+        let data = HTTP2FrameTypeData::WINDOWUPDATE(parser::HTTP2FrameWindowUpdate {
+            reserved: 0,
+            sizeinc: 0x1000,
+        });
+        state.check_new_stream_id(&mut tx, &header, &data, Direction::ToServer);
+        assert!(!tx.has_event(HTTP2Event::StreamIdReuse));
+    }
+
+    #[test]
+    fn test_http2_stream_id_reuse_closed() {
+        for ftype in [
+            parser::HTTP2FrameType::RstStream,
+            parser::HTTP2FrameType::WindowUpdate,
+            parser::HTTP2FrameType::Priority,
+        ] {
+            assert!(frame_allowed_on_closed_stream(ftype as u8));
+        }
+        for ftype in [
+            parser::HTTP2FrameType::Headers,
+            parser::HTTP2FrameType::Data,
+            parser::HTTP2FrameType::Continuation,
+        ] {
+            assert!(!frame_allowed_on_closed_stream(ftype as u8));
+        }
+    }
+}
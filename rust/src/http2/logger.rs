@@ -140,10 +140,23 @@ fn log_http2_frames(frames: &[HTTP2Frame], js: &mut JsonBuilder) -> Result<bool,
 
     let mut has_error_code = false;
     let mut has_priority = false;
+    let mut has_goaway = false;
     let mut has_multiple = false;
     for frame in frames {
         match &frame.data {
             HTTP2FrameTypeData::GOAWAY(goaway) => {
+                if !has_goaway {
+                    js.open_object("goaway")?;
+                    js.set_uint("last_stream_id", goaway.last_stream_id as u64)?;
+                    if !goaway.debug_data.is_empty() {
+                        js.set_string_from_bytes("debug_data", &goaway.debug_data)?;
+                    }
+                    js.close()?;
+                    has_goaway = true;
+                } else if !has_multiple {
+                    js.set_string("has_multiple", "goaway")?;
+                    has_multiple = true;
+                }
                 if !has_error_code {
                     let errcode: Option<parser::HTTP2ErrorCode> =
                         num::FromPrimitive::from_u32(goaway.errorcode);
@@ -204,7 +217,7 @@ fn log_http2_frames(frames: &[HTTP2Frame], js: &mut JsonBuilder) -> Result<bool,
             _ => {}
         }
     }
-    return Ok(has_settings || has_error_code || has_priority);
+    return Ok(has_settings || has_error_code || has_priority || has_goaway);
 }
 
 fn log_http2(tx: &HTTP2Transaction, js: &mut JsonBuilder) -> Result<bool, JsonError> {
@@ -270,6 +283,13 @@ fn log_http2(tx: &HTTP2Transaction, js: &mut JsonBuilder) -> Result<bool, JsonEr
     js.open_object("http2")?;
 
     js.set_uint("stream_id", tx.stream_id as u64)?;
+    if let Some(stats) = &tx.conn_stats {
+        js.set_uint(
+            "max_concurrent_streams",
+            stats.max_concurrent_streams as u64,
+        )?;
+        js.set_uint("rst_count", stats.rst_count as u64)?;
+    }
     js.open_object("request")?;
     let has_request = log_http2_frames(&tx.frames_ts, js)?;
     js.close()?;
@@ -281,7 +301,7 @@ fn log_http2(tx: &HTTP2Transaction, js: &mut JsonBuilder) -> Result<bool, JsonEr
     js.close()?; // http2
     js.close()?; // http
 
-    return Ok(has_request || has_response || has_headers);
+    return Ok(has_request || has_response || has_headers || tx.conn_stats.is_some());
 }
 
 #[no_mangle]
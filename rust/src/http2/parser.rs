@@ -155,14 +155,29 @@ impl std::str::FromStr for HTTP2ErrorCode {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Longest GOAWAY additional debug data kept, the rest is dropped
+pub const HTTP2_GOAWAY_MAX_DEBUG_DATA: usize = 256;
+
+#[derive(Clone, Debug)]
 pub struct HTTP2FrameGoAway {
+    pub last_stream_id: u32,
     pub errorcode: u32, //HTTP2ErrorCode
+    pub debug_data: Vec<u8>,
 }
 
 pub fn http2_parse_frame_goaway(i: &[u8]) -> IResult<&[u8], HTTP2FrameGoAway> {
+    let (i, last_stream_id) = be_u32(i)?;
     let (i, errorcode) = be_u32(i)?;
-    Ok((i, HTTP2FrameGoAway { errorcode }))
+    let (i, debug_data) = rest(i)?;
+    let dlen = std::cmp::min(debug_data.len(), HTTP2_GOAWAY_MAX_DEBUG_DATA);
+    Ok((
+        i,
+        HTTP2FrameGoAway {
+            last_stream_id: last_stream_id & 0x7fff_ffff,
+            errorcode,
+            debug_data: debug_data[..dlen].to_vec(),
+        },
+    ))
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -1038,6 +1053,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_http2_parse_frame_goaway() {
+        let buf: &[u8] = &[
+            0x80, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x0b, 0x62, 0x79, 0x65,
+        ];
+        let (rem, goaway) = http2_parse_frame_goaway(buf).unwrap();
+        assert!(rem.is_empty());
+        // reserved bit is not part of the stream identifier
+        assert_eq!(goaway.last_stream_id, 5);
+        assert_eq!(goaway.errorcode, HTTP2ErrorCode::EnhanceYourCalm as u32);
+        assert_eq!(goaway.debug_data, b"bye");
+
+        let mut buf = vec![0; 8];
+        buf.resize(8 + HTTP2_GOAWAY_MAX_DEBUG_DATA + 10, b'A');
+        let (_, goaway) = http2_parse_frame_goaway(&buf).unwrap();
+        assert_eq!(goaway.debug_data.len(), HTTP2_GOAWAY_MAX_DEBUG_DATA);
+
+        assert!(http2_parse_frame_goaway(&[0, 0, 0, 1]).is_err());
+    }
+
     #[test]
     fn test_http2_parse_frame_header() {
         let buf: &[u8] = &[
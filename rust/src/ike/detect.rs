@@ -64,6 +64,15 @@ pub extern "C" fn SCIkeStateGetSpiResponder(
 ) -> u8 {
     debug_validate_bug_on!(buffer.is_null() || buffer_len.is_null());
 
+    // the responder SPI is not known yet in the first message of the exchange
+    if tx.hdr.spi_responder.bytes().all(|c| c == b'0') {
+        unsafe {
+            *buffer = std::ptr::null();
+            *buffer_len = 0;
+        }
+        return 0;
+    }
+
     unsafe {
         *buffer = tx.hdr.spi_responder.as_ptr();
         *buffer_len = tx.hdr.spi_responder.len() as u32;
@@ -352,6 +352,8 @@ unsafe extern "C" fn ike_state_get_tx_count(state: *mut std::os::raw::c_void) ->
 extern "C" fn ike_tx_get_alstate_progress(
     _tx: *mut std::os::raw::c_void, _direction: u8,
 ) -> std::os::raw::c_int {
+    // Every message gets its own transaction, so it is complete as soon as
+    // it is parsed, whatever the exchange type (main, aggressive, ...).
     return 1;
 }
 
@@ -419,3 +421,150 @@ pub unsafe extern "C" fn SCRegisterIkeParser() {
         SCLogDebug!("Protocol detector and parser disabled for IKE.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESP_SPI: u64 = 0x1112131415161718;
+
+    /// Generic payload header followed by the payload data.
+    fn ikev1_payload(next_payload: u8, data: &[u8]) -> Vec<u8> {
+        let mut buf = vec![next_payload, 0];
+        buf.extend_from_slice(&(data.len() as u16 + 4).to_be_bytes());
+        buf.extend_from_slice(data);
+        buf
+    }
+
+    /// IKEv1 message with the given payloads, already chained.
+    fn ikev1_message(
+        resp_spi: u64, exch_type: u8, flags: u8, next_payload: u8, payloads: &[u8],
+    ) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x0102030405060708_u64.to_be_bytes());
+        buf.extend_from_slice(&resp_spi.to_be_bytes());
+        buf.push(next_payload);
+        buf.push(0x10); // version 1.0
+        buf.push(exch_type);
+        buf.push(flags);
+        buf.extend_from_slice(&0_u32.to_be_bytes()); // message id
+        buf.extend_from_slice(&(28 + payloads.len() as u32).to_be_bytes());
+        buf.extend_from_slice(payloads);
+        buf
+    }
+
+    /// SA payload data for the IPsec DOI with one proposal of one
+    /// transform: AES-CBC 128, SHA1, pre-shared key, group 2, 28800s.
+    fn ikev1_sa() -> Vec<u8> {
+        #[rustfmt::skip]
+        let transform = ikev1_payload(0, &[
+            0x01, 0x01, 0x00, 0x00, // transform 1, KEY_IKE
+            0x80, 0x01, 0x00, 0x07, // encryption: AES-CBC
+            0x80, 0x0e, 0x00, 0x80, // key length: 128
+            0x80, 0x02, 0x00, 0x02, // hash: SHA
+            0x80, 0x03, 0x00, 0x01, // authentication: pre-shared key
+            0x80, 0x04, 0x00, 0x02, // group: 1024-bit MODP
+            0x80, 0x0b, 0x00, 0x01, // life type: seconds
+            0x00, 0x0c, 0x00, 0x04, 0x00, 0x00, 0x70, 0x80, // life duration
+        ]);
+        // proposal 1, ISAKMP, no SPI, one transform
+        let mut proposal = vec![0x01, 0x01, 0x00, 0x01];
+        proposal.extend_from_slice(&transform);
+        // IPsec DOI, identity only situation
+        let mut sa = vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01];
+        sa.extend_from_slice(&ikev1_payload(0, &proposal));
+        sa
+    }
+
+    #[test]
+    fn test_ikev1_aggressive_mode_complete() {
+        let mut state = IKEState::default();
+        let aggressive = ExchangeType::Aggressive as u8;
+        // ID_IPV4_ADDR, UDP port 500
+        let id = [0x01, 0x11, 0x01, 0xf4, 192, 168, 1, 1];
+        // RFC 3947 NAT-T
+        let vendor_id = hex::decode("4a131c81070358455c5728f20e95452f").unwrap();
+
+        // SA, KE, nonce, ID, vendor ID
+        let mut payloads = ikev1_payload(4, &ikev1_sa());
+        payloads.extend_from_slice(&ikev1_payload(10, &[0xaa; 16]));
+        payloads.extend_from_slice(&ikev1_payload(5, &[0xbb; 16]));
+        payloads.extend_from_slice(&ikev1_payload(13, &id));
+        payloads.extend_from_slice(&ikev1_payload(0, &vendor_id));
+        let msg1 = ikev1_message(0, aggressive, 0, 1, &payloads);
+
+        // SA, KE, nonce, ID, hash
+        let mut payloads = ikev1_payload(4, &ikev1_sa());
+        payloads.extend_from_slice(&ikev1_payload(10, &[0xcc; 16]));
+        payloads.extend_from_slice(&ikev1_payload(5, &[0xdd; 16]));
+        payloads.extend_from_slice(&ikev1_payload(8, &id));
+        payloads.extend_from_slice(&ikev1_payload(0, &[0xee; 20]));
+        let msg2 = ikev1_message(RESP_SPI, aggressive, 0, 1, &payloads);
+
+        // the hash of the third message is encrypted
+        let msg3 = ikev1_message(RESP_SPI, aggressive, 0x01, 8, &[0x5a; 32]);
+
+        assert!(state.handle_input(&msg1, Direction::ToServer).status == 0);
+        assert!(state.handle_input(&msg2, Direction::ToClient).status == 0);
+        assert!(state.handle_input(&msg3, Direction::ToServer).status == 0);
+
+        assert_eq!(state.transactions.len(), 3);
+        for tx in state.transactions.iter_mut() {
+            assert_eq!(tx.hdr.ikev1_header.exchange_type, Some(aggressive));
+            let ptr = tx as *mut IKETransaction as *mut std::os::raw::c_void;
+            assert_eq!(
+                ike_tx_get_alstate_progress(ptr, Direction::ToServer.into()),
+                1
+            );
+            assert_eq!(
+                ike_tx_get_alstate_progress(ptr, Direction::ToClient.into()),
+                1
+            );
+        }
+
+        let tx = &state.transactions[0];
+        assert_eq!(tx.hdr.ikev1_header.key_exchange, vec![0xaa; 16]);
+        assert_eq!(tx.hdr.ikev1_header.nonce, vec![0xbb; 16]);
+        assert_eq!(
+            tx.hdr.ikev1_header.vendor_ids,
+            vec!["4a131c81070358455c5728f20e95452f".to_string()]
+        );
+        assert_eq!(tx.hdr.ikev1_transforms.len(), 1);
+        let transform = &tx.hdr.ikev1_transforms[0];
+        assert_eq!(transform.len(), 7);
+        assert!(matches!(
+            transform[0].attribute_value,
+            AttributeValue::EncAesCbc
+        ));
+        assert_eq!(transform[1].numeric_value, Some(128));
+        assert!(matches!(
+            transform[2].attribute_value,
+            AttributeValue::HashSha
+        ));
+        assert!(matches!(
+            transform[3].attribute_value,
+            AttributeValue::AuthPreSharedKey
+        ));
+        assert_eq!(transform[6].numeric_value, Some(28800));
+        let payload_types = tx.payload_types.ikev1_payload_types.as_ref().unwrap();
+        for payload_type in [1, 2, 3, 4, 5, 10, 13] {
+            assert!(payload_types.contains(&payload_type), "{}", payload_type);
+        }
+
+        let tx = &state.transactions[1];
+        assert_eq!(tx.hdr.spi_responder, "1112131415161718");
+        assert_eq!(tx.hdr.ikev1_header.key_exchange, vec![0xcc; 16]);
+        assert_eq!(tx.hdr.ikev1_header.nonce, vec![0xdd; 16]);
+        let payload_types = tx.payload_types.ikev1_payload_types.as_ref().unwrap();
+        assert!(payload_types.contains(&8));
+
+        assert!(state.transactions[2].hdr.ikev1_header.encrypted_payloads);
+        assert!(state.transactions[2].hdr.ikev1_header.nonce.is_empty());
+
+        assert_eq!(state.ikev1_container.domain_of_interpretation, Some(1));
+        assert_eq!(state.ikev1_container.client.nb_transforms, 1);
+        assert_eq!(state.ikev1_container.client.nonce, "bb".repeat(16));
+        assert_eq!(state.ikev1_container.server.nb_transforms, 1);
+        assert_eq!(state.ikev1_container.server.key_exchange, "cc".repeat(16));
+    }
+}
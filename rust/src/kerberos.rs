@@ -23,8 +23,9 @@ use nom7::number::streaming::le_u16;
 use der_parser;
 use der_parser::der::parse_der_oid;
 use der_parser::error::BerError;
-use kerberos_parser::krb5::{ApReq, PrincipalName, Realm};
+use kerberos_parser::krb5::{ApReq, EncryptionType, PrincipalName, Realm};
 use asn1_rs::FromDer;
+use crate::krb::krb5::principal_name_to_string;
 
 #[derive(Debug)]
 pub enum SecBlobError {
@@ -53,6 +54,10 @@ impl<I> ParseError<I> for SecBlobError {
 pub struct Kerberos5Ticket {
     pub realm: Realm,
     pub sname: PrincipalName,
+    /// sname components joined with '/'
+    pub sname_str: String,
+    /// Encryption type of the ticket
+    pub etype: EncryptionType,
 }
 
 fn parse_kerberos5_request_do(blob: &[u8]) -> IResult<&[u8], ApReq, SecBlobError>
@@ -73,6 +78,8 @@ fn parse_kerberos5_request_do(blob: &[u8]) -> IResult<&[u8], ApReq, SecBlobError
 pub fn parse_kerberos5_request(blob: &[u8]) -> IResult<&[u8], Kerberos5Ticket, SecBlobError> {
     let (rem, req) = parse_kerberos5_request_do(blob)?;
     let t = Kerberos5Ticket {
+        sname_str: principal_name_to_string(&req.ticket.sname),
+        etype: req.ticket.enc_part.etype,
         realm: req.ticket.realm,
         sname: req.ticket.sname,
     };
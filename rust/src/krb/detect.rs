@@ -17,10 +17,16 @@
 
 // written by Pierre Chifflier  <chifflier@wzdftpd.net>
 
-use crate::core::DetectEngineThreadCtx;
-use crate::krb::krb5::{test_weak_encryption, KRB5Transaction};
+use crate::core::{DetectEngineThreadCtx, STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::detect::{
+    helper_keyword_register_sticky_buffer, DetectEngineRegistrar, DetectHelperBufferMpmRegister,
+    DetectHelperGetData, DetectSignatureSetAppProto, SigTableElmtStickyBuffer,
+};
+use crate::krb::krb5::{test_weak_encryption, KRB5Transaction, ALPROTO_KRB5};
+use crate::smb::smb::{SMBTransaction, SMBTransactionTypeData, ALPROTO_SMB};
+use suricata_sys::sys::{DetectEngineCtx, SCDetectBufferSetActiveList, Signature};
 
-use kerberos_parser::krb5::EncryptionType;
+use kerberos_parser::krb5::{EncryptionType, PrincipalName};
 
 use nom7::branch::alt;
 use nom7::bytes::complete::{is_a, tag, take_while, take_while1};
@@ -30,7 +36,9 @@ use nom7::multi::many1;
 use nom7::IResult;
 
 use std::ffi::CStr;
-use std::os::raw::c_void;
+use std::os::raw::{c_int, c_void};
+
+static mut G_KRB5_CREALM_BUFFER_ID: c_int = 0;
 
 #[no_mangle]
 pub unsafe extern "C" fn SCKrb5TxGetMsgType(tx: &KRB5Transaction, ptr: *mut u32) {
@@ -73,17 +81,150 @@ pub unsafe extern "C" fn SCKrb5TxGetSname(
     buffer_len: *mut u32,
 ) -> bool {
     let tx = cast_pointer!(tx, KRB5Transaction);
-    if let Some(ref s) = tx.sname {
-        if (i as usize) < s.name_string.len() {
-            let value = &s.name_string[i as usize];
-            *buffer = value.as_ptr();
-            *buffer_len = value.len() as u32;
-            return true;
-        }
+    if let Some(value) = krb5_tx_get_sname(tx, i as usize) {
+        *buffer = value.as_ptr();
+        *buffer_len = value.len() as u32;
+        return true;
+    }
+    false
+}
+
+fn krb5_tx_get_sname(tx: &KRB5Transaction, i: usize) -> Option<&str> {
+    let s = tx.sname.as_ref()?;
+    principal_name_get_buffer(s, i)
+}
+
+/// Each component of the name is a buffer.
+pub(crate) fn principal_name_get_buffer(name: &PrincipalName, i: usize) -> Option<&str> {
+    name.name_string.get(i).map(|c| c.as_str())
+}
+
+unsafe extern "C" fn krb5_detect_crealm_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_KRB5) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_KRB5_CREALM_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn krb5_detect_crealm_get(
+    tx: *const c_void, _flow_flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, KRB5Transaction);
+    if let Some(ref realm) = tx.crealm {
+        *buffer = realm.0.as_ptr();
+        *buffer_len = realm.0.len() as u32;
+        return true;
     }
     false
 }
 
+unsafe extern "C" fn krb5_detect_crealm_get_data(
+    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
+    tx: *const c_void, list_id: c_int,
+) -> *mut c_void {
+    return DetectHelperGetData(
+        de,
+        transforms,
+        flow,
+        flow_flags,
+        tx,
+        list_id,
+        krb5_detect_crealm_get,
+    );
+}
+
+/// The realm of the Kerberos ticket of a SMB session setup request.
+fn smb_tx_get_krb5_srealm(tx: &SMBTransaction) -> Option<&[u8]> {
+    if let Some(SMBTransactionTypeData::SESSIONSETUP(ref x)) = tx.type_data {
+        return x.krb_ticket.as_ref().map(|t| t.realm.0.as_bytes());
+    }
+    None
+}
+
+/// The server name of the Kerberos ticket of a SMB session setup request,
+/// components joined with '/'.
+fn smb_tx_get_krb5_sname_full(tx: &SMBTransaction) -> Option<&[u8]> {
+    if let Some(SMBTransactionTypeData::SESSIONSETUP(ref x)) = tx.type_data {
+        return x.krb_ticket.as_ref().map(|t| t.sname_str.as_bytes());
+    }
+    None
+}
+
+fn krb5_tx_get_sname_full(tx: &KRB5Transaction) -> Option<&[u8]> {
+    tx.sname_str.as_ref().map(|s| s.as_bytes())
+}
+
+detect_sticky_buffer! {
+    mod krb5_sname_full {
+        name: "krb5.sname.full",
+        desc: "sticky buffer to match on the full Kerberos 5 server name",
+        url: "/rules/kerberos-keywords.html#krb5-sname-full",
+        mpm_desc: "Kerberos 5 full server name",
+        protos: {
+            krb5 {
+                alproto: ALPROTO_KRB5,
+                dir: STREAM_TOSERVER | STREAM_TOCLIENT,
+                tx: KRB5Transaction,
+                get: krb5_tx_get_sname_full,
+            },
+            smb {
+                alproto: ALPROTO_SMB,
+                dir: STREAM_TOSERVER,
+                tx: SMBTransaction,
+                get: smb_tx_get_krb5_sname_full,
+            },
+        },
+    }
+}
+
+detect_sticky_buffer! {
+    mod krb5_srealm {
+        name: "krb5.srealm",
+        desc: "sticky buffer to match on Kerberos 5 server realm",
+        url: "/rules/kerberos-keywords.html#krb5-srealm",
+        mpm_desc: "Kerberos 5 server realm",
+        protos: {
+            krb5 {
+                alproto: ALPROTO_KRB5,
+                dir: STREAM_TOSERVER | STREAM_TOCLIENT,
+                tx: KRB5Transaction,
+                get: |tx| tx.srealm.as_ref().map(|r| r.0.as_bytes()),
+            },
+            smb {
+                alproto: ALPROTO_SMB,
+                dir: STREAM_TOSERVER,
+                tx: SMBTransaction,
+                get: smb_tx_get_krb5_srealm,
+            },
+        },
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectKrb5Register() {
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("krb5.crealm"),
+        desc: String::from("sticky buffer to match on Kerberos 5 client realm"),
+        url: String::from("/rules/kerberos-keywords.html#krb5-crealm"),
+        setup: krb5_detect_crealm_setup,
+    };
+    let _g_krb5_crealm_kw_id = helper_keyword_register_sticky_buffer(&kw);
+    G_KRB5_CREALM_BUFFER_ID = DetectHelperBufferMpmRegister(
+        b"krb5.crealm\0".as_ptr() as *const libc::c_char,
+        b"Kerberos 5 client realm\0".as_ptr() as *const libc::c_char,
+        ALPROTO_KRB5,
+        STREAM_TOSERVER | STREAM_TOCLIENT,
+        krb5_detect_crealm_get_data,
+    );
+    krb5_srealm::register(&mut DetectEngineRegistrar);
+    krb5_sname_full::register(&mut DetectEngineRegistrar);
+}
+
 const KRB_TICKET_FASTARRAY_SIZE: usize = 256;
 
 #[derive(Debug)]
@@ -275,6 +416,136 @@ pub unsafe extern "C" fn SCKrb5DetectEncryptionFree(ctx: &mut DetectKrb5TicketEn
 mod tests {
 
     use super::*;
+    use crate::detect::sticky_buffer_tx_data;
+    use crate::direction::Direction;
+    use crate::kerberos::Kerberos5Ticket;
+    use crate::smb::session::SMBTransactionSessionSetup;
+    use kerberos_parser::krb5::{NameType, Realm};
+
+    fn krb5_tx_with_sname(components: &[&str]) -> KRB5Transaction {
+        let mut tx = KRB5Transaction::new(Direction::ToServer, 1);
+        tx.set_sname(Some(PrincipalName {
+            name_type: NameType::KRB_NT_SRV_INST,
+            name_string: components.iter().map(|c| c.to_string()).collect(),
+        }));
+        tx
+    }
+
+    fn krb5_tx_snames(tx: &KRB5Transaction) -> Vec<&str> {
+        (0..).map_while(|i| krb5_tx_get_sname(tx, i)).collect()
+    }
+
+    #[test]
+    fn test_krb5_sname_buffers() {
+        let tx = krb5_tx_with_sname(&["krbtgt"]);
+        assert_eq!(tx.sname_str.as_deref(), Some("krbtgt"));
+        assert_eq!(krb5_tx_snames(&tx), vec!["krbtgt"]);
+
+        let tx = krb5_tx_with_sname(&["krbtgt", "EXAMPLE.COM"]);
+        assert_eq!(tx.sname_str.as_deref(), Some("krbtgt/EXAMPLE.COM"));
+        assert_eq!(krb5_tx_snames(&tx), vec!["krbtgt", "EXAMPLE.COM"]);
+        assert_eq!(
+            krb5_tx_get_sname_full(&tx),
+            Some(&b"krbtgt/EXAMPLE.COM"[..])
+        );
+
+        let tx = krb5_tx_with_sname(&["ldap", "dc1.example.com", "example.com"]);
+        assert_eq!(
+            tx.sname_str.as_deref(),
+            Some("ldap/dc1.example.com/example.com")
+        );
+        assert_eq!(
+            krb5_tx_snames(&tx),
+            vec!["ldap", "dc1.example.com", "example.com"]
+        );
+
+        let tx = KRB5Transaction::new(Direction::ToServer, 1);
+        assert!(tx.sname_str.is_none());
+        assert!(krb5_tx_snames(&tx).is_empty());
+        assert!(krb5_tx_get_sname_full(&tx).is_none());
+    }
+
+    #[test]
+    fn test_krb5_realm_buffers() {
+        let get = |f: unsafe extern "C" fn(*const c_void, u8, *mut *const u8, *mut u32) -> bool,
+                   tx: &KRB5Transaction| {
+            let mut buffer: *const u8 = std::ptr::null();
+            let mut buffer_len: u32 = 0;
+            let tx = tx as *const KRB5Transaction as *const c_void;
+            if unsafe { f(tx, 0, &mut buffer, &mut buffer_len) } {
+                let value = unsafe { std::slice::from_raw_parts(buffer, buffer_len as usize) };
+                Some(std::str::from_utf8(value).unwrap().to_string())
+            } else {
+                None
+            }
+        };
+
+        // Windows clients use upper case realms, MIT clients may not
+        let mut tx = KRB5Transaction::new(Direction::ToClient, 1);
+        tx.crealm = Some(Realm("Example.com".to_string()));
+        tx.srealm = Some(Realm("EXAMPLE.COM".to_string()));
+        assert_eq!(
+            get(krb5_detect_crealm_get, &tx).as_deref(),
+            Some("Example.com")
+        );
+        assert_eq!(
+            get(krb5_srealm::krb5::get_tx_data, &tx).as_deref(),
+            Some("EXAMPLE.COM")
+        );
+
+        // TGS-REQ only has the server realm
+        let mut tx = KRB5Transaction::new(Direction::ToServer, 2);
+        tx.srealm = Some(Realm("example.com".to_string()));
+        assert!(get(krb5_detect_crealm_get, &tx).is_none());
+        assert_eq!(
+            get(krb5_srealm::krb5::get_tx_data, &tx).as_deref(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn test_krb5_srealm_smb() {
+        let mut tx = SMBTransaction::new();
+        assert_eq!(
+            sticky_buffer_tx_data(krb5_srealm::smb::get_tx_data, &tx),
+            None
+        );
+
+        // session setup without a Kerberos ticket, e.g. NTLMSSP
+        tx.type_data = Some(SMBTransactionTypeData::SESSIONSETUP(
+            SMBTransactionSessionSetup::new(),
+        ));
+        assert_eq!(
+            sticky_buffer_tx_data(krb5_srealm::smb::get_tx_data, &tx),
+            None
+        );
+
+        if let Some(SMBTransactionTypeData::SESSIONSETUP(ref mut x)) = tx.type_data {
+            x.krb_ticket = Some(Kerberos5Ticket {
+                realm: Realm("EXAMPLE.COM".to_string()),
+                sname: PrincipalName {
+                    name_type: NameType::KRB_NT_SRV_INST,
+                    name_string: vec!["cifs".to_string(), "dc1.example.com".to_string()],
+                },
+                sname_str: "cifs/dc1.example.com".to_string(),
+                etype: EncryptionType::AES256_CTS_HMAC_SHA1_96,
+            });
+        }
+        assert_eq!(
+            sticky_buffer_tx_data(krb5_srealm::smb::get_tx_data, &tx),
+            Some(&b"EXAMPLE.COM"[..])
+        );
+        assert_eq!(
+            sticky_buffer_tx_data(krb5_sname_full::smb::get_tx_data, &tx),
+            Some(&b"cifs/dc1.example.com"[..])
+        );
+        // krb5 transaction without a server realm
+        let krb5_tx = KRB5Transaction::new(Direction::ToServer, 1);
+        assert_eq!(
+            sticky_buffer_tx_data(krb5_srealm::krb5::get_tx_data, &krb5_tx),
+            None
+        );
+    }
 
     #[test]
     fn test_detect_parse_encryption() {
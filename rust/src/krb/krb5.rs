@@ -74,10 +74,17 @@ pub struct KRB5Transaction {
 
     /// The client PrincipalName, if present
     pub cname: Option<PrincipalName>,
-    /// The server Realm, if present
+    /// The Realm as logged: the server realm in requests, the client realm
+    /// in responses and errors
     pub realm: Option<Realm>,
+    /// The client Realm, if present
+    pub crealm: Option<Realm>,
+    /// The server Realm, if present
+    pub srealm: Option<Realm>,
     /// The server PrincipalName, if present
     pub sname: Option<PrincipalName>,
+    /// The server PrincipalName components joined with '/', if present
+    pub sname_str: Option<String>,
 
     /// Encryption used (only in AS-REP and TGS-REP)
     pub etype: Option<EncryptionType>,
@@ -140,8 +147,12 @@ impl KRB5State {
                             let mut tx = self.new_tx(direction);
                             tx.msg_type = MessageType::KRB_AS_REQ;
                             tx.cname = kdc_req.req_body.cname;
+                            // in the AS exchange, the realm is also the
+                            // client realm
+                            tx.crealm = Some(Realm(kdc_req.req_body.realm.0.clone()));
+                            tx.srealm = Some(Realm(kdc_req.req_body.realm.0.clone()));
                             tx.realm = Some(kdc_req.req_body.realm);
-                            tx.sname = kdc_req.req_body.sname;
+                            tx.set_sname(kdc_req.req_body.sname);
                             tx.etype = None;
                             self.transactions.push(tx);
                         };
@@ -158,8 +169,10 @@ impl KRB5State {
                                 tx.req_type = Some(MessageType(self.req_id.into()));
                             }
                             tx.cname = Some(kdc_rep.cname);
+                            tx.crealm = Some(Realm(kdc_rep.crealm.0.clone()));
+                            tx.srealm = Some(kdc_rep.ticket.realm);
                             tx.realm = Some(kdc_rep.crealm);
-                            tx.sname = Some(kdc_rep.ticket.sname);
+                            tx.set_sname(Some(kdc_rep.ticket.sname));
                             tx.ticket_etype = Some(kdc_rep.ticket.enc_part.etype);
                             tx.etype = Some(kdc_rep.enc_part.etype);
                             self.transactions.push(tx);
@@ -175,8 +188,9 @@ impl KRB5State {
                             let mut tx = self.new_tx(direction);
                             tx.msg_type = MessageType::KRB_TGS_REQ;
                             tx.cname = kdc_req.req_body.cname;
+                            tx.srealm = Some(Realm(kdc_req.req_body.realm.0.clone()));
                             tx.realm = Some(kdc_req.req_body.realm);
-                            tx.sname = kdc_req.req_body.sname;
+                            tx.set_sname(kdc_req.req_body.sname);
                             tx.etype = None;
                             self.transactions.push(tx);
                         };
@@ -193,9 +207,11 @@ impl KRB5State {
                                 tx.req_type = Some(MessageType(self.req_id.into()));
                             }
                             tx.cname = Some(kdc_rep.cname);
+                            tx.crealm = Some(Realm(kdc_rep.crealm.0.clone()));
+                            tx.srealm = Some(kdc_rep.ticket.realm);
                             tx.realm = Some(kdc_rep.crealm);
                             tx.ticket_etype = Some(kdc_rep.ticket.enc_part.etype);
-                            tx.sname = Some(kdc_rep.ticket.sname);
+                            tx.set_sname(Some(kdc_rep.ticket.sname));
                             tx.etype = Some(kdc_rep.enc_part.etype);
                             self.transactions.push(tx);
                             if test_weak_encryption(kdc_rep.enc_part.etype) {
@@ -221,8 +237,10 @@ impl KRB5State {
                             }
                             tx.msg_type = MessageType::KRB_ERROR;
                             tx.cname = error.cname;
+                            tx.crealm = error.crealm.as_ref().map(|r| Realm(r.0.clone()));
+                            tx.srealm = Some(error.realm);
                             tx.realm = error.crealm;
-                            tx.sname = Some(error.sname);
+                            tx.set_sname(Some(error.sname));
                             tx.error_code = Some(error.error_code);
                             self.transactions.push(tx);
                         };
@@ -284,7 +302,10 @@ impl KRB5Transaction {
             msg_type: MessageType(0),
             cname: None,
             realm: None,
+            crealm: None,
+            srealm: None,
             sname: None,
+            sname_str: None,
             etype: None,
             ticket_etype: None,
             error_code: None,
@@ -294,6 +315,16 @@ impl KRB5Transaction {
         };
         return krbtx;
     }
+
+    pub fn set_sname(&mut self, sname: Option<PrincipalName>) {
+        self.sname_str = sname.as_ref().map(principal_name_to_string);
+        self.sname = sname;
+    }
+}
+
+/// Return the components of a `PrincipalName` joined with '/', without the realm
+pub fn principal_name_to_string(name: &PrincipalName) -> String {
+    name.name_string.join("/")
 }
 
 /// Return true if Kerberos `EncryptionType` is weak
@@ -351,7 +382,7 @@ pub extern "C" fn krb5_tx_get_alstate_progress(
     1
 }
 
-static mut ALPROTO_KRB5: AppProto = ALPROTO_UNKNOWN;
+pub(super) static mut ALPROTO_KRB5: AppProto = ALPROTO_UNKNOWN;
 
 unsafe extern "C" fn krb5_probing_parser(
     _flow: *const Flow, _direction: u8, input: *const u8, input_len: u32, _rdir: *mut u8,
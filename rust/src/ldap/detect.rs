@@ -15,21 +15,36 @@
  * 02110-1301, USA.
  */
 
-use super::ldap::{LdapTransaction, ALPROTO_LDAP};
+use super::ldap::{
+    LdapTransaction, ALPROTO_LDAP, LDAP_TX_FLAG_ANONYMOUS, LDAP_TX_FLAG_LATE_RESPONSE,
+    LDAP_TX_FLAG_NOTICE_OF_DISCONNECTION, LDAP_TX_FLAG_SASL_IN_PROGRESS,
+    LDAP_TX_FLAG_SERVER_INITIATED, LDAP_TX_FLAG_STARTTLS, LDAP_TX_FLAG_TRUNCATED, STARTTLS_OID,
+};
 use crate::core::{DetectEngineThreadCtx, STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::detect::transforms::{
+    DetectHelperTransformRegister, DetectSignatureAddTransform, InspectionBufferCheckAndExpand,
+    InspectionBufferLength, InspectionBufferPtr, InspectionBufferTruncate, SCTransformTableElmt,
+};
 use crate::detect::uint::{
-    detect_match_uint, detect_parse_uint_enum, DetectUintData, SCDetectU32Free, SCDetectU32Parse,
-    SCDetectU8Free,
+    detect_match_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    DetectUintMode, SCDetectU32Free, SCDetectU32Parse, SCDetectU8Free,
 };
 use crate::detect::{
-    helper_keyword_register_sticky_buffer, DetectHelperBufferMpmRegister,
-    DetectHelperBufferRegister, DetectHelperGetData, DetectHelperKeywordRegister,
-    DetectHelperMultiBufferMpmRegister, DetectSignatureSetAppProto, SCSigTableAppLiteElmt,
-    SigMatchAppendSMToList, SigTableElmtStickyBuffer,
+    DetectEngineRegistrar, DetectGetDataFn, DetectHelperBufferRegister, DetectHelperGetData,
+    DetectHelperKeywordRegister, DetectHelperMultiBufferMpmRegister, DetectSignatureSetAppProto,
+    EnumString, SCSigTableAppLiteElmt, SigMatchAppendSMToList, SigTableElmtStickyBuffer,
+    StickyBufferRegistrar, SIGMATCH_INFO_STICKY_BUFFER, SIGMATCH_NOOPT, SIGMATCH_OPTIONAL_OPT,
 };
-use crate::ldap::types::{LdapMessage, LdapResultCode, ProtocolOp, ProtocolOpCode};
-use suricata_sys::sys::{DetectEngineCtx, SCDetectBufferSetActiveList, Signature};
+use crate::ldap::filters::{Filter, Substring};
+use crate::ldap::types::{
+    attribute_has_range_option, dn_rdn_attribute, get_ldap_result_code, is_ldap_error_code,
+    ldap_result_code_severity, referral_host, Control, LdapMessage, LdapResultCode, ProtocolOp,
+    ProtocolOpCode,
+};
+use crc::crc32;
+use suricata_sys::sys::{AppProto, DetectEngineCtx, SCDetectBufferSetActiveList, Signature};
 
+use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::ffi::CStr;
 use std::os::raw::{c_int, c_void};
@@ -42,6 +57,52 @@ enum LdapIndex {
     Index(i32),
 }
 
+/// Coarse grouping of request operations.
+#[derive(Debug, PartialEq)]
+enum LdapOpClass {
+    /// bind and unbind
+    Auth,
+    /// search and compare
+    Read,
+    /// add, modify, delete and modify DN
+    Write,
+    /// abandon and extended operations
+    Control,
+}
+
+impl LdapOpClass {
+    fn contains(&self, op: &ProtocolOp) -> bool {
+        match self {
+            LdapOpClass::Auth => {
+                matches!(op, ProtocolOp::BindRequest(_) | ProtocolOp::UnbindRequest)
+            }
+            LdapOpClass::Read => matches!(
+                op,
+                ProtocolOp::SearchRequest(_) | ProtocolOp::CompareRequest(_)
+            ),
+            LdapOpClass::Write => matches!(
+                op,
+                ProtocolOp::AddRequest(_)
+                    | ProtocolOp::ModifyRequest(_)
+                    | ProtocolOp::DelRequest(_)
+                    | ProtocolOp::ModDnRequest(_)
+            ),
+            LdapOpClass::Control => matches!(
+                op,
+                ProtocolOp::AbandonRequest(_) | ProtocolOp::ExtendedRequest(_)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum DetectLdapReqOpData {
+    /// Ldap request operation code
+    Operation(DetectUintData<u8>),
+    /// Class of operations, set with the `class=` form
+    Class(LdapOpClass),
+}
+
 #[derive(Debug, PartialEq)]
 struct DetectLdapRespOpData {
     /// Ldap response operation code
@@ -52,35 +113,128 @@ struct DetectLdapRespOpData {
     pub index: LdapIndex,
 }
 
+/// Expected set of search request attributes, lowercased, sorted and
+/// without duplicates.
+#[derive(Debug, PartialEq)]
+struct DetectLdapSearchAttributesData {
+    pub attributes: Vec<String>,
+}
+
 struct DetectLdapRespResultData {
-    /// Ldap result code
-    pub du32: DetectUintData<u32>,
+    /// Ldap result codes, separated by `|` in the rule, any of them can match
+    pub codes: Vec<DetectUintData<u32>>,
     /// Index can be Any to match with any responses index,
     /// All to match if all indices, or an i32 integer
     /// Negative values represent back to front indexing.
     pub index: LdapIndex,
 }
 
+/// The optional argument of `ldap.responses.message`
+struct DetectLdapRespMsgData {
+    /// Operation of the responses, which selects the buffer
+    pub op: Option<u8>,
+    /// Index of the response, matched next to the buffer
+    pub index: Option<DetectLdapRespResultData>,
+}
+
 static mut G_LDAP_REQUEST_OPERATION_KW_ID: c_int = 0;
 static mut G_LDAP_REQUEST_OPERATION_BUFFER_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_OPERATION_KW_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_OPERATION_BUFFER_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_COUNT_KW_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_COUNT_BUFFER_ID: c_int = 0;
-static mut G_LDAP_REQUEST_DN_BUFFER_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_DN_BUFFER_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_RESULT_CODE_KW_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_RESULT_CODE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_RESULT_CODE_WORST_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_RESULT_CODE_WORST_BUFFER_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_MSG_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_MSG_OP_BUFFER_IDS: [c_int; 8] = [0; 8];
 static mut G_LDAP_REQUEST_ATTRIBUTE_TYPE_BUFFER_ID: c_int = 0;
 static mut G_LDAP_RESPONSES_ATTRIBUTE_TYPE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_SEARCH_ENTRY_VALUE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_SEARCH_ENTRY_VALUE_TRANSFORM_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_STARTTLS_RESULT_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_STARTTLS_RESULT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_ROOTDSE_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_ROOTDSE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_BIND_CONSECUTIVE_FAILURES_KW_ID: c_int = 0;
+static mut G_LDAP_BIND_CONSECUTIVE_FAILURES_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_UNBIND_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_UNBIND_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_REFERRAL_HOST_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_NO_TIME_LIMIT_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_NO_TIME_LIMIT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_NO_SIZE_LIMIT_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_NO_SIZE_LIMIT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_DN_RDN_ATTRIBUTE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_BIND_LEGACY_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_BIND_LEGACY_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_SEARCH_REFERENCE_HOST_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_RANGED_ATTRIBUTE_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_RANGED_ATTRIBUTE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_ATTRIBUTES_EQUALS_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_ATTRIBUTES_EQUALS_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_ATTRIBUTES_HASH_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_ATTRIBUTES_HASH_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_BYTES_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_BYTES_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSE_BYTES_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSE_BYTES_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SPN_QUERY_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SPN_QUERY_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_ASREP_QUERY_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_ASREP_QUERY_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_FILTER_VALUE_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_ADMIN_LIMIT_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_ADMIN_LIMIT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SIZE_LIMIT_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SIZE_LIMIT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_TIME_LIMIT_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_TIME_LIMIT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_PROXY_AUTHZ_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_PROXY_AUTHZ_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_PROXY_AUTHZ_ID_BUFFER_ID: c_int = 0;
+static mut G_LDAP_AUTHZ_ID_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_OPERATION_MATCHES_REQUEST_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_OPERATION_MATCHES_REQUEST_BUFFER_ID: c_int = 0;
+static mut G_LDAP_FLAGS_KW_ID: c_int = 0;
+static mut G_LDAP_FLAGS_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_ANY_ERROR_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_ANY_ERROR_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_TARGETS_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_TARGETS_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSE_ORPHAN_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSE_ORPHAN_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_FILTER_EQUALITY_COUNT_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_FILTER_EQUALITY_COUNT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_EXTENDED_RESPONSE_OID_BUFFER_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_BIND_RESULT_KW_ID: c_int = 0;
+static mut G_LDAP_RESPONSES_BIND_RESULT_BUFFER_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_FILTER_MATCHALL_KW_ID: c_int = 0;
+static mut G_LDAP_REQUEST_SEARCH_FILTER_MATCHALL_BUFFER_ID: c_int = 0;
+
+fn aux_ldap_parse_protocol_req_op(s: &str) -> Option<DetectLdapReqOpData> {
+    if let Some(class) = s.trim().strip_prefix("class=") {
+        let class = match class.trim() {
+            "auth" => LdapOpClass::Auth,
+            "read" => LdapOpClass::Read,
+            "write" => LdapOpClass::Write,
+            "control" => LdapOpClass::Control,
+            _ => return None,
+        };
+        return Some(DetectLdapReqOpData::Class(class));
+    }
+    let du8 = detect_parse_uint_enum::<u8, ProtocolOpCode>(s)?;
+    Some(DetectLdapReqOpData::Operation(du8))
+}
 
 unsafe extern "C" fn ldap_parse_protocol_req_op(
     ustr: *const std::os::raw::c_char,
-) -> *mut DetectUintData<u8> {
+) -> *mut DetectLdapReqOpData {
     let ft_name: &CStr = CStr::from_ptr(ustr); //unsafe
     if let Ok(s) = ft_name.to_str() {
-        if let Some(ctx) = detect_parse_uint_enum::<u8, ProtocolOpCode>(s) {
+        if let Some(ctx) = aux_ldap_parse_protocol_req_op(s) {
             let boxed = Box::new(ctx);
             return Box::into_raw(boxed) as *mut _;
         }
@@ -118,18 +272,22 @@ unsafe extern "C" fn ldap_detect_request_operation_match(
     _sig: *const c_void, ctx: *const c_void,
 ) -> c_int {
     let tx = cast_pointer!(tx, LdapTransaction);
-    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    let ctx = cast_pointer!(ctx, DetectLdapReqOpData);
     if let Some(request) = &tx.request {
-        let option = request.protocol_op.to_u8();
-        return detect_match_uint(ctx, option) as c_int;
+        return match ctx {
+            DetectLdapReqOpData::Operation(du8) => {
+                detect_match_uint(du8, request.protocol_op.to_u8()) as c_int
+            }
+            DetectLdapReqOpData::Class(class) => class.contains(&request.protocol_op) as c_int,
+        };
     }
     return 0;
 }
 
 unsafe extern "C" fn ldap_detect_request_free(_de: *mut c_void, ctx: *mut c_void) {
     // Just unbox...
-    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
-    SCDetectU8Free(ctx);
+    let ctx = cast_pointer!(ctx, DetectLdapReqOpData);
+    std::mem::drop(Box::from_raw(ctx));
 }
 
 fn parse_ldap_index(parts: &[&str]) -> Option<LdapIndex> {
@@ -198,9 +356,9 @@ unsafe extern "C" fn ldap_detect_responses_operation_setup(
     return 0;
 }
 
-fn match_at_index<T, U>(
-    array: &VecDeque<T>, ctx_value: &DetectUintData<U>, get_value: impl Fn(&T) -> Option<U>,
-    detect_match: impl Fn(U, &DetectUintData<U>) -> c_int, index: &LdapIndex,
+fn match_at_index<T, U, C>(
+    array: &VecDeque<T>, ctx_value: &C, get_value: impl Fn(&T) -> Option<U>,
+    detect_match: impl Fn(U, &C) -> c_int, index: &LdapIndex,
 ) -> c_int {
     match index {
         LdapIndex::Any => {
@@ -214,9 +372,10 @@ fn match_at_index<T, U>(
             return 0;
         }
         LdapIndex::All => {
-            for response in array {
+            for (_i, response) in array.iter().enumerate() {
                 if let Some(code) = get_value(response) {
                     if detect_match(code, ctx_value) == 0 {
+                        SCLogDebug!("all: response {} does not match", _i);
                         return 0;
                     }
                 }
@@ -241,18 +400,50 @@ fn match_at_index<T, U>(
     }
 }
 
+/// Match any or all of the distinct values of the responses, which is the
+/// same as matching the value of each response. Returns None for an index,
+/// which needs the responses themselves.
+fn match_distinct<U, C>(
+    mut values: impl Iterator<Item = U>, ctx_value: &C, detect_match: impl Fn(U, &C) -> c_int,
+    index: &LdapIndex,
+) -> Option<c_int> {
+    match index {
+        LdapIndex::Any => Some(values.any(|v| detect_match(v, ctx_value) == 1) as c_int),
+        LdapIndex::All => Some(values.all(|v| detect_match(v, ctx_value) == 1) as c_int),
+        LdapIndex::Index(_) => None,
+    }
+}
+
 unsafe extern "C" fn ldap_detect_responses_operation_match(
     _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
     _sig: *const c_void, ctx: *const c_void,
 ) -> c_int {
     let tx = cast_pointer!(tx, LdapTransaction);
     let ctx = cast_pointer!(ctx, DetectLdapRespOpData);
+    let detect_match =
+        |code, ctx_value: &DetectUintData<u8>| detect_match_uint(ctx_value, code) as c_int;
 
-    return match_at_index::<LdapMessage, u8>(
+    if let Some(summary) = tx.responses_summary() {
+        let ops = summary.op_counts.iter().map(|&(op, _)| op);
+        if let Some(r) = match_distinct(ops, &ctx.du8, detect_match, &ctx.index) {
+            // the summary does not know which response broke the predicate
+            #[cfg(feature = "debug")]
+            if r == 0 && matches!(ctx.index, LdapIndex::All) {
+                let failing = tx
+                    .responses
+                    .iter()
+                    .map(|response| response.protocol_op.to_u8())
+                    .position(|op| detect_match(op, &ctx.du8) == 0);
+                SCLogDebug!("all: response {:?} does not match", failing);
+            }
+            return r;
+        }
+    }
+    return match_at_index::<LdapMessage, u8, DetectUintData<u8>>(
         &tx.responses,
         &ctx.du8,
         |response| Some(response.protocol_op.to_u8()),
-        |code, ctx_value| detect_match_uint(ctx_value, code) as c_int,
+        detect_match,
         &ctx.index,
     );
 }
@@ -304,57 +495,38 @@ unsafe extern "C" fn ldap_detect_responses_count_free(_de: *mut c_void, ctx: *mu
     SCDetectU32Free(ctx);
 }
 
-unsafe extern "C" fn ldap_detect_request_dn_setup(
-    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
-) -> c_int {
-    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
-        return -1;
-    }
-    if SCDetectBufferSetActiveList(de, s, G_LDAP_REQUEST_DN_BUFFER_ID) < 0 {
-        return -1;
+/// Parse a single LDAPResult code, by name or value, for the keywords
+/// matching one result code, to be freed with `ldap_detect_u32_free`
+unsafe extern "C" fn ldap_parse_u32_result_code(
+    ustr: *const std::os::raw::c_char,
+) -> *mut DetectUintData<u32> {
+    let ft_name: &CStr = CStr::from_ptr(ustr); //unsafe
+    if let Ok(s) = ft_name.to_str() {
+        if let Some(ctx) = detect_parse_uint_enum::<u32, LdapResultCode>(s) {
+            let boxed = Box::new(ctx);
+            return Box::into_raw(boxed) as *mut _;
+        }
     }
-    return 0;
+    return std::ptr::null_mut();
 }
 
-unsafe extern "C" fn ldap_detect_request_dn_get_data(
-    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
-    tx: *const c_void, list_id: c_int,
-) -> *mut c_void {
-    return DetectHelperGetData(
-        de,
-        transforms,
-        flow,
-        flow_flags,
-        tx,
-        list_id,
-        ldap_tx_get_request_dn,
-    );
+/// Free of the keywords matching a single `DetectUintData<u32>`
+unsafe extern "C" fn ldap_detect_u32_free(_de: *mut c_void, ctx: *mut c_void) {
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
 }
 
-unsafe extern "C" fn ldap_tx_get_request_dn(
-    tx: *const c_void, _flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
-) -> bool {
-    let tx = cast_pointer!(tx, LdapTransaction);
-
-    *buffer = std::ptr::null();
-    *buffer_len = 0;
-
-    if let Some(request) = &tx.request {
-        let str_buffer: &str = match &request.protocol_op {
-            ProtocolOp::BindRequest(req) => req.name.0.as_str(),
-            ProtocolOp::AddRequest(req) => req.entry.0.as_str(),
-            ProtocolOp::SearchRequest(req) => req.base_object.0.as_str(),
-            ProtocolOp::ModifyRequest(req) => req.object.0.as_str(),
-            ProtocolOp::DelRequest(req) => req.0.as_str(),
-            ProtocolOp::ModDnRequest(req) => req.entry.0.as_str(),
-            ProtocolOp::CompareRequest(req) => req.entry.0.as_str(),
-            _ => return false,
-        };
-        *buffer = str_buffer.as_ptr();
-        *buffer_len = str_buffer.len() as u32;
-        return true;
+detect_sticky_buffer! {
+    mod ldap_request_dn {
+        name: "ldap.request.dn",
+        desc: "match request LDAPDN",
+        url: "/rules/ldap-keywords.html#ldap.request.dn",
+        mpm_desc: "LDAP REQUEST DISTINGUISHED_NAME",
+        alproto: ALPROTO_LDAP,
+        dir: STREAM_TOSERVER,
+        tx: LdapTransaction,
+        get: |tx| tx.request.as_ref().and_then(LdapMessage::request_dn).map(str::as_bytes),
     }
-    return false;
 }
 
 unsafe extern "C" fn ldap_detect_responses_dn_setup(
@@ -403,6 +575,32 @@ unsafe extern "C" fn ldap_tx_get_responses_dn(
     return true;
 }
 
+unsafe extern "C" fn ldap_detect_extended_response_oid_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_EXTENDED_RESPONSE_OID_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_tx_get_extended_response_oid(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    if let Some(oid) = tx.extended_response_oids.get(local_id as usize) {
+        *buffer = oid.as_ptr();
+        *buffer_len = oid.len() as u32;
+        return true;
+    }
+    return false;
+}
+
 fn aux_ldap_parse_resp_result_code(s: &str) -> Option<DetectLdapRespResultData> {
     let parts: Vec<&str> = s.split(',').collect();
     if parts.len() > 2 {
@@ -410,9 +608,12 @@ fn aux_ldap_parse_resp_result_code(s: &str) -> Option<DetectLdapRespResultData>
     }
 
     let index = parse_ldap_index(&parts)?;
-    let du32 = detect_parse_uint_enum::<u32, LdapResultCode>(parts[0])?;
+    let mut codes = Vec::new();
+    for code in parts[0].split('|') {
+        codes.push(detect_parse_uint_enum::<u32, LdapResultCode>(code.trim())?);
+    }
 
-    Some(DetectLdapRespResultData { du32, index })
+    Some(DetectLdapRespResultData { codes, index })
 }
 
 unsafe extern "C" fn ldap_parse_responses_result_code(
@@ -453,32 +654,27 @@ unsafe extern "C" fn ldap_detect_responses_result_code_setup(
     return 0;
 }
 
-fn get_ldap_result_code(response: &LdapMessage) -> Option<u32> {
-    return match &response.protocol_op {
-        ProtocolOp::BindResponse(resp) => Some(resp.result.result_code.0),
-        ProtocolOp::SearchResultDone(resp) => Some(resp.result_code.0),
-        ProtocolOp::ModifyResponse(resp) => Some(resp.result.result_code.0),
-        ProtocolOp::AddResponse(resp) => Some(resp.result_code.0),
-        ProtocolOp::DelResponse(resp) => Some(resp.result_code.0),
-        ProtocolOp::ModDnResponse(resp) => Some(resp.result_code.0),
-        ProtocolOp::CompareResponse(resp) => Some(resp.result_code.0),
-        ProtocolOp::ExtendedResponse(resp) => Some(resp.result.result_code.0),
-        _ => None,
-    };
-}
-
 unsafe extern "C" fn ldap_detect_responses_result_code_match(
     _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
     _sig: *const c_void, ctx: *const c_void,
 ) -> c_int {
     let tx = cast_pointer!(tx, LdapTransaction);
     let ctx = cast_pointer!(ctx, DetectLdapRespResultData);
+    let detect_match = |code, codes: &Vec<DetectUintData<u32>>| {
+        codes.iter().any(|du32| detect_match_uint(du32, code)) as c_int
+    };
 
-    return match_at_index::<LdapMessage, u32>(
+    if let Some(summary) = tx.responses_summary() {
+        let codes = summary.result_codes.iter().copied();
+        if let Some(r) = match_distinct(codes, &ctx.codes, detect_match, &ctx.index) {
+            return r;
+        }
+    }
+    return match_at_index::<LdapMessage, u32, Vec<DetectUintData<u32>>>(
         &tx.responses,
-        &ctx.du32,
+        &ctx.codes,
         get_ldap_result_code,
-        |code, ctx_value| detect_match_uint(ctx_value, code) as c_int,
+        detect_match,
         &ctx.index,
     );
 }
@@ -489,33 +685,157 @@ unsafe extern "C" fn ldap_detect_responses_result_code_free(_de: *mut c_void, ct
     std::mem::drop(Box::from_raw(ctx));
 }
 
-unsafe extern "C" fn ldap_detect_responses_msg_setup(
-    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+/// The result code with the highest severity, the first one seen between
+/// codes of the same severity.
+fn ldap_worst_result_code(codes: impl Iterator<Item = u32>) -> Option<u32> {
+    codes.fold(None, |worst, code| match worst {
+        Some(w) if ldap_result_code_severity(w) >= ldap_result_code_severity(code) => Some(w),
+        _ => Some(code),
+    })
+}
+
+unsafe extern "C" fn ldap_detect_responses_result_code_worst_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
 ) -> c_int {
     if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
         return -1;
     }
-    if SCDetectBufferSetActiveList(de, s, G_LDAP_RESPONSES_MSG_BUFFER_ID) < 0 {
+    let ctx = ldap_parse_u32_result_code(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_RESULT_CODE_WORST_KW_ID,
+        ctx,
+        G_LDAP_RESPONSES_RESULT_CODE_WORST_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
         return -1;
     }
     return 0;
 }
 
-unsafe extern "C" fn ldap_tx_get_responses_msg(
-    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
-    buffer: *mut *const u8, buffer_len: *mut u32,
-) -> bool {
+unsafe extern "C" fn ldap_detect_responses_result_code_worst_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
     let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
 
-    if local_id as usize >= tx.responses.len() {
-        return false;
+    let worst = if let Some(summary) = tx.responses_summary() {
+        ldap_worst_result_code(summary.result_codes.iter().copied())
+    } else {
+        ldap_worst_result_code(tx.responses.iter().filter_map(get_ldap_result_code))
+    };
+    if let Some(code) = worst {
+        return detect_match_uint(ctx, code) as c_int;
     }
-    *buffer = std::ptr::null();
-    *buffer_len = 0;
+    return 0;
+}
 
-    let response = &tx.responses[local_id as usize];
-    // We expect every response in one tx to be the same protocol_op
-    let str_buffer: &str = match &response.protocol_op {
+/// Parse the optional operation of `ldap.responses.message`, which has to
+/// be one of the responses carrying a result.
+fn aux_ldap_parse_responses_msg_op(s: &str) -> Option<u8> {
+    let op = ProtocolOpCode::from_str(s.trim())? as u8;
+    if !LDAP_RESPONSES_MSG_OPS
+        .iter()
+        .any(|(code, _, _)| *code == op)
+    {
+        return None;
+    }
+    Some(op)
+}
+
+/// Parse the optional index of `ldap.responses.message`, the position of
+/// the response with the message. Only the responses with a result have a
+/// message, so this matches any result code at that position.
+fn aux_ldap_parse_responses_msg_index(s: &str) -> Option<DetectLdapRespResultData> {
+    let index = i32::from_str(s.trim()).ok()?;
+    let du32 = DetectUintData::<u32> {
+        arg1: 0,
+        arg2: 0,
+        mode: DetectUintMode::DetectUintModeGte,
+    };
+    Some(DetectLdapRespResultData {
+        codes: vec![du32],
+        index: LdapIndex::Index(index),
+    })
+}
+
+/// Parse the argument of `ldap.responses.message`: an operation, an index,
+/// or an operation and an index separated by a comma.
+fn aux_ldap_parse_responses_msg_arg(s: &str) -> Option<DetectLdapRespMsgData> {
+    if let Some((op, index)) = s.split_once(',') {
+        return Some(DetectLdapRespMsgData {
+            op: Some(aux_ldap_parse_responses_msg_op(op)?),
+            index: Some(aux_ldap_parse_responses_msg_index(index)?),
+        });
+    }
+    if let Some(index) = aux_ldap_parse_responses_msg_index(s) {
+        return Some(DetectLdapRespMsgData {
+            op: None,
+            index: Some(index),
+        });
+    }
+    Some(DetectLdapRespMsgData {
+        op: Some(aux_ldap_parse_responses_msg_op(s)?),
+        index: None,
+    })
+}
+
+unsafe extern "C" fn ldap_detect_responses_msg_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let mut buffer_id = G_LDAP_RESPONSES_MSG_BUFFER_ID;
+    if !raw.is_null() {
+        let arg = if let Some(arg) = CStr::from_ptr(raw)
+            .to_str()
+            .ok()
+            .and_then(aux_ldap_parse_responses_msg_arg)
+        {
+            arg
+        } else {
+            return -1;
+        };
+        if let Some(op) = arg.op {
+            // the messages of that operation have a buffer of their own
+            buffer_id = ldap_responses_msg_op_buffer_id(op);
+        }
+        // the index is matched on the result code of the response, which
+        // the responses with a message all have
+        if let Some(index) = arg.index {
+            let ctx = Box::into_raw(Box::new(index)) as *mut c_void;
+            if SigMatchAppendSMToList(
+                de,
+                s,
+                G_LDAP_RESPONSES_RESULT_CODE_KW_ID,
+                ctx,
+                G_LDAP_RESPONSES_RESULT_CODE_BUFFER_ID,
+            )
+            .is_null()
+            {
+                ldap_detect_responses_result_code_free(std::ptr::null_mut(), ctx);
+                return -1;
+            }
+        }
+    }
+    if SCDetectBufferSetActiveList(de, s, buffer_id) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+/// The diagnostic message of a response, empty for the responses without
+/// a result.
+fn ldap_response_msg(response: &LdapMessage) -> &str {
+    match &response.protocol_op {
         ProtocolOp::BindResponse(resp) => resp.result.diagnostic_message.0.as_str(),
         ProtocolOp::SearchResultDone(resp) => resp.diagnostic_message.0.as_str(),
         ProtocolOp::ModifyResponse(resp) => resp.result.diagnostic_message.0.as_str(),
@@ -527,6 +847,45 @@ unsafe extern "C" fn ldap_tx_get_responses_msg(
         _ => "",
         // This ensures that the iteration continues,
         // allowing other responses in the transaction to be processed correctly
+    }
+}
+
+unsafe extern "C" fn ldap_tx_get_responses_msg(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    if local_id as usize >= tx.responses.len() {
+        return false;
+    }
+    *buffer = std::ptr::null();
+    *buffer_len = 0;
+
+    let str_buffer = ldap_response_msg(&tx.responses[local_id as usize]);
+
+    *buffer = str_buffer.as_ptr();
+    *buffer_len = str_buffer.len() as u32;
+    return true;
+}
+
+/// Getter of `ldap.responses.message:<operation>`, with empty buffers for
+/// the responses of the other operations.
+unsafe extern "C" fn ldap_tx_get_responses_msg_op<const OP: u8>(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    let response = if let Some(response) = tx.responses.get(local_id as usize) {
+        response
+    } else {
+        return false;
+    };
+    let str_buffer = if response.protocol_op.to_u8() == OP {
+        ldap_response_msg(response)
+    } else {
+        ""
     };
 
     *buffer = str_buffer.as_ptr();
@@ -534,6 +893,59 @@ unsafe extern "C" fn ldap_tx_get_responses_msg(
     return true;
 }
 
+/// The operations of the responses with a result, with the message buffer
+/// of each of them for `ldap.responses.message:<operation>`.
+const LDAP_RESPONSES_MSG_OPS: [(u8, &[u8], LdapGetMultiDataFn); 8] = [
+    (
+        ProtocolOpCode::BindResponse as u8,
+        b"ldap.responses.message.bind_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::BindResponse as u8 }>,
+    ),
+    (
+        ProtocolOpCode::SearchResultDone as u8,
+        b"ldap.responses.message.search_result_done\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::SearchResultDone as u8 }>,
+    ),
+    (
+        ProtocolOpCode::ModifyResponse as u8,
+        b"ldap.responses.message.modify_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::ModifyResponse as u8 }>,
+    ),
+    (
+        ProtocolOpCode::AddResponse as u8,
+        b"ldap.responses.message.add_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::AddResponse as u8 }>,
+    ),
+    (
+        ProtocolOpCode::DelResponse as u8,
+        b"ldap.responses.message.del_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::DelResponse as u8 }>,
+    ),
+    (
+        ProtocolOpCode::ModDnResponse as u8,
+        b"ldap.responses.message.mod_dn_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::ModDnResponse as u8 }>,
+    ),
+    (
+        ProtocolOpCode::CompareResponse as u8,
+        b"ldap.responses.message.compare_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::CompareResponse as u8 }>,
+    ),
+    (
+        ProtocolOpCode::ExtendedResponse as u8,
+        b"ldap.responses.message.extended_response\0",
+        ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::ExtendedResponse as u8 }>,
+    ),
+];
+
+/// Buffer of the messages of an operation of `LDAP_RESPONSES_MSG_OPS`.
+unsafe fn ldap_responses_msg_op_buffer_id(op: u8) -> c_int {
+    LDAP_RESPONSES_MSG_OPS
+        .iter()
+        .position(|(code, _, _)| *code == op)
+        .map_or(-1, |i| G_LDAP_RESPONSES_MSG_OP_BUFFER_IDS[i])
+}
+
 unsafe extern "C" fn ldap_detect_request_attibute_type_setup(
     de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
 ) -> c_int {
@@ -631,138 +1043,3894 @@ unsafe extern "C" fn ldap_tx_get_resp_attribute_type(
     return false;
 }
 
-#[no_mangle]
-pub unsafe extern "C" fn SCDetectLdapRegister() {
-    let kw = SCSigTableAppLiteElmt {
-        name: b"ldap.request.operation\0".as_ptr() as *const libc::c_char,
-        desc: b"match LDAP request operation\0".as_ptr() as *const libc::c_char,
-        url: b"/rules/ldap-keywords.html#ldap.request.operation\0".as_ptr() as *const libc::c_char,
-        AppLayerTxMatch: Some(ldap_detect_request_operation_match),
-        Setup: ldap_detect_request_operation_setup,
-        Free: Some(ldap_detect_request_free),
-        flags: 0,
-    };
-    G_LDAP_REQUEST_OPERATION_KW_ID = DetectHelperKeywordRegister(&kw);
-    G_LDAP_REQUEST_OPERATION_BUFFER_ID = DetectHelperBufferRegister(
-        b"ldap.request.operation\0".as_ptr() as *const libc::c_char,
-        ALPROTO_LDAP,
-        STREAM_TOSERVER,
-    );
-    let kw = SCSigTableAppLiteElmt {
-        name: b"ldap.responses.operation\0".as_ptr() as *const libc::c_char,
-        desc: b"match LDAP responses operation\0".as_ptr() as *const libc::c_char,
-        url: b"/rules/ldap-keywords.html#ldap.responses.operation\0".as_ptr()
-            as *const libc::c_char,
-        AppLayerTxMatch: Some(ldap_detect_responses_operation_match),
-        Setup: ldap_detect_responses_operation_setup,
-        Free: Some(ldap_detect_responses_free),
-        flags: 0,
+/// Attribute type selected by `ldap.responses.search.entry.value`
+#[derive(Debug, PartialEq)]
+struct DetectLdapEntryValueData {
+    attr_type: Vec<u8>,
+}
+
+fn aux_ldap_parse_search_entry_value(s: &str) -> Option<DetectLdapEntryValueData> {
+    let attr_type = s.trim();
+    if attr_type.is_empty() || attr_type.contains(|c: char| c == ':' || c.is_whitespace()) {
+        return None;
+    }
+    Some(DetectLdapEntryValueData {
+        attr_type: attr_type.as_bytes().to_vec(),
+    })
+}
+
+/// Returns the value of a `type: value` search entry value if its
+/// attribute type is `attr_type`, ignoring case.
+fn ldap_search_entry_value_select<'a>(item: &'a [u8], attr_type: &[u8]) -> Option<&'a [u8]> {
+    if item.len() < attr_type.len() + 2 {
+        return None;
+    }
+    let (item_type, rest) = item.split_at(attr_type.len());
+    if item_type.eq_ignore_ascii_case(attr_type) && rest.starts_with(b": ") {
+        return Some(&rest[2..]);
+    }
+    return None;
+}
+
+unsafe extern "C" fn ldap_detect_responses_search_entry_value_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if raw.is_null() {
+        SCLogError!("ldap.responses.search.entry.value needs an attribute type");
+        return -1;
+    }
+    let ctx = if let Some(ctx) = CStr::from_ptr(raw)
+        .to_str()
+        .ok()
+        .and_then(aux_ldap_parse_search_entry_value)
+    {
+        Box::into_raw(Box::new(ctx)) as *mut c_void
+    } else {
+        return -1;
     };
-    G_LDAP_RESPONSES_OPERATION_KW_ID = DetectHelperKeywordRegister(&kw);
-    G_LDAP_RESPONSES_OPERATION_BUFFER_ID = DetectHelperBufferRegister(
-        b"ldap.responses.operation\0".as_ptr() as *const libc::c_char,
-        ALPROTO_LDAP,
-        STREAM_TOCLIENT,
-    );
-    let kw = SCSigTableAppLiteElmt {
-        name: b"ldap.responses.count\0".as_ptr() as *const libc::c_char,
-        desc: b"match number of LDAP responses\0".as_ptr() as *const libc::c_char,
-        url: b"/rules/ldap-keywords.html#ldap.responses.count\0".as_ptr() as *const libc::c_char,
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_RESPONSES_SEARCH_ENTRY_VALUE_BUFFER_ID) < 0 {
+        ldap_search_entry_value_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    // the attribute is selected by the first transform of the buffer, before
+    // the ones of the rule
+    if DetectSignatureAddTransform(
+        s as *mut c_void,
+        G_LDAP_RESPONSES_SEARCH_ENTRY_VALUE_TRANSFORM_ID,
+        ctx,
+    ) != 0
+    {
+        ldap_search_entry_value_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+thread_local! {
+    /// Search entry value being inspected, as `type: value`. The buffer is
+    /// copied right away by the attribute selection transform every rule
+    /// using it has, so it is reused for the next value.
+    static SEARCH_ENTRY_VALUE: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe extern "C" fn ldap_tx_get_resp_search_entry_value(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    let mut pos = 0_u32;
+    for response in &tx.responses {
+        if let ProtocolOp::SearchResultEntry(entry) = &response.protocol_op {
+            for attribute in &entry.attributes {
+                let count = attribute.attr_vals.len() as u32;
+                if local_id < pos + count {
+                    let value = &attribute.attr_vals[(local_id - pos) as usize].0;
+                    let attr_type = attribute.attr_type.0.as_bytes();
+                    return SEARCH_ENTRY_VALUE.with(|item| {
+                        let mut item = item.borrow_mut();
+                        item.clear();
+                        item.extend_from_slice(attr_type);
+                        item.extend_from_slice(b": ");
+                        item.extend_from_slice(value);
+                        *buffer = item.as_ptr();
+                        *buffer_len = item.len() as u32;
+                        true
+                    });
+                }
+                pos += count;
+            }
+        }
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_search_entry_value_transform_setup(
+    _de: *mut c_void, _s: *mut c_void, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    SCLogError!("the attribute is selected by the ldap.responses.search.entry.value argument");
+    return -1;
+}
+
+unsafe extern "C" fn ldap_search_entry_value_transform(
+    _det: *mut c_void, buffer: *mut c_void, ctx: *mut c_void,
+) {
+    let input = InspectionBufferPtr(buffer);
+    let input_len = InspectionBufferLength(buffer);
+    if input.is_null() || input_len == 0 {
+        return;
+    }
+    let input = build_slice!(input, input_len as usize);
+
+    let output = InspectionBufferCheckAndExpand(buffer, input_len);
+    if output.is_null() {
+        // allocation failure
+        return;
+    }
+
+    let ctx = cast_pointer!(ctx, DetectLdapEntryValueData);
+    // the values of the other attributes are emptied so that no content
+    // matches them
+    let value = ldap_search_entry_value_select(input, &ctx.attr_type).unwrap_or(&[]);
+    // the output may be the input, the value being its end
+    std::ptr::copy(value.as_ptr(), output, value.len());
+    InspectionBufferTruncate(buffer, value.len() as u32);
+}
+
+unsafe extern "C" fn ldap_search_entry_value_free(_de: *mut c_void, ctx: *mut c_void) {
+    std::mem::drop(Box::from_raw(ctx as *mut DetectLdapEntryValueData));
+}
+
+unsafe extern "C" fn ldap_detect_responses_starttls_result_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = ldap_parse_u32_result_code(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_STARTTLS_RESULT_KW_ID,
+        ctx,
+        G_LDAP_RESPONSES_STARTTLS_RESULT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+fn is_starttls_request(request: &Option<LdapMessage>) -> bool {
+    if let Some(request) = request {
+        if let ProtocolOp::ExtendedRequest(req) = &request.protocol_op {
+            return req.request_name.0 == STARTTLS_OID;
+        }
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_responses_starttls_result_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+
+    // the response name is optional, so also rely on the request
+    let starttls_request = is_starttls_request(&tx.request);
+    for response in &tx.responses {
+        if let ProtocolOp::ExtendedResponse(resp) = &response.protocol_op {
+            let starttls_response = match &resp.response_name {
+                Some(name) => name.0 == STARTTLS_OID,
+                None => starttls_request,
+            };
+            if starttls_response && detect_match_uint(ctx, resp.result.result_code.0) {
+                return 1;
+            }
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_responses_bind_result_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = ldap_parse_u32_result_code(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_BIND_RESULT_KW_ID,
+        ctx,
+        G_LDAP_RESPONSES_BIND_RESULT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+/// Result codes of the bind responses, other responses are ignored
+fn bind_result_codes(tx: &LdapTransaction) -> impl Iterator<Item = u32> + '_ {
+    tx.responses
+        .iter()
+        .filter_map(|response| match &response.protocol_op {
+            ProtocolOp::BindResponse(resp) => Some(resp.result.result_code.0),
+            _ => None,
+        })
+}
+
+unsafe extern "C" fn ldap_detect_responses_bind_result_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    return bind_result_codes(tx).any(|code| detect_match_uint(ctx, code)) as c_int;
+}
+
+unsafe extern "C" fn ldap_detect_request_rootdse_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_ROOTDSE_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_ROOTDSE_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+/// A rootDSE query is a base scope search on the empty DN, usually with
+/// `(objectClass=*)` as filter.
+fn is_rootdse_query(op: &ProtocolOp) -> bool {
+    if let ProtocolOp::SearchRequest(req) = op {
+        if !req.base_object.0.is_empty() || req.scope.0 != 0 {
+            return false;
+        }
+        if let Filter::Present(attr) = &req.filter {
+            return attr.0.eq_ignore_ascii_case("objectClass");
+        }
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_request_rootdse_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        return is_rootdse_query(&request.protocol_op) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_bind_consecutive_failures_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_BIND_CONSECUTIVE_FAILURES_KW_ID,
+        ctx,
+        G_LDAP_BIND_CONSECUTIVE_FAILURES_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_bind_consecutive_failures_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    let has_bind_response = tx
+        .responses
+        .iter()
+        .any(|response| matches!(response.protocol_op, ProtocolOp::BindResponse(_)));
+    if has_bind_response {
+        return detect_match_uint(ctx, tx.bind_failures) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_unbind_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_UNBIND_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_UNBIND_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_unbind_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        return (request.protocol_op.to_u8() == ProtocolOpCode::UnbindRequest as u8) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_responses_referral_host_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_RESPONSES_REFERRAL_HOST_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_tx_get_resp_referral_host(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    let uri = tx
+        .responses
+        .iter()
+        .filter_map(|response| response.result())
+        .flat_map(|result| result.referrals.iter())
+        .nth(local_id as usize);
+    if let Some(uri) = uri {
+        // a malformed URI is inspected as a whole
+        let value = referral_host(uri).unwrap_or(uri);
+        *buffer = value.as_ptr();
+        *buffer_len = value.len() as u32;
+        return true;
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_responses_search_reference_host_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_RESPONSES_SEARCH_REFERENCE_HOST_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_tx_get_resp_search_reference_host(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    let uri = tx
+        .responses
+        .iter()
+        .filter_map(|response| match &response.protocol_op {
+            ProtocolOp::SearchResultReference(uris) => Some(uris),
+            _ => None,
+        })
+        .flatten()
+        .nth(local_id as usize);
+    if let Some(uri) = uri {
+        // a malformed URI is inspected as a whole
+        let value = referral_host(&uri.0).unwrap_or(&uri.0);
+        *buffer = value.as_ptr();
+        *buffer_len = value.len() as u32;
+        return true;
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_no_time_limit_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_NO_TIME_LIMIT_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_SEARCH_NO_TIME_LIMIT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_no_time_limit_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            return (req.time_limit == 0) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_no_size_limit_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_NO_SIZE_LIMIT_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_SEARCH_NO_SIZE_LIMIT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_no_size_limit_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            return (req.size_limit == 0) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_dn_rdn_attribute_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_REQUEST_DN_RDN_ATTRIBUTE_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_dn_rdn_attribute_get_data(
+    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
+    tx: *const c_void, list_id: c_int,
+) -> *mut c_void {
+    return DetectHelperGetData(
+        de,
+        transforms,
+        flow,
+        flow_flags,
+        tx,
+        list_id,
+        ldap_tx_get_request_dn_rdn_attribute,
+    );
+}
+
+unsafe extern "C" fn ldap_tx_get_request_dn_rdn_attribute(
+    tx: *const c_void, _flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    *buffer = std::ptr::null();
+    *buffer_len = 0;
+
+    let attr = tx
+        .request
+        .as_ref()
+        .and_then(LdapMessage::request_dn)
+        .and_then(dn_rdn_attribute);
+    if let Some(attr) = attr {
+        *buffer = attr.as_ptr();
+        *buffer_len = attr.len() as u32;
+        return true;
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_request_bind_legacy_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_BIND_LEGACY_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_BIND_LEGACY_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_bind_legacy_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::BindRequest(req) = &request.protocol_op {
+            return (req.version != 3) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_responses_ranged_attribute_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_RANGED_ATTRIBUTE_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_RESPONSES_RANGED_ATTRIBUTE_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_responses_ranged_attribute_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if tx
+        .responses_summary()
+        .map_or(false, |summary| summary.entries == 0)
+    {
+        return 0;
+    }
+    let ranged = tx.responses.iter().any(|response| {
+        if let ProtocolOp::SearchResultEntry(entry) = &response.protocol_op {
+            return entry
+                .attributes
+                .iter()
+                .any(|attr| attribute_has_range_option(&attr.attr_type.0));
+        }
+        false
+    });
+    return ranged as c_int;
+}
+
+/// Attribute descriptions are case-insensitive and a search request may list
+/// the same attribute more than once, so both sides are compared as
+/// normalized sets.
+fn ldap_attribute_set<'a>(attributes: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut set: Vec<String> = attributes.map(|a| a.to_ascii_lowercase()).collect();
+    set.sort_unstable();
+    set.dedup();
+    set
+}
+
+fn aux_ldap_parse_search_attributes_equals(s: &str) -> Option<DetectLdapSearchAttributesData> {
+    let attributes: Vec<&str> = s.split(',').map(|a| a.trim()).collect();
+    if attributes.iter().any(|a| a.is_empty()) {
+        return None;
+    }
+    Some(DetectLdapSearchAttributesData {
+        attributes: ldap_attribute_set(attributes.into_iter()),
+    })
+}
+
+unsafe extern "C" fn ldap_detect_request_search_attributes_equals_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = match CStr::from_ptr(raw).to_str() {
+        Ok(arg) => match aux_ldap_parse_search_attributes_equals(arg) {
+            Some(ctx) => Box::into_raw(Box::new(ctx)) as *mut c_void,
+            None => return -1,
+        },
+        Err(_) => return -1,
+    };
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_ATTRIBUTES_EQUALS_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_SEARCH_ATTRIBUTES_EQUALS_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_request_search_attributes_equals_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_attributes_equals_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectLdapSearchAttributesData);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            let requested = ldap_attribute_set(req.attributes.iter().map(|a| a.0.as_str()));
+            return (requested == ctx.attributes) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_attributes_equals_free(
+    _de: *mut c_void, ctx: *mut c_void,
+) {
+    let ctx = cast_pointer!(ctx, DetectLdapSearchAttributesData);
+    std::mem::drop(Box::from_raw(ctx));
+}
+
+/// CRC32 of the normalized attribute set joined with commas, so that the
+/// same attribute list always gives the same fingerprint.
+fn ldap_search_attributes_hash<'a>(attributes: impl Iterator<Item = &'a str>) -> u32 {
+    let set = ldap_attribute_set(attributes);
+    crc32::checksum_ieee(set.join(",").as_bytes())
+}
+
+unsafe extern "C" fn ldap_detect_request_search_attributes_hash_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_ATTRIBUTES_HASH_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_SEARCH_ATTRIBUTES_HASH_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_attributes_hash_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            let hash = ldap_search_attributes_hash(req.attributes.iter().map(|a| a.0.as_str()));
+            return detect_match_uint(ctx, hash) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_bytes_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_BYTES_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_BYTES_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_bytes_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    return detect_match_uint(ctx, tx.request_bytes) as c_int;
+}
+
+unsafe extern "C" fn ldap_detect_response_bytes_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSE_BYTES_KW_ID,
+        ctx,
+        G_LDAP_RESPONSE_BYTES_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_response_bytes_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    return detect_match_uint(ctx, tx.response_bytes) as c_int;
+}
+
+unsafe extern "C" fn ldap_detect_request_spn_query_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SPN_QUERY_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_SPN_QUERY_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+/// Check if the filter matches the predicate, directly or in one of the
+/// terms of an `&` or `|` filter. Negated filters are skipped.
+fn filter_has_term(filter: &Filter, pred: &dyn Fn(&Filter) -> bool) -> bool {
+    match filter {
+        Filter::And(filters) | Filter::Or(filters) => {
+            filters.iter().any(|f| filter_has_term(f, pred))
+        }
+        Filter::Not(_) => false,
+        _ => pred(filter),
+    }
+}
+
+/// `(servicePrincipalName=*)`
+fn is_spn_presence_filter(filter: &Filter) -> bool {
+    if let Filter::Present(desc) = filter {
+        return desc.0.eq_ignore_ascii_case("servicePrincipalName");
+    }
+    return false;
+}
+
+/// Kerberoasting enumeration: a search for accounts having a
+/// `servicePrincipalName`, asking for that attribute back.
+fn is_spn_query(op: &ProtocolOp) -> bool {
+    if let ProtocolOp::SearchRequest(req) = op {
+        return req
+            .attributes
+            .iter()
+            .any(|a| a.0.eq_ignore_ascii_case("servicePrincipalName"))
+            && filter_has_term(&req.filter, &is_spn_presence_filter);
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_request_spn_query_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        return is_spn_query(&request.protocol_op) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_asrep_query_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_ASREP_QUERY_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_ASREP_QUERY_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+/// LDAP_MATCHING_RULE_BIT_AND
+const LDAP_MATCHING_RULE_BIT_AND_OID: &str = "1.2.840.113556.1.4.803";
+/// DONT_REQ_PREAUTH flag of userAccountControl
+const UF_DONT_REQUIRE_PREAUTH: u32 = 0x400000;
+
+/// `(userAccountControl:1.2.840.113556.1.4.803:=4194304)`
+fn is_dont_req_preauth_filter(filter: &Filter) -> bool {
+    if let Filter::ExtensibleMatch(mra) = filter {
+        let rule_ok = mra
+            .matching_rule
+            .as_ref()
+            .map_or(false, |r| r.0 == LDAP_MATCHING_RULE_BIT_AND_OID);
+        let attr_ok = mra
+            .rule_type
+            .as_ref()
+            .map_or(false, |t| t.0.eq_ignore_ascii_case("userAccountControl"));
+        if rule_ok && attr_ok {
+            return std::str::from_utf8(&mra.assertion_value.0)
+                .ok()
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .map_or(false, |v| v & UF_DONT_REQUIRE_PREAUTH != 0);
+        }
+    }
+    return false;
+}
+
+/// AS-REP roasting enumeration: a search for accounts not requiring
+/// Kerberos preauthentication.
+fn is_asrep_query(op: &ProtocolOp) -> bool {
+    if let ProtocolOp::SearchRequest(req) = op {
+        return filter_has_term(&req.filter, &is_dont_req_preauth_filter);
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_request_asrep_query_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        return is_asrep_query(&request.protocol_op) as c_int;
+    }
+    return 0;
+}
+
+/// Attributes naming the account in a search filter
+const LDAP_ACCOUNT_ATTRIBUTES: [&str; 4] = [
+    "sAMAccountName",
+    "userPrincipalName",
+    "cn",
+    "distinguishedName",
+];
+
+#[derive(Debug, PartialEq)]
+struct DetectLdapSearchTargetData {
+    account: Vec<u8>,
+}
+
+fn aux_ldap_parse_search_target(s: &str) -> Option<DetectLdapSearchTargetData> {
+    let account = s.trim();
+    if account.is_empty() {
+        return None;
+    }
+    Some(DetectLdapSearchTargetData {
+        account: account.as_bytes().to_vec(),
+    })
+}
+
+/// Check if the value matches the substrings of a substring filter,
+/// ignoring case.
+fn substrings_match(value: &[u8], substrings: &[Substring]) -> bool {
+    let mut rest = value;
+    for substring in substrings {
+        match substring {
+            Substring::Initial(v) => {
+                if rest.len() < v.0.len() || !rest[..v.0.len()].eq_ignore_ascii_case(&v.0) {
+                    return false;
+                }
+                rest = &rest[v.0.len()..];
+            }
+            Substring::Any(v) => {
+                if v.0.is_empty() {
+                    continue;
+                }
+                match rest
+                    .windows(v.0.len())
+                    .position(|w| w.eq_ignore_ascii_case(&v.0))
+                {
+                    Some(pos) => rest = &rest[pos + v.0.len()..],
+                    None => return false,
+                }
+            }
+            Substring::Final(v) => {
+                if rest.len() < v.0.len()
+                    || !rest[rest.len() - v.0.len()..].eq_ignore_ascii_case(&v.0)
+                {
+                    return false;
+                }
+                rest = &[];
+            }
+        }
+    }
+    return true;
+}
+
+fn is_account_attribute(attr: &str) -> bool {
+    LDAP_ACCOUNT_ATTRIBUTES
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case(attr))
+}
+
+/// Equality or substring assertion on an account attribute which the
+/// account satisfies, e.g. `(sAMAccountName=admin*)` for `administrator`.
+fn is_account_filter(filter: &Filter, account: &[u8]) -> bool {
+    match filter {
+        Filter::EqualityMatch(ava) => {
+            is_account_attribute(&ava.attribute_desc.0)
+                && ava.assertion_value.eq_ignore_ascii_case(account)
+        }
+        Filter::Substrings(sf) => {
+            is_account_attribute(&sf.filter_type.0) && substrings_match(account, &sf.substrings)
+        }
+        _ => false,
+    }
+}
+
+fn is_search_for_account(op: &ProtocolOp, account: &[u8]) -> bool {
+    if let ProtocolOp::SearchRequest(req) = op {
+        return filter_has_term(&req.filter, &|f: &Filter| is_account_filter(f, account));
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_targets_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = if let Some(ctx) = CStr::from_ptr(raw)
+        .to_str()
+        .ok()
+        .and_then(aux_ldap_parse_search_target)
+    {
+        Box::into_raw(Box::new(ctx)) as *mut c_void
+    } else {
+        return -1;
+    };
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_TARGETS_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_SEARCH_TARGETS_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_request_search_targets_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_targets_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectLdapSearchTargetData);
+    if let Some(request) = &tx.request {
+        return is_search_for_account(&request.protocol_op, &ctx.account) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_targets_free(_de: *mut c_void, ctx: *mut c_void) {
+    let ctx = cast_pointer!(ctx, DetectLdapSearchTargetData);
+    std::mem::drop(Box::from_raw(ctx));
+}
+
+unsafe extern "C" fn ldap_detect_response_orphan_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSE_ORPHAN_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_RESPONSE_ORPHAN_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_response_orphan_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    return tx.orphan_response as c_int;
+}
+
+/// Count the equality assertions of the filter and its nested filters,
+/// negated ones included.
+fn filter_equality_count(filter: &Filter) -> u32 {
+    match filter {
+        Filter::And(filters) | Filter::Or(filters) => filters
+            .iter()
+            .fold(0, |n, f| n.saturating_add(filter_equality_count(f))),
+        Filter::Not(f) => filter_equality_count(f),
+        Filter::EqualityMatch(_) => 1,
+        _ => 0,
+    }
+}
+
+unsafe extern "C" fn ldap_detect_request_search_filter_equality_count_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_FILTER_EQUALITY_COUNT_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_SEARCH_FILTER_EQUALITY_COUNT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_responses_count_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_filter_equality_count_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            return detect_match_uint(ctx, filter_equality_count(&req.filter)) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_search_filter_matchall_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SEARCH_FILTER_MATCHALL_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_SEARCH_FILTER_MATCHALL_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+/// Check if the filter matches every entry: `(objectClass=*)`, the absolute
+/// true filter `(&)` of RFC 4526, or `&` and `|` filters built from those.
+fn is_matchall_filter(filter: &Filter) -> bool {
+    match filter {
+        Filter::Present(attr) => attr.0.eq_ignore_ascii_case("objectClass"),
+        Filter::And(filters) => filters.iter().all(is_matchall_filter),
+        Filter::Or(filters) => filters.iter().any(is_matchall_filter),
+        _ => false,
+    }
+}
+
+unsafe extern "C" fn ldap_detect_request_search_filter_matchall_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            return is_matchall_filter(&req.filter) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_responses_admin_limit_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_ADMIN_LIMIT_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_RESPONSES_ADMIN_LIMIT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+/// Response with a result code telling a server limit was hit.
+fn is_admin_limit(response: &LdapMessage) -> bool {
+    matches!(
+        get_ldap_result_code(response),
+        Some(code) if code == LdapResultCode::AdminLimitExceeded as u32
+            || code == LdapResultCode::SizeLimitExceeded as u32
+    )
+}
+
+unsafe extern "C" fn ldap_detect_responses_admin_limit_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    return tx.responses.iter().any(is_admin_limit) as c_int;
+}
+
+/// Non-error result codes that `ldap.responses.any_error` is asked to
+/// count as errors.
+#[derive(Debug, Default, PartialEq)]
+struct DetectLdapAnyErrorData {
+    referral: bool,
+    sasl_bind_in_progress: bool,
+}
+
+impl DetectLdapAnyErrorData {
+    fn is_error(&self, code: u32) -> bool {
+        is_ldap_error_code(code)
+            || (self.referral && code == LdapResultCode::Referral as u32)
+            || (self.sasl_bind_in_progress && code == LdapResultCode::SaslBindInProgress as u32)
+    }
+}
+
+fn aux_ldap_parse_any_error(s: &str) -> Option<DetectLdapAnyErrorData> {
+    let mut ctx = DetectLdapAnyErrorData::default();
+    for name in s.split(',') {
+        match LdapResultCode::from_str(name.trim()) {
+            Some(LdapResultCode::Referral) => ctx.referral = true,
+            Some(LdapResultCode::SaslBindInProgress) => ctx.sasl_bind_in_progress = true,
+            _ => {
+                SCLogError!("invalid result code for ldap.responses.any_error: {}", name);
+                return None;
+            }
+        }
+    }
+    Some(ctx)
+}
+
+unsafe extern "C" fn ldap_detect_responses_any_error_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = if raw.is_null() {
+        DetectLdapAnyErrorData::default()
+    } else if let Some(ctx) = CStr::from_ptr(raw)
+        .to_str()
+        .ok()
+        .and_then(aux_ldap_parse_any_error)
+    {
+        ctx
+    } else {
+        return -1;
+    };
+    let ctx = Box::into_raw(Box::new(ctx)) as *mut c_void;
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_ANY_ERROR_KW_ID,
+        ctx,
+        G_LDAP_RESPONSES_ANY_ERROR_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_responses_any_error_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_responses_any_error_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectLdapAnyErrorData);
+    if let Some(summary) = tx.responses_summary() {
+        if summary.has_error {
+            return 1;
+        }
+        return summary.result_codes.iter().any(|&code| ctx.is_error(code)) as c_int;
+    }
+    return tx
+        .responses
+        .iter()
+        .filter_map(get_ldap_result_code)
+        .any(|code| ctx.is_error(code)) as c_int;
+}
+
+unsafe extern "C" fn ldap_detect_responses_any_error_free(_de: *mut c_void, ctx: *mut c_void) {
+    let ctx = cast_pointer!(ctx, DetectLdapAnyErrorData);
+    std::mem::drop(Box::from_raw(ctx));
+}
+
+/// Size and time limits of the search request, None for other requests.
+fn search_request_limits(tx: &LdapTransaction) -> Option<(u32, u32)> {
+    match &tx.request.as_ref()?.protocol_op {
+        ProtocolOp::SearchRequest(req) => Some((req.size_limit, req.time_limit)),
+        _ => None,
+    }
+}
+
+unsafe extern "C" fn ldap_detect_request_size_limit_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_SIZE_LIMIT_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_SIZE_LIMIT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_size_limit_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some((size_limit, _)) = search_request_limits(tx) {
+        return detect_match_uint(ctx, size_limit) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_time_limit_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_TIME_LIMIT_KW_ID,
+        ctx,
+        G_LDAP_REQUEST_TIME_LIMIT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        ldap_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_time_limit_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some((_, time_limit)) = search_request_limits(tx) {
+        return detect_match_uint(ctx, time_limit) as c_int;
+    }
+    return 0;
+}
+
+/// Proxy Authorization control, RFC 4370
+const PROXY_AUTHZ_OID: &str = "2.16.840.1.113730.3.4.18";
+
+fn request_proxy_authz(request: &LdapMessage) -> Option<&Control> {
+    request
+        .controls
+        .as_ref()?
+        .iter()
+        .find(|ctl| ctl.control_type.0 == PROXY_AUTHZ_OID)
+}
+
+unsafe extern "C" fn ldap_detect_request_proxy_authz_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_REQUEST_PROXY_AUTHZ_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_REQUEST_PROXY_AUTHZ_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_proxy_authz_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    if let Some(request) = &tx.request {
+        return request_proxy_authz(request).is_some() as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_proxy_authz_id_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_REQUEST_PROXY_AUTHZ_ID_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_request_proxy_authz_id_get_data(
+    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
+    tx: *const c_void, list_id: c_int,
+) -> *mut c_void {
+    return DetectHelperGetData(
+        de,
+        transforms,
+        flow,
+        flow_flags,
+        tx,
+        list_id,
+        ldap_tx_get_request_proxy_authz_id,
+    );
+}
+
+unsafe extern "C" fn ldap_tx_get_request_proxy_authz_id(
+    tx: *const c_void, _flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    *buffer = std::ptr::null();
+    *buffer_len = 0;
+
+    // the control value is the authzId itself, e.g. "dn:cn=admin,dc=example,dc=com"
+    let authz_id = tx
+        .request
+        .as_ref()
+        .and_then(request_proxy_authz)
+        .and_then(|ctl| ctl.control_value.as_ref());
+    if let Some(value) = authz_id {
+        if !value.is_empty() {
+            *buffer = value.as_ptr();
+            *buffer_len = value.len() as u32;
+            return true;
+        }
+    }
+    return false;
+}
+
+/// Who am I? extended operation, RFC 4532
+const WHOAMI_OID: &str = "1.3.6.1.4.1.4203.1.11.3";
+
+fn is_whoami_request(request: &Option<LdapMessage>) -> bool {
+    if let Some(request) = request {
+        if let ProtocolOp::ExtendedRequest(req) = &request.protocol_op {
+            return req.request_name.0 == WHOAMI_OID;
+        }
+    }
+    return false;
+}
+
+/// Identity the operations are performed as, either asked for by the
+/// client with proxied authorization, or returned by the server to a
+/// Who am I? request.
+fn authz_id(tx: &LdapTransaction) -> Option<&[u8]> {
+    let proxied = tx
+        .request
+        .as_ref()
+        .and_then(request_proxy_authz)
+        .and_then(|ctl| ctl.control_value.as_deref());
+    if proxied.is_some() {
+        return proxied;
+    }
+    if !is_whoami_request(&tx.request) {
+        return None;
+    }
+    for response in &tx.responses {
+        if let ProtocolOp::ExtendedResponse(resp) = &response.protocol_op {
+            // an empty value is returned for anonymous sessions
+            return resp.response_value.as_deref();
+        }
+    }
+    return None;
+}
+
+unsafe extern "C" fn ldap_detect_authz_id_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_AUTHZ_ID_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_authz_id_get_data(
+    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
+    tx: *const c_void, list_id: c_int,
+) -> *mut c_void {
+    return DetectHelperGetData(
+        de,
+        transforms,
+        flow,
+        flow_flags,
+        tx,
+        list_id,
+        ldap_tx_get_authz_id,
+    );
+}
+
+unsafe extern "C" fn ldap_tx_get_authz_id(
+    tx: *const c_void, _flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    *buffer = std::ptr::null();
+    *buffer_len = 0;
+
+    if let Some(value) = authz_id(tx) {
+        if !value.is_empty() {
+            *buffer = value.as_ptr();
+            *buffer_len = value.len() as u32;
+            return true;
+        }
+    }
+    return false;
+}
+
+unsafe extern "C" fn ldap_detect_responses_operation_matches_request_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_LDAP_RESPONSES_OPERATION_MATCHES_REQUEST_KW_ID,
+        std::ptr::null_mut(),
+        G_LDAP_RESPONSES_OPERATION_MATCHES_REQUEST_BUFFER_ID,
+    )
+    .is_null()
+    {
+        return -1;
+    }
+    return 0;
+}
+
+/// The last response is the one ending the operation of the request,
+/// e.g. a search result done for a search request.
+fn response_matches_request(tx: &LdapTransaction) -> bool {
+    let expected = tx
+        .request
+        .as_ref()
+        .and_then(|request| request.protocol_op.terminal_response());
+    match (expected, tx.responses.back()) {
+        (Some(op), Some(response)) => response.protocol_op.to_u8() == op as u8,
+        _ => false,
+    }
+}
+
+unsafe extern "C" fn ldap_detect_responses_operation_matches_request_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, _ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    return response_matches_request(tx) as c_int;
+}
+
+fn ldap_flag_from_str(s: &str) -> Option<u8> {
+    match s {
+        "starttls" => Some(LDAP_TX_FLAG_STARTTLS),
+        "anonymous" => Some(LDAP_TX_FLAG_ANONYMOUS),
+        "sasl_in_progress" => Some(LDAP_TX_FLAG_SASL_IN_PROGRESS),
+        "truncated" => Some(LDAP_TX_FLAG_TRUNCATED),
+        "server_initiated" => Some(LDAP_TX_FLAG_SERVER_INITIATED),
+        "notice_of_disconnection" => Some(LDAP_TX_FLAG_NOTICE_OF_DISCONNECTION),
+        "late_response" => Some(LDAP_TX_FLAG_LATE_RESPONSE),
+        _ => None,
+    }
+}
+
+/// Parse a list like `starttls,!anonymous` into a bitmask of the flags
+/// to look at, and the value they need to have.
+fn aux_ldap_parse_flags(s: &str) -> Option<DetectUintData<u8>> {
+    let mut arg1 = 0;
+    let mut arg2 = 0;
+    for item in s.split(',') {
+        let item = item.trim();
+        let (neg, name) = match item.strip_prefix('!') {
+            Some(name) => (true, name.trim()),
+            None => (false, item),
+        };
+        let flag = if let Some(flag) = ldap_flag_from_str(name) {
+            flag
+        } else {
+            SCLogError!("unknown flag for ldap.flags: {}", name);
+            return None;
+        };
+        if arg1 & flag != 0 {
+            SCLogError!("repeated flag for ldap.flags: {}", name);
+            return None;
+        }
+        arg1 |= flag;
+        if !neg {
+            arg2 |= flag;
+        }
+    }
+    Some(DetectUintData::<u8> {
+        arg1,
+        arg2,
+        mode: DetectUintMode::DetectUintModeBitmask,
+    })
+}
+
+unsafe extern "C" fn ldap_parse_flags(
+    ustr: *const std::os::raw::c_char,
+) -> *mut DetectUintData<u8> {
+    let ft_name: &CStr = CStr::from_ptr(ustr); //unsafe
+    if let Ok(s) = ft_name.to_str() {
+        if let Some(ctx) = aux_ldap_parse_flags(s) {
+            let boxed = Box::new(ctx);
+            return Box::into_raw(boxed) as *mut _;
+        }
+    }
+    return std::ptr::null_mut();
+}
+
+unsafe extern "C" fn ldap_detect_flags_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    let ctx = ldap_parse_flags(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(de, s, G_LDAP_FLAGS_KW_ID, ctx, G_LDAP_FLAGS_BUFFER_ID).is_null() {
+        ldap_detect_flags_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_detect_flags_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    return detect_match_uint(ctx, tx.flags) as c_int;
+}
+
+unsafe extern "C" fn ldap_detect_flags_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    SCDetectU8Free(ctx);
+}
+
+unsafe extern "C" fn ldap_detect_request_search_filter_value_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_LDAP) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_LDAP_REQUEST_SEARCH_FILTER_VALUE_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn ldap_tx_get_req_search_filter_value(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+
+    if let Some(value) = tx.filter_values.get(local_id as usize) {
+        *buffer = value.as_ptr();
+        *buffer_len = value.len() as u32;
+        return true;
+    }
+    return false;
+}
+
+type LdapGetMultiDataFn = unsafe extern "C" fn(
+    *mut DetectEngineThreadCtx,
+    *const c_void,
+    u8,
+    u32,
+    *mut *const u8,
+    *mut u32,
+) -> bool;
+
+/// Registration calls made by `ldap_register_keywords`, so that the keyword table can
+/// be checked by unit tests without a detection engine.
+trait LdapRegistrar: StickyBufferRegistrar {
+    unsafe fn keyword(&mut self, kw: &SCSigTableAppLiteElmt) -> c_int;
+    unsafe fn buffer(&mut self, name: *const libc::c_char, alproto: AppProto, dir: u8) -> c_int;
+    unsafe fn multi_buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_multi_data: LdapGetMultiDataFn,
+    ) -> c_int;
+    unsafe fn transform(&mut self, kw: &SCTransformTableElmt) -> c_int;
+}
+
+struct LdapEngineRegistrar;
+
+impl StickyBufferRegistrar for LdapEngineRegistrar {
+    unsafe fn sticky_buffer(&mut self, kw: &SigTableElmtStickyBuffer) -> c_int {
+        DetectEngineRegistrar.sticky_buffer(kw)
+    }
+
+    unsafe fn buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_data: DetectGetDataFn,
+    ) -> c_int {
+        DetectEngineRegistrar.buffer_mpm(name, desc, alproto, dir, get_data)
+    }
+}
+
+impl LdapRegistrar for LdapEngineRegistrar {
+    unsafe fn keyword(&mut self, kw: &SCSigTableAppLiteElmt) -> c_int {
+        DetectHelperKeywordRegister(kw)
+    }
+
+    unsafe fn buffer(&mut self, name: *const libc::c_char, alproto: AppProto, dir: u8) -> c_int {
+        DetectHelperBufferRegister(name, alproto, dir)
+    }
+
+    unsafe fn multi_buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_multi_data: LdapGetMultiDataFn,
+    ) -> c_int {
+        DetectHelperMultiBufferMpmRegister(name, desc, alproto, dir, get_multi_data)
+    }
+
+    unsafe fn transform(&mut self, kw: &SCTransformTableElmt) -> c_int {
+        DetectHelperTransformRegister(kw)
+    }
+}
+
+/// Forwards the registrations to another registrar, keeping the names of
+/// the keywords and buffers which got a negative id.
+struct LdapCheckedRegistrar<'a, R> {
+    inner: &'a mut R,
+    failed: Vec<String>,
+}
+
+impl<R> LdapCheckedRegistrar<'_, R> {
+    fn check(&mut self, id: c_int, name: impl FnOnce() -> String) -> c_int {
+        if id < 0 {
+            self.failed.push(name());
+        }
+        id
+    }
+}
+
+unsafe fn c_name(name: *const libc::c_char) -> String {
+    CStr::from_ptr(name).to_string_lossy().into_owned()
+}
+
+impl<R: LdapRegistrar> StickyBufferRegistrar for LdapCheckedRegistrar<'_, R> {
+    unsafe fn sticky_buffer(&mut self, kw: &SigTableElmtStickyBuffer) -> c_int {
+        let id = self.inner.sticky_buffer(kw);
+        self.check(id, || kw.name.clone())
+    }
+
+    unsafe fn buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_data: DetectGetDataFn,
+    ) -> c_int {
+        let id = self.inner.buffer_mpm(name, desc, alproto, dir, get_data);
+        self.check(id, || c_name(name))
+    }
+}
+
+impl<R: LdapRegistrar> LdapRegistrar for LdapCheckedRegistrar<'_, R> {
+    unsafe fn keyword(&mut self, kw: &SCSigTableAppLiteElmt) -> c_int {
+        let id = self.inner.keyword(kw);
+        self.check(id, || c_name(kw.name))
+    }
+
+    unsafe fn buffer(&mut self, name: *const libc::c_char, alproto: AppProto, dir: u8) -> c_int {
+        let id = self.inner.buffer(name, alproto, dir);
+        self.check(id, || c_name(name))
+    }
+
+    unsafe fn multi_buffer_mpm(
+        &mut self, name: *const libc::c_char, desc: *const libc::c_char, alproto: AppProto,
+        dir: u8, get_multi_data: LdapGetMultiDataFn,
+    ) -> c_int {
+        let id = self
+            .inner
+            .multi_buffer_mpm(name, desc, alproto, dir, get_multi_data);
+        self.check(id, || c_name(name))
+    }
+
+    unsafe fn transform(&mut self, kw: &SCTransformTableElmt) -> c_int {
+        let id = self.inner.transform(kw);
+        self.check(id, || c_name(kw.name))
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectLdapRegister() {
+    if let Err(failed) = ldap_register(&mut LdapEngineRegistrar) {
+        SCLogError!("Failed registering LDAP keywords: {}", failed.join(", "));
+    }
+}
+
+/// Registers the LDAP keywords, returning the names of the keywords and
+/// buffers which failed to register.
+unsafe fn ldap_register<R: LdapRegistrar>(reg: &mut R) -> Result<(), Vec<String>> {
+    let mut reg = LdapCheckedRegistrar {
+        inner: reg,
+        failed: Vec::new(),
+    };
+    ldap_register_keywords(&mut reg);
+    if reg.failed.is_empty() {
+        Ok(())
+    } else {
+        Err(reg.failed)
+    }
+}
+
+unsafe fn ldap_register_keywords<R: LdapRegistrar>(reg: &mut R) {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.operation\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP request operation\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.operation\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_operation_match),
+        Setup: ldap_detect_request_operation_setup,
+        Free: Some(ldap_detect_request_free),
+        flags: 0,
+    };
+    G_LDAP_REQUEST_OPERATION_KW_ID = reg.keyword(&kw);
+    detect_register_uint_enum::<u8, ProtocolOpCode>(G_LDAP_REQUEST_OPERATION_KW_ID);
+    G_LDAP_REQUEST_OPERATION_BUFFER_ID = reg.buffer(
+        b"ldap.request.operation\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.operation\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP responses operation\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.operation\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_operation_match),
+        Setup: ldap_detect_responses_operation_setup,
+        Free: Some(ldap_detect_responses_free),
+        flags: 0,
+    };
+    G_LDAP_RESPONSES_OPERATION_KW_ID = reg.keyword(&kw);
+    detect_register_uint_enum::<u8, ProtocolOpCode>(G_LDAP_RESPONSES_OPERATION_KW_ID);
+    G_LDAP_RESPONSES_OPERATION_BUFFER_ID = reg.buffer(
+        b"ldap.responses.operation\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.count\0".as_ptr() as *const libc::c_char,
+        desc: b"match number of LDAP responses\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.count\0".as_ptr() as *const libc::c_char,
         AppLayerTxMatch: Some(ldap_detect_responses_count_match),
         Setup: ldap_detect_responses_count_setup,
         Free: Some(ldap_detect_responses_count_free),
         flags: 0,
     };
-    G_LDAP_RESPONSES_COUNT_KW_ID = DetectHelperKeywordRegister(&kw);
-    G_LDAP_RESPONSES_COUNT_BUFFER_ID = DetectHelperBufferRegister(
-        b"ldap.responses.count\0".as_ptr() as *const libc::c_char,
+    G_LDAP_RESPONSES_COUNT_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSES_COUNT_BUFFER_ID = reg.buffer(
+        b"ldap.responses.count\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let _g_ldap_request_dn_kw_id = ldap_request_dn::register(reg);
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.request.dn.rdn_attribute"),
+        desc: String::from("match request LDAPDN leftmost RDN attribute type"),
+        url: String::from("/rules/ldap-keywords.html#ldap.request.dn.rdn_attribute"),
+        setup: ldap_detect_request_dn_rdn_attribute_setup,
+    };
+    let _g_ldap_request_dn_rdn_attribute_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_REQUEST_DN_RDN_ATTRIBUTE_BUFFER_ID = reg.buffer_mpm(
+        b"ldap.request.dn.rdn_attribute\0".as_ptr() as *const libc::c_char,
+        b"LDAP REQUEST DISTINGUISHED_NAME RDN ATTRIBUTE\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+        ldap_detect_request_dn_rdn_attribute_get_data,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.responses.dn"),
+        desc: String::from("match responses LDAPDN"),
+        url: String::from("/rules/ldap-keywords.html#ldap.responses.dn"),
+        setup: ldap_detect_responses_dn_setup,
+    };
+    let _g_ldap_responses_dn_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_RESPONSES_DN_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.responses.dn\0".as_ptr() as *const libc::c_char,
+        b"LDAP RESPONSES DISTINGUISHED_NAME\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_responses_dn,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.result_code\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAPResult code\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.result_code\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_result_code_match),
+        Setup: ldap_detect_responses_result_code_setup,
+        Free: Some(ldap_detect_responses_result_code_free),
+        flags: 0,
+    };
+    G_LDAP_RESPONSES_RESULT_CODE_KW_ID = reg.keyword(&kw);
+    detect_register_uint_enum::<u32, LdapResultCode>(G_LDAP_RESPONSES_RESULT_CODE_KW_ID);
+    G_LDAP_RESPONSES_RESULT_CODE_BUFFER_ID = reg.buffer(
+        b"ldap.responses.result_code\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.result_code.worst\0".as_ptr() as *const libc::c_char,
+        desc: b"match the most severe LDAPResult code of the responses\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.result_code.worst\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_result_code_worst_match),
+        Setup: ldap_detect_responses_result_code_worst_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_RESPONSES_RESULT_CODE_WORST_KW_ID = reg.keyword(&kw);
+    detect_register_uint_enum::<u32, LdapResultCode>(G_LDAP_RESPONSES_RESULT_CODE_WORST_KW_ID);
+    G_LDAP_RESPONSES_RESULT_CODE_WORST_BUFFER_ID = reg.buffer(
+        b"ldap.responses.result_code.worst\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    // a sticky buffer taking an optional operation
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.message\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAPResult message for responses\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.message\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: None,
+        Setup: ldap_detect_responses_msg_setup,
+        Free: None,
+        flags: SIGMATCH_OPTIONAL_OPT | SIGMATCH_INFO_STICKY_BUFFER,
+    };
+    let _g_ldap_responses_msg_kw_id = reg.keyword(&kw);
+    G_LDAP_RESPONSES_MSG_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.responses.message\0".as_ptr() as *const libc::c_char,
+        b"LDAP RESPONSES DISTINGUISHED_NAME\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_responses_msg,
+    );
+    for (i, (_, name, get_data)) in LDAP_RESPONSES_MSG_OPS.iter().enumerate() {
+        G_LDAP_RESPONSES_MSG_OP_BUFFER_IDS[i] = reg.multi_buffer_mpm(
+            name.as_ptr() as *const libc::c_char,
+            b"LDAP RESPONSES MESSAGE OF AN OPERATION\0".as_ptr() as *const libc::c_char,
+            ALPROTO_LDAP,
+            STREAM_TOCLIENT,
+            *get_data,
+        );
+    }
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.request.attribute_type"),
+        desc: String::from("match request LDAP attribute type"),
+        url: String::from("/rules/ldap-keywords.html#ldap.request.attribute_type"),
+        setup: ldap_detect_request_attibute_type_setup,
+    };
+    let _g_ldap_request_attribute_type_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_REQUEST_ATTRIBUTE_TYPE_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.request.attribute_type\0".as_ptr() as *const libc::c_char,
+        b"LDAP REQUEST ATTRIBUTE TYPE\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+        ldap_tx_get_req_attribute_type,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.responses.attribute_type"),
+        desc: String::from("match LDAP responses attribute type"),
+        url: String::from("/rules/ldap-keywords.html#ldap.responses.attribute_type"),
+        setup: ldap_detect_responses_attibute_type_setup,
+    };
+    let _g_ldap_responses_attribute_type_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_RESPONSES_ATTRIBUTE_TYPE_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.responses.attribute_type\0".as_ptr() as *const libc::c_char,
+        b"LDAP RESPONSES ATTRIBUTE TYPE\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_resp_attribute_type,
+    );
+    // a sticky buffer taking an attribute type, selected by a transform
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.search.entry.value\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search result entry values of an attribute\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.search.entry.value\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: None,
+        Setup: ldap_detect_responses_search_entry_value_setup,
+        Free: None,
+        flags: SIGMATCH_INFO_STICKY_BUFFER,
+    };
+    let _g_ldap_responses_search_entry_value_kw_id = reg.keyword(&kw);
+    G_LDAP_RESPONSES_SEARCH_ENTRY_VALUE_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.responses.search.entry.value\0".as_ptr() as *const libc::c_char,
+        b"LDAP RESPONSES SEARCH ENTRY VALUE\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_resp_search_entry_value,
+    );
+    let kw = SCTransformTableElmt {
+        name: b"ldap.responses.search.entry.value.attribute\0".as_ptr() as *const libc::c_char,
+        desc: b"select the values of the ldap.responses.search.entry.value attribute\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.search.entry.value\0".as_ptr()
+            as *const libc::c_char,
+        Setup: ldap_search_entry_value_transform_setup,
+        flags: 0,
+        Transform: ldap_search_entry_value_transform,
+        Free: Some(ldap_search_entry_value_free),
+        TransformValidate: None,
+    };
+    G_LDAP_RESPONSES_SEARCH_ENTRY_VALUE_TRANSFORM_ID = reg.transform(&kw);
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.extended.starttls_result\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAPResult code of StartTLS extended responses\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.extended.starttls_result\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_starttls_result_match),
+        Setup: ldap_detect_responses_starttls_result_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_RESPONSES_STARTTLS_RESULT_KW_ID = reg.keyword(&kw);
+    detect_register_uint_enum::<u32, LdapResultCode>(G_LDAP_RESPONSES_STARTTLS_RESULT_KW_ID);
+    G_LDAP_RESPONSES_STARTTLS_RESULT_BUFFER_ID = reg.buffer(
+        b"ldap.responses.extended.starttls_result\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.bind.result\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAPResult code of bind responses\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.bind.result\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_bind_result_match),
+        Setup: ldap_detect_responses_bind_result_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_RESPONSES_BIND_RESULT_KW_ID = reg.keyword(&kw);
+    detect_register_uint_enum::<u32, LdapResultCode>(G_LDAP_RESPONSES_BIND_RESULT_KW_ID);
+    G_LDAP_RESPONSES_BIND_RESULT_BUFFER_ID = reg.buffer(
+        b"ldap.responses.bind.result\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.extended.response_oid"),
+        desc: String::from("match LDAP extended and intermediate response names"),
+        url: String::from("/rules/ldap-keywords.html#ldap.extended.response_oid"),
+        setup: ldap_detect_extended_response_oid_setup,
+    };
+    let _g_ldap_extended_response_oid_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_EXTENDED_RESPONSE_OID_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.extended.response_oid\0".as_ptr() as *const libc::c_char,
+        b"LDAP EXTENDED RESPONSE OID\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_extended_response_oid,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.rootdse\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP rootDSE search requests\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.rootdse\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_rootdse_match),
+        Setup: ldap_detect_request_rootdse_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_ROOTDSE_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_ROOTDSE_BUFFER_ID = reg.buffer(
+        b"ldap.request.rootdse\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.bind.consecutive_failures\0".as_ptr() as *const libc::c_char,
+        desc: b"match number of consecutive failed LDAP binds in the flow\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.bind.consecutive_failures\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_bind_consecutive_failures_match),
+        Setup: ldap_detect_bind_consecutive_failures_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_BIND_CONSECUTIVE_FAILURES_KW_ID = reg.keyword(&kw);
+    G_LDAP_BIND_CONSECUTIVE_FAILURES_BUFFER_ID = reg.buffer(
+        b"ldap.bind.consecutive_failures\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.unbind\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP unbind requests\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.unbind\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_unbind_match),
+        Setup: ldap_detect_request_unbind_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_UNBIND_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_UNBIND_BUFFER_ID = reg.buffer(
+        b"ldap.request.unbind\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.responses.referral.host"),
+        desc: String::from("match host of LDAP responses referral URIs"),
+        url: String::from("/rules/ldap-keywords.html#ldap.responses.referral.host"),
+        setup: ldap_detect_responses_referral_host_setup,
+    };
+    let _g_ldap_responses_referral_host_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_RESPONSES_REFERRAL_HOST_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.responses.referral.host\0".as_ptr() as *const libc::c_char,
+        b"LDAP RESPONSES REFERRAL HOST\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_resp_referral_host,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.search.no_time_limit\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search requests without time limit\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.search.no_time_limit\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_no_time_limit_match),
+        Setup: ldap_detect_request_search_no_time_limit_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_SEARCH_NO_TIME_LIMIT_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_NO_TIME_LIMIT_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.no_time_limit\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.search.no_size_limit\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search requests without size limit\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.search.no_size_limit\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_no_size_limit_match),
+        Setup: ldap_detect_request_search_no_size_limit_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_SEARCH_NO_SIZE_LIMIT_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_NO_SIZE_LIMIT_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.no_size_limit\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.bind.legacy\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP bind requests with a version other than 3\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.bind.legacy\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_bind_legacy_match),
+        Setup: ldap_detect_request_bind_legacy_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_BIND_LEGACY_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_BIND_LEGACY_BUFFER_ID = reg.buffer(
+        b"ldap.request.bind.legacy\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.responses.search.reference.host"),
+        desc: String::from("match host of LDAP search result reference URIs"),
+        url: String::from("/rules/ldap-keywords.html#ldap.responses.search.reference.host"),
+        setup: ldap_detect_responses_search_reference_host_setup,
+    };
+    let _g_ldap_responses_search_reference_host_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_RESPONSES_SEARCH_REFERENCE_HOST_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.responses.search.reference.host\0".as_ptr() as *const libc::c_char,
+        b"LDAP RESPONSES SEARCH REFERENCE HOST\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_tx_get_resp_search_reference_host,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.search.entry.has_ranged_attribute\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search result entries with ranged attributes\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.search.entry.has_ranged_attribute\0"
+            .as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_ranged_attribute_match),
+        Setup: ldap_detect_responses_ranged_attribute_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_RESPONSES_RANGED_ATTRIBUTE_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSES_RANGED_ATTRIBUTE_BUFFER_ID = reg.buffer(
+        b"ldap.responses.search.entry.has_ranged_attribute\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.search.attributes.equals\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search requests on their exact set of requested attributes\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.search.attributes.equals\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_attributes_equals_match),
+        Setup: ldap_detect_request_search_attributes_equals_setup,
+        Free: Some(ldap_detect_request_search_attributes_equals_free),
+        flags: 0,
+    };
+    G_LDAP_REQUEST_SEARCH_ATTRIBUTES_EQUALS_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_ATTRIBUTES_EQUALS_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.attributes.equals\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.search.attributes.hash\0".as_ptr() as *const libc::c_char,
+        desc: b"match hash of the set of attributes of LDAP search requests\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.search.attributes.hash\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_attributes_hash_match),
+        Setup: ldap_detect_request_search_attributes_hash_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_REQUEST_SEARCH_ATTRIBUTES_HASH_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_ATTRIBUTES_HASH_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.attributes.hash\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.bytes\0".as_ptr() as *const libc::c_char,
+        desc: b"match total size of the LDAP request PDUs\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.bytes\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_bytes_match),
+        Setup: ldap_detect_request_bytes_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_REQUEST_BYTES_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_BYTES_BUFFER_ID = reg.buffer(
+        b"ldap.request.bytes\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.response.bytes\0".as_ptr() as *const libc::c_char,
+        desc: b"match total size of the LDAP response PDUs\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.response.bytes\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_response_bytes_match),
+        Setup: ldap_detect_response_bytes_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_RESPONSE_BYTES_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSE_BYTES_BUFFER_ID = reg.buffer(
+        b"ldap.response.bytes\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.spn_query\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search requests enumerating service principal names\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.spn_query\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_spn_query_match),
+        Setup: ldap_detect_request_spn_query_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_SPN_QUERY_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SPN_QUERY_BUFFER_ID = reg.buffer(
+        b"ldap.request.spn_query\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.asrep_query\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP searches for accounts without Kerberos preauthentication\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.asrep_query\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_asrep_query_match),
+        Setup: ldap_detect_request_asrep_query_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_ASREP_QUERY_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_ASREP_QUERY_BUFFER_ID = reg.buffer(
+        b"ldap.request.asrep_query\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.request.search.filter.value"),
+        desc: String::from("match values asserted by the LDAP search request filter"),
+        url: String::from("/rules/ldap-keywords.html#ldap.request.search.filter.value"),
+        setup: ldap_detect_request_search_filter_value_setup,
+    };
+    let _g_ldap_request_search_filter_value_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_REQUEST_SEARCH_FILTER_VALUE_BUFFER_ID = reg.multi_buffer_mpm(
+        b"ldap.request.search.filter.value\0".as_ptr() as *const libc::c_char,
+        b"LDAP REQUEST SEARCH FILTER VALUE\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+        ldap_tx_get_req_search_filter_value,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.admin_limit\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP responses hitting a server size or administrative limit\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.admin_limit\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_admin_limit_match),
+        Setup: ldap_detect_responses_admin_limit_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_RESPONSES_ADMIN_LIMIT_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSES_ADMIN_LIMIT_BUFFER_ID = reg.buffer(
+        b"ldap.responses.admin_limit\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.size_limit\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search request size limit\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.size_limit\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_size_limit_match),
+        Setup: ldap_detect_request_size_limit_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_REQUEST_SIZE_LIMIT_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SIZE_LIMIT_BUFFER_ID = reg.buffer(
+        b"ldap.request.size_limit\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.time_limit\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search request time limit\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.time_limit\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_time_limit_match),
+        Setup: ldap_detect_request_time_limit_setup,
+        Free: Some(ldap_detect_u32_free),
+        flags: 0,
+    };
+    G_LDAP_REQUEST_TIME_LIMIT_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_TIME_LIMIT_BUFFER_ID = reg.buffer(
+        b"ldap.request.time_limit\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.proxy_authz\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP requests with the proxy authorization control\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.proxy_authz\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_proxy_authz_match),
+        Setup: ldap_detect_request_proxy_authz_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_REQUEST_PROXY_AUTHZ_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_PROXY_AUTHZ_BUFFER_ID = reg.buffer(
+        b"ldap.request.proxy_authz\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.request.proxy_authz.id"),
+        desc: String::from("match authzId of the LDAP request proxy authorization control"),
+        url: String::from("/rules/ldap-keywords.html#ldap.request.proxy_authz.id"),
+        setup: ldap_detect_request_proxy_authz_id_setup,
+    };
+    let _g_ldap_request_proxy_authz_id_kw_id = reg.sticky_buffer(&kw);
+    G_LDAP_REQUEST_PROXY_AUTHZ_ID_BUFFER_ID = reg.buffer_mpm(
+        b"ldap.request.proxy_authz.id\0".as_ptr() as *const libc::c_char,
+        b"LDAP REQUEST PROXY AUTHZ ID\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOSERVER,
+        ldap_detect_request_proxy_authz_id_get_data,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("ldap.authz_id"),
+        desc: String::from("match LDAP authorization identity of proxied or Who am I? operations"),
+        url: String::from("/rules/ldap-keywords.html#ldap.authz_id"),
+        setup: ldap_detect_authz_id_setup,
+    };
+    let _g_ldap_authz_id_kw_id = reg.sticky_buffer(&kw);
+    // inspected once the response is seen, so that both mechanisms are covered
+    G_LDAP_AUTHZ_ID_BUFFER_ID = reg.buffer_mpm(
+        b"ldap.authz_id\0".as_ptr() as *const libc::c_char,
+        b"LDAP AUTHZ ID\0".as_ptr() as *const libc::c_char,
+        ALPROTO_LDAP,
+        STREAM_TOCLIENT,
+        ldap_detect_authz_id_get_data,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.operation.matches_request\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP responses ending with the operation expected for the request\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.operation.matches_request\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_operation_matches_request_match),
+        Setup: ldap_detect_responses_operation_matches_request_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
+    };
+    G_LDAP_RESPONSES_OPERATION_MATCHES_REQUEST_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSES_OPERATION_MATCHES_REQUEST_BUFFER_ID = reg.buffer(
+        b"ldap.responses.operation.matches_request\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
         STREAM_TOCLIENT,
     );
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("ldap.request.dn"),
-        desc: String::from("match request LDAPDN"),
-        url: String::from("/rules/ldap-keywords.html#ldap.request.dn"),
-        setup: ldap_detect_request_dn_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.flags\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the flags of the LDAP transaction\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.flags\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_flags_match),
+        Setup: ldap_detect_flags_setup,
+        Free: Some(ldap_detect_flags_free),
+        flags: 0,
     };
-    let _g_ldap_request_dn_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_LDAP_REQUEST_DN_BUFFER_ID = DetectHelperBufferMpmRegister(
-        b"ldap.request.dn\0".as_ptr() as *const libc::c_char,
-        b"LDAP REQUEST DISTINGUISHED_NAME\0".as_ptr() as *const libc::c_char,
+    G_LDAP_FLAGS_KW_ID = reg.keyword(&kw);
+    // most flags are only known once the response is seen
+    G_LDAP_FLAGS_BUFFER_ID = reg.buffer(
+        b"ldap.flags\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
-        STREAM_TOSERVER,
-        ldap_detect_request_dn_get_data,
+        STREAM_TOCLIENT,
     );
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("ldap.responses.dn"),
-        desc: String::from("match responses LDAPDN"),
-        url: String::from("/rules/ldap-keywords.html#ldap.responses.dn"),
-        setup: ldap_detect_responses_dn_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.responses.any_error\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP responses with an error result code\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.responses.any_error\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_responses_any_error_match),
+        Setup: ldap_detect_responses_any_error_setup,
+        Free: Some(ldap_detect_responses_any_error_free),
+        flags: SIGMATCH_OPTIONAL_OPT,
     };
-    let _g_ldap_responses_dn_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_LDAP_RESPONSES_DN_BUFFER_ID = DetectHelperMultiBufferMpmRegister(
-        b"ldap.responses.dn\0".as_ptr() as *const libc::c_char,
-        b"LDAP RESPONSES DISTINGUISHED_NAME\0".as_ptr() as *const libc::c_char,
+    G_LDAP_RESPONSES_ANY_ERROR_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSES_ANY_ERROR_BUFFER_ID = reg.buffer(
+        b"ldap.responses.any_error\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
         STREAM_TOCLIENT,
-        ldap_tx_get_responses_dn,
     );
     let kw = SCSigTableAppLiteElmt {
-        name: b"ldap.responses.result_code\0".as_ptr() as *const libc::c_char,
-        desc: b"match LDAPResult code\0".as_ptr() as *const libc::c_char,
-        url: b"/rules/ldap-keywords.html#ldap.responses.result_code\0".as_ptr()
+        name: b"ldap.request.search.targets\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search requests looking for an account\0".as_ptr()
             as *const libc::c_char,
-        AppLayerTxMatch: Some(ldap_detect_responses_result_code_match),
-        Setup: ldap_detect_responses_result_code_setup,
-        Free: Some(ldap_detect_responses_result_code_free),
+        url: b"/rules/ldap-keywords.html#ldap.request.search.targets\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_targets_match),
+        Setup: ldap_detect_request_search_targets_setup,
+        Free: Some(ldap_detect_request_search_targets_free),
         flags: 0,
     };
-    G_LDAP_RESPONSES_RESULT_CODE_KW_ID = DetectHelperKeywordRegister(&kw);
-    G_LDAP_RESPONSES_RESULT_CODE_BUFFER_ID = DetectHelperBufferRegister(
-        b"ldap.responses.result_code\0".as_ptr() as *const libc::c_char,
+    G_LDAP_REQUEST_SEARCH_TARGETS_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_TARGETS_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.targets\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
-        STREAM_TOCLIENT,
+        STREAM_TOSERVER,
     );
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("ldap.responses.message"),
-        desc: String::from("match LDAPResult message for responses"),
-        url: String::from("/rules/ldap-keywords.html#ldap.responses.message"),
-        setup: ldap_detect_responses_msg_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.response.orphan\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP responses answering no outstanding request\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.response.orphan\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_response_orphan_match),
+        Setup: ldap_detect_response_orphan_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
     };
-    let _g_ldap_responses_dn_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_LDAP_RESPONSES_MSG_BUFFER_ID = DetectHelperMultiBufferMpmRegister(
-        b"ldap.responses.message\0".as_ptr() as *const libc::c_char,
-        b"LDAP RESPONSES DISTINGUISHED_NAME\0".as_ptr() as *const libc::c_char,
+    G_LDAP_RESPONSE_ORPHAN_KW_ID = reg.keyword(&kw);
+    G_LDAP_RESPONSE_ORPHAN_BUFFER_ID = reg.buffer(
+        b"ldap.response.orphan\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
         STREAM_TOCLIENT,
-        ldap_tx_get_responses_msg,
     );
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("ldap.request.attribute_type"),
-        desc: String::from("match request LDAP attribute type"),
-        url: String::from("/rules/ldap-keywords.html#ldap.request.attribute_type"),
-        setup: ldap_detect_request_attibute_type_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.search.filter.equality.count\0".as_ptr() as *const libc::c_char,
+        desc: b"match number of equality assertions in LDAP search filter\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.search.filter.equality.count\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_filter_equality_count_match),
+        Setup: ldap_detect_request_search_filter_equality_count_setup,
+        Free: Some(ldap_detect_responses_count_free),
+        flags: 0,
     };
-    let _g_ldap_request_attribute_type_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_LDAP_REQUEST_ATTRIBUTE_TYPE_BUFFER_ID = DetectHelperMultiBufferMpmRegister(
-        b"ldap.request.attribute_type\0".as_ptr() as *const libc::c_char,
-        b"LDAP REQUEST ATTRIBUTE TYPE\0".as_ptr() as *const libc::c_char,
+    G_LDAP_REQUEST_SEARCH_FILTER_EQUALITY_COUNT_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_FILTER_EQUALITY_COUNT_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.filter.equality.count\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
         STREAM_TOSERVER,
-        ldap_tx_get_req_attribute_type,
     );
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("ldap.responses.attribute_type"),
-        desc: String::from("match LDAP responses attribute type"),
-        url: String::from("/rules/ldap-keywords.html#ldap.responses.attribute_type"),
-        setup: ldap_detect_responses_attibute_type_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"ldap.request.search.filter.matchall\0".as_ptr() as *const libc::c_char,
+        desc: b"match LDAP search requests with a filter matching all the objects\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/ldap-keywords.html#ldap.request.search.filter.matchall\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(ldap_detect_request_search_filter_matchall_match),
+        Setup: ldap_detect_request_search_filter_matchall_setup,
+        Free: None,
+        flags: SIGMATCH_NOOPT,
     };
-    let _g_ldap_responses_attribute_type_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_LDAP_RESPONSES_ATTRIBUTE_TYPE_BUFFER_ID = DetectHelperMultiBufferMpmRegister(
-        b"ldap.responses.attribute_type\0".as_ptr() as *const libc::c_char,
-        b"LDAP RESPONSES ATTRIBUTE TYPE\0".as_ptr() as *const libc::c_char,
+    G_LDAP_REQUEST_SEARCH_FILTER_MATCHALL_KW_ID = reg.keyword(&kw);
+    G_LDAP_REQUEST_SEARCH_FILTER_MATCHALL_BUFFER_ID = reg.buffer(
+        b"ldap.request.search.filter.matchall\0".as_ptr() as *const libc::c_char,
         ALPROTO_LDAP,
-        STREAM_TOCLIENT,
-        ldap_tx_get_resp_attribute_type,
+        STREAM_TOSERVER,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect::sticky_buffer_tx_data;
+    use crate::detect::uint::detect_parse_uint;
+    use crate::ldap::filters::{
+        AssertionValue, AttributeDescription, AttributeValue, AttributeValueAssertion,
+        MatchingRuleAssertion, PartialAttribute, SubstringFilter,
+    };
+    use crate::ldap::types::{
+        AuthenticationChoice, BindRequest, BindResponse, DerefAliases, ExtendedRequest,
+        ExtendedResponse, IntermediateResponse, LdapDN, LdapOID, LdapResult, LdapString, MessageID,
+        ResultCode, SearchRequest, SearchResultEntry, SearchScope,
+    };
+    use std::collections::HashSet;
+    use std::ptr;
+    use std::time::Instant;
+
+    /// Records what `ldap_register` registers instead of calling into the
+    /// detection engine.
+    #[derive(Default)]
+    struct TestRegistrar {
+        keywords: Vec<String>,
+        buffers: Vec<String>,
+        /// direction of each of the buffers
+        directions: Vec<u8>,
+        transforms: Vec<String>,
+        /// keywords failing to register
+        failing: Vec<&'static str>,
+    }
+
+    impl TestRegistrar {
+        fn add_keyword(&mut self, name: &str, url: &str) -> c_int {
+            assert!(name.starts_with("ldap."), "{}", name);
+            assert_eq!(url, format!("/rules/ldap-keywords.html#{}", name));
+            if self.failing.contains(&name) {
+                return -1;
+            }
+            self.keywords.push(name.to_string());
+            self.keywords.len() as c_int
+        }
+
+        unsafe fn add_buffer(&mut self, name: *const libc::c_char, dir: u8) -> c_int {
+            assert!(!name.is_null());
+            let name = CStr::from_ptr(name).to_str().unwrap();
+            self.buffers.push(name.to_string());
+            self.directions.push(dir);
+            self.buffers.len() as c_int
+        }
+    }
+
+    impl StickyBufferRegistrar for TestRegistrar {
+        unsafe fn sticky_buffer(&mut self, kw: &SigTableElmtStickyBuffer) -> c_int {
+            // converted with CString::new() on registration
+            assert!(!kw.name.contains('\0') && !kw.desc.contains('\0') && !kw.url.contains('\0'));
+            assert!(!kw.desc.is_empty(), "{}", kw.name);
+            self.add_keyword(&kw.name, &kw.url)
+        }
+
+        unsafe fn buffer_mpm(
+            &mut self, name: *const libc::c_char, desc: *const libc::c_char, _alproto: AppProto,
+            dir: u8, _get_data: DetectGetDataFn,
+        ) -> c_int {
+            assert!(!desc.is_null());
+            assert!(dir == STREAM_TOSERVER || dir == STREAM_TOCLIENT);
+            self.add_buffer(name, dir)
+        }
+    }
+
+    impl LdapRegistrar for TestRegistrar {
+        unsafe fn keyword(&mut self, kw: &SCSigTableAppLiteElmt) -> c_int {
+            assert!(!kw.name.is_null() && !kw.desc.is_null() && !kw.url.is_null());
+            let name = CStr::from_ptr(kw.name).to_str().unwrap();
+            let url = CStr::from_ptr(kw.url).to_str().unwrap();
+            assert!(
+                kw.AppLayerTxMatch.is_some() || kw.flags & SIGMATCH_INFO_STICKY_BUFFER != 0,
+                "{}",
+                name
+            );
+            assert!(!CStr::from_ptr(kw.desc).to_bytes().is_empty(), "{}", name);
+            self.add_keyword(name, url)
+        }
+
+        unsafe fn buffer(
+            &mut self, name: *const libc::c_char, _alproto: AppProto, dir: u8,
+        ) -> c_int {
+            assert!(dir == STREAM_TOSERVER || dir == STREAM_TOCLIENT);
+            self.add_buffer(name, dir)
+        }
+
+        unsafe fn multi_buffer_mpm(
+            &mut self, name: *const libc::c_char, desc: *const libc::c_char, _alproto: AppProto,
+            dir: u8, _get_multi_data: LdapGetMultiDataFn,
+        ) -> c_int {
+            assert!(!desc.is_null());
+            assert!(dir == STREAM_TOSERVER || dir == STREAM_TOCLIENT);
+            self.add_buffer(name, dir)
+        }
+
+        unsafe fn transform(&mut self, kw: &SCTransformTableElmt) -> c_int {
+            assert!(!kw.name.is_null() && !kw.desc.is_null() && !kw.url.is_null());
+            let name = CStr::from_ptr(kw.name).to_str().unwrap();
+            assert!(name.starts_with("ldap."), "{}", name);
+            self.transforms.push(name.to_string());
+            self.transforms.len() as c_int
+        }
+    }
+
+    #[test]
+    fn test_ldap_register_keywords() {
+        let mut reg = TestRegistrar::default();
+        unsafe {
+            ldap_register(&mut reg).unwrap();
+        }
+        assert!(!reg.keywords.is_empty());
+
+        let mut names = HashSet::new();
+        for name in &reg.keywords {
+            assert!(names.insert(name), "duplicate keyword {}", name);
+        }
+        let mut buffers = HashSet::new();
+        for name in &reg.buffers {
+            assert!(buffers.insert(name), "duplicate buffer {}", name);
+        }
+        // each keyword has its own buffer or list, and ldap.responses.message
+        // one more per operation
+        buffers.retain(|name| !name.starts_with("ldap.responses.message."));
+        assert_eq!(names, buffers);
+        assert_eq!(
+            reg.transforms,
+            ["ldap.responses.search.entry.value.attribute"]
+        );
+    }
+
+    /// Keywords not named after the messages they inspect, with the
+    /// direction of the messages their match or getter functions read.
+    const LDAP_KEYWORD_DIRECTIONS: &[(&str, u8)] = &[
+        // extended and intermediate responses
+        ("ldap.extended.response_oid", STREAM_TOCLIENT),
+        // evaluated on the bind responses
+        ("ldap.bind.consecutive_failures", STREAM_TOCLIENT),
+        // the request control or the Who am I? response, so once the
+        // response is in
+        ("ldap.authz_id", STREAM_TOCLIENT),
+        // set by the parser up to the response
+        ("ldap.flags", STREAM_TOCLIENT),
+    ];
+
+    fn expected_direction(name: &str) -> Option<u8> {
+        if name.starts_with("ldap.request.") {
+            return Some(STREAM_TOSERVER);
+        }
+        if name.starts_with("ldap.responses.") || name.starts_with("ldap.response.") {
+            return Some(STREAM_TOCLIENT);
+        }
+        LDAP_KEYWORD_DIRECTIONS
+            .iter()
+            .find(|(kw, _)| *kw == name)
+            .map(|(_, dir)| *dir)
+    }
+
+    #[test]
+    fn test_ldap_keyword_directions() {
+        let mut reg = TestRegistrar::default();
+        unsafe {
+            ldap_register(&mut reg).unwrap();
+        }
+        assert_eq!(reg.buffers.len(), reg.directions.len());
+        for (name, dir) in reg.buffers.iter().zip(reg.directions.iter()) {
+            // a buffer registered in the wrong direction is never inspected
+            // with the messages its keyword reads
+            let expected = expected_direction(name).unwrap_or_else(|| {
+                panic!("{} has no expected direction, add it to the table", name)
+            });
+            assert_eq!(
+                *dir, expected,
+                "{} is registered in the wrong direction",
+                name
+            );
+        }
+        // no stale entries in the table
+        for (name, _) in LDAP_KEYWORD_DIRECTIONS {
+            assert!(reg.buffers.iter().any(|b| b == name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_ldap_register_failure() {
+        let mut reg = TestRegistrar {
+            failing: vec!["ldap.request.dn", "ldap.responses.dn"],
+            ..Default::default()
+        };
+        let failed = unsafe { ldap_register(&mut reg) }.unwrap_err();
+        assert_eq!(failed, vec!["ldap.request.dn", "ldap.responses.dn"]);
+        // the other keywords are still registered
+        assert!(reg
+            .keywords
+            .iter()
+            .any(|name| name == "ldap.request.operation"));
+    }
+
+    #[test]
+    fn test_ldap_parse_search_attributes_equals() {
+        let ctx = aux_ldap_parse_search_attributes_equals(
+            "sAMAccountName, objectSid,memberOf,samaccountname",
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.attributes,
+            vec!["memberof", "objectsid", "samaccountname"]
+        );
+
+        // same set whatever the order and case
+        let requested = ldap_attribute_set(["objectSID", "memberOf", "sAMAccountName"].into_iter());
+        assert_eq!(requested, ctx.attributes);
+        let requested = ldap_attribute_set(["objectSid", "memberOf"].into_iter());
+        assert_ne!(requested, ctx.attributes);
+
+        assert!(aux_ldap_parse_search_attributes_equals("").is_none());
+        assert!(aux_ldap_parse_search_attributes_equals("cn,,sn").is_none());
+        assert!(aux_ldap_parse_search_attributes_equals("cn,").is_none());
+    }
+
+    #[test]
+    fn test_ldap_search_attributes_hash() {
+        // zlib.crc32(b"memberof,objectsid,samaccountname")
+        let hash = ldap_search_attributes_hash(
+            ["sAMAccountName", "objectSid", "memberOf", "objectsid"].into_iter(),
+        );
+        assert_eq!(hash, 0xada607d0);
+        assert_eq!(
+            ldap_search_attributes_hash(["memberOf", "sAMAccountName", "objectSid"].into_iter()),
+            hash
+        );
+        // no attributes requested
+        assert_eq!(ldap_search_attributes_hash(std::iter::empty()), 0);
+    }
+
+    fn search_request(filter: Filter, attributes: &[&str]) -> ProtocolOp {
+        ProtocolOp::SearchRequest(SearchRequest {
+            base_object: LdapDN("DC=corp,DC=local".to_string()),
+            scope: SearchScope(2),
+            deref_aliases: DerefAliases(0),
+            size_limit: 0,
+            time_limit: 0,
+            types_only: false,
+            filter,
+            attributes: attributes
+                .iter()
+                .map(|a| LdapString(a.to_string()))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_ldap_spn_query() {
+        // (&(samAccountType=805306368)(servicePrincipalName=*))
+        let filter = Filter::And(vec![
+            Filter::EqualityMatch(AttributeValueAssertion {
+                attribute_desc: LdapString("samAccountType".to_string()),
+                assertion_value: b"805306368".to_vec(),
+            }),
+            Filter::Present(LdapString("servicePrincipalName".to_string())),
+        ]);
+        assert!(is_spn_query(&search_request(
+            filter.clone(),
+            &["sAMAccountName", "servicePrincipalName"]
+        )));
+        assert!(is_spn_query(&search_request(
+            Filter::Present(LdapString("serviceprincipalname".to_string())),
+            &["ServicePrincipalName"]
+        )));
+        // attribute not requested
+        assert!(!is_spn_query(&search_request(filter, &["sAMAccountName"])));
+        // negated presence
+        let filter = Filter::Not(Box::new(Filter::Present(LdapString(
+            "servicePrincipalName".to_string(),
+        ))));
+        assert!(!is_spn_query(&search_request(
+            filter,
+            &["servicePrincipalName"]
+        )));
+    }
+
+    #[test]
+    fn test_ldap_search_targets() {
+        let eq = |attr: &str, value: &[u8]| {
+            Filter::EqualityMatch(AttributeValueAssertion {
+                attribute_desc: LdapString(attr.to_string()),
+                assertion_value: value.to_vec(),
+            })
+        };
+        let sub = |attr: &str, substrings: Vec<Substring>| {
+            Filter::Substrings(SubstringFilter {
+                filter_type: LdapString(attr.to_string()),
+                substrings,
+            })
+        };
+        let av = |v: &[u8]| AssertionValue(v.to_vec());
+        let ctx = aux_ldap_parse_search_target(" Administrator ").unwrap();
+        assert_eq!(ctx.account, b"Administrator");
+        assert!(aux_ldap_parse_search_target("  ").is_none());
+
+        let targets =
+            |filter: Filter| is_search_for_account(&search_request(filter, &[]), &ctx.account);
+        // (&(objectCategory=person)(samaccountname=administrator))
+        assert!(targets(Filter::And(vec![
+            eq("objectCategory", b"person"),
+            eq("samaccountname", b"administrator"),
+        ])));
+        assert!(targets(Filter::Or(vec![
+            eq("mail", b"x@corp.local"),
+            eq("cn", b"ADMINISTRATOR"),
+        ])));
+        // (userPrincipalName=admin*)
+        assert!(targets(sub(
+            "userPrincipalName",
+            vec![Substring::Initial(av(b"admin"))]
+        )));
+        // (cn=*minis*or)
+        assert!(targets(sub(
+            "cn",
+            vec![Substring::Any(av(b"minis")), Substring::Final(av(b"or"))]
+        )));
+        // (cn=adm*adm*)
+        assert!(!targets(sub(
+            "cn",
+            vec![Substring::Initial(av(b"adm")), Substring::Any(av(b"adm"))]
+        )));
+        // not an account attribute
+        assert!(!targets(eq("description", b"administrator")));
+        // another account
+        assert!(!targets(eq("sAMAccountName", b"guest")));
+        // negated
+        assert!(!targets(Filter::Not(Box::new(eq(
+            "sAMAccountName",
+            b"administrator"
+        )))));
+    }
+
+    fn uac_bit_and(value: &str) -> Filter {
+        Filter::ExtensibleMatch(MatchingRuleAssertion {
+            matching_rule: Some(LdapString("1.2.840.113556.1.4.803".to_string())),
+            rule_type: Some(AttributeDescription("userAccountControl".to_string())),
+            assertion_value: AssertionValue(value.as_bytes().to_vec()),
+            dn_attributes: None,
+        })
+    }
+
+    #[test]
+    fn test_ldap_filter_equality_count() {
+        let eq = |attr: &str, value: &[u8]| {
+            Filter::EqualityMatch(AttributeValueAssertion {
+                attribute_desc: LdapString(attr.to_string()),
+                assertion_value: value.to_vec(),
+            })
+        };
+        // (|(sAMAccountName=alice)(sAMAccountName=bob)(sAMAccountName=carol))
+        let users = Filter::Or(vec![
+            eq("sAMAccountName", b"alice"),
+            eq("sAMAccountName", b"bob"),
+            eq("sAMAccountName", b"carol"),
+        ]);
+        assert_eq!(filter_equality_count(&users), 3);
+
+        // (&(objectClass=user)(!(cn=guest))(mail=*)(|...))
+        let filter = Filter::And(vec![
+            eq("objectClass", b"user"),
+            Filter::Not(Box::new(eq("cn", b"guest"))),
+            Filter::Present(LdapString("mail".to_string())),
+            users,
+        ]);
+        assert_eq!(filter_equality_count(&filter), 5);
+        assert_eq!(
+            filter_equality_count(&Filter::Present(LdapString("cn".to_string()))),
+            0
+        );
+    }
+
+    #[test]
+    fn test_ldap_search_filter_matchall() {
+        let present = |attr: &str| Filter::Present(LdapString(attr.to_string()));
+        assert!(is_matchall_filter(&present("objectClass")));
+        assert!(is_matchall_filter(&present("OBJECTCLASS")));
+        assert!(is_matchall_filter(&Filter::And(vec![])));
+        assert!(is_matchall_filter(&Filter::And(vec![present(
+            "objectclass"
+        )])));
+        assert!(is_matchall_filter(&Filter::Or(vec![
+            present("cn"),
+            present("objectClass"),
+        ])));
+
+        assert!(!is_matchall_filter(&present("cn")));
+        // the absolute false filter
+        assert!(!is_matchall_filter(&Filter::Or(vec![])));
+        assert!(!is_matchall_filter(&Filter::And(vec![
+            present("objectClass"),
+            present("servicePrincipalName"),
+        ])));
+        assert!(!is_matchall_filter(&Filter::Not(Box::new(present(
+            "objectClass"
+        )))));
+        assert!(!is_matchall_filter(&Filter::EqualityMatch(
+            AttributeValueAssertion {
+                attribute_desc: LdapString("objectClass".to_string()),
+                assertion_value: b"user".to_vec(),
+            }
+        )));
+    }
+
+    #[test]
+    fn test_ldap_asrep_query() {
+        // (&(userAccountControl:1.2.840.113556.1.4.803:=4194304)
+        //   (!(userAccountControl:1.2.840.113556.1.4.803:=2)))
+        let filter = Filter::And(vec![
+            uac_bit_and("4194304"),
+            Filter::Not(Box::new(uac_bit_and("2"))),
+        ]);
+        assert!(is_asrep_query(&search_request(filter, &["sAMAccountName"])));
+        assert!(is_asrep_query(&search_request(uac_bit_and("4194306"), &[])));
+        // only disabled accounts
+        assert!(!is_asrep_query(&search_request(uac_bit_and("2"), &[])));
+        assert!(!is_asrep_query(&search_request(uac_bit_and("abc"), &[])));
+        // negated
+        let filter = Filter::Not(Box::new(uac_bit_and("4194304")));
+        assert!(!is_asrep_query(&search_request(filter, &[])));
+        // not the bitwise and matching rule
+        let filter = Filter::ExtensibleMatch(MatchingRuleAssertion {
+            matching_rule: Some(LdapString("1.2.840.113556.1.4.804".to_string())),
+            rule_type: Some(AttributeDescription("userAccountControl".to_string())),
+            assertion_value: AssertionValue(b"4194304".to_vec()),
+            dn_attributes: None,
+        });
+        assert!(!is_asrep_query(&search_request(filter, &[])));
+    }
+
+    #[test]
+    fn test_ldap_admin_limit() {
+        let done = |code: LdapResultCode| LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::SearchResultDone(LdapResult {
+                result_code: ResultCode(code as u32),
+                matched_dn: LdapDN(String::new()),
+                diagnostic_message: LdapString(String::new()),
+                referrals: Vec::new(),
+            }),
+            controls: None,
+        };
+        assert!(is_admin_limit(&done(LdapResultCode::AdminLimitExceeded)));
+        assert!(is_admin_limit(&done(LdapResultCode::SizeLimitExceeded)));
+        assert!(!is_admin_limit(&done(LdapResultCode::Success)));
+        assert!(!is_admin_limit(&done(LdapResultCode::TimeLimitExceeded)));
+    }
+
+    type LdapTxMatchFn = unsafe extern "C" fn(
+        *mut c_void,
+        *mut c_void,
+        u8,
+        *mut c_void,
+        *mut c_void,
+        *const c_void,
+        *const c_void,
+    ) -> c_int;
+
+    #[test]
+    fn test_ldap_search_request_limits() {
+        let mut tx = LdapTransaction::new();
+        assert_eq!(search_request_limits(&tx), None);
+
+        let mut op = search_request(Filter::Present(LdapString("objectClass".to_string())), &[]);
+        if let ProtocolOp::SearchRequest(req) = &mut op {
+            req.size_limit = 1000;
+        }
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(1),
+            protocol_op: op,
+            controls: None,
+        });
+        assert_eq!(search_request_limits(&tx), Some((1000, 0)));
+
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::UnbindRequest,
+            controls: None,
+        });
+        assert_eq!(search_request_limits(&tx), None);
+    }
+
+    #[test]
+    fn test_ldap_bytes() {
+        let mut tx = LdapTransaction::new();
+        tx.request_bytes = 39;
+        tx.response_bytes = 70000;
+        let txp = &mut tx as *mut LdapTransaction as *mut c_void;
+        let bytes_match = |f: LdapTxMatchFn, rule: &str| {
+            let (_, ctx) = detect_parse_uint::<u32>(rule).unwrap();
+            let ctx = &ctx as *const DetectUintData<u32> as *const c_void;
+            unsafe {
+                f(
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    0,
+                    ptr::null_mut(),
+                    txp,
+                    ptr::null(),
+                    ctx,
+                )
+            }
+        };
+        assert_eq!(bytes_match(ldap_detect_request_bytes_match, "39"), 1);
+        assert_eq!(bytes_match(ldap_detect_request_bytes_match, ">65536"), 0);
+        assert_eq!(bytes_match(ldap_detect_response_bytes_match, ">65536"), 1);
+        assert_eq!(bytes_match(ldap_detect_response_bytes_match, "<1000"), 0);
+    }
+
+    #[test]
+    fn test_ldap_request_dn_buffer() {
+        let mut tx = LdapTransaction::new();
+        assert_eq!(
+            sticky_buffer_tx_data(ldap_request_dn::get_tx_data, &tx),
+            None
+        );
+
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(1),
+            protocol_op: ProtocolOp::DelRequest(LdapDN("cn=alice,dc=example,dc=com".to_string())),
+            controls: None,
+        });
+        assert_eq!(
+            sticky_buffer_tx_data(ldap_request_dn::get_tx_data, &tx),
+            Some(&b"cn=alice,dc=example,dc=com"[..])
+        );
+
+        // no DN in an unbind request
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::UnbindRequest,
+            controls: None,
+        });
+        assert_eq!(
+            sticky_buffer_tx_data(ldap_request_dn::get_tx_data, &tx),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ldap_proxy_authz() {
+        let control = |oid: &str, value: &[u8]| Control {
+            control_type: LdapOID(oid.to_string()),
+            criticality: true,
+            control_value: Some(value.to_vec()),
+        };
+        let mut request = LdapMessage {
+            message_id: MessageID(3),
+            protocol_op: ProtocolOp::UnbindRequest,
+            controls: None,
+        };
+        assert!(request_proxy_authz(&request).is_none());
+
+        request.controls = Some(vec![
+            control("1.2.840.113556.1.4.319", b""),
+            control(PROXY_AUTHZ_OID, b"u:svc_backup"),
+        ]);
+        let ctl = request_proxy_authz(&request).unwrap();
+        assert_eq!(ctl.control_value.as_deref(), Some(&b"u:svc_backup"[..]));
+    }
+
+    #[test]
+    fn test_ldap_authz_id() {
+        let mut tx = LdapTransaction::new();
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(4),
+            protocol_op: ProtocolOp::ExtendedRequest(ExtendedRequest {
+                request_name: LdapOID(WHOAMI_OID.to_string()),
+                request_value: None,
+            }),
+            controls: None,
+        });
+        assert_eq!(authz_id(&tx), None);
+
+        let whoami_response = |value: &[u8]| LdapMessage {
+            message_id: MessageID(4),
+            protocol_op: ProtocolOp::ExtendedResponse(ExtendedResponse {
+                result: LdapResult {
+                    result_code: ResultCode(0),
+                    matched_dn: LdapDN(String::new()),
+                    diagnostic_message: LdapString(String::new()),
+                    referrals: Vec::new(),
+                },
+                response_name: None,
+                response_value: Some(value.to_vec()),
+            }),
+            controls: None,
+        };
+        tx.responses
+            .push_back(whoami_response(b"dn:cn=svc,dc=example,dc=com"));
+        assert_eq!(authz_id(&tx), Some(&b"dn:cn=svc,dc=example,dc=com"[..]));
+
+        // the proxied identity is the one operations are performed as
+        tx.request.as_mut().unwrap().controls = Some(vec![Control {
+            control_type: LdapOID(PROXY_AUTHZ_OID.to_string()),
+            criticality: true,
+            control_value: Some(b"u:admin".to_vec()),
+        }]);
+        assert_eq!(authz_id(&tx), Some(&b"u:admin"[..]));
+    }
+
+    #[test]
+    fn test_ldap_search_entry_value() {
+        assert_eq!(
+            aux_ldap_parse_search_entry_value(" memberOf "),
+            Some(DetectLdapEntryValueData {
+                attr_type: b"memberOf".to_vec(),
+            })
+        );
+        assert!(aux_ldap_parse_search_entry_value("").is_none());
+        assert!(aux_ldap_parse_search_entry_value("member of").is_none());
+        assert!(aux_ldap_parse_search_entry_value("memberOf:").is_none());
+
+        let entry = |attributes: Vec<(&str, Vec<&[u8]>)>| LdapMessage {
+            message_id: MessageID(3),
+            protocol_op: ProtocolOp::SearchResultEntry(SearchResultEntry {
+                object_name: LdapDN("cn=user,dc=example,dc=com".to_string()),
+                attributes: attributes
+                    .into_iter()
+                    .map(|(attr_type, values)| PartialAttribute {
+                        attr_type: LdapString(attr_type.to_string()),
+                        attr_vals: values
+                            .into_iter()
+                            .map(|v| AttributeValue(v.to_vec()))
+                            .collect(),
+                    })
+                    .collect(),
+            }),
+            controls: None,
+        };
+        let mut tx = LdapTransaction::new();
+        tx.push_response(entry(vec![
+            ("cn", vec![b"user"]),
+            ("memberOf", vec![b"cn=admins", b"cn=users"]),
+        ]));
+        // entries without the attribute are skipped
+        tx.push_response(entry(vec![("cn", vec![b"other"])]));
+        tx.push_response(entry(vec![("MEMBEROF", vec![b"cn=backup"])]));
+
+        let txp = &tx as *const LdapTransaction as *const c_void;
+        let mut items = Vec::new();
+        loop {
+            let mut buffer = ptr::null();
+            let mut buffer_len = 0;
+            unsafe {
+                if !ldap_tx_get_resp_search_entry_value(
+                    ptr::null_mut(),
+                    txp,
+                    0,
+                    items.len() as u32,
+                    &mut buffer,
+                    &mut buffer_len,
+                ) {
+                    break;
+                }
+                // the buffer is reused for the next value
+                items.push(std::slice::from_raw_parts(buffer, buffer_len as usize).to_vec());
+            }
+        }
+        assert_eq!(items.len(), 5);
+        assert_eq!(items[1], b"memberOf: cn=admins");
+
+        let values = |attr_type: &[u8]| {
+            items
+                .iter()
+                .filter_map(|item| ldap_search_entry_value_select(item, attr_type))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values(b"memberof"),
+            [&b"cn=admins"[..], &b"cn=users"[..], &b"cn=backup"[..]]
+        );
+        assert_eq!(values(b"CN"), [&b"user"[..], &b"other"[..]]);
+        // a prefix of an attribute type is another attribute
+        assert!(values(b"member").is_empty());
+        // the attribute appears nowhere
+        assert!(values(b"mail").is_empty());
+    }
+
+    /// A syncrepl refresh: sync info intermediate responses, one of them
+    /// without a name, ending with an extended response.
+    fn intermediate_responses_tx(summary: bool) -> LdapTransaction {
+        let message = |protocol_op| LdapMessage {
+            message_id: MessageID(6),
+            protocol_op,
+            controls: None,
+        };
+        let intermediate = |name: Option<&str>| {
+            message(ProtocolOp::IntermediateResponse(IntermediateResponse {
+                response_name: name.map(|name| LdapOID(name.to_string())),
+                response_value: Some(vec![0xa1, 0x00]),
+            }))
+        };
+        let responses = [
+            intermediate(Some("1.3.6.1.4.1.4203.1.9.1.4")),
+            intermediate(None),
+            message(ProtocolOp::ExtendedResponse(ExtendedResponse {
+                result: LdapResult {
+                    result_code: ResultCode(0),
+                    matched_dn: LdapDN(String::new()),
+                    diagnostic_message: LdapString(String::new()),
+                    referrals: Vec::new(),
+                },
+                response_name: Some(LdapOID("1.3.6.1.4.1.1466.20037".to_string())),
+                response_value: None,
+            })),
+        ];
+        let mut tx = LdapTransaction::new();
+        for response in responses {
+            if summary {
+                tx.push_response(response);
+            } else {
+                tx.responses.push_back(response);
+            }
+        }
+        tx
+    }
+
+    #[test]
+    fn test_ldap_extended_response_oid() {
+        let tx = intermediate_responses_tx(true);
+        let txp = &tx as *const LdapTransaction as *const c_void;
+        let get = |local_id| {
+            let mut buffer = ptr::null();
+            let mut buffer_len = 0;
+            unsafe {
+                if ldap_tx_get_extended_response_oid(
+                    ptr::null_mut(),
+                    txp,
+                    0,
+                    local_id,
+                    &mut buffer,
+                    &mut buffer_len,
+                ) {
+                    Some(std::slice::from_raw_parts(buffer, buffer_len as usize))
+                } else {
+                    None
+                }
+            }
+        };
+        assert_eq!(get(0), Some(&b"1.3.6.1.4.1.4203.1.9.1.4"[..]));
+        assert_eq!(get(1), Some(&b"1.3.6.1.4.1.1466.20037"[..]));
+        assert_eq!(get(2), None);
+    }
+
+    #[test]
+    fn test_ldap_intermediate_response_operation() {
+        // the summary and the scan of the responses must agree
+        for summary in [true, false] {
+            let mut tx = intermediate_responses_tx(summary);
+            assert_eq!(tx.responses_summary().is_some(), summary);
+            let txp = &mut tx as *mut LdapTransaction as *mut c_void;
+            let op_match = |rule: &str| {
+                let ctx = aux_ldap_parse_protocol_resp_op(rule).unwrap();
+                let ctx = &ctx as *const DetectLdapRespOpData as *const c_void;
+                unsafe {
+                    ldap_detect_responses_operation_match(
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                        0,
+                        ptr::null_mut(),
+                        txp,
+                        ptr::null(),
+                        ctx,
+                    )
+                }
+            };
+            // intermediate responses are their own operation, named or not
+            assert_eq!(op_match("intermediate_response"), 1);
+            assert_eq!(op_match("25,1"), 1);
+            assert_eq!(op_match("intermediate_response,all"), 0);
+            assert_eq!(op_match("extended_response,all"), 0);
+            assert_eq!(op_match("extended_response,-1"), 1);
+            assert_eq!(op_match(">23,all"), 1);
+        }
+    }
+
+    #[test]
+    fn test_ldap_bind_result() {
+        let result = |code| LdapResult {
+            result_code: ResultCode(code),
+            matched_dn: LdapDN(String::new()),
+            diagnostic_message: LdapString(String::new()),
+            referrals: Vec::new(),
+        };
+        let message = |protocol_op| LdapMessage {
+            message_id: MessageID(7),
+            protocol_op,
+            controls: None,
+        };
+        let mut tx = LdapTransaction::new();
+        tx.responses
+            .push_back(message(ProtocolOp::SearchResultDone(result(
+                LdapResultCode::InvalidCredentials as u32,
+            ))));
+        let txp = &mut tx as *mut LdapTransaction as *mut c_void;
+        let bind_match = |rule: &str| {
+            let ctx = detect_parse_uint_enum::<u32, LdapResultCode>(rule).unwrap();
+            let ctx = &ctx as *const DetectUintData<u32> as *const c_void;
+            unsafe {
+                ldap_detect_responses_bind_result_match(
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    0,
+                    ptr::null_mut(),
+                    txp,
+                    ptr::null(),
+                    ctx,
+                )
+            }
+        };
+        // the result codes of other operations are ignored
+        assert_eq!(bind_match("invalid_credentials"), 0);
+        assert_eq!(bind_match("!0"), 0);
+
+        tx.responses
+            .push_back(message(ProtocolOp::BindResponse(BindResponse {
+                result: result(LdapResultCode::InvalidCredentials as u32),
+                server_sasl_creds: None,
+            })));
+        assert_eq!(bind_match("invalid_credentials"), 1);
+        assert_eq!(bind_match("49"), 1);
+        assert_eq!(bind_match("success"), 0);
+    }
+
+    #[test]
+    fn test_ldap_bind_legacy() {
+        let legacy_match = |tx: &mut LdapTransaction| unsafe {
+            ldap_detect_request_bind_legacy_match(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                tx as *mut LdapTransaction as *mut c_void,
+                ptr::null(),
+                ptr::null(),
+            )
+        };
+        let message = |protocol_op| LdapMessage {
+            message_id: MessageID(1),
+            protocol_op,
+            controls: None,
+        };
+        let bind = |version| {
+            message(ProtocolOp::BindRequest(BindRequest {
+                version,
+                name: LdapDN("cn=admin,dc=example,dc=com".to_string()),
+                authentication: AuthenticationChoice::Simple(b"secret".to_vec()),
+            }))
+        };
+        let mut tx = LdapTransaction::new();
+        // no request
+        assert_eq!(legacy_match(&mut tx), 0);
+
+        tx.request = Some(bind(2));
+        assert_eq!(legacy_match(&mut tx), 1);
+        tx.request = Some(bind(1));
+        assert_eq!(legacy_match(&mut tx), 1);
+        tx.request = Some(bind(3));
+        assert_eq!(legacy_match(&mut tx), 0);
+
+        // not a bind request
+        tx.request = Some(message(ProtocolOp::UnbindRequest));
+        assert_eq!(legacy_match(&mut tx), 0);
+    }
+
+    #[test]
+    fn test_ldap_response_matches_request() {
+        let result = LdapResult {
+            result_code: ResultCode(0),
+            matched_dn: LdapDN(String::new()),
+            diagnostic_message: LdapString(String::new()),
+            referrals: Vec::new(),
+        };
+        let message = |protocol_op| LdapMessage {
+            message_id: MessageID(5),
+            protocol_op,
+            controls: None,
+        };
+        let mut tx = LdapTransaction::new();
+        tx.request = Some(message(search_request(
+            Filter::Present(LdapString("objectClass".to_string())),
+            &[],
+        )));
+        assert!(!response_matches_request(&tx));
+
+        tx.responses
+            .push_back(message(ProtocolOp::SearchResultDone(result.clone())));
+        assert!(response_matches_request(&tx));
+
+        // a bind response to a search request means the parser lost track
+        tx.responses
+            .push_back(message(ProtocolOp::BindResponse(BindResponse {
+                result,
+                server_sasl_creds: None,
+            })));
+        assert!(!response_matches_request(&tx));
+    }
+
+    #[test]
+    fn test_ldap_parse_resp_result_code_set() {
+        let ctx =
+            aux_ldap_parse_resp_result_code("invalid_credentials|insufficient_access_rights,any")
+                .unwrap();
+        assert_eq!(ctx.codes.len(), 2);
+        assert_eq!(ctx.index, LdapIndex::Any);
+        let ctx = aux_ldap_parse_resp_result_code("49|50|>=80,-1").unwrap();
+        assert_eq!(ctx.codes.len(), 3);
+        assert_eq!(ctx.index, LdapIndex::Index(-1));
+        assert!(aux_ldap_parse_resp_result_code("49|,any").is_none());
+        assert!(aux_ldap_parse_resp_result_code("49|nosuchcode").is_none());
+
+        let response = |code| LdapMessage {
+            message_id: MessageID(6),
+            protocol_op: ProtocolOp::BindResponse(BindResponse {
+                result: LdapResult {
+                    result_code: ResultCode(code),
+                    matched_dn: LdapDN(String::new()),
+                    diagnostic_message: LdapString(String::new()),
+                    referrals: Vec::new(),
+                },
+                server_sasl_creds: None,
+            }),
+            controls: None,
+        };
+        let responses: VecDeque<LdapMessage> = [response(0), response(50)].into_iter().collect();
+        let matches = |rule: &str| {
+            let ctx = aux_ldap_parse_resp_result_code(rule).unwrap();
+            match_at_index(
+                &responses,
+                &ctx.codes,
+                get_ldap_result_code,
+                |code, codes| codes.iter().any(|du32| detect_match_uint(du32, code)) as c_int,
+                &ctx.index,
+            )
+        };
+        assert_eq!(
+            matches("invalid_credentials|insufficient_access_rights,any"),
+            1
+        );
+        assert_eq!(
+            matches("invalid_credentials|insufficient_access_rights,0"),
+            0
+        );
+        assert_eq!(
+            matches("invalid_credentials|insufficient_access_rights,-1"),
+            1
+        );
+        assert_eq!(matches("success|insufficient_access_rights,all"), 1);
+    }
+
+    #[test]
+    fn test_ldap_parse_flags() {
+        let ctx = aux_ldap_parse_flags("starttls").unwrap();
+        assert_eq!(ctx.arg1, LDAP_TX_FLAG_STARTTLS);
+        assert_eq!(ctx.arg2, LDAP_TX_FLAG_STARTTLS);
+        let ctx = aux_ldap_parse_flags("anonymous, !truncated").unwrap();
+        assert_eq!(ctx.arg1, LDAP_TX_FLAG_ANONYMOUS | LDAP_TX_FLAG_TRUNCATED);
+        assert_eq!(ctx.arg2, LDAP_TX_FLAG_ANONYMOUS);
+        assert!(aux_ldap_parse_flags("sasl_in_progress,nosuchflag").is_none());
+        assert!(aux_ldap_parse_flags("truncated,!truncated").is_none());
+        assert!(aux_ldap_parse_flags("").is_none());
+
+        let ctx = aux_ldap_parse_flags("anonymous,sasl_in_progress").unwrap();
+        assert!(!detect_match_uint(&ctx, LDAP_TX_FLAG_ANONYMOUS));
+        assert!(detect_match_uint(
+            &ctx,
+            LDAP_TX_FLAG_ANONYMOUS | LDAP_TX_FLAG_SASL_IN_PROGRESS | LDAP_TX_FLAG_TRUNCATED
+        ));
+        let ctx = aux_ldap_parse_flags("anonymous,!truncated").unwrap();
+        assert!(detect_match_uint(&ctx, LDAP_TX_FLAG_ANONYMOUS));
+        assert!(!detect_match_uint(
+            &ctx,
+            LDAP_TX_FLAG_ANONYMOUS | LDAP_TX_FLAG_TRUNCATED
+        ));
+
+        let ctx = aux_ldap_parse_flags("server_initiated,!notice_of_disconnection").unwrap();
+        assert!(detect_match_uint(&ctx, LDAP_TX_FLAG_SERVER_INITIATED));
+        assert!(!detect_match_uint(
+            &ctx,
+            LDAP_TX_FLAG_SERVER_INITIATED | LDAP_TX_FLAG_NOTICE_OF_DISCONNECTION
+        ));
+
+        let ctx = aux_ldap_parse_flags("late_response").unwrap();
+        assert!(detect_match_uint(&ctx, LDAP_TX_FLAG_LATE_RESPONSE));
+        assert!(!detect_match_uint(&ctx, LDAP_TX_FLAG_TRUNCATED));
+    }
+
+    #[test]
+    fn test_ldap_parse_responses_msg_op() {
+        assert_eq!(
+            aux_ldap_parse_responses_msg_op("bind_response"),
+            Some(ProtocolOpCode::BindResponse as u8)
+        );
+        assert_eq!(
+            aux_ldap_parse_responses_msg_op(" search_result_done "),
+            Some(ProtocolOpCode::SearchResultDone as u8)
+        );
+        // no result in these
+        assert!(aux_ldap_parse_responses_msg_op("bind_request").is_none());
+        assert!(aux_ldap_parse_responses_msg_op("search_result_entry").is_none());
+        assert!(aux_ldap_parse_responses_msg_op("nosuchop").is_none());
+    }
+
+    #[test]
+    fn test_ldap_parse_responses_msg_arg() {
+        let arg = aux_ldap_parse_responses_msg_arg("-1").unwrap();
+        assert!(arg.op.is_none());
+        let index = arg.index.unwrap();
+        assert_eq!(index.index, LdapIndex::Index(-1));
+        assert_eq!(index.codes.len(), 1);
+        assert_eq!(index.codes[0].mode, DetectUintMode::DetectUintModeGte);
+        assert_eq!(index.codes[0].arg1, 0);
+        let arg = aux_ldap_parse_responses_msg_arg("bind_response").unwrap();
+        assert_eq!(arg.op, Some(ProtocolOpCode::BindResponse as u8));
+        assert!(arg.index.is_none());
+        let arg = aux_ldap_parse_responses_msg_arg("search_result_done, 2").unwrap();
+        assert_eq!(arg.op, Some(ProtocolOpCode::SearchResultDone as u8));
+        assert_eq!(arg.index.unwrap().index, LdapIndex::Index(2));
+        // an index is a position, not any or all
+        assert!(aux_ldap_parse_responses_msg_arg("any").is_none());
+        assert!(aux_ldap_parse_responses_msg_arg("bind_response,all").is_none());
+        assert!(aux_ldap_parse_responses_msg_arg("-1,bind_response").is_none());
+        assert!(aux_ldap_parse_responses_msg_arg("bind_response,1,2").is_none());
+    }
+
+    #[test]
+    fn test_ldap_responses_msg_op() {
+        let mut tx = search_results_tx(2);
+        if let Some(ProtocolOp::SearchResultDone(result)) =
+            tx.responses.back_mut().map(|r| &mut r.protocol_op)
+        {
+            result.diagnostic_message = LdapString("Size limit exceeded".to_string());
+        }
+        let txp = &tx as *const LdapTransaction as *const c_void;
+        let get = |get_data: LdapGetMultiDataFn, local_id| {
+            let mut buffer = ptr::null();
+            let mut buffer_len = 0;
+            unsafe {
+                if get_data(
+                    ptr::null_mut(),
+                    txp,
+                    0,
+                    local_id,
+                    &mut buffer,
+                    &mut buffer_len,
+                ) {
+                    Some(std::slice::from_raw_parts(buffer, buffer_len as usize))
+                } else {
+                    None
+                }
+            }
+        };
+        let done = ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::SearchResultDone as u8 }>;
+        let bind = ldap_tx_get_responses_msg_op::<{ ProtocolOpCode::BindResponse as u8 }>;
+        // the entries have no message
+        assert_eq!(get(done, 0), Some(&b""[..]));
+        assert_eq!(get(done, 2), Some(&b"Size limit exceeded"[..]));
+        assert_eq!(get(done, 3), None);
+        // a response of another operation has an empty buffer
+        assert_eq!(get(bind, 2), Some(&b""[..]));
+        assert_eq!(
+            get(ldap_tx_get_responses_msg, 2),
+            Some(&b"Size limit exceeded"[..])
+        );
+    }
+
+    #[test]
+    fn test_ldap_responses_msg_index() {
+        let mut tx = search_results_tx(3);
+        let ctx = aux_ldap_parse_responses_msg_index("-1").unwrap();
+        let ctx = &ctx as *const DetectLdapRespResultData as *const c_void;
+        let txp = &mut tx as *mut LdapTransaction as *mut c_void;
+        let m = |txp, ctx| unsafe {
+            ldap_detect_responses_result_code_match(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                txp,
+                ptr::null(),
+                ctx,
+            )
+        };
+        // the search result done is last
+        assert_eq!(m(txp, ctx), 1);
+        // a search result entry has no message
+        let first = aux_ldap_parse_responses_msg_index("0").unwrap();
+        let first = &first as *const DetectLdapRespResultData as *const c_void;
+        assert_eq!(m(txp, first), 0);
+        // no response at that index
+        let far = aux_ldap_parse_responses_msg_index("10").unwrap();
+        let far = &far as *const DetectLdapRespResultData as *const c_void;
+        assert_eq!(m(txp, far), 0);
+    }
+
+    #[test]
+    fn test_ldap_responses_any_error() {
+        let ctx = DetectLdapAnyErrorData::default();
+        assert!(ctx.is_error(LdapResultCode::InvalidCredentials as u32));
+        assert!(ctx.is_error(LdapResultCode::Other as u32));
+        assert!(!ctx.is_error(LdapResultCode::Success as u32));
+        assert!(!ctx.is_error(LdapResultCode::CompareTrue as u32));
+        assert!(!ctx.is_error(LdapResultCode::Referral as u32));
+        assert!(!ctx.is_error(LdapResultCode::SaslBindInProgress as u32));
+
+        let ctx = aux_ldap_parse_any_error("referral").unwrap();
+        assert!(ctx.is_error(LdapResultCode::Referral as u32));
+        assert!(!ctx.is_error(LdapResultCode::SaslBindInProgress as u32));
+        let ctx = aux_ldap_parse_any_error("sasl_bind_in_progress, referral").unwrap();
+        assert!(ctx.is_error(LdapResultCode::Referral as u32));
+        assert!(ctx.is_error(LdapResultCode::SaslBindInProgress as u32));
+        assert!(!ctx.is_error(LdapResultCode::Success as u32));
+
+        assert!(aux_ldap_parse_any_error("success").is_none());
+        assert!(aux_ldap_parse_any_error("referral,").is_none());
+        assert!(aux_ldap_parse_any_error("nosuchcode").is_none());
+    }
+
+    #[test]
+    fn test_ldap_worst_result_code() {
+        let codes = [
+            LdapResultCode::Success as u32,
+            LdapResultCode::Referral as u32,
+            LdapResultCode::InsufficientAccessRights as u32,
+            LdapResultCode::SizeLimitExceeded as u32,
+            LdapResultCode::Success as u32,
+        ];
+        assert_eq!(
+            ldap_worst_result_code(codes.iter().copied()),
+            Some(LdapResultCode::InsufficientAccessRights as u32)
+        );
+        let codes = [
+            LdapResultCode::Success as u32,
+            LdapResultCode::Referral as u32,
+        ];
+        assert_eq!(
+            ldap_worst_result_code(codes.iter().copied()),
+            Some(LdapResultCode::Referral as u32)
+        );
+        // the first one of the same severity
+        let codes = [
+            LdapResultCode::Busy as u32,
+            LdapResultCode::Other as u32,
+            LdapResultCode::InvalidCredentials as u32,
+        ];
+        assert_eq!(
+            ldap_worst_result_code(codes.iter().copied()),
+            Some(LdapResultCode::Busy as u32)
+        );
+        let codes = [
+            LdapResultCode::Unavailable as u32,
+            LdapResultCode::OperationsError as u32,
+        ];
+        assert_eq!(
+            ldap_worst_result_code(codes.iter().copied()),
+            Some(LdapResultCode::OperationsError as u32)
+        );
+        assert_eq!(ldap_worst_result_code(std::iter::empty()), None);
+
+        let tx = search_results_tx(2);
+        let ctx = detect_parse_uint_enum::<u32, LdapResultCode>("size_limit_exceeded").unwrap();
+        assert_eq!(
+            ldap_worst_result_code(tx.responses.iter().filter_map(get_ldap_result_code)),
+            Some(ctx.arg1)
+        );
+    }
+
+    fn search_results_tx(entries: u32) -> LdapTransaction {
+        let mut tx = LdapTransaction::new();
+        for i in 0..entries {
+            tx.push_response(LdapMessage {
+                message_id: MessageID(1),
+                protocol_op: ProtocolOp::SearchResultEntry(SearchResultEntry {
+                    object_name: LdapDN(format!("cn=user{},dc=example,dc=com", i)),
+                    attributes: vec![PartialAttribute {
+                        attr_type: LdapString("memberOf".to_string()),
+                        attr_vals: vec![AttributeValue(b"cn=users".to_vec())],
+                    }],
+                }),
+                controls: None,
+            });
+        }
+        tx.push_response(LdapMessage {
+            message_id: MessageID(1),
+            protocol_op: ProtocolOp::SearchResultDone(LdapResult {
+                result_code: ResultCode(LdapResultCode::SizeLimitExceeded as u32),
+                matched_dn: LdapDN(String::new()),
+                diagnostic_message: LdapString(String::new()),
+                referrals: Vec::new(),
+            }),
+            controls: None,
+        });
+        tx
+    }
+
+    #[test]
+    fn test_ldap_responses_summary() {
+        let mut tx = search_results_tx(3);
+        let summary = tx.responses_summary().unwrap();
+        assert_eq!(summary.entries, 3);
+        assert_eq!(
+            summary.op_counts,
+            vec![
+                (ProtocolOpCode::SearchResultEntry as u8, 3),
+                (ProtocolOpCode::SearchResultDone as u8, 1)
+            ]
+        );
+        assert_eq!(
+            summary.result_codes,
+            vec![LdapResultCode::SizeLimitExceeded as u32]
+        );
+
+        let txp = &mut tx as *mut LdapTransaction as *mut c_void;
+        let op_match = |rule: &str| {
+            let ctx = aux_ldap_parse_protocol_resp_op(rule).unwrap();
+            let ctx = &ctx as *const DetectLdapRespOpData as *const c_void;
+            unsafe {
+                ldap_detect_responses_operation_match(
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    0,
+                    ptr::null_mut(),
+                    txp,
+                    ptr::null(),
+                    ctx,
+                )
+            }
+        };
+        assert_eq!(op_match("search_result_done,any"), 1);
+        assert_eq!(op_match("search_result_done,all"), 0);
+        assert_eq!(op_match(">3,all"), 1);
+        assert_eq!(op_match("search_result_done,-1"), 1);
+        assert_eq!(op_match("bind_response"), 0);
+
+        // responses added without the summary fall back to a scan
+        let mut tx = search_results_tx(1);
+        tx.responses.clear();
+        assert!(tx.responses_summary().is_none());
+    }
+
+    /// Compares matching 50 rules against the summary of a search returning
+    /// 5000 entries with matching them against each response. Run with
+    /// `cargo test --release -- --ignored --nocapture bench_ldap_responses`.
+    #[test]
+    #[ignore]
+    fn bench_ldap_responses_summary() {
+        let mut tx = search_results_tx(5000);
+        let txp = &mut tx as *mut LdapTransaction as *mut c_void;
+        let rules: Vec<DetectLdapRespResultData> = (0..50)
+            .map(|i| {
+                let index = if i % 2 == 0 { "any" } else { "all" };
+                aux_ldap_parse_resp_result_code(&format!("{},{}", i, index)).unwrap()
+            })
+            .collect();
+        let detect_match = |code, codes: &Vec<DetectUintData<u32>>| {
+            codes.iter().any(|du32| detect_match_uint(du32, code)) as c_int
+        };
+
+        let start = Instant::now();
+        let mut scan = Vec::with_capacity(rules.len());
+        for ctx in &rules {
+            scan.push(match_at_index(
+                &tx.responses,
+                &ctx.codes,
+                get_ldap_result_code,
+                detect_match,
+                &ctx.index,
+            ));
+        }
+        let scan_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut summary = Vec::with_capacity(rules.len());
+        for ctx in &rules {
+            let ctx = ctx as *const DetectLdapRespResultData as *const c_void;
+            summary.push(unsafe {
+                ldap_detect_responses_result_code_match(
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    0,
+                    ptr::null_mut(),
+                    txp,
+                    ptr::null(),
+                    ctx,
+                )
+            });
+        }
+        let summary_time = start.elapsed();
+
+        assert_eq!(scan, summary);
+        println!(
+            "50 rules, 5000 entries: scan {:?}, summary {:?}",
+            scan_time, summary_time
+        );
+    }
+}
@@ -18,10 +18,11 @@
 // written by Giuseppe Longo <giuseppe@glongo.it>
 
 use crate::applayer::{self, *};
+use crate::applayerstats::{applayer_stats_register, AppLayerParserStats};
 use crate::conf::conf_get;
 use crate::core::*;
 use crate::direction::Direction;
-use crate::flow::Flow;
+use crate::flow::{Flow, FLOW_UPGRADE_ACCEPTED, FLOW_UPGRADE_REFUSED, FLOW_UPGRADE_REQUESTED};
 use crate::frames::*;
 use nom7 as nom;
 use std;
@@ -30,15 +31,59 @@ use std::ffi::CString;
 use std::os::raw::{c_char, c_int, c_void};
 use suricata_sys::sys::AppProto;
 
+use crate::ldap::filters::Filter;
 use crate::ldap::types::*;
 
 static LDAP_MAX_TX_DEFAULT: usize = 256;
 
 static mut LDAP_MAX_TX: usize = LDAP_MAX_TX_DEFAULT;
 
+/// Seconds without activity after which an incomplete transaction is
+/// closed, 0 to keep it open until the end of the flow.
+static mut LDAP_TX_TIMEOUT: u64 = 0;
+
 pub(super) static mut ALPROTO_LDAP: AppProto = ALPROTO_UNKNOWN;
 
-const STARTTLS_OID: &str = "1.3.6.1.4.1.1466.20037";
+#[cfg(not(test))]
+fn ldap_stats() -> &'static AppLayerParserStats {
+    static LDAP_STATS: AppLayerParserStats = AppLayerParserStats::new("ldap");
+    &LDAP_STATS
+}
+
+/// Counters of the thread, so that tests running in parallel do not
+/// update the counters another test checks.
+#[cfg(test)]
+fn ldap_stats() -> &'static AppLayerParserStats {
+    thread_local! {
+        static STATS: &'static AppLayerParserStats =
+            Box::leak(Box::new(AppLayerParserStats::new("ldap")));
+    }
+    STATS.with(|stats| *stats)
+}
+
+pub(super) const STARTTLS_OID: &str = "1.3.6.1.4.1.1466.20037";
+pub(super) const NOTICE_OF_DISCONNECTION_OID: &str = "1.3.6.1.4.1.1466.20036";
+
+/// The transaction is a STARTTLS operation accepted by the server, the
+/// rest of the flow is encrypted
+pub(super) const LDAP_TX_FLAG_STARTTLS: u8 = 0x01;
+/// The transaction is an anonymous or unauthenticated simple bind, with an
+/// empty password
+pub(super) const LDAP_TX_FLAG_ANONYMOUS: u8 = 0x02;
+/// The server asked for more SASL exchanges to complete the bind
+pub(super) const LDAP_TX_FLAG_SASL_IN_PROGRESS: u8 = 0x04;
+/// The transaction was closed before it completed, because of a timeout
+/// or too many transactions
+pub(super) const LDAP_TX_FLAG_TRUNCATED: u8 = 0x08;
+/// The transaction is an unsolicited notification sent by the server, an
+/// extended response with messageID 0
+pub(super) const LDAP_TX_FLAG_SERVER_INITIATED: u8 = 0x10;
+/// The transaction is a notice of disconnection, the server is about to
+/// close the connection
+pub(super) const LDAP_TX_FLAG_NOTICE_OF_DISCONNECTION: u8 = 0x20;
+/// The transaction has the responses to a request whose transaction was
+/// closed by the timeout
+pub(super) const LDAP_TX_FLAG_LATE_RESPONSE: u8 = 0x40;
 
 #[derive(AppLayerFrameType)]
 pub enum LdapFrameType {
@@ -51,6 +96,94 @@ enum LdapEvent {
     InvalidData,
     RequestNotFound,
     IncompleteData,
+    MalformedReferral,
+    TransactionTimeout,
+    OpWrongDirection,
+    MessageIdMismatch,
+    OrphanResponse,
+    LateResponse,
+}
+
+/// Summary of the responses of a transaction, updated by the parser as the
+/// responses are added, so that the keywords matching any or all of the
+/// responses do not walk through thousands of search entries for each
+/// signature.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TransactionSummary {
+    /// Number of responses summarized
+    pub count: usize,
+    /// Number of responses for each operation code seen
+    pub op_counts: Vec<(u8, u32)>,
+    /// Distinct result codes of the responses carrying a result
+    pub result_codes: Vec<u32>,
+    /// Number of search result entries
+    pub entries: u32,
+    /// A response has a result code reporting an error
+    pub has_error: bool,
+}
+
+impl TransactionSummary {
+    #[cfg(test)]
+    fn from_responses(responses: &VecDeque<LdapMessage>) -> Self {
+        let mut summary = Self::default();
+        for response in responses {
+            summary.add(response);
+        }
+        summary
+    }
+
+    fn add(&mut self, response: &LdapMessage) {
+        let op = response.protocol_op.to_u8();
+        if let Some((_, count)) = self.op_counts.iter_mut().find(|(o, _)| *o == op) {
+            *count = count.saturating_add(1);
+        } else {
+            self.op_counts.push((op, 1));
+        }
+        if let Some(code) = get_ldap_result_code(response) {
+            if !self.result_codes.contains(&code) {
+                self.result_codes.push(code);
+            }
+            self.has_error |= is_ldap_error_code(code);
+        }
+        if let ProtocolOp::SearchResultEntry(_) = response.protocol_op {
+            self.entries = self.entries.saturating_add(1);
+        }
+        self.count += 1;
+    }
+}
+
+/// Collect the values asserted by the filter and its nested filters.
+/// Substrings are left out as they only hold parts of a value.
+fn filter_values(filter: &Filter, values: &mut Vec<Vec<u8>>) {
+    match filter {
+        Filter::And(filters) | Filter::Or(filters) => {
+            for f in filters {
+                filter_values(f, values);
+            }
+        }
+        Filter::Not(f) => filter_values(f, values),
+        Filter::EqualityMatch(ava)
+        | Filter::GreaterOrEqual(ava)
+        | Filter::LessOrEqual(ava)
+        | Filter::ApproxMatch(ava) => values.push(ava.assertion_value.clone()),
+        Filter::ExtensibleMatch(mra) => values.push(mra.assertion_value.0.clone()),
+        Filter::Substrings(_) | Filter::Present(_) => {}
+    }
+}
+
+/// What the parser found out about a response, before adding it to a
+/// transaction
+struct ResponseInfo {
+    /// Size of the response PDU
+    len: usize,
+    /// LDAP_TX_FLAG_* flags set by the response
+    flags: u8,
+    /// Consecutive failed binds on the flow, including this response
+    bind_failures: u32,
+    /// The response is a request sent by the server
+    wrong_direction: bool,
+    /// The response answers no outstanding request
+    orphan: bool,
 }
 
 #[derive(Debug)]
@@ -58,6 +191,27 @@ pub struct LdapTransaction {
     pub tx_id: u64,
     pub request: Option<LdapMessage>,
     pub responses: VecDeque<LdapMessage>,
+    summary: TransactionSummary,
+    /// Values asserted by the filter of the search request
+    pub filter_values: Vec<Vec<u8>>,
+    /// Names of the extended and intermediate responses, in order,
+    /// responses without a name are skipped
+    pub extended_response_oids: Vec<String>,
+    /// Consecutive failed binds on the flow, as of the last bind response
+    /// in this transaction
+    pub bind_failures: u32,
+    /// Number of response PDUs seen for this transaction
+    pub response_pdu_count: u32,
+    /// Size in bytes of the request PDU
+    pub request_bytes: u32,
+    /// Total size in bytes of the response PDUs
+    pub response_bytes: u32,
+    /// Time of the last request or response PDU, in seconds
+    last_ts: u64,
+    /// LDAP_TX_FLAG_* flags
+    pub flags: u8,
+    /// A response answers no outstanding request of the flow
+    pub orphan_response: bool,
     complete: bool,
 
     tx_data: AppLayerTxData,
@@ -75,10 +229,96 @@ impl LdapTransaction {
             tx_id: 0,
             request: None,
             responses: VecDeque::new(),
+            summary: TransactionSummary::default(),
+            filter_values: Vec::new(),
+            extended_response_oids: Vec::new(),
+            bind_failures: 0,
+            response_pdu_count: 0,
+            request_bytes: 0,
+            response_bytes: 0,
+            last_ts: 0,
+            flags: 0,
+            orphan_response: false,
             complete: false,
             tx_data: AppLayerTxData::new(),
         }
     }
+
+    fn set_event(&mut self, e: LdapEvent) {
+        self.tx_data.set_event(e as u8);
+        applayer_stats_incr!(ldap_stats(), events);
+    }
+
+    /// A transaction has a single request PDU
+    fn set_request_len(&mut self, len: usize) {
+        self.request_bytes = u32::try_from(len).unwrap_or(u32::MAX);
+    }
+
+    fn set_request(&mut self, request: LdapMessage) {
+        if let ProtocolOp::SearchRequest(req) = &request.protocol_op {
+            filter_values(&req.filter, &mut self.filter_values);
+        }
+        self.request = Some(request);
+    }
+
+    pub(super) fn push_response(&mut self, response: LdapMessage) {
+        self.summary.add(&response);
+        match &response.protocol_op {
+            ProtocolOp::ExtendedResponse(resp) => {
+                if let Some(name) = &resp.response_name {
+                    self.extended_response_oids.push(name.0.clone());
+                }
+            }
+            ProtocolOp::IntermediateResponse(resp) => {
+                if let Some(name) = &resp.response_name {
+                    self.extended_response_oids.push(name.0.clone());
+                }
+            }
+            _ => {}
+        }
+        self.responses.push_back(response);
+    }
+
+    /// The summary of the responses, if it covers all of them
+    pub fn responses_summary(&self) -> Option<&TransactionSummary> {
+        if self.summary.count == self.responses.len() {
+            Some(&self.summary)
+        } else {
+            None
+        }
+    }
+
+    fn set_orphan_response(&mut self) {
+        self.orphan_response = true;
+        self.set_event(LdapEvent::OrphanResponse);
+    }
+
+    /// Add a response to the transaction, with the flags and events it
+    /// brings.
+    fn apply_response(&mut self, response: LdapMessage, info: &ResponseInfo) {
+        self.tx_data.updated_tc = true;
+        self.complete |= tx_is_complete(&response.protocol_op, Direction::ToClient);
+        self.bind_failures = info.bind_failures;
+        self.flags |= info.flags;
+        if response.has_malformed_referral() {
+            self.set_event(LdapEvent::MalformedReferral);
+        }
+        if info.wrong_direction {
+            self.set_event(LdapEvent::OpWrongDirection);
+        }
+        if info.orphan {
+            self.set_orphan_response();
+        }
+        self.add_response_pdu(info.len);
+        self.push_response(response);
+    }
+
+    fn add_response_pdu(&mut self, len: usize) {
+        self.response_pdu_count = self.response_pdu_count.saturating_add(1);
+        self.response_bytes = self
+            .response_bytes
+            .saturating_add(u32::try_from(len).unwrap_or(u32::MAX));
+    }
 }
 
 impl Transaction for LdapTransaction {
@@ -98,6 +338,14 @@ pub struct LdapState {
     response_gap: bool,
     request_tls: bool,
     has_starttls: bool,
+    bind_failures: u32,
+    /// MessageIDs of the requests waiting for their last response
+    outstanding_requests: VecDeque<MessageID>,
+    /// MessageIDs of the requests whose transaction timed out before
+    /// their last response
+    timed_out_requests: VecDeque<MessageID>,
+    /// Time of the last flow update, in seconds
+    ts: u64,
 }
 
 impl State<LdapTransaction> for LdapState {
@@ -122,6 +370,10 @@ impl LdapState {
             response_gap: false,
             request_tls: false,
             has_starttls: false,
+            bind_failures: 0,
+            outstanding_requests: VecDeque::new(),
+            timed_out_requests: VecDeque::new(),
+            ts: 0,
         }
     }
 
@@ -139,7 +391,9 @@ impl LdapState {
             }
         }
         if found {
-            self.transactions.remove(index);
+            if let Some(tx) = self.transactions.remove(index) {
+                applayer_stats_tx_freed!(ldap_stats(), tx.complete);
+            }
         }
     }
 
@@ -154,9 +408,8 @@ impl LdapState {
                     tx_old.tx_data.updated_tc = true;
                     tx_old.tx_data.updated_ts = true;
                     tx_old.complete = true;
-                    tx_old
-                        .tx_data
-                        .set_event(LdapEvent::TooManyTransactions as u8);
+                    tx_old.flags |= LDAP_TX_FLAG_TRUNCATED;
+                    tx_old.set_event(LdapEvent::TooManyTransactions);
                 }
             }
             return None;
@@ -164,16 +417,174 @@ impl LdapState {
         let mut tx = LdapTransaction::new();
         self.tx_id += 1;
         tx.tx_id = self.tx_id;
+        tx.last_ts = self.ts;
+        applayer_stats_incr!(ldap_stats(), tx_created);
         return Some(tx);
     }
 
+    /// Update the time of the flow and close the transactions that have
+    /// been waiting for more than the configured timeout, so that they
+    /// get inspected and logged. The responses that come after that are
+    /// late responses, see add_late_response.
+    fn update_ts(&mut self, ts: u64) {
+        self.ts = ts;
+        self.timeout_txs(unsafe { LDAP_TX_TIMEOUT });
+    }
+
+    fn timeout_txs(&mut self, timeout: u64) {
+        if timeout == 0 {
+            return;
+        }
+        let ts = self.ts;
+        let mut timed_out = Vec::new();
+        for tx in &mut self.transactions {
+            if !tx.complete && ts.saturating_sub(tx.last_ts) > timeout {
+                tx.tx_data.updated_tc = true;
+                tx.tx_data.updated_ts = true;
+                tx.complete = true;
+                tx.flags |= LDAP_TX_FLAG_TRUNCATED;
+                tx.set_event(LdapEvent::TransactionTimeout);
+                if let Some(message_id) = tx_message_id(tx) {
+                    timed_out.push(message_id);
+                }
+            }
+        }
+        for message_id in timed_out {
+            self.set_request_timed_out(message_id);
+        }
+    }
+
+    /// Move a request from the outstanding requests to the timed out
+    /// ones, keeping at most as many requests as transactions.
+    fn set_request_timed_out(&mut self, message_id: MessageID) {
+        self.outstanding_requests.retain(|id| *id != message_id);
+        if self.timed_out_requests.contains(&message_id) {
+            return;
+        }
+        if self.timed_out_requests.len() >= unsafe { LDAP_MAX_TX } {
+            self.timed_out_requests.pop_front();
+        }
+        self.timed_out_requests.push_back(message_id);
+    }
+
     fn set_event(&mut self, e: LdapEvent) {
         if let Some(tx) = self.transactions.back_mut() {
-            tx.tx_data.set_event(e as u8);
+            tx.set_event(e);
+        }
+    }
+
+    /// Count consecutive binds rejected with invalidCredentials, the
+    /// counter is reset by a successful bind.
+    fn update_bind_failures(&mut self, response: &LdapMessage) -> u32 {
+        if let ProtocolOp::BindResponse(resp) = &response.protocol_op {
+            if resp.result.result_code == ResultCode(LdapResultCode::Success as u32) {
+                self.bind_failures = 0;
+            } else if resp.result.result_code
+                == ResultCode(LdapResultCode::InvalidCredentials as u32)
+            {
+                self.bind_failures = self.bind_failures.saturating_add(1);
+            }
+        }
+        return self.bind_failures;
+    }
+
+    /// Remember the messageID of a request expecting a response, keeping
+    /// at most as many requests as transactions.
+    fn add_outstanding_request(&mut self, request: &LdapMessage) {
+        if matches!(
+            request.protocol_op,
+            ProtocolOp::UnbindRequest | ProtocolOp::AbandonRequest(_)
+        ) || request.is_response()
+        {
+            return;
+        }
+        // a new request reusing the messageID is not timed out
+        self.timed_out_requests
+            .retain(|id| *id != request.message_id);
+        if self.outstanding_requests.len() >= unsafe { LDAP_MAX_TX } {
+            self.outstanding_requests.pop_front();
+        }
+        self.outstanding_requests.push_back(request.message_id);
+    }
+
+    /// Whether the response answers a request whose transaction timed
+    /// out. The request is no longer timed out once its last response is
+    /// seen.
+    fn is_late_response(&mut self, response: &LdapMessage) -> bool {
+        match self
+            .timed_out_requests
+            .iter()
+            .position(|id| *id == response.message_id)
+        {
+            Some(index) => {
+                if tx_is_complete(&response.protocol_op, Direction::ToClient) {
+                    self.timed_out_requests.remove(index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a response to a request whose transaction timed out. That
+    /// transaction may have been logged and freed already, so the late
+    /// responses go to a new transaction, without request, flagged with
+    /// LDAP_TX_FLAG_LATE_RESPONSE and with the late_response event. It
+    /// completes like the transaction of the request would have.
+    fn add_late_response(&mut self, response: LdapMessage, info: &ResponseInfo) -> Option<u64> {
+        let ts = self.ts;
+        let message_id = response.message_id;
+        let open = self.transactions.iter_mut().find(|tx| {
+            !tx.complete
+                && tx.flags & LDAP_TX_FLAG_LATE_RESPONSE != 0
+                && tx_message_id(tx) == Some(message_id)
+        });
+        if let Some(tx) = open {
+            tx.last_ts = ts;
+            tx.apply_response(response, info);
+            return Some(tx.id());
+        }
+        let mut tx = self.new_tx()?;
+        tx.flags |= LDAP_TX_FLAG_LATE_RESPONSE;
+        tx.set_event(LdapEvent::LateResponse);
+        tx.apply_response(response, info);
+        let tx_id = tx.id();
+        self.transactions.push_back(tx);
+        return Some(tx_id);
+    }
+
+    /// Whether the response answers no outstanding request of the flow.
+    /// The request is no longer outstanding once its last response is
+    /// seen, so that a response injected after it is an orphan too.
+    fn is_orphan_response(&mut self, response: &LdapMessage) -> bool {
+        // unsolicited notifications answer no request
+        if response.message_id == MessageID(0) {
+            return false;
+        }
+        match self
+            .outstanding_requests
+            .iter()
+            .position(|id| *id == response.message_id)
+        {
+            Some(index) => {
+                if tx_is_complete(&response.protocol_op, Direction::ToClient) {
+                    self.outstanding_requests.remove(index);
+                }
+                return false;
+            }
+            None => {
+                return true;
+            }
         }
     }
 
+    /// Find the transaction of the request answered by a response.
     fn find_request(&mut self, message_id: MessageID) -> Option<&mut LdapTransaction> {
+        // messageID 0 is reserved for unsolicited notifications, which are
+        // never an answer to a request
+        if message_id == MessageID(0) {
+            return None;
+        }
         self.transactions.iter_mut().find(|tx| {
             tx.request
                 .as_ref()
@@ -181,6 +592,55 @@ impl LdapState {
         })
     }
 
+    /// Attach a response to the transaction of its request, or to a new
+    /// transaction if it answers no request. Returns the id of the
+    /// transaction, or None if no transaction could be created.
+    fn add_response(&mut self, response: LdapMessage, len: usize, flags: u8) -> Option<u64> {
+        let wrong_direction = op_in_wrong_direction(&response, Direction::ToClient);
+        let late = !wrong_direction && self.is_late_response(&response);
+        let info = ResponseInfo {
+            len,
+            flags: flags | response_flags(&response.protocol_op) | notification_flags(&response),
+            bind_failures: self.update_bind_failures(&response),
+            wrong_direction,
+            orphan: !wrong_direction && !late && self.is_orphan_response(&response),
+        };
+        if late {
+            return self.add_late_response(response, &info);
+        }
+        let ts = self.ts;
+        // a request sent by the server answers no request
+        let request_tx = if wrong_direction {
+            None
+        } else {
+            self.find_request(response.message_id)
+        };
+        if let Some(tx) = request_tx {
+            tx.last_ts = ts;
+            tx.apply_response(response, &info);
+            return Some(tx.id());
+        }
+
+        // unsolicited notifications have no request
+        let request_not_found =
+            !wrong_direction && !matches!(response.protocol_op, ProtocolOp::ExtendedResponse(_));
+        // the response is not attached to a request with another messageID,
+        // which could be spoofed to complete it
+        let id_mismatch = !wrong_direction && message_id_mismatch(&self.transactions, &response);
+        let mut tx = self.new_tx()?;
+        tx.complete = true;
+        tx.apply_response(response, &info);
+        let tx_id = tx.id();
+        self.transactions.push_back(tx);
+        if request_not_found {
+            self.set_event(LdapEvent::RequestNotFound);
+        }
+        if id_mismatch {
+            self.set_event(LdapEvent::MessageIdMismatch);
+        }
+        return Some(tx_id);
+    }
+
     fn parse_request(&mut self, flow: *const Flow, stream_slice: StreamSlice) -> AppLayerResult {
         let input = stream_slice.as_slice();
         if input.is_empty() {
@@ -232,12 +692,23 @@ impl LdapState {
                     if let ProtocolOp::ExtendedRequest(request) = &request.protocol_op {
                         if request.request_name.0 == STARTTLS_OID {
                             self.request_tls = true;
+                            if !flow.is_null() {
+                                unsafe {
+                                    AppLayerSetTLSUpgrade(flow, FLOW_UPGRADE_REQUESTED);
+                                }
+                            }
                         }
                     }
+                    tx.flags |= request_flags(&request.protocol_op);
                     tx.complete |= tx_is_complete(&request.protocol_op, Direction::ToServer);
-                    tx.request = Some(request);
-                    self.transactions.push_back(tx);
+                    if op_in_wrong_direction(&request, Direction::ToServer) {
+                        tx.set_event(LdapEvent::OpWrongDirection);
+                    }
+                    self.add_outstanding_request(&request);
+                    tx.set_request(request);
                     let consumed = start.len() - rem.len();
+                    tx.set_request_len(consumed);
+                    self.transactions.push_back(tx);
                     start = rem;
                     self.set_frame_ts(flow, tx_id, consumed as i64);
                 }
@@ -288,52 +759,35 @@ impl LdapState {
             }
             match ldap_parse_msg(start) {
                 Ok((rem, msg)) => {
-                    let response = LdapMessage::from(msg);
+                    let pdu = &start[..start.len() - rem.len()];
+                    let mut response = LdapMessage::from(msg);
+                    response.set_referrals(pdu);
+                    let mut flags = 0;
                     // check if STARTTLS was requested
                     if self.request_tls {
                         if let ProtocolOp::ExtendedResponse(response) = &response.protocol_op {
                             if response.result.result_code == ResultCode(0) {
                                 SCLogDebug!("LDAP: STARTTLS detected");
                                 self.has_starttls = true;
+                                flags |= LDAP_TX_FLAG_STARTTLS;
+                                if !flow.is_null() {
+                                    unsafe {
+                                        AppLayerSetTLSUpgrade(flow, FLOW_UPGRADE_ACCEPTED);
+                                    }
+                                }
+                            } else if !flow.is_null() {
+                                unsafe {
+                                    AppLayerSetTLSUpgrade(flow, FLOW_UPGRADE_REFUSED);
+                                }
                             }
                             self.request_tls = false;
                         }
                     }
-                    if let Some(tx) = self.find_request(response.message_id) {
-                        tx.complete |= tx_is_complete(&response.protocol_op, Direction::ToClient);
-                        let tx_id = tx.id();
-                        tx.tx_data.updated_tc = true;
-                        tx.responses.push_back(response);
-                        let consumed = start.len() - rem.len();
-                        self.set_frame_tc(flow, tx_id, consumed as i64);
-                    } else if let ProtocolOp::ExtendedResponse(_) = response.protocol_op {
-                        // this is an unsolicited notification, which means
-                        // there is no request
-                        let tx = self.new_tx();
-                        if tx.is_none() {
-                            return AppLayerResult::err();
-                        }
-                        let mut tx = tx.unwrap();
-                        let tx_id = tx.id();
-                        tx.complete = true;
-                        tx.responses.push_back(response);
-                        self.transactions.push_back(tx);
-                        let consumed = start.len() - rem.len();
-                        self.set_frame_tc(flow, tx_id, consumed as i64);
-                    } else {
-                        let tx = self.new_tx();
-                        if tx.is_none() {
-                            return AppLayerResult::err();
-                        }
-                        let mut tx = tx.unwrap();
-                        tx.complete = true;
-                        let tx_id = tx.id();
-                        tx.responses.push_back(response);
-                        self.transactions.push_back(tx);
-                        self.set_event(LdapEvent::RequestNotFound);
-                        let consumed = start.len() - rem.len();
-                        self.set_frame_tc(flow, tx_id, consumed as i64);
+                    let tx_id = match self.add_response(response, pdu.len(), flags) {
+                        Some(tx_id) => tx_id,
+                        None => return AppLayerResult::err(),
                     };
+                    self.set_frame_tc(flow, tx_id, pdu.len() as i64);
                     start = rem;
                 }
                 Err(nom::Err::Incomplete(_)) => {
@@ -373,8 +827,14 @@ impl LdapState {
                 }
                 let mut tx = tx.unwrap();
                 let request = LdapMessage::from(msg);
+                tx.flags |= request_flags(&request.protocol_op);
                 tx.complete |= tx_is_complete(&request.protocol_op, Direction::ToServer);
-                tx.request = Some(request);
+                if op_in_wrong_direction(&request, Direction::ToServer) {
+                    tx.set_event(LdapEvent::OpWrongDirection);
+                }
+                self.add_outstanding_request(&request);
+                tx.set_request(request);
+                tx.set_request_len(input.len());
                 self.transactions.push_back(tx);
             }
             Err(nom::Err::Incomplete(_)) => {
@@ -413,41 +873,14 @@ impl LdapState {
             }
             match ldap_parse_msg(start) {
                 Ok((rem, msg)) => {
-                    let response = LdapMessage::from(msg);
-                    if let Some(tx) = self.find_request(response.message_id) {
-                        tx.complete |= tx_is_complete(&response.protocol_op, Direction::ToClient);
-                        let tx_id = tx.id();
-                        tx.responses.push_back(response);
-                        let consumed = start.len() - rem.len();
-                        self.set_frame_tc(flow, tx_id, consumed as i64);
-                    } else if let ProtocolOp::ExtendedResponse(_) = response.protocol_op {
-                        // this is an unsolicited notification, which means
-                        // there is no request
-                        let tx = self.new_tx();
-                        if tx.is_none() {
-                            return AppLayerResult::err();
-                        }
-                        let mut tx = tx.unwrap();
-                        tx.complete = true;
-                        let tx_id = tx.id();
-                        tx.responses.push_back(response);
-                        self.transactions.push_back(tx);
-                        let consumed = start.len() - rem.len();
-                        self.set_frame_tc(flow, tx_id, consumed as i64);
-                    } else {
-                        let tx = self.new_tx();
-                        if tx.is_none() {
-                            return AppLayerResult::err();
-                        }
-                        let mut tx = tx.unwrap();
-                        tx.complete = true;
-                        let tx_id = tx.id();
-                        tx.responses.push_back(response);
-                        self.transactions.push_back(tx);
-                        self.set_event(LdapEvent::RequestNotFound);
-                        let consumed = start.len() - rem.len();
-                        self.set_frame_tc(flow, tx_id, consumed as i64);
+                    let pdu = &start[..start.len() - rem.len()];
+                    let mut response = LdapMessage::from(msg);
+                    response.set_referrals(pdu);
+                    let tx_id = match self.add_response(response, pdu.len(), 0) {
+                        Some(tx_id) => tx_id,
+                        None => return AppLayerResult::err(),
                     };
+                    self.set_frame_tc(flow, tx_id, pdu.len() as i64);
                     start = rem;
                 }
                 Err(nom::Err::Incomplete(_)) => {
@@ -489,6 +922,47 @@ impl LdapState {
     }
 }
 
+/// Whether the message can not be sent in this direction, e.g. a search
+/// request sent by the server or a bind response sent by the client.
+fn op_in_wrong_direction(msg: &LdapMessage, dir: Direction) -> bool {
+    match dir {
+        Direction::ToServer => msg.is_response(),
+        Direction::ToClient => msg.is_request(),
+    }
+}
+
+/// Whether the request is answered by the server.
+fn expects_response(request: &LdapMessage) -> bool {
+    request.is_request()
+        && !matches!(
+            request.protocol_op,
+            ProtocolOp::UnbindRequest | ProtocolOp::AbandonRequest(_)
+        )
+}
+
+/// MessageID of the request of the transaction, or of its first response
+fn tx_message_id(tx: &LdapTransaction) -> Option<MessageID> {
+    tx.request
+        .as_ref()
+        .or_else(|| tx.responses.front())
+        .map(|msg| msg.message_id)
+}
+
+/// Whether a request still waiting for its response has another messageID
+/// than the response.
+fn message_id_mismatch(transactions: &VecDeque<LdapTransaction>, response: &LdapMessage) -> bool {
+    // unsolicited notifications answer no request
+    if response.message_id == MessageID(0) {
+        return false;
+    }
+    transactions.iter().any(|tx| {
+        !tx.complete
+            && tx.request.as_ref().map_or(false, |req| {
+                expects_response(req) && req.message_id != response.message_id
+            })
+    })
+}
+
 fn tx_is_complete(op: &ProtocolOp, dir: Direction) -> bool {
     match dir {
         Direction::ToServer => match op {
@@ -509,6 +983,46 @@ fn tx_is_complete(op: &ProtocolOp, dir: Direction) -> bool {
     }
 }
 
+fn request_flags(op: &ProtocolOp) -> u8 {
+    match op {
+        ProtocolOp::BindRequest(bind) => match &bind.authentication {
+            AuthenticationChoice::Simple(password) if password.is_empty() => LDAP_TX_FLAG_ANONYMOUS,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+fn response_flags(op: &ProtocolOp) -> u8 {
+    match op {
+        ProtocolOp::BindResponse(bind)
+            if bind.result.result_code == ResultCode(LdapResultCode::SaslBindInProgress as u32) =>
+        {
+            LDAP_TX_FLAG_SASL_IN_PROGRESS
+        }
+        _ => 0,
+    }
+}
+
+/// Flags of an unsolicited notification, an extended response sent by
+/// the server with messageID 0.
+fn notification_flags(msg: &LdapMessage) -> u8 {
+    if msg.message_id != MessageID(0) {
+        return 0;
+    }
+    match &msg.protocol_op {
+        ProtocolOp::ExtendedResponse(response) => {
+            let name = response.response_name.as_ref().map(|name| name.0.as_str());
+            if name == Some(NOTICE_OF_DISCONNECTION_OID) {
+                LDAP_TX_FLAG_SERVER_INITIATED | LDAP_TX_FLAG_NOTICE_OF_DISCONNECTION
+            } else {
+                LDAP_TX_FLAG_SERVER_INITIATED
+            }
+        }
+        _ => 0,
+    }
+}
+
 fn probe(input: &[u8], direction: Direction, rdir: *mut u8) -> AppProto {
     match ldap_parse_msg(input) {
         Ok((_, msg)) => {
@@ -547,11 +1061,18 @@ unsafe extern "C" fn ldap_probing_parser(
 extern "C" fn ldap_state_new(_orig_state: *mut c_void, _orig_proto: AppProto) -> *mut c_void {
     let state = LdapState::new();
     let boxed = Box::new(state);
+    applayer_stats_incr!(ldap_stats(), states_live);
     return Box::into_raw(boxed) as *mut c_void;
 }
 
 unsafe extern "C" fn ldap_state_free(state: *mut c_void) {
-    std::mem::drop(Box::from_raw(state as *mut LdapState));
+    let state = Box::from_raw(state as *mut LdapState);
+    // the transactions left are freed with the state
+    for tx in &state.transactions {
+        applayer_stats_tx_freed!(ldap_stats(), tx.complete);
+    }
+    std::mem::drop(state);
+    applayer_stats_sub!(ldap_stats(), states_live, 1);
 }
 
 unsafe extern "C" fn ldap_state_tx_free(state: *mut c_void, tx_id: u64) {
@@ -571,11 +1092,12 @@ unsafe extern "C" fn ldap_parse_request(
         }
     }
     let state = cast_pointer!(state, LdapState);
+    state.update_ts(cast_pointer!(flow, Flow).get_last_time().as_secs());
 
     if stream_slice.is_gap() {
         state.on_request_gap(stream_slice.gap_size());
     } else {
-        return state.parse_request(flow, stream_slice);
+        return applayer_stats_result!(ldap_stats(), state.parse_request(flow, stream_slice));
     }
     AppLayerResult::ok()
 }
@@ -592,10 +1114,11 @@ unsafe extern "C" fn ldap_parse_response(
         }
     }
     let state = cast_pointer!(state, LdapState);
+    state.update_ts(cast_pointer!(flow, Flow).get_last_time().as_secs());
     if stream_slice.is_gap() {
         state.on_response_gap(stream_slice.gap_size());
     } else {
-        return state.parse_response(flow, stream_slice);
+        return applayer_stats_result!(ldap_stats(), state.parse_response(flow, stream_slice));
     }
     AppLayerResult::ok()
 }
@@ -605,7 +1128,8 @@ unsafe extern "C" fn ldap_parse_request_udp(
     _data: *const c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, LdapState);
-    state.parse_request_udp(flow, stream_slice)
+    state.update_ts(cast_pointer!(flow, Flow).get_last_time().as_secs());
+    applayer_stats_result!(ldap_stats(), state.parse_request_udp(flow, stream_slice))
 }
 
 unsafe extern "C" fn ldap_parse_response_udp(
@@ -613,7 +1137,8 @@ unsafe extern "C" fn ldap_parse_response_udp(
     _data: *const c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, LdapState);
-    state.parse_response_udp(flow, stream_slice)
+    state.update_ts(cast_pointer!(flow, Flow).get_last_time().as_secs());
+    applayer_stats_result!(ldap_stats(), state.parse_response_udp(flow, stream_slice))
 }
 
 unsafe extern "C" fn ldap_state_get_tx(state: *mut c_void, tx_id: u64) -> *mut c_void {
@@ -641,6 +1166,15 @@ unsafe extern "C" fn ldap_tx_get_alstate_progress(tx: *mut c_void, _direction: u
     return 0;
 }
 
+/// Whether the request side of the transaction is done, which is when the
+/// request is seen or, for responses without a request, when the
+/// transaction completes.
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapTxRequestDone(tx: *mut c_void) -> bool {
+    let tx = cast_pointer!(tx, LdapTransaction);
+    tx.request.is_some() || tx.complete
+}
+
 export_tx_data_get!(ldap_get_tx_data, LdapTransaction);
 export_state_data_get!(ldap_get_state_data, LdapState);
 
@@ -689,6 +1223,7 @@ pub unsafe extern "C" fn SCRegisterLdapTcpParser() {
         ALPROTO_LDAP = alproto;
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
+            applayer_stats_register(ldap_stats());
         }
         if let Some(val) = conf_get("app-layer.protocols.ldap.max-tx") {
             if let Ok(v) = val.parse::<usize>() {
@@ -699,6 +1234,13 @@ pub unsafe extern "C" fn SCRegisterLdapTcpParser() {
                 SCLogError!("Invalid value for ldap.max-tx");
             }
         }
+        if let Some(val) = conf_get("app-layer.protocols.ldap.tx-timeout") {
+            if let Ok(v) = val.parse::<u64>() {
+                LDAP_TX_TIMEOUT = v;
+            } else {
+                SCLogError!("Invalid value for ldap.tx-timeout");
+            }
+        }
         AppLayerParserRegisterLogger(IPPROTO_TCP, ALPROTO_LDAP);
     } else {
         SCLogDebug!("Protocol detection and parser disabled for LDAP/TCP.");
@@ -748,6 +1290,7 @@ pub unsafe extern "C" fn SCRegisterLdapUdpParser() {
         ALPROTO_LDAP = alproto;
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
+            applayer_stats_register(ldap_stats());
         }
         if let Some(val) = conf_get("app-layer.protocols.ldap.max-tx") {
             if let Ok(v) = val.parse::<usize>() {
@@ -758,8 +1301,580 @@ pub unsafe extern "C" fn SCRegisterLdapUdpParser() {
                 SCLogError!("Invalid value for ldap.max-tx");
             }
         }
+        if let Some(val) = conf_get("app-layer.protocols.ldap.tx-timeout") {
+            if let Ok(v) = val.parse::<u64>() {
+                LDAP_TX_TIMEOUT = v;
+            } else {
+                SCLogError!("Invalid value for ldap.tx-timeout");
+            }
+        }
         AppLayerParserRegisterLogger(IPPROTO_UDP, ALPROTO_LDAP);
     } else {
         SCLogDebug!("Protocol detection and parser disabled for LDAP/UDP.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // bindResponse, message id 1, result code success
+    const BIND_RESPONSE: [u8; 14] = [
+        0x30, 0x0c, 0x02, 0x01, 0x01, 0x61, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+    ];
+
+    #[test]
+    fn test_ldap_op_in_wrong_direction() {
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&BIND_RESPONSE, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        let tx = &state.transactions[0];
+        let request = tx.request.as_ref().unwrap();
+        assert!(matches!(request.protocol_op, ProtocolOp::BindResponse(_)));
+        assert!(op_in_wrong_direction(request, Direction::ToServer));
+        assert!(!op_in_wrong_direction(request, Direction::ToClient));
+    }
+
+    #[test]
+    fn test_ldap_pdu_bytes() {
+        // searchRequest, message id 2, (objectClass=*)
+        #[rustfmt::skip]
+        let search_request = [
+            0x30, 0x25, 0x02, 0x01, 0x02, 0x63, 0x20,
+            0x04, 0x00, 0x0a, 0x01, 0x00, 0x0a, 0x01, 0x00, 0x02, 0x01, 0x00,
+            0x02, 0x01, 0x00, 0x01, 0x01, 0x00,
+            0x87, 0x0b, b'o', b'b', b'j', b'e', b'c', b't', b'C', b'l', b'a', b's', b's',
+            0x30, 0x00,
+        ];
+        // searchResEntry and searchResDone, message id 2
+        #[rustfmt::skip]
+        let search_responses = [
+            0x30, 0x09, 0x02, 0x01, 0x02, 0x64, 0x04, 0x04, 0x00, 0x30, 0x00,
+            0x30, 0x0c, 0x02, 0x01, 0x02, 0x65, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+        ];
+
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&search_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&search_responses, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        let tx = &state.transactions[0];
+        assert!(matches!(
+            tx.request.as_ref().unwrap().protocol_op,
+            ProtocolOp::SearchRequest(_)
+        ));
+        assert_eq!(tx.request_bytes, 39);
+        assert_eq!(tx.response_pdu_count, 2);
+        assert_eq!(tx.response_bytes, 25);
+
+        // the next request is counted in its own transaction
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&search_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 2);
+        assert_eq!(state.transactions[1].request_bytes, 39);
+        assert_eq!(state.transactions[1].response_pdu_count, 0);
+    }
+
+    #[test]
+    fn test_ldap_filter_values() {
+        // searchRequest, message id 2,
+        // (&(objectClass=user)(sAMAccountName=svc_backup)(!(cn=guest))(mail=*))
+        #[rustfmt::skip]
+        let search_request = [
+            0x30, 0x62, 0x02, 0x01, 0x02, 0x63, 0x5d,
+            0x04, 0x00, 0x0a, 0x01, 0x00, 0x0a, 0x01, 0x00, 0x02, 0x01, 0x00,
+            0x02, 0x01, 0x00, 0x01, 0x01, 0x00,
+            0xa0, 0x48,
+            0xa3, 0x13,
+            0x04, 0x0b, b'o', b'b', b'j', b'e', b'c', b't', b'C', b'l', b'a', b's', b's',
+            0x04, 0x04, b'u', b's', b'e', b'r',
+            0xa3, 0x1c,
+            0x04, 0x0e, b's', b'A', b'M', b'A', b'c', b'c', b'o', b'u', b'n', b't',
+            b'N', b'a', b'm', b'e',
+            0x04, 0x0a, b's', b'v', b'c', b'_', b'b', b'a', b'c', b'k', b'u', b'p',
+            0xa2, 0x0d, 0xa3, 0x0b,
+            0x04, 0x02, b'c', b'n', 0x04, 0x05, b'g', b'u', b'e', b's', b't',
+            0x87, 0x04, b'm', b'a', b'i', b'l',
+            0x30, 0x00,
+        ];
+
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&search_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        // the presence filter asserts no value
+        let expected: [&[u8]; 3] = [b"user", b"svc_backup", b"guest"];
+        assert_eq!(state.transactions[0].filter_values, expected);
+    }
+
+    #[test]
+    fn test_ldap_parser_stats() {
+        use std::sync::atomic::Ordering;
+
+        // bindRequest, message id 1, simple anonymous bind
+        let bind_request = [
+            0x30, 0x0c, 0x02, 0x01, 0x01, 0x60, 0x07, 0x02, 0x01, 0x03, 0x04, 0x00, 0x80, 0x00,
+        ];
+        // bindResponse, message id 1, result code success
+        let bind_response = [
+            0x30, 0x0c, 0x02, 0x01, 0x01, 0x61, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+        ];
+        let stats = ldap_stats();
+        let counters = || {
+            [
+                &stats.tx_created,
+                &stats.tx_completed,
+                &stats.tx_freed,
+                &stats.parse_errors,
+                &stats.events,
+                &stats.states_live,
+            ]
+            .map(|counter| counter.load(Ordering::Relaxed))
+        };
+        let delta = |before: [u64; 6]| {
+            let after = counters();
+            [0, 1, 2, 3, 4, 5].map(|i| after[i] - before[i])
+        };
+        let before = counters();
+
+        let state = ldap_state_new(std::ptr::null_mut(), ALPROTO_UNKNOWN);
+        assert_eq!(delta(before), [0, 0, 0, 0, 0, 1]);
+
+        let ldap = unsafe { &mut *(state as *mut LdapState) };
+        let r = ldap.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        let r = ldap.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_response, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(delta(before), [1, 0, 0, 0, 0, 1]);
+
+        unsafe { ldap_state_tx_free(state, 0) };
+        assert_eq!(delta(before), [1, 1, 1, 0, 0, 1]);
+
+        // the response again answers no request, in a transaction of its own
+        let ldap = unsafe { &mut *(state as *mut LdapState) };
+        let r = ldap.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_response, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        let tx = &ldap.transactions[0];
+        assert!(tx.request.is_none());
+        assert!(tx.orphan_response);
+        assert_eq!(delta(before), [2, 1, 1, 0, 2, 1]);
+
+        // the transactions left are freed with the state
+        unsafe { ldap_state_free(state) };
+        assert_eq!(delta(before), [2, 2, 2, 0, 2, 0]);
+    }
+
+    #[test]
+    fn test_ldap_starttls_null_flow() {
+        // extendedRequest, message id 1, request name 1.3.6.1.4.1.1466.20037
+        let mut starttls_request = vec![0x30, 0x1d, 0x02, 0x01, 0x01, 0x77, 0x18, 0x80, 0x16];
+        starttls_request.extend_from_slice(STARTTLS_OID.as_bytes());
+        // extendedResponse, message id 1, result code success
+        let starttls_response = [
+            0x30, 0x0c, 0x02, 0x01, 0x01, 0x78, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+        ];
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&starttls_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert!(state.request_tls);
+        // the upgrade is not recorded without a flow
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&starttls_response, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert!(state.has_starttls);
+        assert!(!state.request_tls);
+        assert_eq!(state.transactions.len(), 1);
+        let tx = &state.transactions[0];
+        assert!(tx.complete);
+        assert_eq!(tx.flags & LDAP_TX_FLAG_STARTTLS, LDAP_TX_FLAG_STARTTLS);
+    }
+
+    #[test]
+    fn test_ldap_unsolicited_notification_own_tx() {
+        // unbindRequest, message id 0
+        let unbind_request = [0x30, 0x05, 0x02, 0x01, 0x00, 0x42, 0x00];
+        // extendedResponse, message id 0, result code success
+        let notification = [
+            0x30, 0x0c, 0x02, 0x01, 0x00, 0x78, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+        ];
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&unbind_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&notification, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].responses.is_empty());
+        let tx = &state.transactions[1];
+        assert!(tx.request.is_none());
+        assert!(matches!(
+            tx.responses[0].protocol_op,
+            ProtocolOp::ExtendedResponse(_)
+        ));
+        assert!(!message_id_mismatch(tx, &tx.responses[0]));
+        assert_eq!(tx.flags, LDAP_TX_FLAG_SERVER_INITIATED);
+    }
+
+    #[test]
+    fn test_ldap_notice_of_disconnection() {
+        // extendedResponse, message id 0, result code unavailable, response
+        // name 1.3.6.1.4.1.1466.20036
+        let mut notice = vec![
+            0x30, 0x24, 0x02, 0x01, 0x00, 0x78, 0x1f, 0x0a, 0x01, 0x34, 0x04, 0x00, 0x04, 0x00,
+            0x8a, 0x16,
+        ];
+        notice.extend_from_slice(NOTICE_OF_DISCONNECTION_OID.as_bytes());
+        let mut state = LdapState::new();
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&notice, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        let tx = &state.transactions[0];
+        assert!(tx.complete);
+        assert!(!tx.orphan_response);
+        assert_eq!(
+            tx.flags,
+            LDAP_TX_FLAG_SERVER_INITIATED | LDAP_TX_FLAG_NOTICE_OF_DISCONNECTION
+        );
+
+        // the same response answering a request is no notification
+        notice[4] = 0x01;
+        assert_eq!(
+            notification_flags(&LdapMessage::from(ldap_parse_msg(&notice).unwrap().1)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_ldap_request_to_client() {
+        // bindRequest, message id 1, anonymous simple bind
+        let bind_request = [
+            0x30, 0x0c, 0x02, 0x01, 0x01, 0x60, 0x07, 0x02, 0x01, 0x03, 0x04, 0x00, 0x80, 0x00,
+        ];
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        // the server sends the request back, with the same message id
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_request, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].responses.is_empty());
+        assert_eq!(state.outstanding_requests, vec![MessageID(1)]);
+        let tx = &state.transactions[1];
+        assert!(tx.request.is_none());
+        assert!(matches!(
+            tx.responses[0].protocol_op,
+            ProtocolOp::BindRequest(_)
+        ));
+        assert!(!tx.orphan_response);
+        assert!(op_in_wrong_direction(&tx.responses[0], Direction::ToClient));
+        assert_eq!(tx.flags & LDAP_TX_FLAG_SERVER_INITIATED, 0);
+
+        // the real response still completes the request
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&BIND_RESPONSE, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions[0].responses.len(), 1);
+        assert!(!state.transactions[0].orphan_response);
+    }
+
+    #[test]
+    fn test_ldap_orphan_response() {
+        // bindRequest, message id 1, anonymous simple bind
+        let bind_request = [
+            0x30, 0x0c, 0x02, 0x01, 0x01, 0x60, 0x07, 0x02, 0x01, 0x03, 0x04, 0x00, 0x80, 0x00,
+        ];
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.outstanding_requests, vec![MessageID(1)]);
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&BIND_RESPONSE, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert!(state.outstanding_requests.is_empty());
+        assert!(!state.transactions[0].orphan_response);
+
+        // the request already got its response
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&BIND_RESPONSE, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        assert!(state.transactions[0].orphan_response);
+
+        // no request at all
+        let mut state = LdapState::new();
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&BIND_RESPONSE, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert!(state.transactions[0].orphan_response);
+
+        // unsolicited notifications are no orphans
+        let notification = [
+            0x30, 0x0c, 0x02, 0x01, 0x00, 0x78, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+        ];
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&notification, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert!(!state.transactions[1].orphan_response);
+    }
+
+    #[test]
+    fn test_ldap_outstanding_requests_max() {
+        let mut state = LdapState::new();
+        let max = unsafe { LDAP_MAX_TX };
+        for id in 1..=max as u32 + 1 {
+            state.add_outstanding_request(&LdapMessage {
+                message_id: MessageID(id),
+                protocol_op: ProtocolOp::UnbindRequest,
+                controls: None,
+            });
+        }
+        assert!(state.outstanding_requests.is_empty());
+        for id in 1..=max as u32 + 1 {
+            state.add_outstanding_request(&LdapMessage {
+                message_id: MessageID(id),
+                protocol_op: ProtocolOp::DelRequest(LdapDN("cn=x".to_string())),
+                controls: None,
+            });
+        }
+        assert_eq!(state.outstanding_requests.len(), max);
+        // the oldest request is dropped
+        assert_eq!(state.outstanding_requests.front(), Some(&MessageID(2)));
+    }
+
+    #[test]
+    fn test_ldap_tx_timeout() {
+        // searchRequest, message id 2, (objectClass=*)
+        #[rustfmt::skip]
+        let search_request = [
+            0x30, 0x25, 0x02, 0x01, 0x02, 0x63, 0x20,
+            0x04, 0x00, 0x0a, 0x01, 0x00, 0x0a, 0x01, 0x00, 0x02, 0x01, 0x00,
+            0x02, 0x01, 0x00, 0x01, 0x01, 0x00,
+            0x87, 0x0b, b'o', b'b', b'j', b'e', b'c', b't', b'C', b'l', b'a', b's', b's',
+            0x30, 0x00,
+        ];
+        // searchResEntry, message id 2
+        let search_entry = [
+            0x30, 0x09, 0x02, 0x01, 0x02, 0x64, 0x04, 0x04, 0x00, 0x30, 0x00,
+        ];
+        // searchResDone, message id 2
+        let search_done = [
+            0x30, 0x0c, 0x02, 0x01, 0x02, 0x65, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+        ];
+        let response = |state: &mut LdapState, buf: &[u8]| {
+            let r = state.parse_response(
+                std::ptr::null(),
+                StreamSlice::from_slice(buf, STREAM_TOCLIENT, 0),
+            );
+            assert_eq!(r, AppLayerResult::ok());
+        };
+
+        let mut state = LdapState::new();
+        state.ts = 100;
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&search_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        state.ts = 105;
+        response(&mut state, &search_entry);
+
+        // disabled, or not idle for long enough
+        state.ts = 1000;
+        state.timeout_txs(0);
+        assert!(!state.transactions[0].complete);
+        state.ts = 115;
+        state.timeout_txs(10);
+        assert!(!state.transactions[0].complete);
+
+        state.ts = 116;
+        state.timeout_txs(10);
+        let tx = &state.transactions[0];
+        assert!(tx.complete);
+        assert_ne!(tx.flags & LDAP_TX_FLAG_TRUNCATED, 0);
+        assert_eq!(tx.responses.len(), 1);
+        assert!(state.outstanding_requests.is_empty());
+        assert_eq!(state.timed_out_requests, [MessageID(2)]);
+
+        // the late responses go to a new transaction, which completes
+        // with the last one
+        response(&mut state, &search_entry);
+        assert_eq!(state.transactions.len(), 2);
+        let tx = &state.transactions[1];
+        assert!(!tx.complete);
+        assert!(tx.request.is_none());
+        assert_ne!(tx.flags & LDAP_TX_FLAG_LATE_RESPONSE, 0);
+        assert!(!tx.orphan_response);
+        response(&mut state, &search_done);
+        assert_eq!(state.transactions.len(), 2);
+        let tx = &state.transactions[1];
+        assert!(tx.complete);
+        assert_eq!(tx.responses.len(), 2);
+        assert!(state.timed_out_requests.is_empty());
+        // the timed out transaction is left as it was
+        assert_eq!(state.transactions[0].responses.len(), 1);
+
+        // after its last response, the request is no longer waited for
+        // and a response to it is an orphan, as for a completed request
+        response(&mut state, &search_done);
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].orphan_response);
+        assert!(!state.transactions[1].orphan_response);
+    }
+
+    #[test]
+    fn test_ldap_message_id_mismatch() {
+        // bindRequest, message id 1, anonymous simple bind
+        let bind_request = [
+            0x30, 0x0c, 0x02, 0x01, 0x01, 0x60, 0x07, 0x02, 0x01, 0x03, 0x04, 0x00, 0x80, 0x00,
+        ];
+        // bindResponse, message id 2, result code success
+        let mut bind_response = BIND_RESPONSE;
+        bind_response[4] = 0x02;
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&bind_response, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        // the response does not complete the request waiting for it
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].responses.is_empty());
+        assert!(!state.transactions[0].complete);
+        let tx = &state.transactions[1];
+        assert!(tx.request.is_none());
+        assert!(tx.orphan_response);
+        assert!(message_id_mismatch(&state.transactions, &tx.responses[0]));
+
+        // the real response completes it
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&BIND_RESPONSE, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].complete);
+        let tx = &state.transactions[1];
+        // no request is waiting anymore
+        assert!(!message_id_mismatch(&state.transactions, &tx.responses[0]));
+    }
+
+    #[test]
+    fn test_ldap_summary_consistent() {
+        // searchRequest, message id 2, filter (cn=*)
+        let search_request = [
+            0x30, 0x1c, 0x02, 0x01, 0x02, 0x63, 0x17, 0x04, 0x00, 0x0a, 0x01, 0x00, 0x0a, 0x01,
+            0x00, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x01, 0x01, 0x00, 0x87, 0x02, 0x63, 0x6e,
+            0x30, 0x00,
+        ];
+        // searchResEntry, message id 2, object "a" without attributes
+        let entry = [
+            0x30, 0x0a, 0x02, 0x01, 0x02, 0x64, 0x05, 0x04, 0x01, 0x61, 0x30, 0x00,
+        ];
+        // searchResDone, message id 2, result code noSuchObject
+        let done = [
+            0x30, 0x0c, 0x02, 0x01, 0x02, 0x65, 0x07, 0x0a, 0x01, 0x20, 0x04, 0x00, 0x04, 0x00,
+        ];
+        let mut state = LdapState::new();
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&search_request, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        for _ in 0..3 {
+            let r = state.parse_response(
+                std::ptr::null(),
+                StreamSlice::from_slice(&entry, STREAM_TOCLIENT, 0),
+            );
+            assert_eq!(r, AppLayerResult::ok());
+            let tx = &state.transactions[0];
+            assert_eq!(
+                tx.responses_summary(),
+                Some(&TransactionSummary::from_responses(&tx.responses))
+            );
+            assert!(!tx.summary.has_error);
+        }
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&done, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        let tx = &state.transactions[0];
+        assert_eq!(
+            tx.responses_summary(),
+            Some(&TransactionSummary::from_responses(&tx.responses))
+        );
+        let summary = tx.responses_summary().unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.entries, 3);
+        assert_eq!(summary.op_counts, vec![(4, 3), (5, 1)]);
+        assert_eq!(
+            summary.result_codes,
+            vec![LdapResultCode::NoSuchObject as u32]
+        );
+        assert!(summary.has_error);
+    }
+}
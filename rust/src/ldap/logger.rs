@@ -17,30 +17,278 @@
 
 // written by Giuseppe Longo <giuseppe@glongo.it>
 
+use suricata_sys::sys::SCConfNode;
+
+use crate::conf::ConfNode;
 use crate::detect::EnumString;
 use crate::jsonbuilder::{JsonBuilder, JsonError};
 use crate::ldap::filters::*;
-use crate::ldap::ldap::LdapTransaction;
+use crate::ldap::ldap::{LdapTransaction, LDAP_TX_FLAG_SERVER_INITIATED};
 use crate::ldap::types::*;
 
-fn log_ldap(tx: &LdapTransaction, js: &mut JsonBuilder) -> Result<(), JsonError> {
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+
+/// Parts of the transaction to log, selected with the `fields` list of
+/// the eve-log ldap configuration. Everything is logged by default.
+///
+/// With `request-summary`, records without the request, like the response
+/// records of `split-records`, get its operation, DN and search filter.
+#[derive(Clone, Copy)]
+struct LdapFields {
+    request: bool,
+    responses: bool,
+    results: bool,
+    request_summary: bool,
+}
+
+impl Default for LdapFields {
+    fn default() -> Self {
+        Self {
+            request: true,
+            responses: true,
+            results: true,
+            request_summary: false,
+        }
+    }
+}
+
+/// What is logged of the values of a sensitive attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LdapRedactAction {
+    Drop,
+    /// sha256, in hex
+    Hash,
+    /// the first bytes only
+    Truncate(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LdapRedaction {
+    attribute: Cow<'static, str>,
+    action: LdapRedactAction,
+}
+
+const fn redact_default(attribute: &'static str, action: LdapRedactAction) -> LdapRedaction {
+    LdapRedaction {
+        attribute: Cow::Borrowed(attribute),
+        action,
+    }
+}
+
+/// Attributes redacted unless the `redact` list is configured. Passwords
+/// are dropped, the key credentials hashed so that they can still be
+/// correlated.
+static LDAP_REDACT_DEFAULT: [LdapRedaction; 9] = [
+    redact_default("unicodePwd", LdapRedactAction::Drop),
+    redact_default("userPassword", LdapRedactAction::Drop),
+    redact_default("unixUserPassword", LdapRedactAction::Drop),
+    redact_default("sambaNTPassword", LdapRedactAction::Drop),
+    redact_default("sambaLMPassword", LdapRedactAction::Drop),
+    redact_default("ms-Mcs-AdmPwd", LdapRedactAction::Drop),
+    redact_default("msLAPS-Password", LdapRedactAction::Drop),
+    redact_default("msLAPS-EncryptedPassword", LdapRedactAction::Drop),
+    redact_default("msDS-KeyCredentialLink", LdapRedactAction::Hash),
+];
+
+/// Parses an entry of the `redact` list, `attribute[:action]` where the
+/// action is `drop` (the default), `hash` or `truncate:N`.
+fn parse_redaction(s: &str) -> Option<LdapRedaction> {
+    let (attribute, action) = match s.split_once(':') {
+        Some((attribute, action)) => (attribute.trim(), action.trim()),
+        None => (s.trim(), "drop"),
+    };
+    if attribute.is_empty() {
+        return None;
+    }
+    let action = match action {
+        "drop" => LdapRedactAction::Drop,
+        "hash" => LdapRedactAction::Hash,
+        _ => {
+            let len = action.strip_prefix("truncate:")?.trim().parse().ok()?;
+            LdapRedactAction::Truncate(len)
+        }
+    };
+    Some(LdapRedaction {
+        attribute: Cow::Owned(attribute.to_string()),
+        action,
+    })
+}
+
+/// The action for an attribute, by its case-insensitive name, options
+/// like `;binary` being ignored.
+fn redact_action(redact: &[LdapRedaction], attr_type: &str) -> Option<LdapRedactAction> {
+    let name = attr_type.split(';').next().unwrap_or(attr_type);
+    redact
+        .iter()
+        .find(|r| r.attribute.eq_ignore_ascii_case(name))
+        .map(|r| r.action)
+}
+
+/// The value to log, None if it is dropped.
+fn redact_value(value: &[u8], action: Option<LdapRedactAction>) -> Option<Cow<[u8]>> {
+    match action {
+        None => Some(Cow::Borrowed(value)),
+        Some(LdapRedactAction::Drop) => None,
+        Some(LdapRedactAction::Hash) => {
+            let hash = format!("{:x}", Sha256::digest(value));
+            Some(Cow::Owned(hash.into_bytes()))
+        }
+        Some(LdapRedactAction::Truncate(len)) => {
+            Some(Cow::Borrowed(&value[..value.len().min(len)]))
+        }
+    }
+}
+
+/// Logs the values of an attribute as an array, unless they are dropped.
+fn log_attribute_values(
+    key: &str, attr_type: &str, values: &[AttributeValue], redact: &[LdapRedaction],
+    js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
+    let action = redact_action(redact, attr_type);
+    if values.is_empty() || action == Some(LdapRedactAction::Drop) {
+        return Ok(());
+    }
+    js.open_array(key)?;
+    for val in values {
+        if let Some(val) = redact_value(&val.0, action) {
+            js.append_string_from_bytes(&val)?;
+        }
+    }
+    js.close()?;
+    Ok(())
+}
+
+/// The eve-log ldap configuration.
+///
+/// With `split-records` the request is logged in a record of its own as
+/// soon as it is seen, and the responses in a second record once the
+/// transaction completes.
+///
+/// The values of the attributes of the `redact` list are dropped, hashed
+/// or truncated, see `LDAP_REDACT_DEFAULT` for the default list.
+pub struct LdapLogger {
+    fields: LdapFields,
+    split_records: bool,
+    redact: Vec<LdapRedaction>,
+}
+
+impl Default for LdapLogger {
+    fn default() -> Self {
+        Self {
+            fields: LdapFields::default(),
+            split_records: false,
+            redact: LDAP_REDACT_DEFAULT.to_vec(),
+        }
+    }
+}
+
+impl LdapLogger {
+    pub fn new(conf: ConfNode) -> Self {
+        let split_records = conf.get_child_bool("split-records");
+        let request_summary = conf.get_child_bool("request-summary");
+        let redact = if let Some(node) = conf.get_child_node("redact") {
+            Self::parse_redact(node)
+        } else {
+            LDAP_REDACT_DEFAULT.to_vec()
+        };
+        let node = if let Some(node) = conf.get_child_node("fields") {
+            node
+        } else {
+            return Self {
+                fields: LdapFields {
+                    request_summary,
+                    ..LdapFields::default()
+                },
+                split_records,
+                redact,
+            };
+        };
+        let mut fields = LdapFields {
+            request: false,
+            responses: false,
+            results: false,
+            request_summary,
+        };
+        let mut child = node.first();
+        while let Some(field) = child {
+            match field.value() {
+                "request" => fields.request = true,
+                "responses" => fields.responses = true,
+                "results" => fields.results = true,
+                _ => {
+                    SCLogWarning!("unknown field for ldap logging: {}", field.value());
+                }
+            }
+            child = field.next();
+        }
+        if !fields.request && !fields.responses && !fields.results {
+            SCLogWarning!("empty fields list for ldap is interpreted as logging all");
+            fields = LdapFields {
+                request_summary,
+                ..LdapFields::default()
+            };
+        }
+        return Self {
+            fields,
+            split_records,
+            redact,
+        };
+    }
+
+    fn parse_redact(node: ConfNode) -> Vec<LdapRedaction> {
+        let mut redact = Vec::new();
+        let mut child = node.first();
+        while let Some(entry) = child {
+            if let Some(r) = parse_redaction(entry.value()) {
+                redact.push(r);
+            } else {
+                SCLogWarning!("invalid redact entry for ldap logging: {}", entry.value());
+            }
+            child = entry.next();
+        }
+        redact
+    }
+
+    /// The fields of the request record when splitting records.
+    fn request_record(&self) -> LdapFields {
+        LdapFields {
+            responses: false,
+            results: false,
+            ..self.fields
+        }
+    }
+
+    /// The fields of the response record when splitting records.
+    fn response_record(&self) -> LdapFields {
+        LdapFields {
+            request: false,
+            ..self.fields
+        }
+    }
+}
+
+fn log_ldap(
+    tx: &LdapTransaction, fields: &LdapFields, redact: &[LdapRedaction], js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
     js.open_object("ldap")?;
 
-    if let Some(req) = &tx.request {
+    if let (true, Some(req)) = (fields.request, &tx.request) {
         let protocol_op_str = req.protocol_op.to_string();
         js.open_object("request")?;
         js.set_uint("message_id", req.message_id.0)?;
         js.set_string("operation", &protocol_op_str)?;
+        js.set_uint("operation_code", req.protocol_op.to_u8())?;
 
         match &req.protocol_op {
             ProtocolOp::SearchRequest(msg) => log_search_request(msg, js)?,
             ProtocolOp::BindRequest(msg) => log_bind_request(msg, js)?,
             ProtocolOp::UnbindRequest => (),
-            ProtocolOp::ModifyRequest(msg) => log_modify_request(msg, js)?,
-            ProtocolOp::AddRequest(msg) => log_add_request(msg, js)?,
+            ProtocolOp::ModifyRequest(msg) => log_modify_request(msg, redact, js)?,
+            ProtocolOp::AddRequest(msg) => log_add_request(msg, redact, js)?,
             ProtocolOp::DelRequest(msg) => log_del_request(msg, js)?,
             ProtocolOp::ModDnRequest(msg) => log_mod_dn_request(msg, js)?,
-            ProtocolOp::CompareRequest(msg) => log_compare_request(msg, js)?,
+            ProtocolOp::CompareRequest(msg) => log_compare_request(msg, redact, js)?,
             ProtocolOp::ExtendedRequest(msg) => log_extended_request(msg, js)?,
             _ => {}
         };
@@ -48,9 +296,11 @@ fn log_ldap(tx: &LdapTransaction, js: &mut JsonBuilder) -> Result<(), JsonError>
         log_controls(&req.controls, js)?;
 
         js.close()?;
+    } else if let (true, Some(req)) = (fields.request_summary, &tx.request) {
+        log_request_summary(req, redact, js)?;
     }
 
-    if !tx.responses.is_empty() {
+    if fields.responses && !tx.responses.is_empty() {
         js.open_array("responses")?;
 
         for response in &tx.responses {
@@ -58,13 +308,16 @@ fn log_ldap(tx: &LdapTransaction, js: &mut JsonBuilder) -> Result<(), JsonError>
 
             let protocol_op_str = response.protocol_op.to_string();
             js.set_string("operation", &protocol_op_str)?;
+            js.set_uint("operation_code", response.protocol_op.to_u8())?;
 
-            if tx.request.is_none() {
+            // log the id of the response too if it does not match the request
+            let request_id = tx.request.as_ref().map(|req| req.message_id);
+            if !fields.request || request_id != Some(response.message_id) {
                 js.set_uint("message_id", response.message_id.0)?;
             }
 
             match &response.protocol_op {
-                ProtocolOp::SearchResultEntry(msg) => log_search_result_entry(msg, js)?,
+                ProtocolOp::SearchResultEntry(msg) => log_search_result_entry(msg, redact, js)?,
                 ProtocolOp::SearchResultDone(msg) => log_search_result_done(msg, js)?,
                 ProtocolOp::BindResponse(msg) => log_bind_response(msg, js)?,
                 ProtocolOp::ModifyResponse(msg) => log_modify_response(msg, js)?,
@@ -76,12 +329,196 @@ fn log_ldap(tx: &LdapTransaction, js: &mut JsonBuilder) -> Result<(), JsonError>
                 ProtocolOp::IntermediateResponse(msg) => log_intermediate_response(msg, js)?,
                 _ => {}
             }
+            log_references(response, js)?;
             log_controls(&response.controls, js)?;
             js.close()?;
         }
         js.close()?;
     }
 
+    if fields.request && tx.request_bytes > 0 {
+        js.set_uint("request_bytes", tx.request_bytes)?;
+    }
+    if fields.responses && tx.response_pdu_count > 0 {
+        js.set_uint("response_pdu_count", tx.response_pdu_count)?;
+        js.set_uint("response_bytes", tx.response_bytes)?;
+    }
+    if tx.flags & LDAP_TX_FLAG_SERVER_INITIATED != 0 {
+        js.set_bool("server_initiated", true)?;
+    }
+
+    if fields.results {
+        log_results(tx, js)?;
+    }
+
+    js.close()?;
+    Ok(())
+}
+
+/// Append a filter value, escaped as in RFC 4515 and redacted like the
+/// values of the attribute.
+fn push_filter_value(out: &mut String, attr: &str, value: &[u8], redact: &[LdapRedaction]) {
+    let value = match redact_value(value, redact_action(redact, attr)) {
+        Some(value) => value,
+        None => {
+            out.push_str("<redacted>");
+            return;
+        }
+    };
+    for &b in value.iter() {
+        if b == b' ' || (b.is_ascii_graphic() && !matches!(b, b'*' | b'(' | b')' | b'\\')) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("\\{:02x}", b));
+        }
+    }
+}
+
+/// Append the string representation of a search filter, as in RFC 4515.
+fn push_filter(out: &mut String, filter: &Filter, redact: &[LdapRedaction]) {
+    let (attr, op, value) = match filter {
+        Filter::And(filters) | Filter::Or(filters) => {
+            out.push_str(if matches!(filter, Filter::And(_)) {
+                "(&"
+            } else {
+                "(|"
+            });
+            for f in filters {
+                push_filter(out, f, redact);
+            }
+            out.push(')');
+            return;
+        }
+        Filter::Not(f) => {
+            out.push_str("(!");
+            push_filter(out, f, redact);
+            out.push(')');
+            return;
+        }
+        Filter::Present(attr) => {
+            out.push('(');
+            out.push_str(&attr.0);
+            out.push_str("=*)");
+            return;
+        }
+        Filter::Substrings(sf) => {
+            out.push('(');
+            out.push_str(&sf.filter_type.0);
+            out.push('=');
+            let mut last_final = false;
+            for s in &sf.substrings {
+                let value = match s {
+                    Substring::Initial(v) => v,
+                    Substring::Any(v) | Substring::Final(v) => {
+                        out.push('*');
+                        v
+                    }
+                };
+                push_filter_value(out, &sf.filter_type.0, &value.0, redact);
+                last_final = matches!(s, Substring::Final(_));
+            }
+            if !last_final {
+                out.push('*');
+            }
+            out.push(')');
+            return;
+        }
+        Filter::ExtensibleMatch(mra) => {
+            out.push('(');
+            let attr = mra.rule_type.as_ref().map_or("", |t| t.0.as_str());
+            out.push_str(attr);
+            if mra.dn_attributes == Some(true) {
+                out.push_str(":dn");
+            }
+            if let Some(rule) = &mra.matching_rule {
+                out.push(':');
+                out.push_str(&rule.0);
+            }
+            out.push_str(":=");
+            push_filter_value(out, attr, &mra.assertion_value.0, redact);
+            out.push(')');
+            return;
+        }
+        Filter::EqualityMatch(ava) => (&ava.attribute_desc, "=", &ava.assertion_value),
+        Filter::GreaterOrEqual(ava) => (&ava.attribute_desc, ">=", &ava.assertion_value),
+        Filter::LessOrEqual(ava) => (&ava.attribute_desc, "<=", &ava.assertion_value),
+        Filter::ApproxMatch(ava) => (&ava.attribute_desc, "~=", &ava.assertion_value),
+    };
+    out.push('(');
+    out.push_str(&attr.0);
+    out.push_str(op);
+    push_filter_value(out, &attr.0, value, redact);
+    out.push(')');
+}
+
+/// Log what the response record needs to know of the request
+fn log_request_summary(
+    req: &LdapMessage, redact: &[LdapRedaction], js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
+    js.open_object("request_summary")?;
+    js.set_uint("message_id", req.message_id.0)?;
+    js.set_string("operation", &req.protocol_op.to_string())?;
+    js.set_uint("operation_code", req.protocol_op.to_u8())?;
+    if let Some(dn) = req.request_dn() {
+        js.set_string("dn", dn)?;
+    }
+    if let ProtocolOp::SearchRequest(msg) = &req.protocol_op {
+        let mut filter = String::new();
+        push_filter(&mut filter, &msg.filter, redact);
+        js.set_string("filter", &filter)?;
+    }
+    js.close()?;
+    Ok(())
+}
+
+/// Log the continuation references of a search, where the search goes on,
+/// apart from the referrals of results, where the operation is to be retried.
+fn log_references(response: &LdapMessage, js: &mut JsonBuilder) -> Result<(), JsonError> {
+    if let ProtocolOp::SearchResultReference(uris) = &response.protocol_op {
+        js.open_object("search")?;
+        js.open_array("references")?;
+        for uri in uris {
+            js.append_string(&uri.0)?;
+        }
+        js.close()?;
+        js.close()?;
+    } else if let Some(result) = response.result() {
+        if !result.referrals.is_empty() {
+            js.open_array("referrals")?;
+            for uri in &result.referrals {
+                js.append_string(uri)?;
+            }
+            js.close()?;
+        }
+    }
+    Ok(())
+}
+
+/// Log the result codes of the responses grouped by operation, like
+/// `"results": {"bind_response": [49, 0]}`
+fn log_results(tx: &LdapTransaction, js: &mut JsonBuilder) -> Result<(), JsonError> {
+    let mut results: Vec<(String, Vec<u32>)> = Vec::new();
+    for response in &tx.responses {
+        if let Some(code) = get_ldap_result_code(response) {
+            let op = response.protocol_op.to_string();
+            if let Some((_, codes)) = results.iter_mut().find(|(name, _)| *name == op) {
+                codes.push(code);
+            } else {
+                results.push((op, vec![code]));
+            }
+        }
+    }
+    if results.is_empty() {
+        return Ok(());
+    }
+    js.open_object("results")?;
+    for (op, codes) in &results {
+        js.open_array(op)?;
+        for code in codes {
+            js.append_uint(*code as u64)?;
+        }
+        js.close()?;
+    }
     js.close()?;
     Ok(())
 }
@@ -128,7 +565,9 @@ fn log_bind_request(msg: &BindRequest, js: &mut JsonBuilder) -> Result<(), JsonE
     Ok(())
 }
 
-fn log_modify_request(msg: &ModifyRequest, js: &mut JsonBuilder) -> Result<(), JsonError> {
+fn log_modify_request(
+    msg: &ModifyRequest, redact: &[LdapRedaction], js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
     js.open_object("modify_request")?;
     js.set_string("object", &msg.object.0)?;
     if !msg.changes.is_empty() {
@@ -138,13 +577,13 @@ fn log_modify_request(msg: &ModifyRequest, js: &mut JsonBuilder) -> Result<(), J
             js.set_string("operation", &change.operation.to_string())?;
             js.open_object("modification")?;
             js.set_string("attribute_type", &change.modification.attr_type.0)?;
-            if !change.modification.attr_vals.is_empty() {
-                js.open_array("attribute_values")?;
-                for attr in &change.modification.attr_vals {
-                    js.append_string_from_bytes(&attr.0[..])?;
-                }
-                js.close()?;
-            }
+            log_attribute_values(
+                "attribute_values",
+                &change.modification.attr_type.0,
+                &change.modification.attr_vals,
+                redact,
+                js,
+            )?;
             js.close()?;
             js.close()?;
         }
@@ -154,7 +593,9 @@ fn log_modify_request(msg: &ModifyRequest, js: &mut JsonBuilder) -> Result<(), J
     Ok(())
 }
 
-fn log_add_request(msg: &AddRequest, js: &mut JsonBuilder) -> Result<(), JsonError> {
+fn log_add_request(
+    msg: &AddRequest, redact: &[LdapRedaction], js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
     js.open_object("add_request")?;
     js.set_string("entry", &msg.entry.0)?;
     if !msg.attributes.is_empty() {
@@ -162,13 +603,7 @@ fn log_add_request(msg: &AddRequest, js: &mut JsonBuilder) -> Result<(), JsonErr
         for attr in &msg.attributes {
             js.start_object()?;
             js.set_string("name", &attr.attr_type.0)?;
-            if !attr.attr_vals.is_empty() {
-                js.open_array("values")?;
-                for val in &attr.attr_vals {
-                    js.append_string_from_bytes(&val.0)?;
-                }
-                js.close()?;
-            }
+            log_attribute_values("values", &attr.attr_type.0, &attr.attr_vals, redact, js)?;
             js.close()?;
         }
         js.close()?;
@@ -196,12 +631,17 @@ fn log_mod_dn_request(msg: &ModDnRequest, js: &mut JsonBuilder) -> Result<(), Js
     Ok(())
 }
 
-fn log_compare_request(msg: &CompareRequest, js: &mut JsonBuilder) -> Result<(), JsonError> {
+fn log_compare_request(
+    msg: &CompareRequest, redact: &[LdapRedaction], js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
     js.open_object("compare_request")?;
     js.set_string("entry", &msg.entry.0)?;
     js.open_object("attribute_value_assertion")?;
     js.set_string("description", &msg.ava.attribute_desc.0)?;
-    js.set_string_from_bytes("value", &msg.ava.assertion_value[..])?;
+    let action = redact_action(redact, &msg.ava.attribute_desc.0);
+    if let Some(value) = redact_value(&msg.ava.assertion_value, action) {
+        js.set_string_from_bytes("value", &value)?;
+    }
     js.close()?;
     js.close()?;
     Ok(())
@@ -217,7 +657,9 @@ fn log_extended_request(msg: &ExtendedRequest, js: &mut JsonBuilder) -> Result<(
     Ok(())
 }
 
-fn log_search_result_entry(msg: &SearchResultEntry, js: &mut JsonBuilder) -> Result<(), JsonError> {
+fn log_search_result_entry(
+    msg: &SearchResultEntry, redact: &[LdapRedaction], js: &mut JsonBuilder,
+) -> Result<(), JsonError> {
     js.open_object("search_result_entry")?;
     js.set_string("base_object", &msg.object_name.0)?;
     if !msg.attributes.is_empty() {
@@ -225,13 +667,7 @@ fn log_search_result_entry(msg: &SearchResultEntry, js: &mut JsonBuilder) -> Res
         for attr in &msg.attributes {
             js.start_object()?;
             js.set_string("type", &attr.attr_type.0)?;
-            if !attr.attr_vals.is_empty() {
-                js.open_array("values")?;
-                for val in &attr.attr_vals {
-                    js.append_string_from_bytes(&val.0)?;
-                }
-                js.close()?;
-            }
+            log_attribute_values("values", &attr.attr_type.0, &attr.attr_vals, redact, js)?;
             js.close()?;
         }
         js.close()?;
@@ -314,6 +750,7 @@ fn log_intermediate_response(
     }
     if let Some(value) = &msg.response_value {
         js.set_string_from_bytes("value", &value[..])?;
+        js.set_uint("value_length", value.len() as u64)?;
     }
     js.close()?;
     Ok(())
@@ -352,5 +789,383 @@ pub unsafe extern "C" fn SCLdapLoggerLog(
     tx: *mut std::os::raw::c_void, js: &mut JsonBuilder,
 ) -> bool {
     let tx = cast_pointer!(tx, LdapTransaction);
-    log_ldap(tx, js).is_ok()
+    log_ldap(tx, &LdapFields::default(), &LDAP_REDACT_DEFAULT, js).is_ok()
+}
+
+#[no_mangle]
+pub extern "C" fn SCLdapLoggerNew(conf: *const SCConfNode) -> *mut std::os::raw::c_void {
+    let conf = ConfNode::wrap(conf);
+    let boxed = Box::new(LdapLogger::new(conf));
+    return Box::into_raw(boxed) as *mut _;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLoggerFree(logger: *mut std::os::raw::c_void) {
+    std::mem::drop(Box::from_raw(logger as *mut LdapLogger));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLoggerLogWithConfig(
+    logger: *mut std::os::raw::c_void, tx: *mut std::os::raw::c_void, js: &mut JsonBuilder,
+) -> bool {
+    let logger = cast_pointer!(logger, LdapLogger);
+    let tx = cast_pointer!(tx, LdapTransaction);
+    log_ldap(tx, &logger.fields, &logger.redact, js).is_ok()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLoggerSplitRecords(logger: *mut std::os::raw::c_void) -> bool {
+    let logger = cast_pointer!(logger, LdapLogger);
+    logger.split_records
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLogRequestEnabled(
+    logger: *mut std::os::raw::c_void, tx: *mut std::os::raw::c_void,
+) -> bool {
+    let logger = cast_pointer!(logger, LdapLogger);
+    let tx = cast_pointer!(tx, LdapTransaction);
+    logger.split_records && logger.fields.request && tx.request.is_some()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLogResponseEnabled(
+    logger: *mut std::os::raw::c_void, tx: *mut std::os::raw::c_void,
+) -> bool {
+    let logger = cast_pointer!(logger, LdapLogger);
+    let tx = cast_pointer!(tx, LdapTransaction);
+    (logger.fields.responses || logger.fields.results) && !tx.responses.is_empty()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLoggerLogRequest(
+    logger: *mut std::os::raw::c_void, tx: *mut std::os::raw::c_void, js: &mut JsonBuilder,
+) -> bool {
+    let logger = cast_pointer!(logger, LdapLogger);
+    let tx = cast_pointer!(tx, LdapTransaction);
+    log_ldap(tx, &logger.request_record(), &logger.redact, js).is_ok()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCLdapLoggerLogResponse(
+    logger: *mut std::os::raw::c_void, tx: *mut std::os::raw::c_void, js: &mut JsonBuilder,
+) -> bool {
+    let logger = cast_pointer!(logger, LdapLogger);
+    let tx = cast_pointer!(tx, LdapTransaction);
+    log_ldap(tx, &logger.response_record(), &logger.redact, js).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonbuilder::{SCJbLen, SCJbPtr};
+
+    fn attribute(attr_type: &str, value: &[u8]) -> PartialAttribute {
+        PartialAttribute {
+            attr_type: LdapString(attr_type.to_string()),
+            attr_vals: vec![AttributeValue(value.to_vec())],
+        }
+    }
+
+    fn log_to_string(tx: &LdapTransaction, redact: &[LdapRedaction]) -> String {
+        log_fields_to_string(tx, &LdapFields::default(), redact)
+    }
+
+    fn log_fields_to_string(
+        tx: &LdapTransaction, fields: &LdapFields, redact: &[LdapRedaction],
+    ) -> String {
+        let mut js = JsonBuilder::try_new_object().unwrap();
+        log_ldap(tx, fields, redact, &mut js).unwrap();
+        js.close().unwrap();
+        let buf = unsafe { std::slice::from_raw_parts(SCJbPtr(&mut js), SCJbLen(&js)) };
+        String::from_utf8(buf.to_vec()).unwrap()
+    }
+
+    fn sensitive_tx() -> LdapTransaction {
+        let mut tx = LdapTransaction::new();
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::AddRequest(AddRequest {
+                entry: LdapDN("cn=svc,dc=example,dc=com".to_string()),
+                attributes: vec![Attribute {
+                    attr_type: LdapString("UNICODEPWD".to_string()),
+                    attr_vals: vec![AttributeValue(b"add-secret".to_vec())],
+                }],
+            }),
+            controls: None,
+        });
+        tx.responses.push_back(LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::SearchResultEntry(SearchResultEntry {
+                object_name: LdapDN("cn=svc,dc=example,dc=com".to_string()),
+                attributes: vec![
+                    attribute("userPassword;binary", b"entry-secret"),
+                    attribute("msDS-KeyCredentialLink", b"key-material"),
+                    attribute("description", b"service account"),
+                ],
+            }),
+            controls: None,
+        });
+        tx
+    }
+
+    #[test]
+    fn test_ldap_parse_redaction() {
+        let r = parse_redaction("unicodePwd").unwrap();
+        assert_eq!(r.attribute, "unicodePwd");
+        assert_eq!(r.action, LdapRedactAction::Drop);
+        let r = parse_redaction("userPassword: hash").unwrap();
+        assert_eq!(r.attribute, "userPassword");
+        assert_eq!(r.action, LdapRedactAction::Hash);
+        let r = parse_redaction("msDS-KeyCredentialLink:truncate:8").unwrap();
+        assert_eq!(r.action, LdapRedactAction::Truncate(8));
+
+        assert!(parse_redaction("").is_none());
+        assert!(parse_redaction(":hash").is_none());
+        assert!(parse_redaction("userPassword:encrypt").is_none());
+        assert!(parse_redaction("userPassword:truncate:x").is_none());
+    }
+
+    #[test]
+    fn test_ldap_log_redact_default() {
+        let tx = sensitive_tx();
+        let out = log_to_string(&tx, &LDAP_REDACT_DEFAULT);
+        assert!(!out.contains("add-secret"), "{}", out);
+        assert!(!out.contains("entry-secret"), "{}", out);
+        assert!(!out.contains("key-material"), "{}", out);
+        // hashed values can still be correlated
+        let hash = format!("{:x}", Sha256::digest(b"key-material"));
+        assert!(out.contains(&hash), "{}", out);
+        // the attributes themselves are still logged
+        assert!(out.contains(r#""name":"UNICODEPWD""#), "{}", out);
+        assert!(out.contains(r#""type":"userPassword;binary""#), "{}", out);
+        assert!(out.contains("service account"), "{}", out);
+    }
+
+    #[test]
+    fn test_ldap_log_redact_configured() {
+        let tx = sensitive_tx();
+        let redact = vec![
+            parse_redaction("userpassword:truncate:5").unwrap(),
+            parse_redaction("description:hash").unwrap(),
+        ];
+        let out = log_to_string(&tx, &redact);
+        assert!(out.contains(r#""values":["entry"]"#), "{}", out);
+        assert!(!out.contains("entry-secret"), "{}", out);
+        assert!(!out.contains("service account"), "{}", out);
+        // not in the configured list
+        assert!(out.contains("add-secret"), "{}", out);
+        assert!(out.contains("key-material"), "{}", out);
+
+        // an empty list disables redaction
+        let out = log_to_string(&tx, &[]);
+        assert!(out.contains("entry-secret"), "{}", out);
+    }
+
+    fn filter_to_string(filter: &Filter, redact: &[LdapRedaction]) -> String {
+        let mut out = String::new();
+        push_filter(&mut out, filter, redact);
+        out
+    }
+
+    fn equality(attr: &str, value: &[u8]) -> Filter {
+        Filter::EqualityMatch(AttributeValueAssertion {
+            attribute_desc: LdapString(attr.to_string()),
+            assertion_value: value.to_vec(),
+        })
+    }
+
+    #[test]
+    fn test_ldap_filter_string() {
+        let filter = Filter::And(vec![
+            Filter::Present(LdapString("objectClass".to_string())),
+            Filter::Not(Box::new(equality("cn", b"a*b(c)\\"))),
+            Filter::Or(vec![
+                Filter::Substrings(SubstringFilter {
+                    filter_type: LdapString("sn".to_string()),
+                    substrings: vec![
+                        Substring::Initial(AssertionValue(b"ad".to_vec())),
+                        Substring::Any(AssertionValue(b"mi".to_vec())),
+                    ],
+                }),
+                Filter::GreaterOrEqual(AttributeValueAssertion {
+                    attribute_desc: LdapString("uidNumber".to_string()),
+                    assertion_value: b"1000".to_vec(),
+                }),
+            ]),
+            Filter::ExtensibleMatch(MatchingRuleAssertion {
+                matching_rule: Some(LdapString("1.2.840.113556.1.4.803".to_string())),
+                rule_type: Some(AttributeDescription("userAccountControl".to_string())),
+                assertion_value: AssertionValue(b"2".to_vec()),
+                dn_attributes: None,
+            }),
+        ]);
+        assert_eq!(
+            filter_to_string(&filter, &[]),
+            "(&(objectClass=*)(!(cn=a\\2ab\\28c\\29\\5c))(|(sn=ad*mi*)(uidNumber>=1000))\
+             (userAccountControl:1.2.840.113556.1.4.803:=2))"
+        );
+
+        let filter = Filter::Substrings(SubstringFilter {
+            filter_type: LdapString("cn".to_string()),
+            substrings: vec![Substring::Final(AssertionValue(b"\xc3\xa9\0".to_vec()))],
+        });
+        assert_eq!(filter_to_string(&filter, &[]), "(cn=*\\c3\\a9\\00)");
+
+        let filter = Filter::Or(vec![
+            equality("userPassword", b"secret"),
+            equality("msDS-KeyCredentialLink", b"key-material"),
+        ]);
+        let hash = format!("{:x}", Sha256::digest(b"key-material"));
+        assert_eq!(
+            filter_to_string(&filter, &LDAP_REDACT_DEFAULT),
+            format!(
+                "(|(userPassword=<redacted>)(msDS-KeyCredentialLink={}))",
+                hash
+            )
+        );
+    }
+
+    #[test]
+    fn test_ldap_log_request_summary() {
+        let mut tx = LdapTransaction::new();
+        tx.request = Some(LdapMessage {
+            message_id: MessageID(3),
+            protocol_op: ProtocolOp::SearchRequest(SearchRequest {
+                base_object: LdapDN("dc=example,dc=com".to_string()),
+                scope: SearchScope(2),
+                deref_aliases: DerefAliases(0),
+                size_limit: 0,
+                time_limit: 0,
+                types_only: false,
+                filter: equality("userPassword", b"guess"),
+                attributes: Vec::new(),
+            }),
+            controls: None,
+        });
+        tx.responses.push_back(LdapMessage {
+            message_id: MessageID(3),
+            protocol_op: ProtocolOp::SearchResultDone(LdapResult {
+                result_code: ResultCode(0),
+                matched_dn: LdapDN(String::new()),
+                diagnostic_message: LdapString(String::new()),
+                referrals: Vec::new(),
+            }),
+            controls: None,
+        });
+
+        let mut fields = LdapFields {
+            request: false,
+            ..LdapFields::default()
+        };
+        let out = log_fields_to_string(&tx, &fields, &LDAP_REDACT_DEFAULT);
+        assert!(!out.contains("request"), "{}", out);
+
+        fields.request_summary = true;
+        let out = log_fields_to_string(&tx, &fields, &LDAP_REDACT_DEFAULT);
+        assert!(
+            out.contains(
+                r#""request_summary":{"message_id":3,"operation":"search_request","operation_code":3,"dn":"dc=example,dc=com","filter":"(userPassword=<redacted>)"}"#
+            ),
+            "{}",
+            out
+        );
+        assert!(out.contains(r#""responses":"#), "{}", out);
+
+        // the full request already has all of it
+        fields.request = true;
+        let out = log_fields_to_string(&tx, &fields, &LDAP_REDACT_DEFAULT);
+        assert!(!out.contains("request_summary"), "{}", out);
+    }
+
+    #[test]
+    fn test_ldap_log_server_initiated() {
+        let mut tx = sensitive_tx();
+        let out = log_to_string(&tx, &LDAP_REDACT_DEFAULT);
+        assert!(!out.contains("server_initiated"), "{}", out);
+        tx.flags |= LDAP_TX_FLAG_SERVER_INITIATED;
+        let out = log_to_string(&tx, &LDAP_REDACT_DEFAULT);
+        assert!(out.contains(r#""server_initiated":true"#), "{}", out);
+    }
+
+    #[test]
+    fn test_ldap_log_references() {
+        let mut tx = sensitive_tx();
+        let out = log_to_string(&tx, &LDAP_REDACT_DEFAULT);
+        assert!(!out.contains("references"), "{}", out);
+        assert!(!out.contains("referrals"), "{}", out);
+
+        tx.responses.push_back(LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::SearchResultReference(vec![
+                LdapString("ldap://dc1.example.com/dc=example,dc=com".to_string()),
+                LdapString("ldap://dc2.example.com/dc=example,dc=com".to_string()),
+            ]),
+            controls: None,
+        });
+        tx.responses.push_back(LdapMessage {
+            message_id: MessageID(2),
+            protocol_op: ProtocolOp::SearchResultDone(LdapResult {
+                result_code: ResultCode(LdapResultCode::Referral as u32),
+                matched_dn: LdapDN(String::new()),
+                diagnostic_message: LdapString(String::new()),
+                referrals: vec!["ldap://dc3.example.com/".to_string()],
+            }),
+            controls: None,
+        });
+        let out = log_to_string(&tx, &LDAP_REDACT_DEFAULT);
+        assert!(
+            out.contains(concat!(
+                r#"{"operation":"search_result_reference","operation_code":19,"#,
+                r#""search":{"references":["ldap://dc1.example.com/dc=example,dc=com","#,
+                r#""ldap://dc2.example.com/dc=example,dc=com"]}}"#
+            )),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains(r#""referrals":["ldap://dc3.example.com/"]}]"#),
+            "{}",
+            out
+        );
+        // references and referrals are only logged in the responses
+        assert!(!out.contains("search_references"), "{}", out);
+        assert!(!out.contains(r#"}],"referrals""#), "{}", out);
+    }
+
+    #[test]
+    fn test_ldap_log_split_records() {
+        let mut logger = LdapLogger {
+            split_records: true,
+            ..LdapLogger::default()
+        };
+        let mut tx = sensitive_tx();
+        let txp = &mut tx as *mut LdapTransaction as *mut std::os::raw::c_void;
+        let loggerp = &mut logger as *mut LdapLogger as *mut std::os::raw::c_void;
+        assert!(unsafe { SCLdapLogRequestEnabled(loggerp, txp) });
+        assert!(unsafe { SCLdapLogResponseEnabled(loggerp, txp) });
+
+        let out = log_fields_to_string(&tx, &logger.request_record(), &logger.redact);
+        assert!(
+            out.contains(r#""request":{"message_id":2,"operation":"add_request""#),
+            "{}",
+            out
+        );
+        assert!(!out.contains("responses"), "{}", out);
+
+        // the responses carry the message id as the request is not there
+        let out = log_fields_to_string(&tx, &logger.response_record(), &logger.redact);
+        assert!(!out.contains(r#""request""#), "{}", out);
+        assert!(
+            out.contains(
+                r#""responses":[{"operation":"search_result_entry","operation_code":4,"message_id":2,"#
+            ),
+            "{}",
+            out
+        );
+
+        logger.split_records = false;
+        let loggerp = &mut logger as *mut LdapLogger as *mut std::os::raw::c_void;
+        assert!(!unsafe { SCLdapLogRequestEnabled(loggerp, txp) });
+    }
 }
@@ -21,7 +21,7 @@ use std::convert::From;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
-use asn1_rs::{FromBer, ParseResult};
+use asn1_rs::{Any, Class, FromBer, ParseResult, Tag};
 use ldap_parser::error::LdapError;
 
 use crate::ldap::filters::*;
@@ -92,6 +92,7 @@ pub struct LdapResult {
     pub result_code: ResultCode,
     pub matched_dn: LdapDN,
     pub diagnostic_message: LdapString,
+    pub referrals: Vec<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -355,6 +356,21 @@ impl ProtocolOp {
             ProtocolOp::IntermediateResponse(_) => 25,
         }
     }
+
+    /// Operation ending the response to this request, if any is expected.
+    pub fn terminal_response(&self) -> Option<ProtocolOpCode> {
+        match self {
+            ProtocolOp::BindRequest(_) => Some(ProtocolOpCode::BindResponse),
+            ProtocolOp::SearchRequest(_) => Some(ProtocolOpCode::SearchResultDone),
+            ProtocolOp::ModifyRequest(_) => Some(ProtocolOpCode::ModifyResponse),
+            ProtocolOp::AddRequest(_) => Some(ProtocolOpCode::AddResponse),
+            ProtocolOp::DelRequest(_) => Some(ProtocolOpCode::DelResponse),
+            ProtocolOp::ModDnRequest(_) => Some(ProtocolOpCode::ModDnResponse),
+            ProtocolOp::CompareRequest(_) => Some(ProtocolOpCode::CompareResponse),
+            ProtocolOp::ExtendedRequest(_) => Some(ProtocolOpCode::ExtendedResponse),
+            _ => None,
+        }
+    }
 }
 
 impl Display for ProtocolOp {
@@ -479,6 +495,70 @@ impl LdapMessage {
         return !self.is_request();
     }
 
+    /// Return the DN the request operates on, if it has one
+    pub fn request_dn(&self) -> Option<&str> {
+        let dn = match &self.protocol_op {
+            ProtocolOp::BindRequest(req) => req.name.0.as_str(),
+            ProtocolOp::AddRequest(req) => req.entry.0.as_str(),
+            ProtocolOp::SearchRequest(req) => req.base_object.0.as_str(),
+            ProtocolOp::ModifyRequest(req) => req.object.0.as_str(),
+            ProtocolOp::DelRequest(req) => req.0.as_str(),
+            ProtocolOp::ModDnRequest(req) => req.entry.0.as_str(),
+            ProtocolOp::CompareRequest(req) => req.entry.0.as_str(),
+            _ => return None,
+        };
+        Some(dn)
+    }
+
+    /// Return the LDAPResult of the response, if it has one
+    pub fn result(&self) -> Option<&LdapResult> {
+        match &self.protocol_op {
+            ProtocolOp::BindResponse(resp) => Some(&resp.result),
+            ProtocolOp::ModifyResponse(resp) => Some(&resp.result),
+            ProtocolOp::ExtendedResponse(resp) => Some(&resp.result),
+            ProtocolOp::SearchResultDone(result)
+            | ProtocolOp::AddResponse(result)
+            | ProtocolOp::DelResponse(result)
+            | ProtocolOp::ModDnResponse(result)
+            | ProtocolOp::CompareResponse(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    fn result_mut(&mut self) -> Option<&mut LdapResult> {
+        match &mut self.protocol_op {
+            ProtocolOp::BindResponse(resp) => Some(&mut resp.result),
+            ProtocolOp::ModifyResponse(resp) => Some(&mut resp.result),
+            ProtocolOp::ExtendedResponse(resp) => Some(&mut resp.result),
+            ProtocolOp::SearchResultDone(result)
+            | ProtocolOp::AddResponse(result)
+            | ProtocolOp::DelResponse(result)
+            | ProtocolOp::ModDnResponse(result)
+            | ProtocolOp::CompareResponse(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// ldap-parser skips the referral field of LDAPResult, so it is
+    /// decoded here from the raw message.
+    pub fn set_referrals(&mut self, raw: &[u8]) {
+        if let Some(result) = self.result_mut() {
+            if let Some(referrals) = ldap_parse_referrals(raw) {
+                result.referrals = referrals;
+            }
+        }
+    }
+
+    /// True if one of the referral URIs has no parsable host
+    pub fn has_malformed_referral(&self) -> bool {
+        self.result().map_or(false, |result| {
+            result
+                .referrals
+                .iter()
+                .any(|uri| referral_host(uri).is_none())
+        })
+    }
+
     fn from_bind_request(msg: ldap_parser::ldap::BindRequest) -> ProtocolOp {
         let authentication = match msg.authentication {
             ldap_parser::ldap::AuthenticationChoice::Simple(val) => {
@@ -504,6 +584,7 @@ impl LdapMessage {
                 result_code: ResultCode(msg.result.result_code.0),
                 matched_dn: LdapDN(msg.result.matched_dn.0.to_string()),
                 diagnostic_message: LdapString(msg.result.diagnostic_message.0.to_string()),
+                referrals: Vec::new(),
             },
             server_sasl_creds: msg
                 .server_sasl_creds
@@ -542,6 +623,7 @@ impl LdapMessage {
             result_code: ResultCode(msg.result_code.0),
             matched_dn: LdapDN(msg.matched_dn.0.to_string()),
             diagnostic_message: LdapString(msg.diagnostic_message.0.to_string()),
+            referrals: Vec::new(),
         })
     }
 
@@ -571,6 +653,7 @@ impl LdapMessage {
                 result_code: ResultCode(msg.result.result_code.0),
                 matched_dn: LdapDN(msg.result.matched_dn.0.to_string()),
                 diagnostic_message: LdapString(msg.result.diagnostic_message.0.to_string()),
+                referrals: Vec::new(),
             },
         })
     }
@@ -588,6 +671,7 @@ impl LdapMessage {
             result_code: ResultCode(msg.result_code.0),
             matched_dn: LdapDN(msg.matched_dn.0.to_string()),
             diagnostic_message: LdapString(msg.diagnostic_message.0.to_string()),
+            referrals: Vec::new(),
         })
     }
 
@@ -600,6 +684,7 @@ impl LdapMessage {
             result_code: ResultCode(msg.result_code.0),
             matched_dn: LdapDN(msg.matched_dn.0.to_string()),
             diagnostic_message: LdapString(msg.diagnostic_message.0.to_string()),
+            referrals: Vec::new(),
         })
     }
 
@@ -621,6 +706,7 @@ impl LdapMessage {
             result_code: ResultCode(msg.result_code.0),
             matched_dn: LdapDN(msg.matched_dn.0.to_string()),
             diagnostic_message: LdapString(msg.diagnostic_message.0.to_string()),
+            referrals: Vec::new(),
         })
     }
 
@@ -636,6 +722,7 @@ impl LdapMessage {
             result_code: ResultCode(msg.result_code.0),
             matched_dn: LdapDN(msg.matched_dn.0.to_string()),
             diagnostic_message: LdapString(msg.diagnostic_message.0.to_string()),
+            referrals: Vec::new(),
         })
     }
 
@@ -658,6 +745,7 @@ impl LdapMessage {
                 result_code: ResultCode(msg.result.result_code.0),
                 matched_dn: LdapDN(msg.result.matched_dn.0.to_string()),
                 diagnostic_message: LdapString(msg.result.diagnostic_message.0.to_string()),
+                referrals: Vec::new(),
             },
             response_name: msg
                 .response_name
@@ -680,6 +768,264 @@ impl LdapMessage {
     }
 }
 
+pub fn get_ldap_result_code(response: &LdapMessage) -> Option<u32> {
+    response.result().map(|result| result.result_code.0)
+}
+
+/// Whether a result code reports an error. Besides success, the compare
+/// results, referral and saslBindInProgress do not (RFC 4511, Appendix A.1).
+pub fn is_ldap_error_code(code: u32) -> bool {
+    !(code == LdapResultCode::Success as u32
+        || code == LdapResultCode::CompareFalse as u32
+        || code == LdapResultCode::CompareTrue as u32
+        || code == LdapResultCode::Referral as u32
+        || code == LdapResultCode::SaslBindInProgress as u32)
+}
+
+/// Severity of a result code, from 0 for success to 6 for operationsError,
+/// to find out how an operation ultimately failed over all its responses:
+///
+/// - 0: success, compareFalse, compareTrue
+/// - 1: referral, saslBindInProgress
+/// - 2: timeLimitExceeded, sizeLimitExceeded, adminLimitExceeded
+/// - 3: attribute, name, update and control errors
+/// - 4: security errors, like invalidCredentials or insufficientAccessRights
+/// - 5: service errors, like busy or unavailable, protocolError, other and
+///   the codes not defined by RFC 4511
+/// - 6: operationsError
+pub fn ldap_result_code_severity(code: u32) -> u8 {
+    match code {
+        0 | 5 | 6 => 0,
+        10 | 14 => 1,
+        3 | 4 | 11 => 2,
+        12 | 16..=21 | 32..=36 | 60 | 61 | 64..=71 | 76 => 3,
+        7 | 8 | 13 | 48..=50 => 4,
+        1 => 6,
+        _ => 5,
+    }
+}
+
 pub fn ldap_parse_msg(input: &[u8]) -> ParseResult<ldap_parser::ldap::LdapMessage, LdapError> {
     ldap_parser::ldap::LdapMessage::from_ber(input)
 }
+
+/// Walk the raw LDAPMessage down to the optional referral field
+/// (`[3] SEQUENCE OF URI`) that follows the diagnostic message.
+fn ldap_parse_referrals(input: &[u8]) -> Option<Vec<String>> {
+    let (_, msg) = Any::from_ber(input).ok()?;
+    let (rem, _message_id) = Any::from_ber(msg.data).ok()?;
+    let (_, protocol_op) = Any::from_ber(rem).ok()?;
+    let (rem, _result_code) = Any::from_ber(protocol_op.data).ok()?;
+    let (rem, _matched_dn) = Any::from_ber(rem).ok()?;
+    let (rem, _diagnostic_message) = Any::from_ber(rem).ok()?;
+    if rem.is_empty() {
+        return None;
+    }
+    let (_, referral) = Any::from_ber(rem).ok()?;
+    if referral.header.class() != Class::ContextSpecific || referral.header.tag() != Tag(3) {
+        return None;
+    }
+    let mut referrals = Vec::new();
+    let mut i = referral.data;
+    while !i.is_empty() {
+        let (rem, uri) = Any::from_ber(i).ok()?;
+        referrals.push(String::from_utf8_lossy(uri.data).to_string());
+        i = rem;
+    }
+    Some(referrals)
+}
+
+/// Return the host of an LDAP URL (RFC 4516), `ldap://host:port/dn?...`.
+///
+/// The host may be empty, as in `ldap:///dc=example,dc=com`. None is
+/// returned if the URI is malformed.
+pub fn referral_host(uri: &str) -> Option<&str> {
+    let (scheme, rest) = uri.split_once("://")?;
+    if scheme.is_empty()
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+    {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let hostport = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let (host, port) = if let Some(ipv6) = hostport.strip_prefix('[') {
+        let (host, rest) = ipv6.split_once(']')?;
+        if rest.is_empty() {
+            (host, None)
+        } else {
+            (host, Some(rest.strip_prefix(':')?))
+        }
+    } else if let Some((host, port)) = hostport.split_once(':') {
+        (host, Some(port))
+    } else {
+        (hostport, None)
+    };
+    if let Some(port) = port {
+        if !port.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+    if host
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || c == '[' || c == ']')
+    {
+        return None;
+    }
+    Some(host)
+}
+
+/// Return the attribute type of the leftmost RDN of a DN (RFC 4514),
+/// e.g. `cn` for `cn=admin,ou=People,dc=example,dc=com`.
+///
+/// None is returned for an empty DN or if the first RDN has no type.
+pub fn dn_rdn_attribute(dn: &str) -> Option<&str> {
+    let (attr, _) = dn.split_once('=')?;
+    let attr = attr.trim();
+    if attr.is_empty() || attr.contains([',', '+', ';']) {
+        return None;
+    }
+    Some(attr)
+}
+
+/// Check if an attribute description has a `range=` option, as returned
+/// by Active Directory for ranged retrieval of multi-valued attributes,
+/// e.g. `member;range=0-1499`. Options are case-insensitive.
+pub fn attribute_has_range_option(desc: &str) -> bool {
+    desc.split(';').skip(1).any(|option| {
+        option
+            .as_bytes()
+            .get(..6)
+            .map_or(false, |name| name.eq_ignore_ascii_case(b"range="))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_has_range_option() {
+        assert!(attribute_has_range_option("member;range=0-1499"));
+        assert!(attribute_has_range_option("member;Range=1500-*"));
+        assert!(attribute_has_range_option("member;binary;range=0-*"));
+        assert!(!attribute_has_range_option("member"));
+        assert!(!attribute_has_range_option("member;binary"));
+        assert!(!attribute_has_range_option("range=0-1499"));
+        assert!(!attribute_has_range_option("member;rangefoo"));
+    }
+
+    #[test]
+    fn test_dn_rdn_attribute() {
+        assert_eq!(
+            dn_rdn_attribute("cn=admin,ou=People,dc=example,dc=com"),
+            Some("cn")
+        );
+        assert_eq!(dn_rdn_attribute("uid=jdoe+cn=John,dc=com"), Some("uid"));
+        assert_eq!(dn_rdn_attribute(" OU = x"), Some("OU"));
+        assert_eq!(dn_rdn_attribute("2.5.4.3=admin"), Some("2.5.4.3"));
+        assert_eq!(dn_rdn_attribute(""), None);
+        assert_eq!(dn_rdn_attribute("admin"), None);
+        assert_eq!(dn_rdn_attribute("=admin"), None);
+        assert_eq!(dn_rdn_attribute("admin,cn=x"), None);
+    }
+
+    #[test]
+    fn test_ldap_result_code_severity() {
+        use LdapResultCode::*;
+        let table = [
+            (Success, 0),
+            (CompareFalse, 0),
+            (CompareTrue, 0),
+            (Referral, 1),
+            (SaslBindInProgress, 1),
+            (TimeLimitExceeded, 2),
+            (SizeLimitExceeded, 2),
+            (AdminLimitExceeded, 2),
+            (UnavailableCriticalExtension, 3),
+            (NoSuchAttribute, 3),
+            (InvalidAttributeSyntax, 3),
+            (NoSuchObject, 3),
+            (AliasDereferencingProblem, 3),
+            (SortControlMissing, 3),
+            (OffsetRangeError, 3),
+            (NamingViolation, 3),
+            (AffectsMultipleDsas, 3),
+            (ControlError, 3),
+            (AuthMethodNotSupported, 4),
+            (StrongerAuthRequired, 4),
+            (ConfidentialityRequired, 4),
+            (InappropriateAuthentication, 4),
+            (InvalidCredentials, 4),
+            (InsufficientAccessRights, 4),
+            (ProtocolError, 5),
+            (Busy, 5),
+            (Unavailable, 5),
+            (UnwillingToPerform, 5),
+            (LoopDetect, 5),
+            (Other, 5),
+            (ServerDown, 5),
+            (OperationsError, 6),
+        ];
+        for (code, severity) in table {
+            assert_eq!(
+                ldap_result_code_severity(code.clone() as u32),
+                severity,
+                "{:?}",
+                code
+            );
+        }
+        // codes not defined by RFC 4511
+        assert_eq!(ldap_result_code_severity(9), 5);
+        assert_eq!(ldap_result_code_severity(1000), 5);
+    }
+
+    #[test]
+    fn test_referral_host() {
+        assert_eq!(
+            referral_host("ldap://dc2.example.com/dc=example,dc=com"),
+            Some("dc2.example.com")
+        );
+        assert_eq!(
+            referral_host("ldaps://evil.example:636"),
+            Some("evil.example")
+        );
+        assert_eq!(referral_host("ldap://user@10.0.0.1:389/"), Some("10.0.0.1"));
+        assert_eq!(
+            referral_host("ldap://[2001:db8::1]:389/"),
+            Some("2001:db8::1")
+        );
+        assert_eq!(referral_host("ldap:///dc=example,dc=com"), Some(""));
+        assert_eq!(referral_host("dc2.example.com"), None);
+        assert_eq!(referral_host("ldap://host:port/"), None);
+        assert_eq!(referral_host("ldap://[2001:db8::1/"), None);
+        assert_eq!(referral_host("ldap://bad host/"), None);
+    }
+
+    #[test]
+    fn test_parse_referrals() {
+        // searchResDone, result code referral, with two referral URIs
+        let buf = [
+            0x30, 0x39, 0x02, 0x01, 0x02, 0x65, 0x34, 0x0a, 0x01, 0x0a, 0x04, 0x00, 0x04, 0x00,
+            0xa3, 0x2b, 0x04, 0x14, b'l', b'd', b'a', b'p', b':', b'/', b'/', b'e', b'v', b'i',
+            b'l', b'.', b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'/', 0x04, 0x13, b'l', b'd',
+            b'a', b'p', b':', b'/', b'/', b'd', b'c', b'2', b'.', b'e', b'x', b'a', b'm', b'p',
+            b'l', b'e', b'/',
+        ];
+        let (rem, msg) = ldap_parse_msg(&buf).unwrap();
+        assert!(rem.is_empty());
+        let mut msg = LdapMessage::from(msg);
+        msg.set_referrals(&buf);
+        let result = msg.result().unwrap();
+        assert_eq!(
+            result.result_code,
+            ResultCode(LdapResultCode::Referral as u32)
+        );
+        assert_eq!(
+            result.referrals,
+            vec!["ldap://evil.example/", "ldap://dc2.example/"]
+        );
+        assert!(!msg.has_malformed_referral());
+    }
+}
@@ -87,10 +87,13 @@ pub mod conf;
 pub mod jsonbuilder;
 #[macro_use]
 pub mod applayer;
+#[macro_use]
+pub mod applayerstats;
 pub mod frames;
 pub mod filecontainer;
 pub mod filetracker;
 pub mod kerberos;
+#[macro_use]
 pub mod detect;
 pub mod utils;
 
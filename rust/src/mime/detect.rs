@@ -61,6 +61,38 @@ pub unsafe extern "C" fn SCDetectMimeEmailGetUrl(
     return 0;
 }
 
+unsafe fn mime_email_get_array_item(
+    array: &[Vec<u8>], buffer: *mut *const u8, buffer_len: *mut u32, idx: u32,
+) -> u8 {
+    if let Some(item) = array.get(idx as usize) {
+        *buffer = item.as_ptr();
+        *buffer_len = item.len() as u32;
+        return 1;
+    }
+
+    *buffer = ptr::null();
+    *buffer_len = 0;
+
+    return 0;
+}
+
+/// Attachment file names, decoded from RFC 2231, as soon as the headers of each part are parsed.
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectMimeEmailGetAttachmentFilename(
+    ctx: &MimeStateSMTP, buffer: *mut *const u8, buffer_len: *mut u32, idx: u32,
+) -> u8 {
+    return mime_email_get_array_item(&ctx.attachment_filenames, buffer, buffer_len, idx);
+}
+
+/// Content types of the attachments, as soon as the headers of each part are parsed.
+/// The index is the one of the file name, with an empty buffer for a part without content type.
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectMimeEmailGetAttachmentContentType(
+    ctx: &MimeStateSMTP, buffer: *mut *const u8, buffer_len: *mut u32, idx: u32,
+) -> u8 {
+    return mime_email_get_array_item(&ctx.attachment_content_types, buffer, buffer_len, idx);
+}
+
 /// Intermediary function used in detect-email.c to access data from the MimeStateSMTP structure
 /// for array header fields.
 /// The hname parameter determines which data will be returned.
@@ -151,6 +151,84 @@ pub fn mime_find_header_token<'a>(
     }
 }
 
+/// Percent-decodes a RFC 2231 extended parameter value. The first section
+/// starts with `charset'language'`, which is skipped: the value is not
+/// converted from its charset.
+fn mime_decode_rfc2231_value(value: &[u8], first: bool) -> Option<Vec<u8>> {
+    let value = if first {
+        let mut parts = value.splitn(3, |&c| c == b'\'');
+        let _charset = parts.next()?;
+        let _language = parts.next()?;
+        parts.next()?
+    } else {
+        value
+    };
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut i = 0;
+    while i < value.len() {
+        if value[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            if !hex.iter().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let hex = std::str::from_utf8(hex).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(value[i]);
+            i += 1;
+        }
+    }
+    return Some(decoded);
+}
+
+/// Looks for a parameter of a header, like mime_find_header_token, also
+/// decoding the RFC 2231 extended values (`filename*=utf-8''%e2%82%ac.pdf`)
+/// and sections (`filename*0*=...; filename*1=...`). A value with a
+/// malformed encoding is returned as it is.
+pub fn mime_find_header_param(header: &[u8], param: &[u8]) -> Option<Vec<u8>> {
+    let (_rem, t) = mime_parse_header_tokens(header).ok()?;
+    if let Some(value) = t.tokens.get(param) {
+        return Some(value.to_vec());
+    }
+    let mut name = param.to_vec();
+    name.push(b'*');
+    if let Some(value) = t.tokens.get(&name[..]) {
+        return Some(mime_decode_rfc2231_value(value, true).unwrap_or_else(|| value.to_vec()));
+    }
+
+    let mut raw = Vec::new();
+    let mut decoded = Some(Vec::new());
+    let mut section = 0;
+    loop {
+        name.truncate(param.len() + 1);
+        name.extend_from_slice(section.to_string().as_bytes());
+        let (value, extended) = if let Some(value) = t.tokens.get(&name[..]) {
+            (value, false)
+        } else {
+            name.push(b'*');
+            match t.tokens.get(&name[..]) {
+                Some(value) => (value, true),
+                None => break,
+            }
+        };
+        raw.extend_from_slice(value);
+        decoded = decoded.and_then(|mut d| {
+            if extended {
+                d.extend(mime_decode_rfc2231_value(value, section == 0)?);
+            } else {
+                d.extend_from_slice(value);
+            }
+            Some(d)
+        });
+        section += 1;
+    }
+    if section == 0 {
+        return None;
+    }
+    return Some(decoded.unwrap_or(raw));
+}
+
 pub(crate) const RS_MIME_MAX_TOKEN_LEN: usize = 255;
 
 #[derive(Debug)]
@@ -566,4 +644,47 @@ mod test {
         assert_eq!(multi, Some("123456".as_bytes()));
         outvec.clear();
     }
+
+    #[test]
+    fn test_mime_find_header_param() {
+        assert_eq!(
+            mime_find_header_param(b"attachment; filename=\"test.pdf\"", b"filename"),
+            Some(b"test.pdf".to_vec())
+        );
+        assert_eq!(
+            mime_find_header_param(
+                b"attachment; filename*=utf-8''%e2%82%ac%20rates.txt",
+                b"filename"
+            ),
+            Some("\u{20ac} rates.txt".as_bytes().to_vec())
+        );
+        assert_eq!(
+            mime_find_header_param(
+                b"attachment; filename*0*=us-ascii'en'This%20is; filename*1=\" a \"; \
+                  filename*2*=file%2Eexe",
+                b"filename"
+            ),
+            Some(b"This is a file.exe".to_vec())
+        );
+        assert_eq!(
+            mime_find_header_param(b"attachment; filename*0=abc; filename*1=def", b"filename"),
+            Some(b"abcdef".to_vec())
+        );
+        // malformed encodings are kept as they are
+        assert_eq!(
+            mime_find_header_param(b"attachment; filename*=bad%zz.exe", b"filename"),
+            Some(b"bad%zz.exe".to_vec())
+        );
+        assert_eq!(
+            mime_find_header_param(
+                b"attachment; filename*0*=''ok%20; filename*1*=bad%4",
+                b"filename"
+            ),
+            Some(b"''ok%20bad%4".to_vec())
+        );
+        assert_eq!(
+            mime_find_header_param(b"attachment; size=12", b"filename"),
+            None
+        );
+    }
 }
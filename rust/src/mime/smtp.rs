@@ -78,6 +78,9 @@ pub struct MimeStateSMTP<'a> {
     pub(crate) main_headers_nb: usize,
     filename: Vec<u8>,
     pub(crate) attachments: Vec<Vec<u8>>,
+    pub(crate) attachment_filenames: Vec<Vec<u8>>,
+    // one per attachment file name, empty for a part without content type
+    pub(crate) attachment_content_types: Vec<Vec<u8>>,
     pub(crate) urls: Vec<Vec<u8>>,
     boundaries: Vec<Vec<u8>>,
     encoding: MimeSmtpEncoding,
@@ -104,6 +107,8 @@ pub fn mime_smtp_state_init(
         main_headers_nb: 0,
         filename: Vec::new(),
         attachments: Vec::new(),
+        attachment_filenames: Vec::new(),
+        attachment_content_types: Vec::new(),
         urls: Vec::new(),
         boundaries: Vec::new(),
         decoded_line: Vec::new(),
@@ -176,8 +181,16 @@ fn mime_smtp_process_headers(ctx: &mut MimeStateSMTP) -> (u32, bool) {
     let mut sections_values = Vec::new();
     let mut warnings = 0;
     let mut encap = false;
+    // decoded attachment metadata for detection, set as soon as the headers
+    // of the part are complete
+    let mut part_filename = None;
+    let mut part_name = None;
+    let mut part_content_type = None;
     for h in &ctx.headers[ctx.main_headers_nb..] {
         if mime::slice_equals_lowercase(&h.name, b"content-disposition") {
+            if let Some(value) = mime::mime_find_header_param(&h.value, b"filename") {
+                part_filename = Some(value);
+            }
             if ctx.filename.is_empty() {
                 if let Some(value) =
                     mime::mime_find_header_token(&h.value, b"filename", &mut sections_values)
@@ -203,6 +216,9 @@ fn mime_smtp_process_headers(ctx: &mut MimeStateSMTP) -> (u32, bool) {
                 ctx.encoding = MimeSmtpEncoding::QuotedPrintable;
             }
         } else if mime::slice_equals_lowercase(&h.name, b"content-type") {
+            if let Some(value) = mime::mime_find_header_param(&h.value, b"name") {
+                part_name = Some(value);
+            }
             if ctx.filename.is_empty() {
                 if let Some(value) =
                     mime::mime_find_header_token(&h.value, b"name", &mut sections_values)
@@ -239,6 +255,7 @@ fn mime_smtp_process_headers(ctx: &mut MimeStateSMTP) -> (u32, bool) {
             } else {
                 &h.value
             };
+            part_content_type = Some(ct.to_vec());
             match ct {
                 b"text/plain" => {
                     ctx.content_type = MimeSmtpContentType::PlainText;
@@ -255,6 +272,13 @@ fn mime_smtp_process_headers(ctx: &mut MimeStateSMTP) -> (u32, bool) {
             }
         }
     }
+    if let Some(mut filename) = part_filename.or(part_name) {
+        filename.truncate(mime::RS_MIME_MAX_TOKEN_LEN);
+        ctx.attachment_filenames.push(filename);
+        // keep the indexes of both buffers in line
+        ctx.attachment_content_types
+            .push(part_content_type.unwrap_or_default());
+    }
     return (warnings, encap);
 }
 
@@ -755,3 +779,62 @@ pub unsafe extern "C" fn SCMimeSmtpConfigExtractUrlsSchemeAdd(
     }
     return -1;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process_headers(ctx: &mut MimeStateSMTP, headers: &[(&str, &str)]) {
+        for (name, value) in headers {
+            ctx.headers.push(MimeHeader {
+                name: name.as_bytes().to_vec(),
+                value: value.as_bytes().to_vec(),
+            });
+        }
+        mime_smtp_process_headers(ctx);
+        if ctx.main_headers_nb == 0 {
+            ctx.main_headers_nb = ctx.headers.len();
+        }
+        ctx.headers.truncate(ctx.main_headers_nb);
+    }
+
+    #[test]
+    fn test_mime_smtp_attachments() {
+        let mut files = FileContainer::default();
+        let mut ctx = mime_smtp_state_init(&mut files, std::ptr::null()).unwrap();
+        process_headers(
+            &mut ctx,
+            &[("Content-Type", "multipart/mixed; boundary=\"b\"")],
+        );
+        process_headers(
+            &mut ctx,
+            &[("Content-Type", "application/pdf; name=\"a.pdf\"")],
+        );
+        // no content type
+        process_headers(
+            &mut ctx,
+            &[("Content-Disposition", "attachment; filename=\"b.bin\"")],
+        );
+        // not an attachment
+        process_headers(&mut ctx, &[("Content-Type", "text/html")]);
+        process_headers(
+            &mut ctx,
+            &[
+                ("Content-Type", "text/plain"),
+                ("Content-Disposition", "attachment; filename=\"c.txt\""),
+            ],
+        );
+        assert_eq!(
+            ctx.attachment_filenames,
+            vec![b"a.pdf".to_vec(), b"b.bin".to_vec(), b"c.txt".to_vec()]
+        );
+        assert_eq!(
+            ctx.attachment_content_types,
+            vec![
+                b"application/pdf".to_vec(),
+                Vec::new(),
+                b"text/plain".to_vec()
+            ]
+        );
+    }
+}
@@ -795,6 +795,44 @@ mod tests {
         0xFF, // Exception code
     ];
 
+    const RD_HOLD_REGS_REQ_5: &[u8] = &[
+        0x00, 0x05, // Transaction ID
+        0x00, 0x00, // Protocol ID
+        0x00, 0x06, // Length
+        0x01, // Unit ID
+        0x03, // Function code
+        0x10, 0x00, // Starting Address
+        0x00, 0x02, // Quantity of Registers
+    ];
+
+    const RD_HOLD_REGS_REQ_6: &[u8] = &[
+        0x00, 0x06, // Transaction ID
+        0x00, 0x00, // Protocol ID
+        0x00, 0x06, // Length
+        0x01, // Unit ID
+        0x03, // Function code
+        0xFF, 0xF0, // Starting Address
+        0x00, 0x20, // Quantity of Registers
+    ];
+
+    const RD_HOLD_REGS_ILLEGAL_ADDR_RESP_6: &[u8] = &[
+        0x00, 0x06, // Transaction ID
+        0x00, 0x00, // Protocol ID
+        0x00, 0x03, // Length
+        0x01, // Unit ID
+        0x83, // Function code
+        0x02, // Exception code
+    ];
+
+    const RD_HOLD_REGS_ILLEGAL_ADDR_RESP_OTHER_UNIT: &[u8] = &[
+        0x00, 0x05, // Transaction ID
+        0x00, 0x00, // Protocol ID
+        0x00, 0x03, // Length
+        0x02, // Unit ID
+        0x83, // Function code
+        0x02, // Exception code
+    ];
+
     const WR_SINGLE_REG_REQ: &[u8] = &[
         0x00, 0x0A, // Transaction ID
         0x00, 0x00, // Protocol ID
@@ -1187,6 +1225,80 @@ mod tests {
         assert_eq!(msg.error_flags, ErrorFlags::EXC_CODE);
     }
 
+    #[test]
+    fn exception_illegal_data_address() {
+        let mut state = ModbusState::new();
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.parse(RD_HOLD_REGS_REQ_6, Direction::ToServer)
+        );
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.parse(RD_HOLD_REGS_ILLEGAL_ADDR_RESP_6, Direction::ToClient)
+        );
+        assert_eq!(state.transactions.len(), 1);
+
+        let tx = &state.transactions[0];
+        let msg = tx.request.as_ref().unwrap();
+        assert_eq!(msg.function.code, FunctionCode::RdHoldRegs);
+        let msg = tx.response.as_ref().unwrap();
+        assert_eq!(msg.function.raw, 0x83);
+        assert_eq!(
+            msg.data,
+            Data::Exception(Exception {
+                raw: 2,
+                code: ExceptionCode::IllegalDataAddr
+            })
+        );
+        assert!(msg.error_flags.is_empty());
+    }
+
+    #[test]
+    fn exception_matches_pending_request() {
+        let mut state = ModbusState::new();
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.parse(RD_HOLD_REGS_REQ_5, Direction::ToServer)
+        );
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.parse(RD_HOLD_REGS_REQ_6, Direction::ToServer)
+        );
+        assert_eq!(state.transactions.len(), 2);
+
+        // The exception is for the second request, the first one stays pending.
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.parse(RD_HOLD_REGS_ILLEGAL_ADDR_RESP_6, Direction::ToClient)
+        );
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.transactions[0].response.is_none());
+
+        let tx = &state.transactions[1];
+        assert_eq!(tx.request.as_ref().unwrap().transaction_id, 6);
+        let msg = tx.response.as_ref().unwrap();
+        assert_eq!(msg.transaction_id, 6);
+        assert_eq!(
+            msg.data,
+            Data::Exception(Exception {
+                raw: 2,
+                code: ExceptionCode::IllegalDataAddr
+            })
+        );
+
+        // Same transaction id but a different unit id: not a response to the first request.
+        assert_eq!(
+            AppLayerResult::ok(),
+            state.parse(
+                RD_HOLD_REGS_ILLEGAL_ADDR_RESP_OTHER_UNIT,
+                Direction::ToClient
+            )
+        );
+        assert_eq!(state.transactions.len(), 3);
+        assert!(state.transactions[0].response.is_none());
+        assert!(state.transactions[2].request.is_none());
+    }
+
     #[test]
     fn fragmentation_1_adu_in_2_tcp_packets() {
         let mut state = ModbusState::new();
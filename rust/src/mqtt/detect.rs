@@ -19,8 +19,9 @@
 
 use crate::core::{DetectEngineThreadCtx, STREAM_TOCLIENT, STREAM_TOSERVER};
 use crate::detect::uint::{
-    detect_match_uint, detect_parse_uint, detect_parse_uint_enum, DetectUintData, DetectUintMode,
-    SCDetectU8Free, SCDetectU8Parse,
+    detect_match_uint, detect_parse_uint, detect_parse_uint_enum, detect_register_uint_enum,
+    DetectUintData, DetectUintMode, SCDetectU32Free, SCDetectU32Parse, SCDetectU8Free,
+    SCDetectU8Parse,
 };
 use crate::detect::{
     helper_keyword_register_sticky_buffer, DetectHelperBufferMpmRegister,
@@ -299,6 +300,8 @@ static mut G_MQTT_CONNACK_SESSIONPRESENT_KW_ID: c_int = 0;
 static mut G_MQTT_CONNACK_SESSIONPRESENT_BUFFER_ID: c_int = 0;
 static mut G_MQTT_CONN_FLAGS_KW_ID: c_int = 0;
 static mut G_MQTT_CONN_FLAGS_BUFFER_ID: c_int = 0;
+static mut G_MQTT_CONNECT_COUNT_KW_ID: c_int = 0;
+static mut G_MQTT_CONNECT_COUNT_BUFFER_ID: c_int = 0;
 
 unsafe extern "C" fn unsub_topic_get_data(
     _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flow_flags: u8, local_id: u32,
@@ -680,6 +683,49 @@ unsafe extern "C" fn mqtt_protocol_version_free(_de: *mut c_void, ctx: *mut c_vo
     SCDetectU8Free(ctx);
 }
 
+unsafe extern "C" fn mqtt_connect_count_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_MQTT) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_MQTT_CONNECT_COUNT_KW_ID,
+        ctx,
+        G_MQTT_CONNECT_COUNT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        mqtt_connect_count_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn mqtt_connect_count_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, MQTTTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    // only CONNECT transactions have a count
+    if tx.connect_count > 0 && detect_match_uint(ctx, tx.connect_count) {
+        return 1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn mqtt_connect_count_free(_de: *mut c_void, ctx: *mut c_void) {
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
 // maybe to factor with websocket.flags
 struct MqttParsedFlagItem {
     neg: bool,
@@ -1105,6 +1151,7 @@ pub unsafe extern "C" fn SCDetectMqttRegister() {
         flags: 0,
     };
     G_MQTT_TYPE_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u8, MQTTTypeCode>(G_MQTT_TYPE_KW_ID);
     G_MQTT_TYPE_BUFFER_ID = DetectHelperBufferRegister(
         b"mqtt.type\0".as_ptr() as *const libc::c_char,
         ALPROTO_MQTT,
@@ -1225,6 +1272,22 @@ pub unsafe extern "C" fn SCDetectMqttRegister() {
         ALPROTO_MQTT,
         STREAM_TOSERVER,
     );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"mqtt.connect_count\0".as_ptr() as *const libc::c_char,
+        desc: b"match number of MQTT CONNECT messages in the flow\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/mqtt-keywords.html#mqtt-connect-count\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(mqtt_connect_count_match),
+        Setup: mqtt_connect_count_setup,
+        Free: Some(mqtt_connect_count_free),
+        flags: 0,
+    };
+    G_MQTT_CONNECT_COUNT_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_MQTT_CONNECT_COUNT_BUFFER_ID = DetectHelperBufferRegister(
+        b"mqtt.connect_count\0".as_ptr() as *const libc::c_char,
+        ALPROTO_MQTT,
+        STREAM_TOSERVER,
+    );
     let kw = SCSigTableAppLiteElmt {
         name: b"mqtt.flags\0".as_ptr() as *const libc::c_char,
         desc: b"match MQTT fixed header flags\0".as_ptr() as *const libc::c_char,
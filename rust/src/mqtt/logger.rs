@@ -99,6 +99,13 @@ fn log_mqtt(
                     }
                     js.close()?; // properties
                 }
+                js.set_uint("connect_count", tx.connect_count as u64)?;
+                if tx.double_connect {
+                    js.set_bool("double_connect", true)?;
+                }
+                if tx.duplicate_connect {
+                    js.set_bool("duplicate_connect", true)?;
+                }
                 js.close()?; // connect
             }
             MQTTOperation::CONNACK(ref connack) => {
@@ -18,9 +18,11 @@
 // written by Sascha Steinbiss <sascha@steinbiss.name>
 
 use super::mqtt_message::*;
+use super::mqtt_property::MQTTProperty;
 use super::parser::*;
 use crate::applayer::*;
 use crate::applayer;
+use crate::applayerstats::{applayer_stats_register, AppLayerParserStats};
 use crate::conf::{conf_get, get_memval};
 use crate::core::*;
 use crate::direction::Direction;
@@ -29,7 +31,7 @@ use crate::frames::*;
 use nom7::Err;
 use suricata_sys::sys::AppProto;
 use std;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::ffi::CString;
 
 // Used as a special pseudo packet identifier to denote the first CONNECT
@@ -37,6 +39,9 @@ use std::ffi::CString;
 // parsed packet identifier because in the protocol these are only 16 bit
 // unsigned.
 const MQTT_CONNECT_PKT_ID: u32 = u32::MAX;
+// Maximum number of distinct client ids remembered per flow to spot
+// reconnects with the same client id.
+const MQTT_MAX_CLIENT_IDS: usize = 32;
 // Maximum message length in bytes. If the length of a message exceeds
 // this value, it will be truncated. Default: 1MB.
 static mut MAX_MSG_LEN: u32 = 1048576;
@@ -45,6 +50,8 @@ static mut MQTT_MAX_TX: usize = 1024;
 
 pub(super) static mut ALPROTO_MQTT: AppProto = ALPROTO_UNKNOWN;
 
+static MQTT_STATS: AppLayerParserStats = AppLayerParserStats::new("mqtt");
+
 #[derive(AppLayerFrameType)]
 pub enum MQTTFrameType {
     Pdu,
@@ -65,6 +72,8 @@ pub enum MQTTEvent {
     UnassignedMsgType,
     TooManyTransactions,
     MalformedTraffic,
+    UnresolvedTopicAlias,
+    DuplicateConnect,
 }
 
 #[derive(Debug)]
@@ -75,6 +84,15 @@ pub struct MQTTTransaction {
     complete: bool,
     toclient: bool,
     toserver: bool,
+    /// Number of CONNECT messages in the flow, this one included, or 0
+    /// if this is not a CONNECT transaction.
+    pub connect_count: u32,
+    /// CONNECT on a session that was already connected
+    pub double_connect: bool,
+    /// CONNECT with the client id of an earlier CONNECT of the flow
+    pub duplicate_connect: bool,
+    /// Bits of the MQTTEvent set on the transaction
+    events: u32,
 
     tx_data: applayer::AppLayerTxData,
 }
@@ -94,9 +112,18 @@ impl MQTTTransaction {
             msg: Vec::new(),
             toclient: direction.is_to_client(),
             toserver: direction.is_to_server(),
+            connect_count: 0,
+            double_connect: false,
+            duplicate_connect: false,
+            events: 0,
             tx_data: applayer::AppLayerTxData::for_direction(direction),
         };
     }
+
+    #[cfg(test)]
+    fn has_event(&self, event: MQTTEvent) -> bool {
+        self.events & (1 << (event as u32)) != 0
+    }
 }
 
 impl Transaction for MQTTTransaction {
@@ -115,6 +142,14 @@ pub struct MQTTState {
     skip_response: usize,
     max_msg_len: u32,
     tx_index_completed: usize,
+    // MQTT 5.0 topic aliases, per direction, and the maximum alias value
+    // each side accepts as announced in CONNECT and CONNACK.
+    topic_aliases_ts: HashMap<u16, String>,
+    topic_aliases_tc: HashMap<u16, String>,
+    topic_alias_max_ts: u16,
+    topic_alias_max_tc: u16,
+    connect_count: u32,
+    client_ids: Vec<String>,
 }
 
 impl State<MQTTTransaction> for MQTTState {
@@ -145,6 +180,12 @@ impl MQTTState {
             skip_response: 0,
             max_msg_len: unsafe { MAX_MSG_LEN },
             tx_index_completed: 0,
+            topic_aliases_ts: HashMap::new(),
+            topic_aliases_tc: HashMap::new(),
+            topic_alias_max_ts: 0,
+            topic_alias_max_tc: 0,
+            connect_count: 0,
+            client_ids: Vec::new(),
         }
     }
 
@@ -162,7 +203,9 @@ impl MQTTState {
         }
         if found {
             self.tx_index_completed = 0;
-            self.transactions.remove(index);
+            if let Some(tx) = self.transactions.remove(index) {
+                applayer_stats_tx_freed!(MQTT_STATS, tx.complete);
+            }
         }
     }
 
@@ -194,6 +237,7 @@ impl MQTTState {
         let mut tx = MQTTTransaction::new(msg, direction);
         self.tx_id += 1;
         tx.tx_id = self.tx_id;
+        applayer_stats_incr!(MQTT_STATS, tx_created);
         if self.transactions.len() > unsafe { MQTT_MAX_TX } {
             let mut index = self.tx_index_completed;
             for tx_old in &mut self.transactions.range_mut(self.tx_index_completed..) {
@@ -211,6 +255,56 @@ impl MQTTState {
         return tx;
     }
 
+    /// Remember the client id of a CONNECT, returns true if an earlier
+    /// CONNECT of the flow had the same one. Empty client ids, which let
+    /// the server assign one, are not tracked.
+    fn track_client_id(&mut self, client_id: &str) -> bool {
+        if client_id.is_empty() {
+            return false;
+        }
+        if self.client_ids.iter().any(|id| id == client_id) {
+            return true;
+        }
+        if self.client_ids.len() < MQTT_MAX_CLIENT_IDS {
+            self.client_ids.push(client_id.to_string());
+        }
+        return false;
+    }
+
+    /// Resolve the topic alias of a MQTT 5.0 PUBLISH message. An alias sent
+    /// with a topic is remembered for the direction, an alias sent with an
+    /// empty topic is replaced by the remembered topic. Returns false if the
+    /// alias is outside of the range accepted by the receiver or unknown.
+    fn resolve_topic_alias(&mut self, publish: &mut MQTTPublishData, toclient: bool) -> bool {
+        let alias = publish.properties.as_ref().and_then(|props| {
+            props.iter().find_map(|prop| match prop {
+                MQTTProperty::TOPIC_ALIAS(alias) => Some(*alias),
+                _ => None,
+            })
+        });
+        let alias = match alias {
+            Some(alias) => alias,
+            None => return true,
+        };
+        let (aliases, max) = if toclient {
+            (&mut self.topic_aliases_tc, self.topic_alias_max_tc)
+        } else {
+            (&mut self.topic_aliases_ts, self.topic_alias_max_ts)
+        };
+        if alias == 0 || alias > max {
+            return false;
+        }
+        if publish.topic.is_empty() {
+            if let Some(topic) = aliases.get(&alias) {
+                publish.topic = topic.clone();
+                return true;
+            }
+            return false;
+        }
+        aliases.insert(alias, publish.topic.clone());
+        return true;
+    }
+
     // Handle a MQTT message depending on the direction and state.
     // Note that we are trying to only have one mutable reference to msg
     // and its components, however, since we are in a large match operation,
@@ -218,15 +312,33 @@ impl MQTTState {
     // without having to introduce lifetimes etc.
     // This is the reason for the code duplication below. Maybe there is a
     // more concise way to do it, but this works for now.
-    fn handle_msg(&mut self, msg: MQTTMessage, toclient: bool) {
+    fn handle_msg(&mut self, mut msg: MQTTMessage, toclient: bool) {
+        let mut topic_alias_unresolved = false;
+        if let MQTTOperation::PUBLISH(ref mut publish) = msg.op {
+            topic_alias_unresolved = !self.resolve_topic_alias(publish, toclient);
+        }
         match msg.op {
             MQTTOperation::CONNECT(ref conn) => {
                 self.protocol_version = conn.protocol_version;
+                // a new session starts without aliases, the client announces
+                // the maximum alias it accepts from the server
+                self.topic_aliases_ts.clear();
+                self.topic_aliases_tc.clear();
+                self.topic_alias_max_ts = 0;
+                self.topic_alias_max_tc = topic_alias_maximum(&conn.properties);
+                self.connect_count = self.connect_count.saturating_add(1);
+                let duplicate = self.track_client_id(&conn.client_id);
                 let mut tx = self.new_tx(msg, toclient);
                 tx.pkt_id = Some(MQTT_CONNECT_PKT_ID);
+                tx.connect_count = self.connect_count;
                 if self.connected {
+                    tx.double_connect = true;
                     MQTTState::set_event(&mut tx, MQTTEvent::DoubleConnect);
                 }
+                if duplicate {
+                    tx.duplicate_connect = true;
+                    MQTTState::set_event(&mut tx, MQTTEvent::DuplicateConnect);
+                }
                 self.transactions.push_back(tx);
             }
             MQTTOperation::PUBLISH(ref publish) => {
@@ -250,6 +362,9 @@ impl MQTTState {
                         MQTTState::set_event(&mut tx, MQTTEvent::InvalidQosLevel);
                     }
                 }
+                if topic_alias_unresolved {
+                    MQTTState::set_event(&mut tx, MQTTEvent::UnresolvedTopicAlias);
+                }
                 if !self.connected {
                     MQTTState::set_event(&mut tx, MQTTEvent::UnintroducedMessage);
                 }
@@ -299,7 +414,8 @@ impl MQTTState {
                 }
                 self.transactions.push_back(tx);
             }
-            MQTTOperation::CONNACK(ref _connack) => {
+            MQTTOperation::CONNACK(ref connack) => {
+                self.topic_alias_max_ts = topic_alias_maximum(&connack.properties);
                 if let Some(tx) = self.get_tx_by_pkt_id(MQTT_CONNECT_PKT_ID) {
                     tx.msg.push(msg);
                     tx.complete = true;
@@ -576,7 +692,10 @@ impl MQTTState {
     }
 
     fn set_event(tx: &mut MQTTTransaction, event: MQTTEvent) {
-        tx.tx_data.set_event(event as u8);
+        let event = event as u8;
+        tx.events |= 1 << event;
+        tx.tx_data.set_event(event);
+        applayer_stats_incr!(MQTT_STATS, events);
     }
 
     fn set_event_notx(&mut self, event: MQTTEvent, toclient: bool) {
@@ -587,13 +706,14 @@ impl MQTTState {
         });
         self.tx_id += 1;
         tx.tx_id = self.tx_id;
+        applayer_stats_incr!(MQTT_STATS, tx_created);
         if toclient {
             tx.toclient = true;
         } else {
             tx.toserver = true;
         }
         tx.complete = true;
-        tx.tx_data.set_event(event as u8);
+        MQTTState::set_event(&mut tx, event);
         self.transactions.push_back(tx);
     }
 
@@ -656,11 +776,18 @@ extern "C" fn mqtt_state_new(
 ) -> *mut std::os::raw::c_void {
     let state = MQTTState::new();
     let boxed = Box::new(state);
+    applayer_stats_incr!(MQTT_STATS, states_live);
     return Box::into_raw(boxed) as *mut _;
 }
 
 extern "C" fn mqtt_state_free(state: *mut std::os::raw::c_void) {
-    std::mem::drop(unsafe { Box::from_raw(state as *mut MQTTState) });
+    let state = unsafe { Box::from_raw(state as *mut MQTTState) };
+    // the transactions left are freed with the state
+    for tx in &state.transactions {
+        applayer_stats_tx_freed!(MQTT_STATS, tx.complete);
+    }
+    std::mem::drop(state);
+    applayer_stats_sub!(MQTT_STATS, states_live, 1);
 }
 
 unsafe extern "C" fn mqtt_state_tx_free(state: *mut std::os::raw::c_void, tx_id: u64) {
@@ -673,7 +800,7 @@ unsafe extern "C" fn mqtt_parse_request(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, MQTTState);
-    return state.parse_request(flow, stream_slice);
+    return applayer_stats_result!(MQTT_STATS, state.parse_request(flow, stream_slice));
 }
 
 unsafe extern "C" fn mqtt_parse_response(
@@ -681,7 +808,7 @@ unsafe extern "C" fn mqtt_parse_response(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, MQTTState);
-    return state.parse_response(flow, stream_slice);
+    return applayer_stats_result!(MQTT_STATS, state.parse_response(flow, stream_slice));
 }
 
 unsafe extern "C" fn mqtt_state_get_tx(
@@ -732,6 +859,19 @@ unsafe extern "C" fn mqtt_tx_get_alstate_progress(
     return 0;
 }
 
+/// Return the Topic Alias Maximum property of a CONNECT or CONNACK, 0 (no
+/// aliases allowed) if absent.
+fn topic_alias_maximum(properties: &Option<Vec<MQTTProperty>>) -> u16 {
+    if let Some(props) = properties {
+        for prop in props {
+            if let MQTTProperty::TOPIC_ALIAS_MAXIMUM(max) = prop {
+                return *max;
+            }
+        }
+    }
+    return 0;
+}
+
 // Parser name as a C style string.
 const PARSER_NAME: &[u8] = b"mqtt\0";
 
@@ -782,6 +922,7 @@ pub unsafe extern "C" fn SCMqttRegisterParser() {
         ALPROTO_MQTT = alproto;
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
+            applayer_stats_register(&MQTT_STATS);
         }
         if let Some(val) = conf_get("app-layer.protocols.mqtt.max-tx") {
             if let Ok(v) = val.parse::<usize>() {
@@ -801,3 +942,158 @@ pub unsafe extern "C" fn SCMqttRegisterParser() {
         SCLogDebug!("Protocol detector and parser disabled for MQTT.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // MQTT 5.0 CONNECT, client id "test", Topic Alias Maximum 10
+    const CONNECT_V5: &[u8] = &[
+        0x10, 0x14, // CONNECT, remaining length
+        0x00, 0x04, b'M', b'Q', b'T', b'T', // protocol name
+        0x05, // protocol version
+        0x02, // flags: clean start
+        0x00, 0x3c, // keepalive
+        0x03, 0x22, 0x00, 0x0a, // properties: Topic Alias Maximum 10
+        0x00, 0x04, b't', b'e', b's', b't', // client id
+    ];
+
+    // MQTT 5.0 CONNACK, Topic Alias Maximum 10
+    const CONNACK_V5: &[u8] = &[
+        0x20, 0x06, // CONNACK, remaining length
+        0x00, 0x00, // flags, reason code
+        0x03, 0x22, 0x00, 0x0a, // properties: Topic Alias Maximum 10
+    ];
+
+    // PUBLISH QoS 0 "sensors/temp" with Topic Alias 1
+    const PUBLISH_TOPIC_ALIAS_1: &[u8] = &[
+        0x30, 0x16, // PUBLISH, remaining length
+        0x00, 0x0c, b's', b'e', b'n', b's', b'o', b'r', b's', b'/', b't', b'e', b'm',
+        b'p', // topic
+        0x03, 0x23, 0x00, 0x01, // properties: Topic Alias 1
+        b'2', b'1', b'.', b'5', // payload
+    ];
+
+    // PUBLISH QoS 0 with an empty topic and Topic Alias 1
+    const PUBLISH_ALIAS_1: &[u8] = &[
+        0x30, 0x0a, // PUBLISH, remaining length
+        0x00, 0x00, // topic
+        0x03, 0x23, 0x00, 0x01, // properties: Topic Alias 1
+        b'2', b'2', b'.', b'0', // payload
+    ];
+
+    // PUBLISH QoS 0 with an empty topic and Topic Alias 2
+    const PUBLISH_ALIAS_2: &[u8] = &[
+        0x30, 0x0a, // PUBLISH, remaining length
+        0x00, 0x00, // topic
+        0x03, 0x23, 0x00, 0x02, // properties: Topic Alias 2
+        b'2', b'2', b'.', b'5', // payload
+    ];
+
+    fn publish_topic(tx: &MQTTTransaction) -> &str {
+        match &tx.msg[0].op {
+            MQTTOperation::PUBLISH(publish) => &publish.topic,
+            _ => panic!("not a PUBLISH transaction"),
+        }
+    }
+
+    fn connect_v5(state: &mut MQTTState) {
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(CONNECT_V5, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(CONNACK_V5, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.topic_alias_max_ts, 10);
+        assert_eq!(state.topic_alias_max_tc, 10);
+    }
+
+    #[test]
+    fn test_mqtt_topic_alias_resolved() {
+        let mut state = MQTTState::new();
+        connect_v5(&mut state);
+
+        let mut input = PUBLISH_TOPIC_ALIAS_1.to_vec();
+        input.extend_from_slice(PUBLISH_ALIAS_1);
+        input.extend_from_slice(PUBLISH_ALIAS_1);
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&input, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 4);
+        for tx in state.transactions.range(1..) {
+            assert_eq!(publish_topic(tx), "sensors/temp");
+        }
+    }
+
+    #[test]
+    fn test_mqtt_duplicate_connect() {
+        let mut state = MQTTState::new();
+        connect_v5(&mut state);
+        assert_eq!(state.transactions[0].connect_count, 1);
+        assert!(!state.transactions[0].double_connect);
+        assert!(!state.transactions[0].duplicate_connect);
+
+        // reconnect with the same client id on the connected session
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(CONNECT_V5, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 2);
+        let tx = &state.transactions[1];
+        assert_eq!(tx.connect_count, 2);
+        assert!(tx.double_connect);
+        assert!(tx.duplicate_connect);
+
+        assert!(!state.track_client_id(""));
+        assert!(!state.track_client_id("other"));
+        assert!(state.track_client_id("other"));
+        assert_eq!(state.client_ids.len(), 2);
+    }
+
+    #[test]
+    fn test_mqtt_topic_alias_unresolved() {
+        let mut state = MQTTState::new();
+        connect_v5(&mut state);
+
+        // alias 2 was never bound to a topic
+        let mut input = PUBLISH_TOPIC_ALIAS_1.to_vec();
+        input.extend_from_slice(PUBLISH_ALIAS_2);
+        let r = state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&input, STREAM_TOSERVER, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 3);
+        assert!(!state.transactions[1].has_event(MQTTEvent::UnresolvedTopicAlias));
+        assert_eq!(publish_topic(&state.transactions[2]), "");
+        assert!(state.transactions[2].has_event(MQTTEvent::UnresolvedTopicAlias));
+
+        // aliases are per direction
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(PUBLISH_ALIAS_1, STREAM_TOCLIENT, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 4);
+        assert_eq!(publish_topic(&state.transactions[3]), "");
+        assert!(state.transactions[3].has_event(MQTTEvent::UnresolvedTopicAlias));
+
+        let mut publish = MQTTPublishData {
+            topic: "sensors/temp".to_string(),
+            message_id: None,
+            message: Vec::new(),
+            properties: Some(vec![MQTTProperty::TOPIC_ALIAS(11)]),
+        };
+        // above the Topic Alias Maximum
+        assert!(!state.resolve_topic_alias(&mut publish, false));
+        publish.properties = Some(vec![MQTTProperty::TOPIC_ALIAS(0)]);
+        assert!(!state.resolve_topic_alias(&mut publish, false));
+    }
+}
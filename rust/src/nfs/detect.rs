@@ -0,0 +1,221 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+use super::nfs::{NFSTransaction, ALPROTO_NFS};
+use super::types::RPCAUTH_UNIX;
+use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::detect::uint::{DetectUintData, SCDetectU32Free, SCDetectU32Match, SCDetectU32Parse};
+use crate::detect::{
+    DetectEngineRegistrar, DetectHelperBufferRegister, DetectHelperKeywordRegister,
+    DetectSignatureSetAppProto, SCSigTableAppLiteElmt, SigMatchAppendSMToList,
+};
+use std::os::raw::{c_int, c_void};
+use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+detect_sticky_buffer! {
+    mod nfs_export {
+        name: "nfs.export",
+        desc: "sticky buffer to match on the export of a NFS file transaction",
+        url: "/rules/nfs-keywords.html#nfs-export",
+        mpm_desc: "nfs export",
+        alproto: ALPROTO_NFS,
+        dir: STREAM_TOSERVER | STREAM_TOCLIENT,
+        tx: NFSTransaction,
+        get: |tx| Some(tx.export.as_slice()).filter(|e| !e.is_empty()),
+    }
+}
+
+static mut G_NFS_UID_KW_ID: c_int = 0;
+static mut G_NFS_UID_BUFFER_ID: c_int = 0;
+static mut G_NFS_GID_KW_ID: c_int = 0;
+static mut G_NFS_GID_BUFFER_ID: c_int = 0;
+
+/// The uid and gid are only known for AUTH_UNIX credentials, the other
+/// flavors never match.
+fn nfs_tx_unix_creds(tx: &NFSTransaction) -> Option<(u32, u32)> {
+    if tx.auth_type == RPCAUTH_UNIX {
+        return Some((tx.request_uid, tx.request_gid));
+    }
+    return None;
+}
+
+unsafe extern "C" fn nfs_detect_uid_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_NFS) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(de, s, G_NFS_UID_KW_ID, ctx, G_NFS_UID_BUFFER_ID).is_null() {
+        nfs_detect_creds_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn nfs_detect_uid_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, NFSTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some((uid, _)) = nfs_tx_unix_creds(tx) {
+        return SCDetectU32Match(uid, ctx);
+    }
+    return 0;
+}
+
+unsafe extern "C" fn nfs_detect_gid_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_NFS) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(de, s, G_NFS_GID_KW_ID, ctx, G_NFS_GID_BUFFER_ID).is_null() {
+        nfs_detect_creds_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn nfs_detect_gid_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, NFSTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some((_, gid)) = nfs_tx_unix_creds(tx) {
+        return SCDetectU32Match(gid, ctx);
+    }
+    return 0;
+}
+
+unsafe extern "C" fn nfs_detect_creds_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectNfsRegister() {
+    nfs_export::register(&mut DetectEngineRegistrar);
+
+    let kw = SCSigTableAppLiteElmt {
+        name: b"nfs.uid\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the uid of the AUTH_UNIX credentials of a NFS call\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/nfs-keywords.html#nfs-uid\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(nfs_detect_uid_match),
+        Setup: nfs_detect_uid_setup,
+        Free: Some(nfs_detect_creds_free),
+        flags: 0,
+    };
+    G_NFS_UID_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_NFS_UID_BUFFER_ID = DetectHelperBufferRegister(
+        b"nfs.uid\0".as_ptr() as *const libc::c_char,
+        ALPROTO_NFS,
+        STREAM_TOSERVER,
+    );
+
+    let kw = SCSigTableAppLiteElmt {
+        name: b"nfs.gid\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the gid of the AUTH_UNIX credentials of a NFS call\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/nfs-keywords.html#nfs-gid\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(nfs_detect_gid_match),
+        Setup: nfs_detect_gid_setup,
+        Free: Some(nfs_detect_creds_free),
+        flags: 0,
+    };
+    G_NFS_GID_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_NFS_GID_BUFFER_ID = DetectHelperBufferRegister(
+        b"nfs.gid\0".as_ptr() as *const libc::c_char,
+        ALPROTO_NFS,
+        STREAM_TOSERVER,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect::uint::{detect_match_uint, detect_parse_uint};
+    use crate::nfs::nfs::NFSState;
+    use crate::nfs::rpc_records::parse_rpc;
+
+    /// NFSv3 GETATTR call of handle fh01 with the given credentials
+    fn getattr_call(xid: u8, creds: &[u8]) -> Vec<u8> {
+        #[rustfmt::skip]
+        let mut call = vec![
+            0x80, 0x00, 0x00, 0x00, /*fragment header*/
+            0x00, 0x00, 0x00, xid, /*xid*/
+            0x00, 0x00, 0x00, 0x00, /*msgtype: call*/
+            0x00, 0x00, 0x00, 0x02, /*rpcver*/
+            0x00, 0x01, 0x86, 0xa3, /*program: nfs*/
+            0x00, 0x00, 0x00, 0x03, /*progver*/
+            0x00, 0x00, 0x00, 0x01, /*procedure: getattr*/
+        ];
+        #[rustfmt::skip]
+        let tail = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, /*verifier: AUTH_NULL*/
+            0x00, 0x00, 0x00, 0x04, b'f', b'h', b'0', b'1', /*handle*/
+        ];
+        call.extend_from_slice(creds);
+        call.extend_from_slice(&tail);
+        call[3] = (call.len() - 4) as u8;
+        call
+    }
+
+    #[test]
+    fn test_nfs_unix_creds() {
+        #[rustfmt::skip]
+        let unix_creds = [
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x18, /*flavor: AUTH_UNIX, length*/
+            0x65, 0xf1, 0x2a, 0x00, /*stamp*/
+            0x00, 0x00, 0x00, 0x04, b'h', b'o', b's', b't', /*machine name*/
+            0x00, 0x00, 0x03, 0xe8, /*uid: 1000*/
+            0x00, 0x00, 0x00, 0x64, /*gid: 100*/
+            0x00, 0x00, 0x00, 0x00, /*aux gids*/
+        ];
+        let null_creds = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+        let mut state = NFSState::new();
+        let call = getattr_call(1, &unix_creds);
+        let (_, rpc) = parse_rpc(&call, true).unwrap();
+        state.process_request_record_v3(&rpc);
+        let call = getattr_call(2, &null_creds);
+        let (_, rpc) = parse_rpc(&call, true).unwrap();
+        state.process_request_record_v3(&rpc);
+        assert_eq!(state.transactions.len(), 2);
+
+        let (_, uid) = detect_parse_uint::<u32>("1000").unwrap();
+        let (_, gid) = detect_parse_uint::<u32>("<1000").unwrap();
+        let (uid_value, gid_value) = nfs_tx_unix_creds(&state.transactions[0]).unwrap();
+        assert!(detect_match_uint(&uid, uid_value));
+        assert!(detect_match_uint(&gid, gid_value));
+        assert_eq!(gid_value, 100);
+
+        // AUTH_NULL has no uid and gid, not even 0
+        assert_eq!(nfs_tx_unix_creds(&state.transactions[1]), None);
+    }
+}
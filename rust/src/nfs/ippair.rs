@@ -0,0 +1,118 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! Exports shared by the NFS and MOUNT flows of a client and a server. The
+//! handles of the export roots are kept in the IP-pair storage, so a MNT
+//! on one flow maps the handles used on the others.
+
+use crate::flow::Flow;
+use std::collections::HashMap;
+use std::os::raw::c_void;
+
+/// max number of export roots kept per IP-pair
+const NFS_MAX_IPPAIR_EXPORTS: usize = 64;
+
+#[cfg(not(test))]
+extern "C" {
+    fn NFSIPPairExportsGet(f: *const Flow, create: bool, ipp: *mut *mut c_void) -> *mut c_void;
+    fn NFSIPPairRelease(ipp: *mut c_void);
+}
+
+#[derive(Debug, Default)]
+pub struct NFSIPPairExports {
+    /// map the handle of an export root (1) to the path of the export (2)
+    map: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl NFSIPPairExports {
+    fn insert(&mut self, handle: &[u8], export: &[u8]) {
+        if self.map.len() >= NFS_MAX_IPPAIR_EXPORTS && !self.map.contains_key(handle) {
+            SCLogDebug!("NFSIPPairExports: full, {:?} not added", handle);
+            return;
+        }
+        self.map.insert(handle.to_vec(), export.to_vec());
+    }
+
+    fn get(&self, handle: &[u8]) -> Option<Vec<u8>> {
+        self.map.get(handle).cloned()
+    }
+}
+
+/// Run `f` on the exports of the IP-pair of the flow. They are created
+/// if `create` is set.
+#[cfg(not(test))]
+fn with_ippair_exports<T>(
+    flow: *const Flow, create: bool, f: impl FnOnce(&mut NFSIPPairExports) -> T,
+) -> Option<T> {
+    let mut ipp: *mut c_void = std::ptr::null_mut();
+    let r = unsafe {
+        let exports = NFSIPPairExportsGet(flow, create, &mut ipp) as *mut NFSIPPairExports;
+        exports.as_mut().map(f)
+    };
+    if !ipp.is_null() {
+        unsafe { NFSIPPairRelease(ipp) };
+    }
+    r
+}
+
+/// The IP-pair storage is provided by the C side, so the Rust unit tests
+/// run like on a flow without IP-pair.
+#[cfg(test)]
+fn with_ippair_exports<T>(
+    _flow: *const Flow, _create: bool, _f: impl FnOnce(&mut NFSIPPairExports) -> T,
+) -> Option<T> {
+    None
+}
+
+pub fn nfs_ippair_export_insert(flow: *const Flow, handle: &[u8], export: &[u8]) {
+    with_ippair_exports(flow, true, |e| e.insert(handle, export));
+}
+
+pub fn nfs_ippair_export_get(flow: *const Flow, handle: &[u8]) -> Option<Vec<u8>> {
+    with_ippair_exports(flow, false, |e| e.get(handle)).flatten()
+}
+
+#[no_mangle]
+pub extern "C" fn SCNfsIPPairExportsNew() -> *mut c_void {
+    Box::into_raw(Box::<NFSIPPairExports>::default()) as *mut c_void
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCNfsIPPairExportsFree(exports: *mut c_void) {
+    if !exports.is_null() {
+        std::mem::drop(Box::from_raw(exports as *mut NFSIPPairExports));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nfs_ippair_exports_max() {
+        let mut exports = NFSIPPairExports::default();
+        for i in 0..NFS_MAX_IPPAIR_EXPORTS + 1 {
+            exports.insert(format!("fh{}", i).as_bytes(), b"/srv");
+        }
+        assert_eq!(exports.map.len(), NFS_MAX_IPPAIR_EXPORTS);
+        assert_eq!(exports.get(b"fh0"), Some(b"/srv".to_vec()));
+        assert_eq!(exports.get(b"fh64"), None);
+        // updating a known handle is not limited
+        exports.insert(b"fh0", b"/home");
+        assert_eq!(exports.get(b"fh0"), Some(b"/home".to_vec()));
+    }
+}
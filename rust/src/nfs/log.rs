@@ -89,6 +89,10 @@ fn nfs_common_header(
     js.set_string("procedure", &proc_string)?;
     let file_name = String::from_utf8_lossy(&tx.file_name);
     js.set_string("filename", &file_name)?;
+    if !tx.export.is_empty() {
+        let export = String::from_utf8_lossy(&tx.export);
+        js.set_string("export", &export)?;
+    }
 
     if !tx.file_handle.is_empty() {
         //js.set_string("handle", &nfs_handle2hex(&tx.file_handle));
@@ -122,7 +126,19 @@ fn nfs_log_response(
             js.open_object("rename")?;
             nfs_rename_object(tx, js)?;
             js.close()?;
+        } else if tx.procedure == NFSPROC3_LINK {
+            js.open_object("link")?;
+            nfs_rename_object(tx, js)?;
+            js.close()?;
         }
+    } else if tx.procedure == NFSPROC4_RENAME {
+        js.open_object("rename")?;
+        nfs_rename_object(tx, js)?;
+        js.close()?;
+    } else if tx.procedure == NFSPROC4_LINK {
+        js.open_object("link")?;
+        nfs_rename_object(tx, js)?;
+        js.close()?;
     }
     Ok(())
 }
@@ -17,7 +17,11 @@
 
 //! NFS application layer, parser, logger module.
 
+pub mod detect;
+pub mod ippair;
 pub mod log;
+pub mod mount;
+pub mod mount_records;
 pub mod nfs;
 pub mod nfs2;
 pub mod nfs2_records;
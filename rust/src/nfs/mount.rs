@@ -0,0 +1,72 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! MOUNT protocol, used by NFSv2 and NFSv3 clients to get the handle of
+//! the root of an export. Only MNT is tracked, to learn the exports.
+
+use crate::nfs::mount_records::*;
+use crate::nfs::nfs::*;
+use crate::nfs::rpc_records::*;
+use crate::nfs::types::*;
+
+impl NFSState {
+    /// complete MOUNT request record
+    pub fn process_request_record_mount(&mut self, r: &RpcPacket) {
+        SCLogDebug!(
+            "MOUNT: REQUEST {} procedure {} blob size {}",
+            r.hdr.xid,
+            r.procedure,
+            r.prog_data.len()
+        );
+
+        if r.procedure != MOUNTPROC_MNT {
+            return;
+        }
+        match parse_mount_request_mnt(r.prog_data) {
+            Ok((_, dirpath)) => {
+                let mut xidmap = NFSRequestXidMap::new(r.progver, r.procedure, 0);
+                xidmap.program = RPC_PROGRAM_MOUNT;
+                xidmap.export_path = dirpath.to_vec();
+                self.requestmap.insert(r.hdr.xid, xidmap);
+            }
+            _ => {
+                self.set_event(NFSEvent::MalformedData);
+            }
+        }
+    }
+
+    pub fn process_reply_record_mount(&mut self, r: &RpcReplyPacket, xidmap: &NFSRequestXidMap) {
+        if xidmap.procedure != MOUNTPROC_MNT {
+            return;
+        }
+        let reply = match xidmap.progver {
+            1 => parse_mount1_reply_mnt(r.prog_data),
+            _ => parse_mount3_reply_mnt(r.prog_data),
+        };
+        match reply {
+            Ok((_, reply)) => {
+                SCLogDebug!("MOUNT: MNT {:?} reply {:?}", xidmap.export_path, reply);
+                if let Some(handle) = reply.handle {
+                    self.export_root_insert(handle, &xidmap.export_path);
+                }
+            }
+            _ => {
+                self.set_event(NFSEvent::MalformedData);
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! Nom parsers for the MOUNT protocol
+
+use crate::nfs::nfs2_records::*;
+use crate::nfs::nfs3_records::*;
+use crate::nfs::types::*;
+use nom7::bytes::streaming::take;
+use nom7::combinator::{cond, verify};
+use nom7::number::streaming::be_u32;
+use nom7::IResult;
+
+/// MNT request: the path of the export to mount
+pub fn parse_mount_request_mnt(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    let (i, len) = verify(be_u32, |&len| len <= MNTPATHLEN)(i)?;
+    let (i, dirpath) = take(len as usize)(i)?;
+    let (i, _fill_bytes) = cond(len % 4 != 0, take(4 - (len % 4)))(i)?;
+    Ok((i, dirpath))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MountReplyMnt<'a> {
+    pub status: u32,
+    /// handle of the root of the export
+    pub handle: Option<&'a [u8]>,
+}
+
+/// MNT reply of version 1, with the fixed size handle of NFSv2
+pub fn parse_mount1_reply_mnt(i: &[u8]) -> IResult<&[u8], MountReplyMnt> {
+    let (i, status) = be_u32(i)?;
+    let (i, handle) = cond(status == MNT3_OK, parse_nfs2_handle)(i)?;
+    let reply = MountReplyMnt {
+        status,
+        handle: handle.map(|h| h.value),
+    };
+    Ok((i, reply))
+}
+
+/// MNT reply of version 3. The list of auth flavors that follows the
+/// handle is not parsed.
+pub fn parse_mount3_reply_mnt(i: &[u8]) -> IResult<&[u8], MountReplyMnt> {
+    let (i, status) = be_u32(i)?;
+    let (i, handle) = cond(status == MNT3_OK, parse_nfs3_handle)(i)?;
+    let reply = MountReplyMnt {
+        status,
+        handle: handle.map(|h| h.value),
+    };
+    Ok((i, reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nfs::mount_records::*;
+
+    #[test]
+    fn test_mount_request_mnt() {
+        #[rustfmt::skip]
+        let buf: &[u8] = &[
+            0x00, 0x00, 0x00, 0x0c, 0x2f, 0x73, 0x72, 0x76, /*dirpath: (/srv/backups)*/
+            0x2f, 0x62, 0x61, 0x63, 0x6b, 0x75, 0x70, 0x73,
+        ];
+        let (r, dirpath) = parse_mount_request_mnt(buf).unwrap();
+        assert_eq!(r.len(), 0);
+        assert_eq!(dirpath, b"/srv/backups");
+
+        // the path is longer than MNTPATHLEN
+        let buf: &[u8] = &[0x00, 0x00, 0x04, 0x01, 0x2f];
+        assert!(parse_mount_request_mnt(buf).is_err());
+    }
+
+    #[test]
+    fn test_mount3_reply_mnt() {
+        #[rustfmt::skip]
+        let buf: &[u8] = &[
+            0x00, 0x00, 0x00, 0x00, /*status*/
+            0x00, 0x00, 0x00, 0x08, 0x01, 0x00, 0x01, 0x00, /*handle*/
+            0x00, 0x2a, 0x00, 0x07,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, /*auth flavors*/
+        ];
+        let (_, reply) = parse_mount3_reply_mnt(buf).unwrap();
+        assert_eq!(reply.status, MNT3_OK);
+        assert_eq!(reply.handle, Some(&buf[8..16]));
+
+        // MNT3ERR_ACCES
+        let buf: &[u8] = &[0x00, 0x00, 0x00, 0x0d];
+        let (r, reply) = parse_mount3_reply_mnt(buf).unwrap();
+        assert_eq!(r.len(), 0);
+        assert_eq!(reply.status, 13);
+        assert_eq!(reply.handle, None);
+    }
+
+    #[test]
+    fn test_mount1_reply_mnt() {
+        #[rustfmt::skip]
+        let buf: &[u8] = &[
+            0x00, 0x00, 0x00, 0x00, /*status*/
+            0x00, 0x10, 0x10, 0x85, 0x00, 0x00, 0x03, 0xe7, /*handle*/
+            0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0xb2, 0x5a,
+            0x00, 0x00, 0x00, 0x29, 0x00, 0x0a, 0x00, 0x00,
+            0x00, 0x00, 0xb2, 0x5a, 0x00, 0x00, 0x00, 0x29,
+        ];
+        let (r, reply) = parse_mount1_reply_mnt(buf).unwrap();
+        assert_eq!(r.len(), 0);
+        assert_eq!(reply.handle, Some(&buf[4..]));
+    }
+}
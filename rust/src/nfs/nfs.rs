@@ -36,6 +36,7 @@ use crate::filetracker::*;
 use crate::flow::Flow;
 use crate::frames::*;
 
+use crate::nfs::ippair::*;
 use crate::nfs::nfs2_records::*;
 use crate::nfs::nfs3_records::*;
 use crate::nfs::nfs_records::*;
@@ -48,10 +49,13 @@ pub const NFS_MIN_FRAME_LEN: u16 = 32;
 
 static mut NFS_MAX_TX: usize = 1024;
 
+/// Bound on the number of file handles mapped to an export
+const NFS_MAX_EXPORTMAP: usize = 4096;
+
 pub const RPC_TCP_PRE_CREDS: usize = 28;
 pub const RPC_UDP_PRE_CREDS: usize = 24;
 
-static mut ALPROTO_NFS: AppProto = ALPROTO_UNKNOWN;
+pub(super) static mut ALPROTO_NFS: AppProto = ALPROTO_UNKNOWN;
 /*
  * Record parsing.
  *
@@ -69,6 +73,24 @@ static mut ALPROTO_NFS: AppProto = ALPROTO_UNKNOWN;
  * To be able to log the names we store a mapping between file handles
  * and file names in NFSState::namemap.
  *
+ * Getting exports.
+ *
+ * The export a file belongs to is learned the same way: NFSv4 clients
+ * mount an export with PUTROOTFH, one LOOKUP per path component and GETFH.
+ * The walk stops at the handle of the first GETFH, or at a path already
+ * known as an export: this is the export root. It is stored in
+ * NFSState::exportmap, and the handles that LOOKUP and OPEN return for its
+ * directories and files inherit the export. NFSv2/3 clients get the handle
+ * of the export root from the separate MOUNT protocol: the handle of a MNT
+ * reply is mapped to the path of its request, and the handles LOOKUP,
+ * CREATE and READDIRPLUS return in it inherit the export through
+ * NFSState::namemap_insert.
+ *
+ * MOUNT usually runs on another flow than NFS, so the handles of the export
+ * roots are also stored in the IP-pair storage of the client and the server
+ * (see ippair.rs). A handle missing from NFSState::exportmap is looked up
+ * there.
+ *
  * Mapping NFS to Suricata's transaction model.
  *
  * The easiest way to do transactions would be to map each command/reply with
@@ -191,9 +213,11 @@ pub struct NFSTransaction {
     pub xid: u32,
     /// nfs req/reply pair id
     pub procedure: u32,
-    /// file name of the object we're dealing with. In case of RENAME
+    /// file name of the object we're dealing with. In case of RENAME or LINK
     /// this is the 'from' or original name.
     pub file_name: Vec<u8>,
+    /// path of the export the file handle belongs to, if known
+    pub export: Vec<u8>,
 
     pub auth_type: u32,
     pub request_machine_name: Vec<u8>,
@@ -239,6 +263,7 @@ impl NFSTransaction {
             xid: 0,
             procedure: 0,
             file_name: Vec::new(),
+            export: Vec::new(),
             request_machine_name: Vec::new(),
             request_uid: 0,
             request_gid: 0,
@@ -283,6 +308,8 @@ impl Drop for NFSTransaction {
 
 #[derive(Debug)]
 pub struct NFSRequestXidMap {
+    /// RPC program, NFS or MOUNT
+    pub program: u32,
     pub progver: u32,
     pub procedure: u32,
     pub chunk_offset: u64,
@@ -290,6 +317,14 @@ pub struct NFSRequestXidMap {
 
     /// READ replies can use this to get to the handle the request used
     pub file_handle: Vec<u8>,
+    /// directory of file_name for LOOKUP and OPEN, or of new_file_name
+    /// for RENAME and LINK
+    pub dir_handle: Vec<u8>,
+    /// RENAME/LINK target name, applied to the namemap on success
+    pub new_file_name: Vec<u8>,
+    /// export of the handle returned by the first GETFH or by MNT, for the
+    /// exportmap
+    pub export_path: Vec<u8>,
 
     pub gssapi_proc: u32,
     pub gssapi_service: u32,
@@ -298,17 +333,84 @@ pub struct NFSRequestXidMap {
 impl NFSRequestXidMap {
     pub fn new(progver: u32, procedure: u32, chunk_offset: u64) -> NFSRequestXidMap {
         NFSRequestXidMap {
+            program: RPC_PROGRAM_NFS,
             progver,
             procedure,
             chunk_offset,
             file_name: Vec::new(),
             file_handle: Vec::new(),
+            dir_handle: Vec::new(),
+            new_file_name: Vec::new(),
+            export_path: Vec::new(),
             gssapi_proc: 0,
             gssapi_service: 0,
         }
     }
 }
 
+/// Append a LOOKUP component to a path from the root of the NFSv4
+/// pseudo file system.
+pub fn nfs_export_path_join(path: &[u8], name: &[u8]) -> Vec<u8> {
+    let mut joined = path.to_vec();
+    if !joined.ends_with(b"/") {
+        joined.push(b'/');
+    }
+    joined.extend_from_slice(name);
+    joined
+}
+
+/// Export of the current filehandle of a NFSv4 COMPOUND. After PUTROOTFH it
+/// is the path the LOOKUPs walk, up to the export root: the handle returned
+/// by the first GETFH, or a path already known as an export. After that, and
+/// after PUTFH, LOOKUP and OPEN stay in the export.
+#[derive(Debug, Default)]
+pub struct NFSExportWalk {
+    export: Option<Vec<u8>>,
+    from_root: bool,
+}
+
+impl NFSExportWalk {
+    pub fn put_fh(&mut self, export: Option<Vec<u8>>) {
+        self.export = export;
+        self.from_root = false;
+    }
+
+    pub fn put_root_fh(&mut self) {
+        self.export = Some(b"/".to_vec());
+        self.from_root = true;
+    }
+
+    /// Export of the handle returned by GETFH.
+    pub fn get_fh(&mut self) -> Option<&[u8]> {
+        // the root of the pseudo file system is not an export
+        if self.from_root && self.export.as_deref() == Some(b"/") {
+            return None;
+        }
+        self.from_root = false;
+        self.export.as_deref()
+    }
+
+    pub fn open(&mut self) {
+        if self.from_root {
+            // a file below a walk that did not reach an export root
+            self.export = None;
+            self.from_root = false;
+        }
+    }
+
+    pub fn lookup(&mut self, name: &[u8], is_export_path: impl FnOnce(&[u8]) -> bool) {
+        if !self.from_root {
+            return;
+        }
+        if let Some(ref mut path) = self.export {
+            *path = nfs_export_path_join(path, name);
+            if is_export_path(path) {
+                self.from_root = false;
+            }
+        }
+    }
+}
+
 /// little wrapper around the FileTransferTracker::new_chunk method
 pub fn filetracker_newchunk(
     ft: &mut FileTransferTracker, name: &[u8], data: &[u8], chunk_offset: u64, chunk_size: u32,
@@ -358,6 +460,16 @@ pub struct NFSState {
     /// map file handle (1) to name (2)
     pub namemap: HashMap<Vec<u8>, Vec<u8>>,
 
+    /// map directory handle and name (1) to file handle (2)
+    pub dirmap: HashMap<(Vec<u8>, Vec<u8>), Vec<u8>>,
+
+    /// map file handle (1) to the path of its export (2)
+    pub exportmap: HashMap<Vec<u8>, Vec<u8>>,
+
+    /// flow of the state, to share the export roots with the other flows
+    /// between the client and the server
+    pub flow: Option<*const Flow>,
+
     /// transactions list
     pub transactions: Vec<NFSTransaction>,
 
@@ -416,6 +528,9 @@ impl NFSState {
             state_data: AppLayerStateData::new(),
             requestmap: HashMap::new(),
             namemap: HashMap::new(),
+            dirmap: HashMap::new(),
+            exportmap: HashMap::new(),
+            flow: None,
             transactions: Vec::new(),
             ts_chunk_xid: 0,
             tc_chunk_xid: 0,
@@ -856,6 +971,7 @@ impl NFSState {
         match parse_nfs3_request_lookup(r.prog_data) {
             Ok((_, lookup)) => {
                 SCLogDebug!("LOOKUP {:?}", lookup);
+                xidmap.dir_handle = lookup.handle.value.to_vec();
                 xidmap.file_name = lookup.name_vec;
             }
             _ => {
@@ -876,6 +992,73 @@ impl NFSState {
         }
     }
 
+    /// Map a file handle to its name, and the name in its directory to the
+    /// handle if the directory is known.
+    pub fn namemap_insert(&mut self, dir_handle: &[u8], name: &[u8], file_handle: &[u8]) {
+        self.namemap.insert(file_handle.to_vec(), name.to_vec());
+        if !dir_handle.is_empty() {
+            self.dirmap
+                .insert((dir_handle.to_vec(), name.to_vec()), file_handle.to_vec());
+        }
+        // the object is in the export of its directory
+        if let Some(export) = self.export_get(dir_handle) {
+            self.exportmap_insert(file_handle, &export);
+        }
+    }
+
+    /// Update the handle to name mapping after a successful RENAME. The
+    /// request only has the names, so the renamed object is found by its
+    /// old name in the source directory.
+    pub fn namemap_rename(
+        &mut self, from_dir: &[u8], old_name: &[u8], to_dir: &[u8], new_name: &[u8],
+    ) {
+        if old_name.is_empty() || new_name.is_empty() {
+            return;
+        }
+        if let Some(fh) = self.dirmap.remove(&(from_dir.to_vec(), old_name.to_vec())) {
+            SCLogDebug!("namemap_rename: {:?} -> {:?}", old_name, new_name);
+            self.namemap_insert(to_dir, new_name, &fh);
+        }
+    }
+
+    pub fn exportmap_insert(&mut self, file_handle: &[u8], export: &[u8]) {
+        if file_handle.is_empty() || export.is_empty() {
+            return;
+        }
+        if self.exportmap.len() >= NFS_MAX_EXPORTMAP && !self.exportmap.contains_key(file_handle) {
+            SCLogDebug!("exportmap_insert: map full, {:?} not added", file_handle);
+            return;
+        }
+        self.exportmap.insert(file_handle.to_vec(), export.to_vec());
+    }
+
+    /// Map the handle of the root of an export, on this flow and on the
+    /// other flows between the client and the server.
+    pub fn export_root_insert(&mut self, handle: &[u8], export: &[u8]) {
+        self.exportmap_insert(handle, export);
+        if let Some(flow) = self.flow {
+            if !handle.is_empty() && !export.is_empty() {
+                nfs_ippair_export_insert(flow, handle, export);
+            }
+        }
+    }
+
+    /// Get the export of a handle. The roots of the exports mounted on
+    /// another flow are looked up in the IP-pair, and added to the map.
+    pub fn export_get(&mut self, handle: &[u8]) -> Option<Vec<u8>> {
+        if let Some(export) = self.exportmap.get(handle) {
+            return Some(export.to_vec());
+        }
+        let export = nfs_ippair_export_get(self.flow?, handle)?;
+        self.exportmap_insert(handle, &export);
+        Some(export)
+    }
+
+    /// true if a handle was mapped to this export
+    pub fn is_export_path(&self, path: &[u8]) -> bool {
+        self.exportmap.values().any(|e| e == path)
+    }
+
     /// complete request record
     fn process_request_record(
         &mut self, flow: *const Flow, stream_slice: &StreamSlice, r: &RpcPacket,
@@ -888,6 +1071,10 @@ impl NFSState {
             r.prog_data.len()
         );
 
+        if r.program == RPC_PROGRAM_MOUNT {
+            self.process_request_record_mount(r);
+            return;
+        }
         match r.progver {
             4 => {
                 self.add_nfs4_ts_frames(flow, stream_slice, r.prog_data, r.prog_data_size as i64);
@@ -911,6 +1098,9 @@ impl NFSState {
         let mut tx = self.new_tx();
         tx.file_name = file_name.to_vec();
         tx.file_handle = file_handle.to_vec();
+        if let Some(export) = self.export_get(file_handle) {
+            tx.export = export;
+        }
         tx.is_file_tx = true;
 
         tx.type_data = Some(NFSTransactionTypeData::FILE(NFSTransactionFile::new()));
@@ -1092,6 +1282,10 @@ impl NFSState {
             r.hdr.xid
         );
 
+        if xidmap.program == RPC_PROGRAM_MOUNT {
+            self.process_reply_record_mount(r, &xidmap);
+            return 0;
+        }
         if self.nfs_version == 0 {
             self.nfs_version = xidmap.progver as u16;
         }
@@ -1924,6 +2118,7 @@ unsafe extern "C" fn nfs_parse_request(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, NFSState);
+    state.flow = Some(flow);
     let flow = cast_pointer!(flow, Flow);
 
     if stream_slice.is_gap() {
@@ -1944,6 +2139,7 @@ unsafe extern "C" fn nfs_parse_response(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, NFSState);
+    state.flow = Some(flow);
     let flow = cast_pointer!(flow, Flow);
 
     if stream_slice.is_gap() {
@@ -1965,6 +2161,7 @@ unsafe extern "C" fn nfs_parse_request_udp(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, NFSState);
+    state.flow = Some(f);
 
     SCLogDebug!("parsing {} bytes of request data", stream_slice.len());
     state.parse_udp_ts(f, &stream_slice)
@@ -1975,6 +2172,7 @@ unsafe extern "C" fn nfs_parse_response_udp(
     stream_slice: StreamSlice, _data: *const std::os::raw::c_void,
 ) -> AppLayerResult {
     let state = cast_pointer!(state, NFSState);
+    state.flow = Some(f);
     SCLogDebug!("parsing {} bytes of response data", stream_slice.len());
     state.parse_udp_tc(f, &stream_slice)
 }
@@ -2086,6 +2284,16 @@ fn nfs_probe_dir(i: &[u8], rdir: *mut u8) -> i8 {
     }
 }
 
+/// NFS calls, and the calls of the MOUNT protocol NFSv2/3 clients use to
+/// get the root handle of an export
+fn nfs_probe_call(program: u32, progver: u32, procedure: u32) -> bool {
+    match program {
+        RPC_PROGRAM_NFS => (progver == 3 || progver == 4) && procedure <= NFSPROC3_COMMIT,
+        RPC_PROGRAM_MOUNT => (progver == 1 || progver == 3) && procedure <= MOUNTPROC_EXPORT,
+        _ => false,
+    }
+}
+
 pub fn nfs_probe(i: &[u8], direction: Direction) -> i32 {
     if direction == Direction::ToClient {
         match parse_rpc_reply(i, false) {
@@ -2142,9 +2350,7 @@ pub fn nfs_probe(i: &[u8], direction: Direction) -> i32 {
                 if rpc.hdr.frag_len >= 40
                     && rpc.hdr.msgtype == 0
                     && rpc.rpcver == 2
-                    && (rpc.progver == 3 || rpc.progver == 4)
-                    && rpc.program == 100003
-                    && rpc.procedure <= NFSPROC3_COMMIT
+                    && nfs_probe_call(rpc.program, rpc.progver, rpc.procedure)
                 {
                     return rpc_auth_type_known(rpc.creds_flavor) as i32;
                 } else {
@@ -2201,6 +2407,11 @@ pub fn nfs_probe_udp(i: &[u8], direction: Direction) -> i32 {
                 {
                     SCLogDebug!("NFSv2!");
                     return 1;
+                } else if i.len() >= 48
+                    && rpc.hdr.msgtype == 0
+                    && nfs_probe_call(rpc.program, rpc.progver, rpc.procedure)
+                {
+                    return 1;
                 } else {
                     return -1;
                 }
@@ -2300,7 +2511,8 @@ const PARSER_NAME: &[u8] = b"nfs\0";
 
 #[no_mangle]
 pub unsafe extern "C" fn SCRegisterNfsParser() {
-    let default_port = CString::new("[2049]").unwrap();
+    // NFS and the usual port of mountd
+    let default_port = CString::new("[2049,20048]").unwrap();
     let parser = RustParser {
         name: PARSER_NAME.as_ptr() as *const std::os::raw::c_char,
         default_port: std::ptr::null(),
@@ -2391,7 +2603,8 @@ pub unsafe extern "C" fn SCRegisterNfsParser() {
 
 #[no_mangle]
 pub unsafe extern "C" fn SCRegisterNfsUdpParser() {
-    let default_port = CString::new("[2049]").unwrap();
+    // NFS and the usual port of mountd
+    let default_port = CString::new("[2049,20048]").unwrap();
     let parser = RustParser {
         name: PARSER_NAME.as_ptr() as *const std::os::raw::c_char,
         default_port: std::ptr::null(),
@@ -2471,3 +2684,195 @@ pub unsafe extern "C" fn SCRegisterNfsUdpParser() {
         SCLogDebug!("Protocol detector and parser disabled for nfs.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nfs_export_path_join() {
+        let path = nfs_export_path_join(b"/", b"srv");
+        assert_eq!(path, b"/srv");
+        assert_eq!(nfs_export_path_join(&path, b"backups"), b"/srv/backups");
+    }
+
+    #[test]
+    fn test_nfs_export_get() {
+        let mut state = NFSState::new();
+        state.exportmap_insert(b"fh1", b"/srv/backups");
+        // empty exports are not mapped
+        state.exportmap_insert(b"fh2", b"");
+        assert_eq!(state.exportmap.len(), 1);
+        assert_eq!(state.export_get(b"fh1"), Some(b"/srv/backups".to_vec()));
+        assert_eq!(state.export_get(b"fh2"), None);
+
+        // an object looked up in an export is in it
+        state.namemap_insert(b"fh1", b"db.tar", b"fh3");
+        assert_eq!(state.export_get(b"fh3"), Some(b"/srv/backups".to_vec()));
+        state.namemap_insert(b"fh2", b"notes.txt", b"fh4");
+        assert_eq!(state.export_get(b"fh4"), None);
+    }
+
+    #[test]
+    fn test_nfs_export_walk() {
+        let exports = [b"/srv/backups".to_vec()];
+        let is_export_path = |path: &[u8]| exports.iter().any(|e| e == path);
+
+        // PUTROOTFH, LOOKUP srv, LOOKUP backups, GETFH
+        let mut walk = NFSExportWalk::default();
+        walk.put_root_fh();
+        // the root of the pseudo file system is not an export
+        assert_eq!(walk.get_fh(), None);
+        walk.lookup(b"srv", |_| false);
+        walk.lookup(b"backups", |_| false);
+        assert_eq!(walk.get_fh(), Some(&b"/srv/backups"[..]));
+
+        // a walk from the root stops at the known export
+        let mut walk = NFSExportWalk::default();
+        walk.put_root_fh();
+        for name in [&b"srv"[..], b"backups", b"logs"] {
+            walk.lookup(name, is_export_path);
+        }
+        assert_eq!(walk.get_fh(), Some(&b"/srv/backups"[..]));
+
+        // a directory looked up from the export root is in the export
+        let mut walk = NFSExportWalk::default();
+        walk.put_fh(Some(b"/srv/backups".to_vec()));
+        walk.lookup(b"db", is_export_path);
+        assert_eq!(walk.get_fh(), Some(&b"/srv/backups"[..]));
+
+        // a file opened before reaching an export root
+        let mut walk = NFSExportWalk::default();
+        walk.put_root_fh();
+        walk.lookup(b"tmp", is_export_path);
+        walk.open();
+        assert_eq!(walk.get_fh(), None);
+
+        // a handle from an unknown export
+        let mut walk = NFSExportWalk::default();
+        walk.put_fh(None);
+        walk.lookup(b"db", is_export_path);
+        assert_eq!(walk.get_fh(), None);
+    }
+
+    fn rpc_request(
+        xid: u32, program: u32, progver: u32, procedure: u32, prog_data: &[u8],
+    ) -> RpcPacket {
+        RpcPacket {
+            hdr: RpcPacketHeader {
+                frag_is_last: true,
+                frag_len: 0,
+                xid,
+                msgtype: 0,
+            },
+            rpcver: 2,
+            program,
+            progver,
+            procedure,
+            creds_flavor: 0,
+            creds_len: 0,
+            creds: RpcRequestCreds::Unknown(&[]),
+            verifier_flavor: 0,
+            verifier_len: 0,
+            verifier: &[],
+            prog_data_size: prog_data.len() as u32,
+            prog_data,
+        }
+    }
+
+    fn rpc_reply(xid: u32, prog_data: &[u8]) -> RpcReplyPacket {
+        RpcReplyPacket {
+            hdr: RpcPacketHeader {
+                frag_is_last: true,
+                frag_len: 0,
+                xid,
+                msgtype: 1,
+            },
+            verifier_flavor: 0,
+            verifier_len: 0,
+            verifier: None,
+            reply_state: 0,
+            accept_state: 0,
+            prog_data_size: prog_data.len() as u32,
+            prog_data,
+        }
+    }
+
+    #[test]
+    fn test_nfs_rename_same_name_other_dir() {
+        let mut state = NFSState::new();
+        state.namemap_insert(b"dirA", b"x", b"filA");
+        state.namemap_insert(b"dirB", b"x", b"filB");
+
+        // RENAME dirA/x to dirA/y
+        state.namemap_rename(b"dirA", b"x", b"dirA", b"y");
+        let key = |dir: &[u8], name: &[u8]| (dir.to_vec(), name.to_vec());
+        assert_eq!(state.dirmap.get(&key(b"dirA", b"x")), None);
+        assert_eq!(state.dirmap[&key(b"dirA", b"y")], b"filA");
+        assert_eq!(state.dirmap[&key(b"dirB", b"x")], b"filB");
+
+        // names of both files, as for a READ
+        let mut xidmap = NFSRequestXidMap::new(3, NFSPROC3_READ, 0);
+        xidmap.file_handle = b"filA".to_vec();
+        state.xidmap_handle2name(&mut xidmap);
+        assert_eq!(xidmap.file_name, b"y");
+        xidmap.file_handle = b"filB".to_vec();
+        state.xidmap_handle2name(&mut xidmap);
+        assert_eq!(xidmap.file_name, b"x");
+    }
+
+    #[test]
+    fn test_nfs3_mount_export() {
+        let mut state = NFSState::new();
+
+        // MNT of /srv/backups
+        #[rustfmt::skip]
+        let request = [
+            0x00, 0x00, 0x00, 0x0c, b'/', b's', b'r', b'v', /*dirpath*/
+            b'/', b'b', b'a', b'c', b'k', b'u', b'p', b's',
+        ];
+        #[rustfmt::skip]
+        let reply = [
+            0x00, 0x00, 0x00, 0x00, /*status*/
+            0x00, 0x00, 0x00, 0x04, b'f', b'h', b'0', b'1', /*handle*/
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, /*auth_flavors*/
+        ];
+        let mnt = rpc_request(1, RPC_PROGRAM_MOUNT, 3, MOUNTPROC_MNT, &request);
+        state.process_request_record_mount(&mnt);
+        let xidmap = state.requestmap.remove(&1).unwrap();
+        assert_eq!(xidmap.program, RPC_PROGRAM_MOUNT);
+        state.process_reply_record_mount(&rpc_reply(1, &reply), &xidmap);
+        assert_eq!(state.exportmap[&b"fh01".to_vec()], b"/srv/backups");
+
+        // a LOOKUP from the export root is in the export
+        state.namemap_insert(b"fh01", b"db", b"fh02");
+        assert_eq!(state.exportmap[&b"fh02".to_vec()], b"/srv/backups");
+
+        // a failed MNT maps nothing
+        let mnt = rpc_request(3, RPC_PROGRAM_MOUNT, 3, MOUNTPROC_MNT, &request);
+        state.process_request_record_mount(&mnt);
+        let xidmap = state.requestmap.remove(&3).unwrap();
+        state.process_reply_record_mount(&rpc_reply(3, &[0x00, 0x00, 0x00, 0x0d]), &xidmap);
+        assert_eq!(state.exportmap.len(), 2);
+    }
+
+    #[test]
+    fn test_nfs_probe_mount() {
+        #[rustfmt::skip]
+        let buf = [
+            0x80, 0x00, 0x00, 0x34, /*frag*/
+            0x00, 0x00, 0x00, 0x01, /*xid*/
+            0x00, 0x00, 0x00, 0x00, /*msgtype*/
+            0x00, 0x00, 0x00, 0x02, /*rpcver*/
+            0x00, 0x01, 0x86, 0xa5, /*program: MOUNT*/
+            0x00, 0x00, 0x00, 0x03, /*progver*/
+            0x00, 0x00, 0x00, 0x01, /*procedure: MNT*/
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, /*creds*/
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, /*verifier*/
+            0x00, 0x00, 0x00, 0x0c, b'/', b's', b'r', b'v', /*dirpath*/
+            b'/', b'b', b'a', b'c', b'k', b'u', b'p', b's',
+        ];
+        assert_eq!(nfs_probe(&buf, Direction::ToServer), 1);
+        assert_eq!(nfs_probe_udp(&buf[4..], Direction::ToServer), 1);
+    }
+}
@@ -99,10 +99,22 @@ impl NFSState {
             if let Ok((_, rd)) = parse_nfs3_request_rename(r.prog_data) {
                 xidmap.file_handle = rd.from_handle.value.to_vec();
                 xidmap.file_name = rd.from_name_vec;
+                xidmap.dir_handle = rd.to_handle.value.to_vec();
+                xidmap.new_file_name = rd.to_name_vec.to_vec();
                 aux_file_name = rd.to_name_vec;
             } else {
                 self.set_event(NFSEvent::MalformedData);
             };
+        } else if r.procedure == NFSPROC3_LINK {
+            if let Ok((_, rd)) = parse_nfs3_request_link(r.prog_data) {
+                xidmap.file_handle = rd.handle.value.to_vec();
+                self.xidmap_handle2name(&mut xidmap);
+                xidmap.dir_handle = rd.link_dir_handle.value.to_vec();
+                xidmap.new_file_name = rd.link_name_vec.to_vec();
+                aux_file_name = rd.link_name_vec;
+            } else {
+                self.set_event(NFSEvent::MalformedData);
+            };
         } else if r.procedure == NFSPROC3_MKDIR {
             if let Ok((_, rd)) = parse_nfs3_request_mkdir(r.prog_data) {
                 xidmap.file_handle = rd.handle.value.to_vec();
@@ -150,7 +162,7 @@ impl NFSState {
             tx.nfs_version = r.progver as u16;
             tx.file_handle = xidmap.file_handle.to_vec();
 
-            if r.procedure == NFSPROC3_RENAME {
+            if r.procedure == NFSPROC3_RENAME || r.procedure == NFSPROC3_LINK {
                 tx.type_data = Some(NFSTransactionTypeData::RENAME(aux_file_name));
             }
 
@@ -207,8 +219,7 @@ impl NFSState {
                 nfs_status = rd.status;
 
                 SCLogDebug!("LOOKUP handle {:?}", rd.handle);
-                self.namemap
-                    .insert(rd.handle.value.to_vec(), xidmap.file_name.to_vec());
+                self.namemap_insert(&xidmap.dir_handle, &xidmap.file_name, rd.handle.value);
                 resp_handle = rd.handle.value.to_vec();
             } else {
                 self.set_event(NFSEvent::MalformedData);
@@ -221,8 +232,8 @@ impl NFSState {
 
                 if let Some(h) = rd.handle {
                     SCLogDebug!("handle {:?}", h);
-                    self.namemap
-                        .insert(h.value.to_vec(), xidmap.file_name.to_vec());
+                    // the handle of a CREATE request is the directory
+                    self.namemap_insert(&xidmap.file_handle, &xidmap.file_name, h.value);
                     resp_handle = h.value.to_vec();
                 }
             } else {
@@ -255,7 +266,7 @@ impl NFSState {
                             SCLogDebug!("e {:?}", e);
                             if let Some(ref h) = e.handle {
                                 SCLogDebug!("h {:?}", h);
-                                self.namemap.insert(h.value.to_vec(), e.name_vec.to_vec());
+                                self.namemap_insert(&xidmap.file_handle, &e.name_vec, h.value);
                             }
                         }
                     }
@@ -265,6 +276,27 @@ impl NFSState {
             } else {
                 self.set_event(NFSEvent::MalformedData);
             }
+        } else if xidmap.procedure == NFSPROC3_RENAME || xidmap.procedure == NFSPROC3_LINK {
+            if let Ok((_, stat)) = be_u32(r.prog_data) as IResult<&[u8], _> {
+                nfs_status = stat;
+                if stat == NFS3_OK && xidmap.procedure == NFSPROC3_RENAME {
+                    self.namemap_rename(
+                        &xidmap.file_handle,
+                        &xidmap.file_name,
+                        &xidmap.dir_handle,
+                        &xidmap.new_file_name,
+                    );
+                } else if stat == NFS3_OK {
+                    // the object is now (also) known by the new link name
+                    self.namemap_insert(
+                        &xidmap.dir_handle,
+                        &xidmap.new_file_name,
+                        &xidmap.file_handle,
+                    );
+                }
+            } else {
+                self.set_event(NFSEvent::MalformedData);
+            }
         }
         // for all other record types only parse the status
         else {
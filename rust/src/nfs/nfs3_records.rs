@@ -175,6 +175,27 @@ pub fn parse_nfs3_request_rename(i: &[u8]) -> IResult<&[u8], Nfs3RequestRename>
     Ok((i, req))
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct Nfs3RequestLink<'a> {
+    pub handle: Nfs3Handle<'a>,
+    pub link_dir_handle: Nfs3Handle<'a>,
+    pub link_name_vec: Vec<u8>,
+}
+
+pub fn parse_nfs3_request_link(i: &[u8]) -> IResult<&[u8], Nfs3RequestLink> {
+    let (i, handle) = parse_nfs3_handle(i)?;
+    let (i, link_dir_handle) = parse_nfs3_handle(i)?;
+    let (i, link_name_len) = be_u32(i)?;
+    let (i, link_name) = take(link_name_len as usize)(i)?;
+    let (i, _fill_bytes) = rest(i)?;
+    let req = Nfs3RequestLink {
+        handle,
+        link_dir_handle,
+        link_name_vec: link_name.to_vec(),
+    };
+    Ok((i, req))
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Nfs3RequestGetAttr<'a> {
     pub handle: Nfs3Handle<'a>,
@@ -612,6 +633,39 @@ mod tests {
         assert_eq!(request.to_name_vec, br#"am"#.to_vec());
     }
 
+    #[test]
+    fn test_nfs3_request_link() {
+        #[rustfmt::skip]
+        let buf: &[u8] = &[
+        // [handle]
+            0x00, 0x00, 0x00, 0x20, /*handle_len: (32)*/
+            0x00, 0x10, 0x10, 0x85, 0x00, 0x00, 0x03, 0xe7, /*handle*/
+            0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0xb2, 0x5a,
+            0x00, 0x00, 0x00, 0x29, 0x00, 0x0a, 0x00, 0x00,
+            0x00, 0x00, 0xb2, 0x5b, 0x00, 0x00, 0x00, 0x2a,
+        // [link_dir_handle]
+            0x00, 0x00, 0x00, 0x20, /*handle_len: (32)*/
+            0x00, 0x10, 0x10, 0x85, 0x00, 0x00, 0x03, 0xe7, /*handle*/
+            0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0xb2, 0x5a,
+            0x00, 0x00, 0x00, 0x29, 0x00, 0x0a, 0x00, 0x00,
+            0x00, 0x00, 0xb2, 0x5a, 0x00, 0x00, 0x00, 0x29,
+        // [link_name]
+            0x00, 0x00, 0x00, 0x03, /*name_len: (3)*/
+            0x62, 0x2e, 0x6c, /*name: (b.l)*/
+            0x00, /*_fill_bytes*/
+        ];
+
+        let (_, expected_handle) = parse_nfs3_handle(&buf[..36]).unwrap();
+        let (_, expected_link_dir_handle) = parse_nfs3_handle(&buf[36..72]).unwrap();
+
+        let (r, request) = parse_nfs3_request_link(buf).unwrap();
+        assert_eq!(r.len(), 0);
+
+        assert_eq!(request.handle, expected_handle);
+        assert_eq!(request.link_dir_handle, expected_link_dir_handle);
+        assert_eq!(request.link_name_vec, br#"b.l"#.to_vec());
+    }
+
     #[test]
     fn test_nfs3_request_getattr() {
         #[rustfmt::skip]
@@ -149,6 +149,12 @@ impl NFSState {
         tx.nfs_version = r.progver as u16;
         tx.file_handle = xidmap.file_handle.to_vec();
 
+        if procedure == NFSPROC4_RENAME || procedure == NFSPROC4_LINK {
+            tx.type_data = Some(NFSTransactionTypeData::RENAME(
+                xidmap.new_file_name.to_vec(),
+            ));
+        }
+
         tx.auth_type = r.creds_flavor;
         #[allow(clippy::single_match)]
         match r.creds {
@@ -176,8 +182,10 @@ impl NFSState {
         xidmap: &mut NFSRequestXidMap,
     ) {
         let mut last_putfh: Option<&'b [u8]> = None;
+        let mut saved_fh: Option<&'b [u8]> = None;
         let mut main_opcode: u32 = 0;
         let mut aux_opcodes: Vec<u32> = Vec::new();
+        let mut walk = NFSExportWalk::default();
 
         for c in &cr.commands {
             SCLogDebug!("c {:?}", c);
@@ -185,6 +193,20 @@ impl NFSState {
                 Nfs4RequestContent::PutFH(ref rd) => {
                     last_putfh = Some(rd.value);
                     aux_opcodes.push(NFSPROC4_PUTFH);
+                    walk.put_fh(self.export_get(rd.value));
+                }
+                Nfs4RequestContent::PutRootFH => {
+                    walk.put_root_fh();
+                }
+                Nfs4RequestContent::GetFH => {
+                    if let Some(e) = walk.get_fh() {
+                        if xidmap.export_path.is_empty() {
+                            xidmap.export_path = e.to_vec();
+                        }
+                    }
+                }
+                Nfs4RequestContent::SaveFH => {
+                    saved_fh = last_putfh;
                 }
                 Nfs4RequestContent::Read(ref rd) => {
                     SCLogDebug!("READv4: {:?}", rd);
@@ -196,11 +218,16 @@ impl NFSState {
                 }
                 Nfs4RequestContent::Open(ref rd) => {
                     SCLogDebug!("OPENv4: {}", String::from_utf8_lossy(rd.filename));
+                    if let Some(fh) = last_putfh {
+                        xidmap.dir_handle = fh.to_vec();
+                    }
                     xidmap.file_name = rd.filename.to_vec();
+                    walk.open();
                 }
                 Nfs4RequestContent::Lookup(ref rd) => {
                     SCLogDebug!("LOOKUPv4: {}", String::from_utf8_lossy(rd.filename));
                     xidmap.file_name = rd.filename.to_vec();
+                    walk.lookup(rd.filename, |path| self.is_export_path(path));
                 }
                 Nfs4RequestContent::Write(ref rd) => {
                     SCLogDebug!("WRITEv4: {:?}", rd);
@@ -228,6 +255,34 @@ impl NFSState {
                     xidmap.file_name = rd.filename.to_vec();
                     main_opcode = NFSPROC4_CREATE;
                 }
+                Nfs4RequestContent::Rename(ref rd) => {
+                    SCLogDebug!("RENAMEv4: {:?}", rd);
+                    // SAVEFH holds the source directory, the current
+                    // filehandle the target directory
+                    if let Some(fh) = saved_fh {
+                        xidmap.file_handle = fh.to_vec();
+                    }
+                    if let Some(fh) = last_putfh {
+                        xidmap.dir_handle = fh.to_vec();
+                    }
+                    xidmap.file_name = rd.oldname.to_vec();
+                    xidmap.new_file_name = rd.newname.to_vec();
+                    main_opcode = NFSPROC4_RENAME;
+                }
+                Nfs4RequestContent::Link(newname) => {
+                    SCLogDebug!("LINKv4: {}", String::from_utf8_lossy(newname));
+                    // SAVEFH holds the object being linked, the current
+                    // filehandle the target directory
+                    if let Some(fh) = saved_fh {
+                        xidmap.file_handle = fh.to_vec();
+                        self.xidmap_handle2name(xidmap);
+                    }
+                    if let Some(fh) = last_putfh {
+                        xidmap.dir_handle = fh.to_vec();
+                    }
+                    xidmap.new_file_name = newname.to_vec();
+                    main_opcode = NFSPROC4_LINK;
+                }
                 Nfs4RequestContent::Remove(rd) => {
                     SCLogDebug!("REMOVEv4: {:?}", rd);
                     xidmap.file_name = rd.to_vec();
@@ -324,6 +379,7 @@ impl NFSState {
         xidmap: &mut NFSRequestXidMap,
     ) {
         let mut insert_filename_with_getfh = false;
+        let mut insert_export_with_getfh = !xidmap.export_path.is_empty();
         let mut main_opcode_status: u32 = 0;
         let mut main_opcode_status_set: bool = false;
 
@@ -345,6 +401,31 @@ impl NFSState {
                     main_opcode_status = s;
                     main_opcode_status_set = true;
                 }
+                Nfs4ResponseContent::Rename(s) => {
+                    SCLogDebug!("RENAME4: status {}", s);
+                    main_opcode_status = s;
+                    main_opcode_status_set = true;
+                    if s == NFS4_OK {
+                        self.namemap_rename(
+                            &xidmap.file_handle,
+                            &xidmap.file_name,
+                            &xidmap.dir_handle,
+                            &xidmap.new_file_name,
+                        );
+                    }
+                }
+                Nfs4ResponseContent::Link(s) => {
+                    SCLogDebug!("LINK4: status {}", s);
+                    main_opcode_status = s;
+                    main_opcode_status_set = true;
+                    if s == NFS4_OK && !xidmap.file_handle.is_empty() {
+                        self.namemap_insert(
+                            &xidmap.dir_handle,
+                            &xidmap.new_file_name,
+                            &xidmap.file_handle,
+                        );
+                    }
+                }
                 Nfs4ResponseContent::Create(s) => {
                     SCLogDebug!("CREATE4: status {}", s);
                     main_opcode_status = s;
@@ -375,8 +456,11 @@ impl NFSState {
                 }
                 Nfs4ResponseContent::GetFH(_s, Some(ref rd)) => {
                     if insert_filename_with_getfh {
-                        self.namemap
-                            .insert(rd.value.to_vec(), xidmap.file_name.to_vec());
+                        self.namemap_insert(&xidmap.dir_handle, &xidmap.file_name, rd.value);
+                    }
+                    if insert_export_with_getfh {
+                        self.export_root_insert(rd.value, &xidmap.export_path);
+                        insert_export_with_getfh = false;
                     }
                 }
                 Nfs4ResponseContent::PutRootFH(s) => {
@@ -50,6 +50,7 @@ pub enum Nfs4RequestContent<'a> {
     Write(Nfs4RequestWrite<'a>),
     Close(Nfs4StateId<'a>),
     Rename(Nfs4RequestRename<'a>),
+    Link(&'a [u8]),
     Create(Nfs4RequestCreate<'a>),
     OpenConfirm(Nfs4RequestOpenConfirm<'a>),
     Access(u32),
@@ -370,6 +371,10 @@ fn nfs4_req_remove(i: &[u8]) -> IResult<&[u8], Nfs4RequestContent> {
     map(nfs4_parse_nfsstring, Nfs4RequestContent::Remove)(i)
 }
 
+fn nfs4_req_link(i: &[u8]) -> IResult<&[u8], Nfs4RequestContent> {
+    map(nfs4_parse_nfsstring, Nfs4RequestContent::Link)(i)
+}
+
 fn nfs4_req_secinfo_no_name(i: &[u8]) -> IResult<&[u8], Nfs4RequestContent> {
     map(be_u32, Nfs4RequestContent::SecInfoNoName)(i)
 }
@@ -580,6 +585,7 @@ fn parse_request_compound_command(i: &[u8]) -> IResult<&[u8], Nfs4RequestContent
         NFSPROC4_OPEN_CONFIRM => nfs4_req_open_confirm(i)?,
         NFSPROC4_REMOVE => nfs4_req_remove(i)?,
         NFSPROC4_RENAME => nfs4_req_rename(i)?,
+        NFSPROC4_LINK => nfs4_req_link(i)?,
         NFSPROC4_CREATE => nfs4_req_create(i)?,
         NFSPROC4_DELEGRETURN => nfs4_req_delegreturn(i)?,
         NFSPROC4_SETATTR => nfs4_req_setattr(i)?,
@@ -628,6 +634,7 @@ pub enum Nfs4ResponseContent<'a> {
     Lookup(u32),
     SaveFH(u32),
     Rename(u32),
+    Link(u32),
     Write(u32, Option<Nfs4ResponseWrite>),
     Read(u32, Option<Nfs4ResponseRead<'a>>),
     Renew(u32),
@@ -1079,7 +1086,16 @@ fn nfs4_res_remove(i: &[u8]) -> IResult<&[u8], Nfs4ResponseContent> {
 }
 
 fn nfs4_res_rename(i: &[u8]) -> IResult<&[u8], Nfs4ResponseContent> {
-    map(be_u32, Nfs4ResponseContent::Rename)(i)
+    let (i, status) = be_u32(i)?;
+    // change_info of both the source and the target directory
+    let (i, _) = cond(status == 0, take(40_usize))(i)?;
+    Ok((i, Nfs4ResponseContent::Rename(status)))
+}
+
+fn nfs4_res_link(i: &[u8]) -> IResult<&[u8], Nfs4ResponseContent> {
+    let (i, status) = be_u32(i)?;
+    let (i, _) = cond(status == 0, take(20_usize))(i)?;
+    Ok((i, Nfs4ResponseContent::Link(status)))
 }
 
 fn nfs4_res_savefh(i: &[u8]) -> IResult<&[u8], Nfs4ResponseContent> {
@@ -1189,6 +1205,7 @@ fn nfs4_res_compound_command(i: &[u8]) -> IResult<&[u8], Nfs4ResponseContent> {
         NFSPROC4_PUTFH => nfs4_res_putfh(i)?,
         NFSPROC4_SAVEFH => nfs4_res_savefh(i)?,
         NFSPROC4_RENAME => nfs4_res_rename(i)?,
+        NFSPROC4_LINK => nfs4_res_link(i)?,
         NFSPROC4_READDIR => nfs4_res_readdir(i)?,
         NFSPROC4_GETATTR => nfs4_res_getattr(i)?,
         NFSPROC4_SETATTR => nfs4_res_setattr(i)?,
@@ -1517,6 +1534,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nfs4_request_link() {
+        #[rustfmt::skip]
+        let buf: &[u8] = &[
+            0x00, 0x00, 0x00, 0x0b, /*opcode*/
+            0x00, 0x00, 0x00, 0x06, 0x66, 0x69, 0x6c, 0x65, /*newname: (file.l)*/
+            0x2e, 0x6c, 0x00, 0x00,
+        ];
+
+        let (_, newname_buf) = nfs4_parse_nfsstring(&buf[4..]).unwrap();
+
+        let (r, request) = parse_request_compound_command(buf).unwrap();
+        assert_eq!(r.len(), 0);
+        match request {
+            Nfs4RequestContent::Link(newname) => {
+                assert_eq!(newname, newname_buf);
+                assert_eq!(newname, b"file.l");
+            }
+            _ => {
+                panic!("Failure, {:?}", request);
+            }
+        }
+    }
+
     #[test]
     fn test_nfs4_request_putfh() {
         #[rustfmt::skip]
@@ -1799,6 +1840,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nfs4_response_rename_link() {
+        // Operations: RENAME, LINK, PUTFH
+        #[rustfmt::skip]
+        let buf: &[u8] = &[
+            0x00, 0x00, 0x00, 0x00, /*status*/
+            0x00, 0x00, 0x00, 0x00, /*Tag*/
+            0x00, 0x00, 0x00, 0x03, /*ops_cnt*/
+        // RENAME
+            0x00, 0x00, 0x00, 0x1d, /*opcode*/
+            0x00, 0x00, 0x00, 0x00, /*status*/
+            0x00, 0x00, 0x00, 0x00, /*source_cinfo*/
+            0x62, 0x7a, 0x9f, 0x3e, 0x1a, 0x2b, 0x3c, 0x4d,
+            0x62, 0x7a, 0x9f, 0x3e, 0x1a, 0x2b, 0x3c, 0x4e,
+            0x00, 0x00, 0x00, 0x00, /*target_cinfo*/
+            0x62, 0x7a, 0x9f, 0x3e, 0x1a, 0x2b, 0x3c, 0x4d,
+            0x62, 0x7a, 0x9f, 0x3e, 0x1a, 0x2b, 0x3c, 0x4e,
+        // LINK
+            0x00, 0x00, 0x00, 0x0b, /*opcode*/
+            0x00, 0x00, 0x00, 0x00, /*status*/
+            0x00, 0x00, 0x00, 0x00, /*cinfo*/
+            0x62, 0x7a, 0x9f, 0x3e, 0x1a, 0x2b, 0x3c, 0x4e,
+            0x62, 0x7a, 0x9f, 0x3e, 0x1a, 0x2b, 0x3c, 0x4f,
+        // PUTFH
+            0x00, 0x00, 0x00, 0x16, /*opcode*/
+            0x00, 0x00, 0x00, 0x00, /*status*/
+        ];
+
+        let (r, _) = nfs4_res_rename(&buf[16..]).unwrap();
+        assert_eq!(r.len(), 36);
+        let (r, _) = nfs4_res_link(&buf[64..]).unwrap();
+        assert_eq!(r.len(), 8);
+
+        let (_, compound_ops) = parse_nfs4_response_compound(buf).unwrap();
+        assert_eq!(compound_ops.commands.len(), 3);
+        assert_eq!(compound_ops.commands[0], Nfs4ResponseContent::Rename(0));
+        assert_eq!(compound_ops.commands[1], Nfs4ResponseContent::Link(0));
+        assert_eq!(compound_ops.commands[2], Nfs4ResponseContent::PutFH(0));
+
+        // NFS4ERR_EXIST: no change info follows the status
+        let (r, result) = nfs4_res_link(&[0x00, 0x00, 0x00, 0x11]).unwrap();
+        assert_eq!(r.len(), 0);
+        assert_eq!(result, Nfs4ResponseContent::Link(17));
+    }
+
     #[test]
     fn test_nfs4_response_access() {
         #[rustfmt::skip]
@@ -15,6 +15,23 @@
  * 02110-1301, USA.
  */
 
+/* RPC program numbers */
+pub const RPC_PROGRAM_NFS: u32 = 100003;
+pub const RPC_PROGRAM_MOUNT: u32 = 100005;
+
+/* RFC 1813, appendix I 'Mount protocol'. Version 1 is used with NFSv2,
+ * version 3 with NFSv3. */
+pub const MOUNTPROC_NULL: u32 = 0;
+pub const MOUNTPROC_MNT: u32 = 1;
+pub const MOUNTPROC_DUMP: u32 = 2;
+pub const MOUNTPROC_UMNT: u32 = 3;
+pub const MOUNTPROC_UMNTALL: u32 = 4;
+pub const MOUNTPROC_EXPORT: u32 = 5;
+
+pub const MNT3_OK: u32 = 0;
+/// maximum length of the path of an export
+pub const MNTPATHLEN: u32 = 1024;
+
 /* RFC 1813, section '3. Server Procedures' */
 pub const NFSPROC3_NULL: u32 = 0;
 pub const NFSPROC3_GETATTR: u32 = 1;
@@ -0,0 +1,173 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+//! PostgreSQL keywords
+
+use super::parser::PgsqlFEMessage;
+use super::pgsql::{PgsqlAuthMethod, PgsqlTransaction, ALPROTO_PGSQL};
+use crate::conf::conf_get_bool;
+use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::detect::uint::{
+    detect_parse_uint_enum, detect_register_uint_enum, DetectUintData, SCDetectU8Free,
+    SCDetectU8Match,
+};
+use crate::detect::{
+    DetectEngineRegistrar, DetectHelperBufferRegister, DetectHelperKeywordRegister,
+    DetectSignatureSetAppProto, SCSigTableAppLiteElmt, SigMatchAppendSMToList,
+};
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_void};
+use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+detect_sticky_buffer! {
+    mod pgsql_password {
+        name: "pgsql.password",
+        desc: "sticky buffer to match on the PostgreSQL cleartext password",
+        url: "/rules/pgsql-keywords.html#pgsql-password",
+        mpm_desc: "pgsql password",
+        alproto: ALPROTO_PGSQL,
+        dir: STREAM_TOSERVER,
+        tx: PgsqlTransaction,
+        get: pgsql_tx_get_password,
+    }
+}
+
+static mut G_PGSQL_AUTH_METHOD_KW_ID: c_int = 0;
+static mut G_PGSQL_AUTH_METHOD_BUFFER_ID: c_int = 0;
+
+/// The password of a PasswordMessage, when the backend asked for it in
+/// cleartext. The md5 hash of a PasswordMessage is not exposed.
+fn pgsql_tx_get_password(tx: &PgsqlTransaction) -> Option<&[u8]> {
+    if tx.auth_method != Some(PgsqlAuthMethod::Password) {
+        return None;
+    }
+    if let Some(PgsqlFEMessage::PasswordMessage(msg)) = &tx.request {
+        return Some(&msg.payload);
+    }
+    return None;
+}
+
+unsafe extern "C" fn pgsql_parse_auth_method(
+    ustr: *const std::os::raw::c_char,
+) -> *mut DetectUintData<u8> {
+    let ft_name: &CStr = CStr::from_ptr(ustr); //unsafe
+    if let Ok(s) = ft_name.to_str() {
+        if let Some(ctx) = detect_parse_uint_enum::<u8, PgsqlAuthMethod>(s) {
+            let boxed = Box::new(ctx);
+            return Box::into_raw(boxed) as *mut _;
+        }
+    }
+    return std::ptr::null_mut();
+}
+
+unsafe extern "C" fn pgsql_auth_method_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_PGSQL) != 0 {
+        return -1;
+    }
+    let ctx = pgsql_parse_auth_method(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_PGSQL_AUTH_METHOD_KW_ID,
+        ctx,
+        G_PGSQL_AUTH_METHOD_BUFFER_ID,
+    )
+    .is_null()
+    {
+        pgsql_auth_method_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn pgsql_auth_method_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, PgsqlTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    if let Some(method) = tx.auth_method {
+        return SCDetectU8Match(method as u8, ctx);
+    }
+    return 0;
+}
+
+unsafe extern "C" fn pgsql_auth_method_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u8>);
+    SCDetectU8Free(ctx);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectPgsqlRegister() {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"pgsql.auth_method\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the PostgreSQL authentication method\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/pgsql-keywords.html#pgsql-auth-method\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(pgsql_auth_method_match),
+        Setup: pgsql_auth_method_setup,
+        Free: Some(pgsql_auth_method_free),
+        flags: 0,
+    };
+    G_PGSQL_AUTH_METHOD_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u8, PgsqlAuthMethod>(G_PGSQL_AUTH_METHOD_KW_ID);
+    G_PGSQL_AUTH_METHOD_BUFFER_ID = DetectHelperBufferRegister(
+        b"pgsql.auth_method\0".as_ptr() as *const libc::c_char,
+        ALPROTO_PGSQL,
+        STREAM_TOSERVER | STREAM_TOCLIENT,
+    );
+
+    // passwords are not inspected unless explicitly asked for
+    if conf_get_bool("app-layer.protocols.pgsql.detect-passwords") {
+        pgsql_password::register(&mut DetectEngineRegistrar);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pgsql::parser::RegularPacket;
+
+    #[test]
+    fn test_pgsql_auth_method_parse() {
+        let ctx = detect_parse_uint_enum::<u8, PgsqlAuthMethod>("password").unwrap();
+        assert_eq!(ctx.arg1, PgsqlAuthMethod::Password as u8);
+        let ctx = detect_parse_uint_enum::<u8, PgsqlAuthMethod>("scram_sha256").unwrap();
+        assert_eq!(ctx.arg1, PgsqlAuthMethod::ScramSha256 as u8);
+        assert!(detect_parse_uint_enum::<u8, PgsqlAuthMethod>("kerberos").is_none());
+    }
+
+    #[test]
+    fn test_pgsql_tx_get_password() {
+        let mut tx = PgsqlTransaction::new();
+        tx.request = Some(PgsqlFEMessage::PasswordMessage(RegularPacket {
+            identifier: b'p',
+            length: 11,
+            payload: b"secret".to_vec(),
+        }));
+        tx.auth_method = Some(PgsqlAuthMethod::Password);
+        assert_eq!(pgsql_tx_get_password(&tx), Some(&b"secret"[..]));
+        // md5 hashes are not cleartext passwords
+        tx.auth_method = Some(PgsqlAuthMethod::Md5);
+        assert!(pgsql_tx_get_password(&tx).is_none());
+    }
+}
@@ -19,6 +19,7 @@
 
 //! PostgreSQL parser json logger
 
+use crate::detect::EnumString;
 use crate::jsonbuilder::{JsonBuilder, JsonError};
 use crate::pgsql::parser::*;
 use crate::pgsql::pgsql::*;
@@ -29,6 +30,9 @@ pub const PGSQL_LOG_PASSWORDS: u32 = BIT_U32!(0);
 fn log_pgsql(tx: &PgsqlTransaction, flags: u32, js: &mut JsonBuilder) -> Result<(), JsonError> {
     js.open_object("pgsql")?;
     js.set_uint("tx_id", tx.tx_id)?;
+    if let Some(auth_method) = tx.auth_method {
+        js.set_string("auth_method", auth_method.to_str())?;
+    }
     if let Some(request) = &tx.request {
         js.set_object("request", &log_request(request, flags)?)?;
     } else if tx.responses.is_empty() {
@@ -88,7 +92,10 @@ fn log_request(req: &PgsqlFEMessage, flags: u32) -> Result<JsonBuilder, JsonErro
             length: _,
             payload,
         }) => {
-            js.set_string_from_bytes("sasl_response", payload)?;
+            // the SCRAM client proof is as sensitive as a password
+            if flags & PGSQL_LOG_PASSWORDS != 0 {
+                js.set_string_from_bytes("sasl_response", payload)?;
+            }
         }
         PgsqlFEMessage::SimpleQuery(RegularPacket {
             identifier: _,
@@ -25,16 +25,17 @@ use crate::applayer::*;
 use crate::conf::*;
 use crate::core::{ALPROTO_FAILED, ALPROTO_UNKNOWN, IPPROTO_TCP, *};
 use crate::direction::Direction;
-use crate::flow::Flow;
+use crate::flow::{Flow, FLOW_UPGRADE_ACCEPTED, FLOW_UPGRADE_REFUSED, FLOW_UPGRADE_REQUESTED};
 use nom7::{Err, IResult};
 use std;
 use std::collections::VecDeque;
 use std::ffi::CString;
+use suricata_derive::EnumStringU8;
 use suricata_sys::sys::AppProto;
 
 pub const PGSQL_CONFIG_DEFAULT_STREAM_DEPTH: u32 = 0;
 
-static mut ALPROTO_PGSQL: AppProto = ALPROTO_UNKNOWN;
+pub(super) static mut ALPROTO_PGSQL: AppProto = ALPROTO_UNKNOWN;
 
 static mut PGSQL_MAX_TX: usize = 1024;
 
@@ -55,6 +56,20 @@ pub enum PgsqlTxProgress {
     TxFlushedOut,
 }
 
+/// Authentication method requested by the backend, named after the
+/// AuthenticationRequest message type that announces it.
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, EnumStringU8)]
+pub enum PgsqlAuthMethod {
+    // AuthenticationOk right after the startup message
+    Trust = 0,
+    Password = 3,
+    Md5 = 5,
+    Sspi = 9,
+    // SASL, where SCRAM-SHA-256 is the only mechanism PostgreSQL offers
+    ScramSha256 = 10,
+}
+
 #[derive(Debug)]
 pub struct PgsqlTransaction {
     pub tx_id: u64,
@@ -66,6 +81,9 @@ pub struct PgsqlTransaction {
     pub data_row_cnt: u64,
     pub data_size: u64,
 
+    /// Set on the transactions of the authentication exchange
+    pub auth_method: Option<PgsqlAuthMethod>,
+
     tx_data: AppLayerTxData,
 }
 
@@ -91,6 +109,7 @@ impl PgsqlTransaction {
             responses: Vec::<PgsqlBEMessage>::new(),
             data_row_cnt: 0,
             data_size: 0,
+            auth_method: None,
             tx_data: AppLayerTxData::new(),
         }
     }
@@ -154,6 +173,7 @@ pub struct PgsqlState {
     backend_pid: u32,
     state_progress: PgsqlStateProgress,
     tx_index_completed: usize,
+    auth_method: Option<PgsqlAuthMethod>,
 }
 
 impl State<PgsqlTransaction> for PgsqlState {
@@ -184,6 +204,7 @@ impl PgsqlState {
             backend_pid: 0,
             state_progress: PgsqlStateProgress::IdleState,
             tx_index_completed: 0,
+            auth_method: None,
         }
     }
 
@@ -365,10 +386,23 @@ impl PgsqlState {
                 Ok((rem, request)) => {
                     start = rem;
                     let new_state = PgsqlState::request_next_state(&request);
+                    if let PgsqlFEMessage::SSLRequest(_) = request {
+                        if !flow.is_null() {
+                            unsafe {
+                                AppLayerSetTLSUpgrade(flow, FLOW_UPGRADE_REQUESTED);
+                            }
+                        }
+                    }
 
                     if let Some(state) = new_state {
                         self.state_progress = state;
                     };
+                    let auth_method = match request {
+                        PgsqlFEMessage::PasswordMessage(_)
+                        | PgsqlFEMessage::SASLInitialResponse(_)
+                        | PgsqlFEMessage::SASLResponse(_) => self.auth_method,
+                        _ => None,
+                    };
                     // PostreSQL progress states can be represented as a finite state machine
                     // After the connection phase, the backend/ server will be mostly waiting in a state of `ReadyForQuery`, unless
                     // it's processing some request.
@@ -381,6 +415,9 @@ impl PgsqlState {
                     if let Some(tx) = self.find_or_create_tx() {
                         tx.tx_data.updated_ts = true;
                         tx.request = Some(request);
+                        if auth_method.is_some() {
+                            tx.auth_method = auth_method;
+                        }
                         if let Some(state) = new_state {
                             if Self::request_is_complete(state) {
                                 // The request is always complete at this point
@@ -454,13 +491,21 @@ impl PgsqlState {
         match response {
             PgsqlBEMessage::SSLResponse(parser::SSLResponseMessage::SSLAccepted) => {
                 SCLogDebug!("SSL Request accepted");
-                unsafe {
-                    AppLayerRequestProtocolTLSUpgrade(f);
+                if !f.is_null() {
+                    unsafe {
+                        AppLayerSetTLSUpgrade(f, FLOW_UPGRADE_ACCEPTED);
+                        AppLayerRequestProtocolTLSUpgrade(f);
+                    }
                 }
                 Some(PgsqlStateProgress::Finished)
             }
             PgsqlBEMessage::SSLResponse(parser::SSLResponseMessage::SSLRejected) => {
                 SCLogDebug!("SSL Request rejected");
+                if !f.is_null() {
+                    unsafe {
+                        AppLayerSetTLSUpgrade(f, FLOW_UPGRADE_REFUSED);
+                    }
+                }
                 Some(PgsqlStateProgress::SSLRejectedReceived)
             }
             PgsqlBEMessage::AuthenticationSASL(_) => {
@@ -521,6 +566,49 @@ impl PgsqlState {
         }
     }
 
+    /// Authentication method announced by an authentication response
+    ///
+    /// AuthenticationOk only tells of the trust method when no other
+    /// authentication request came before it.
+    fn response_auth_method(response: &PgsqlBEMessage) -> Option<PgsqlAuthMethod> {
+        match response {
+            PgsqlBEMessage::AuthenticationOk(_) => Some(PgsqlAuthMethod::Trust),
+            PgsqlBEMessage::AuthenticationCleartextPassword(_) => Some(PgsqlAuthMethod::Password),
+            PgsqlBEMessage::AuthenticationMD5Password(_) => Some(PgsqlAuthMethod::Md5),
+            PgsqlBEMessage::AuthenticationSSPI(_) => Some(PgsqlAuthMethod::Sspi),
+            PgsqlBEMessage::AuthenticationSASL(_)
+            | PgsqlBEMessage::AuthenticationSASLContinue(_)
+            | PgsqlBEMessage::AuthenticationSASLFinal(_) => Some(PgsqlAuthMethod::ScramSha256),
+            _ => None,
+        }
+    }
+
+    /// NoticeResponse, NotificationResponse and ParameterStatus can be sent by the
+    /// backend at any time, even in the middle of the response to a query
+    fn is_async_response(response: &PgsqlBEMessage) -> bool {
+        matches!(
+            response,
+            PgsqlBEMessage::NoticeResponse(_)
+                | PgsqlBEMessage::NotificationResponse(_)
+                | PgsqlBEMessage::ParameterStatus(_)
+        )
+    }
+
+    /// Whether the backend is still responding to a query
+    fn query_in_progress(&self) -> bool {
+        matches!(
+            self.state_progress,
+            PgsqlStateProgress::SimpleQueryReceived
+                | PgsqlStateProgress::RowDescriptionReceived
+                | PgsqlStateProgress::DataRowReceived
+                | PgsqlStateProgress::CopyOutResponseReceived
+                | PgsqlStateProgress::CopyDataOutReceived
+                | PgsqlStateProgress::CopyDoneReceived
+                | PgsqlStateProgress::CommandCompletedReceived
+                | PgsqlStateProgress::ErrorMessageReceived
+        )
+    }
+
     fn state_based_resp_parsing(
         state: PgsqlStateProgress, input: &[u8],
     ) -> IResult<&[u8], parser::PgsqlBEMessage, PgsqlParseError<&[u8]>> {
@@ -569,12 +657,31 @@ impl PgsqlState {
                 Ok((rem, response)) => {
                     start = rem;
                     SCLogDebug!("Response is {:?}", &response);
+                    if Self::is_async_response(&response) && self.query_in_progress() {
+                        // Keep asynchronous messages with the query transaction, but
+                        // don't let them advance the state: more rows, or the
+                        // ReadyForQuery closing the transaction, may still follow
+                        if let Some(tx) = self.transactions.back_mut() {
+                            tx.tx_data.updated_tc = true;
+                            if tx.tx_res_state == PgsqlTxProgress::TxInit {
+                                tx.tx_res_state = PgsqlTxProgress::TxReceived;
+                            }
+                            tx.responses.push(response);
+                        }
+                        continue;
+                    }
                     let new_state = self.response_process_next_state(&response, flow);
                     if let Some(state) = new_state {
                         self.state_progress = state;
                     }
+                    // the first authentication request gives the method
+                    let auth_method = Self::response_auth_method(&response)
+                        .map(|method| *self.auth_method.get_or_insert(method));
                     if let Some(tx) = self.find_or_create_tx() {
                         tx.tx_data.updated_tc = true;
+                        if auth_method.is_some() {
+                            tx.auth_method = auth_method;
+                        }
                         if tx.tx_res_state == PgsqlTxProgress::TxInit {
                             tx.tx_res_state = PgsqlTxProgress::TxReceived;
                         }
@@ -980,6 +1087,31 @@ mod test {
         // TODO add test for startup request
     }
 
+    #[test]
+    fn test_ssl_response_null_flow() {
+        // an SSL Request
+        let buf: &[u8] = &[0x00, 0x00, 0x00, 0x08, 0x04, 0xd2, 0x16, 0x2f];
+
+        let mut state = PgsqlState::new();
+        state.parse_request(std::ptr::null_mut(), buf);
+        // the upgrade is not recorded without a flow
+        let r = state.parse_response(std::ptr::null_mut(), b"S");
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.state_progress, PgsqlStateProgress::Finished);
+        assert_eq!(state.transactions.len(), 1);
+        assert_eq!(response_types(&state.transactions[0]), vec!["ssl_accepted"]);
+
+        let mut state = PgsqlState::new();
+        state.parse_request(std::ptr::null_mut(), buf);
+        let r = state.parse_response(std::ptr::null_mut(), b"N");
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(
+            state.state_progress,
+            PgsqlStateProgress::SSLRejectedReceived
+        );
+        assert_eq!(response_types(&state.transactions[0]), vec!["ssl_rejected"]);
+    }
+
     #[test]
     fn test_incomplete_request() {
         let mut state = PgsqlState::new();
@@ -1044,4 +1176,197 @@ mod test {
         tx.incr_row_cnt();
         assert_eq!(tx.get_row_cnt(), 1);
     }
+
+    /* N 19 -- notice response, severity NOTICE, message "slow" */
+    const NOTICE: &[u8] = &[
+        0x4e, 0x00, 0x00, 0x00, 0x13, 0x53, 0x4e, 0x4f, 0x54, 0x49, 0x43, 0x45, 0x00, 0x4d, 0x73,
+        0x6c, 0x6f, 0x77, 0x00, 0x00,
+    ];
+    /* Z 5 -- ready for query, idle */
+    const READY_FOR_QUERY: &[u8] = &[0x5a, 0x00, 0x00, 0x00, 0x05, 0x49];
+
+    fn response_types(tx: &PgsqlTransaction) -> Vec<&str> {
+        tx.responses.iter().map(|r| r.to_str()).collect()
+    }
+
+    #[test]
+    fn test_notice_during_select() {
+        let mut state = PgsqlState::new();
+        /* Q 13 -- simple query "select 1" */
+        let buf: &[u8] = &[
+            0x51, 0x00, 0x00, 0x00, 0x0d, 0x73, 0x65, 0x6c, 0x65, 0x63, 0x74, 0x20, 0x31, 0x00,
+        ];
+        state.parse_request(std::ptr::null_mut(), buf);
+        assert_eq!(state.transactions.len(), 1);
+
+        let mut buf: Vec<u8> = Vec::new();
+        // the notice arrives before the first response message
+        buf.extend_from_slice(NOTICE);
+        /* T 26 -- row description, one column "n" */
+        buf.extend_from_slice(&[
+            0x54, 0x00, 0x00, 0x00, 0x1a, 0x00, 0x01, 0x6e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x17, 0x00, 0x04, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+        ]);
+        /* D 11 -- data row "1" */
+        let data_row: &[u8] = &[
+            0x44, 0x00, 0x00, 0x00, 0x0b, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x31,
+        ];
+        buf.extend_from_slice(data_row);
+        // and another one between data rows
+        buf.extend_from_slice(NOTICE);
+        buf.extend_from_slice(data_row);
+        /* C 13 -- command complete "SELECT 2" */
+        buf.extend_from_slice(&[
+            0x43, 0x00, 0x00, 0x00, 0x0d, 0x53, 0x45, 0x4c, 0x45, 0x43, 0x54, 0x20, 0x32, 0x00,
+        ]);
+        buf.extend_from_slice(READY_FOR_QUERY);
+
+        let r = state.parse_response(std::ptr::null_mut(), &buf);
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        assert_eq!(
+            state.state_progress,
+            PgsqlStateProgress::ReadyForQueryReceived
+        );
+        let tx = &state.transactions[0];
+        assert_eq!(tx.tx_res_state, PgsqlTxProgress::TxDone);
+        assert_eq!(
+            response_types(tx),
+            vec![
+                "notice_response",
+                "row_description",
+                "notice_response",
+                "data_row",
+                "command_completed",
+                "ready_for_query"
+            ]
+        );
+        if let PgsqlBEMessage::ConsolidatedDataRow(row) = &tx.responses[3] {
+            assert_eq!(row.row_cnt, 2);
+        } else {
+            panic!("expected a consolidated data row");
+        }
+    }
+
+    #[test]
+    fn test_async_messages_during_copy() {
+        let mut state = PgsqlState::new();
+        /* Q 21 -- simple query "copy t to stdout" */
+        let buf: &[u8] = &[
+            0x51, 0x00, 0x00, 0x00, 0x15, 0x63, 0x6f, 0x70, 0x79, 0x20, 0x74, 0x20, 0x74, 0x6f,
+            0x20, 0x73, 0x74, 0x64, 0x6f, 0x75, 0x74, 0x00,
+        ];
+        state.parse_request(std::ptr::null_mut(), buf);
+        assert_eq!(state.transactions.len(), 1);
+
+        let mut buf: Vec<u8> = Vec::new();
+        /* H 9 -- copy out response, text format, one column */
+        buf.extend_from_slice(&[0x48, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x01, 0x00, 0x00]);
+        /* d 6 -- copy data "a\n" */
+        let copy_data: &[u8] = &[0x64, 0x00, 0x00, 0x00, 0x06, 0x61, 0x0a];
+        buf.extend_from_slice(copy_data);
+        buf.extend_from_slice(NOTICE);
+        /* S 17 -- parameter status TimeZone UTC */
+        buf.extend_from_slice(&[
+            0x53, 0x00, 0x00, 0x00, 0x11, 0x54, 0x69, 0x6d, 0x65, 0x5a, 0x6f, 0x6e, 0x65, 0x00,
+            0x55, 0x54, 0x43, 0x00,
+        ]);
+        buf.extend_from_slice(copy_data);
+        /* c 4 -- copy done */
+        buf.extend_from_slice(&[0x63, 0x00, 0x00, 0x00, 0x04]);
+        /* C 11 -- command complete "COPY 2" */
+        buf.extend_from_slice(&[
+            0x43, 0x00, 0x00, 0x00, 0x0b, 0x43, 0x4f, 0x50, 0x59, 0x20, 0x32, 0x00,
+        ]);
+        buf.extend_from_slice(READY_FOR_QUERY);
+
+        let r = state.parse_response(std::ptr::null_mut(), &buf);
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        assert_eq!(
+            state.state_progress,
+            PgsqlStateProgress::ReadyForQueryReceived
+        );
+        let tx = &state.transactions[0];
+        assert_eq!(tx.tx_res_state, PgsqlTxProgress::TxDone);
+        assert_eq!(
+            response_types(tx),
+            vec![
+                "copy_out_response",
+                "notice_response",
+                "parameter_status",
+                "copy_data_out",
+                "copy_done",
+                "command_completed",
+                "ready_for_query"
+            ]
+        );
+        if let PgsqlBEMessage::ConsolidatedCopyDataOut(row) = &tx.responses[3] {
+            assert_eq!(row.row_cnt, 2);
+        } else {
+            panic!("expected a consolidated copy data out");
+        }
+    }
+
+    /* startup message, protocol 3.0, user "alice" */
+    const STARTUP: &[u8] = &[
+        0x00, 0x00, 0x00, 0x14, 0x00, 0x03, 0x00, 0x00, 0x75, 0x73, 0x65, 0x72, 0x00, 0x61, 0x6c,
+        0x69, 0x63, 0x65, 0x00, 0x00,
+    ];
+    /* R 8 -- authentication ok */
+    const AUTH_OK: &[u8] = &[0x52, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00];
+
+    #[test]
+    fn test_auth_method_password() {
+        let mut state = PgsqlState::new();
+        state.parse_request(std::ptr::null_mut(), STARTUP);
+        /* R 8 -- authentication cleartext password */
+        let buf: &[u8] = &[0x52, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x03];
+        let r = state.parse_response(std::ptr::null_mut(), buf);
+        assert_eq!(r, AppLayerResult::ok());
+        /* p 11 -- password message "secret" */
+        let buf: &[u8] = &[
+            0x70, 0x00, 0x00, 0x00, 0x0b, 0x73, 0x65, 0x63, 0x72, 0x65, 0x74, 0x00,
+        ];
+        state.parse_request(std::ptr::null_mut(), buf);
+        let mut buf: Vec<u8> = Vec::new();
+        buf.extend_from_slice(AUTH_OK);
+        buf.extend_from_slice(READY_FOR_QUERY);
+        let r = state.parse_response(std::ptr::null_mut(), &buf);
+        assert_eq!(r, AppLayerResult::ok());
+
+        assert_eq!(state.transactions.len(), 2);
+        assert_eq!(
+            state.transactions[0].auth_method,
+            Some(PgsqlAuthMethod::Password)
+        );
+        let tx = &state.transactions[1];
+        assert!(matches!(
+            tx.request,
+            Some(PgsqlFEMessage::PasswordMessage(_))
+        ));
+        // the AuthenticationOk closing the exchange is no trust auth
+        assert_eq!(tx.auth_method, Some(PgsqlAuthMethod::Password));
+        assert_eq!(
+            response_types(tx),
+            vec!["authentication_ok", "ready_for_query"]
+        );
+    }
+
+    #[test]
+    fn test_auth_method_trust() {
+        let mut state = PgsqlState::new();
+        state.parse_request(std::ptr::null_mut(), STARTUP);
+        let mut buf: Vec<u8> = Vec::new();
+        buf.extend_from_slice(AUTH_OK);
+        buf.extend_from_slice(READY_FOR_QUERY);
+        let r = state.parse_response(std::ptr::null_mut(), &buf);
+        assert_eq!(r, AppLayerResult::ok());
+
+        assert_eq!(state.transactions.len(), 1);
+        assert_eq!(
+            state.transactions[0].auth_method,
+            Some(PgsqlAuthMethod::Trust)
+        );
+    }
 }
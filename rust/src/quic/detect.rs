@@ -16,9 +16,18 @@
  */
 
 use crate::core::{DetectEngineThreadCtx, STREAM_TOCLIENT, STREAM_TOSERVER};
-use crate::quic::quic::QuicTransaction;
-use std::os::raw::c_void;
+use crate::detect::uint::{detect_match_uint, DetectUintData, SCDetectU32Free, SCDetectU32Parse};
+use crate::detect::{
+    DetectHelperBufferRegister, DetectHelperKeywordRegister, DetectSignatureSetAppProto,
+    SCSigTableAppLiteElmt, SigMatchAppendSMToList,
+};
+use crate::quic::quic::{QuicTransaction, ALPROTO_QUIC};
+use std::os::raw::{c_int, c_void};
 use std::ptr;
+use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+static mut G_QUIC_TOKEN_LENGTH_KW_ID: c_int = 0;
+static mut G_QUIC_TOKEN_LENGTH_BUFFER_ID: c_int = 0;
 
 #[no_mangle]
 pub unsafe extern "C" fn SCQuicTxGetUa(
@@ -144,3 +153,67 @@ pub unsafe extern "C" fn SCQuicTxGetCyuString(
         false
     }
 }
+
+unsafe extern "C" fn quic_detect_token_length_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_QUIC) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_QUIC_TOKEN_LENGTH_KW_ID,
+        ctx,
+        G_QUIC_TOKEN_LENGTH_BUFFER_ID,
+    )
+    .is_null()
+    {
+        quic_detect_token_length_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn quic_detect_token_length_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, QuicTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    // an empty token is still a token, only packets without one do not match
+    if let Some(token) = &tx.header.token {
+        return detect_match_uint(ctx, token.len() as u32) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn quic_detect_token_length_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectQuicRegister() {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"quic.token_length\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the length of the token of QUIC Initial and Retry packets\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/quic-keywords.html#quic-token-length\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(quic_detect_token_length_match),
+        Setup: quic_detect_token_length_setup,
+        Free: Some(quic_detect_token_length_free),
+        flags: 0,
+    };
+    G_QUIC_TOKEN_LENGTH_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_QUIC_TOKEN_LENGTH_BUFFER_ID = DetectHelperBufferRegister(
+        b"quic.token_length\0".as_ptr() as *const libc::c_char,
+        ALPROTO_QUIC,
+        STREAM_TOSERVER | STREAM_TOCLIENT,
+    );
+}
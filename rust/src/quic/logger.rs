@@ -92,6 +92,13 @@ fn log_quic(tx: &QuicTransaction, js: &mut JsonBuilder) -> Result<(), JsonError>
     js.open_object("quic")?;
     if tx.header.ty != QuicType::Short {
         js.set_string("version", String::from(tx.header.version).as_str())?;
+        if let Some(token) = &tx.header.token {
+            js.set_uint("token_length", token.len() as u64)?;
+            if !token.is_empty() {
+                let hash = format!("{:x}", Md5::new().chain(token).finalize());
+                js.set_string("token_hash", &hash)?;
+            }
+        }
 
         if let Some(sni) = &tx.sni {
             js.set_string("sni", &String::from_utf8_lossy(sni))?;
@@ -94,6 +94,9 @@ const QUIC_FLAG_DCID_LEN: u8 = 0x8;
 const QUIC_FLAG_NONCE: u8 = 0x4;
 const QUIC_FLAG_VERSION: u8 = 0x1;
 
+/// Length of the integrity tag ending a Retry packet
+const QUIC_RETRY_TAG_LEN: usize = 16;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct PublicFlags {
     pub is_long: bool,
@@ -171,6 +174,8 @@ pub struct QuicHeader {
     pub dcid: Vec<u8>,
     pub scid: Vec<u8>,
     pub length: u16,
+    /// token of an IETF Initial or Retry packet, possibly empty
+    pub token: Option<Vec<u8>>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -191,6 +196,7 @@ impl QuicHeader {
             dcid,
             scid,
             length: 0,
+            token: None,
         }
     }
 
@@ -244,6 +250,7 @@ impl QuicHeader {
                         dcid: dcid.to_vec(),
                         scid: Vec::new(),
                         length: plength,
+                        token: None,
                     },
                 ));
             } else {
@@ -264,6 +271,7 @@ impl QuicHeader {
                         dcid: Vec::new(),
                         scid: Vec::new(),
                         length: plength,
+                        token: None,
                     },
                 ));
             } else {
@@ -343,6 +351,7 @@ impl QuicHeader {
             };
 
             let mut has_length = false;
+            let mut token = None;
             let rest = match ty {
                 QuicType::Initial => {
                     if version.is_gquic() {
@@ -352,13 +361,17 @@ impl QuicHeader {
                         rest
                     } else {
                         let (rest, token_length) = quic_var_uint(rest)?;
-                        let (rest, _token) = take(token_length as usize)(rest)?;
+                        let (rest, token_buf) = take(token_length as usize)(rest)?;
+                        token = Some(token_buf.to_vec());
                         has_length = true;
                         rest
                     }
                 }
                 QuicType::Retry => {
                     // opaque retry token and 16 bytes retry integrity tag
+                    if !version.is_gquic() && rest.len() >= QUIC_RETRY_TAG_LEN {
+                        token = Some(rest[..rest.len() - QUIC_RETRY_TAG_LEN].to_vec());
+                    }
                     &rest[rest.len()..]
                 }
                 _ => rest,
@@ -389,6 +402,7 @@ impl QuicHeader {
                     dcid,
                     scid,
                     length,
+                    token,
                 },
             ))
         }
@@ -446,6 +460,7 @@ mod tests {
                     .unwrap()
                     .to_vec(),
                 length: 1154,
+                token: Some(Vec::new()),
             },
             value
         );
@@ -469,6 +484,7 @@ mod tests {
                 dcid: hex::decode("05cad2cc06c4d0e4").unwrap().to_vec(),
                 scid: Vec::new(),
                 length: 1042,
+                token: None,
             },
             header
         );
@@ -32,7 +32,7 @@ use std::ffi::CString;
 use suricata_sys::sys::AppProto;
 use tls_parser::TlsExtensionType;
 
-static mut ALPROTO_QUIC: AppProto = ALPROTO_UNKNOWN;
+pub(super) static mut ALPROTO_QUIC: AppProto = ALPROTO_UNKNOWN;
 
 const DEFAULT_DCID_LEN: usize = 16;
 const PKT_NUM_BUF_MAX_LEN: usize = 4;
@@ -323,6 +323,28 @@ impl QuicState {
         self.transactions.push_back(tx);
     }
 
+    /// Derive the initial keys from the destination connection id chosen
+    /// by the client. A Retry discards them, so they are derived again
+    /// from the new connection id of the next Initial.
+    fn update_initial_keys(&mut self, header: &QuicHeader) {
+        if self.keys.is_none() {
+            self.keys = quic_keys_initial(u32::from(header.version), &header.dcid);
+        }
+    }
+
+    fn handle_retry(&mut self) {
+        // a retry packet discards the current keys, client will resend an initial packet with new keys
+        self.hello_ts = false;
+        self.keys = None;
+        // the client restarts the handshake, so crypto data sent before
+        // the retry must not be reassembled with the new one
+        self.crypto_frag_ts.clear();
+        self.crypto_fraglen_ts = 0;
+        // RFC 9000 17.2.5.2 After the client has received and processed an Initial or Retry packet
+        // from the server, it MUST discard any subsequent Retry packets that it receives.
+        self.has_retried = true;
+    }
+
     fn parse(&mut self, input: &[u8], to_server: bool) -> bool {
         // so as to loop over multiple quic headers in one packet
         let mut buf = input;
@@ -339,19 +361,14 @@ impl QuicState {
                     }
 
                     // unprotect/decrypt packet
-                    if self.keys.is_none() && header.ty == QuicType::Initial {
-                        self.keys = quic_keys_initial(u32::from(header.version), &header.dcid);
+                    if header.ty == QuicType::Initial {
+                        self.update_initial_keys(&header);
                     } else if !to_server
                         && self.keys.is_some()
                         && header.ty == QuicType::Retry
                         && !self.has_retried
                     {
-                        // a retry packet discards the current keys, client will resend an initial packet with new keys
-                        self.hello_ts = false;
-                        self.keys = None;
-                        // RFC 9000 17.2.5.2 After the client has received and processed an Initial or Retry packet
-                        // from the server, it MUST discard any subsequent Retry packets that it receives.
-                        self.has_retried = true;
+                        self.handle_retry();
                     }
                     // header.length was checked against rest.len() during parsing
                     let (mut framebuf, next_buf) = rest.split_at(header.length.into());
@@ -587,3 +604,77 @@ pub unsafe extern "C" fn SCRegisterQuicParser() {
         SCLogDebug!("Protocol detector and parser disabled for quic.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quic::parser::{PublicFlags, QuicVersion};
+
+    const ORIG_DCID: [u8; 8] = [0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+    const RETRY_SCID: [u8; 8] = [0xf0, 0x67, 0xa5, 0x50, 0x2a, 0x42, 0x62, 0xb5];
+
+    // QUIC v1 Retry from the server, with the new connection id as source
+    const RETRY: [u8; 43] = [
+        0xf0, 0x00, 0x00, 0x00, 0x01, 0x00, 0x08, 0xf0, 0x67, 0xa5, 0x50, 0x2a, 0x42, 0x62, 0xb5,
+        0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x04, 0xa2, 0x65, 0xba, 0x2e, 0xff, 0x4d, 0x82, 0x90, 0x58,
+        0xfb, 0x3f, 0x0f, 0x24, 0x96, 0xba, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_quic_retry_resets_initial_state() {
+        let mut state = QuicState::new();
+        state.keys = quic_keys_initial(1, &ORIG_DCID);
+        state.crypto_frag_ts = vec![0x01, 0x00, 0x01, 0xfc];
+        state.crypto_fraglen_ts = 4;
+
+        assert!(state.parse(&RETRY, false));
+        assert!(state.keys.is_none());
+        assert!(state.crypto_frag_ts.is_empty());
+        assert_eq!(state.crypto_fraglen_ts, 0);
+        assert!(state.has_retried);
+
+        // a second retry must be ignored
+        state.keys = quic_keys_initial(1, &RETRY_SCID);
+        state.crypto_frag_ts = vec![0x01, 0x00, 0x01, 0xfc];
+        assert!(state.parse(&RETRY, false));
+        assert!(state.keys.is_some());
+        assert!(!state.crypto_frag_ts.is_empty());
+    }
+
+    #[test]
+    fn test_quic_retry_token() {
+        let mut state = QuicState::new();
+        assert!(state.parse(&RETRY, false));
+        let tx = state.transactions.back().unwrap();
+        assert_eq!(tx.header.ty, QuicType::Retry);
+        // token is what is left before the 16 bytes integrity tag
+        assert_eq!(tx.header.token.as_deref(), Some(&RETRY[15..27]));
+    }
+
+    #[test]
+    fn test_quic_initial_after_retry_new_keys() {
+        let mut state = QuicState::new();
+        let initial = QuicHeader {
+            flags: PublicFlags::new(0xc3),
+            ty: QuicType::Initial,
+            version: QuicVersion(1),
+            version_buf: vec![0x00, 0x00, 0x00, 0x01],
+            dcid: ORIG_DCID.to_vec(),
+            scid: Vec::new(),
+            length: 0,
+            token: None,
+        };
+        state.update_initial_keys(&initial);
+        assert!(state.keys.is_some());
+
+        // the Initial after the retry uses the new connection id
+        assert!(state.parse(&RETRY, false));
+        assert!(state.keys.is_none());
+        let retried = QuicHeader {
+            dcid: RETRY_SCID.to_vec(),
+            ..initial
+        };
+        state.update_initial_keys(&retried);
+        assert!(state.keys.is_some());
+    }
+}
@@ -0,0 +1,173 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+use super::parser::NegotiationFromServer;
+use super::rdp::{RdpTransaction, RdpTransactionItem, ALPROTO_RDP};
+use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
+use crate::detect::uint::{
+    detect_match_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    SCDetectU32Free, SCDetectU32Parse,
+};
+use crate::detect::{
+    DetectHelperBufferRegister, DetectHelperKeywordRegister, DetectSignatureSetAppProto,
+    SCSigTableAppLiteElmt, SigMatchAppendSMToList,
+};
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_void};
+use suricata_derive::EnumStringU32;
+use suricata_sys::sys::{DetectEngineCtx, Signature};
+
+/// Protocol selected by the server, named as in the eve log.
+#[derive(Clone, Debug, EnumStringU32)]
+#[repr(u32)]
+pub enum RdpSelectedProtocol {
+    Rdp = 0,
+    Ssl = 1,
+    Hybrid = 2,
+    RdsTls = 4,
+    HybridEx = 8,
+}
+
+static mut G_RDP_REQUESTED_PROTOCOLS_KW_ID: c_int = 0;
+static mut G_RDP_REQUESTED_PROTOCOLS_BUFFER_ID: c_int = 0;
+static mut G_RDP_PROTOCOL_KW_ID: c_int = 0;
+static mut G_RDP_PROTOCOL_BUFFER_ID: c_int = 0;
+
+unsafe extern "C" fn rdp_detect_requested_protocols_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_RDP) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_RDP_REQUESTED_PROTOCOLS_KW_ID,
+        ctx,
+        G_RDP_REQUESTED_PROTOCOLS_BUFFER_ID,
+    )
+    .is_null()
+    {
+        rdp_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn rdp_detect_requested_protocols_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, RdpTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let RdpTransactionItem::X224ConnectionRequest(x224) = &tx.item {
+        if let Some(req) = &x224.negotiation_request {
+            return detect_match_uint(ctx, req.protocols.bits()) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn rdp_detect_protocol_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_RDP) != 0 {
+        return -1;
+    }
+    let ctx = rdp_parse_protocol(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(de, s, G_RDP_PROTOCOL_KW_ID, ctx, G_RDP_PROTOCOL_BUFFER_ID).is_null()
+    {
+        rdp_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe fn rdp_parse_protocol(ustr: *const std::os::raw::c_char) -> *mut DetectUintData<u32> {
+    let ft_name: &CStr = CStr::from_ptr(ustr); //unsafe
+    if let Ok(s) = ft_name.to_str() {
+        if let Some(ctx) = detect_parse_uint_enum::<u32, RdpSelectedProtocol>(s) {
+            let boxed = Box::new(ctx);
+            return Box::into_raw(boxed) as *mut _;
+        }
+    }
+    return std::ptr::null_mut();
+}
+
+unsafe extern "C" fn rdp_detect_protocol_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, RdpTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let RdpTransactionItem::X224ConnectionConfirm(x224) = &tx.item {
+        if let Some(NegotiationFromServer::Response(resp)) = &x224.negotiation_from_server {
+            return detect_match_uint(ctx, resp.protocol.clone() as u32) as c_int;
+        }
+    }
+    return 0;
+}
+
+unsafe extern "C" fn rdp_detect_u32_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectRdpRegister() {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"rdp.requested_protocols\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the protocols requested by the RDP client\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/rdp-keywords.html#rdp-requested-protocols\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(rdp_detect_requested_protocols_match),
+        Setup: rdp_detect_requested_protocols_setup,
+        Free: Some(rdp_detect_u32_free),
+        flags: 0,
+    };
+    G_RDP_REQUESTED_PROTOCOLS_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_RDP_REQUESTED_PROTOCOLS_BUFFER_ID = DetectHelperBufferRegister(
+        b"rdp.requested_protocols\0".as_ptr() as *const libc::c_char,
+        ALPROTO_RDP,
+        STREAM_TOSERVER,
+    );
+
+    let kw = SCSigTableAppLiteElmt {
+        name: b"rdp.protocol\0".as_ptr() as *const libc::c_char,
+        desc: b"match on the protocol selected by the RDP server\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/rdp-keywords.html#rdp-protocol\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(rdp_detect_protocol_match),
+        Setup: rdp_detect_protocol_setup,
+        Free: Some(rdp_detect_u32_free),
+        flags: 0,
+    };
+    G_RDP_PROTOCOL_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u32, RdpSelectedProtocol>(G_RDP_PROTOCOL_KW_ID);
+    G_RDP_PROTOCOL_BUFFER_ID = DetectHelperBufferRegister(
+        b"rdp.protocol\0".as_ptr() as *const libc::c_char,
+        ALPROTO_RDP,
+        STREAM_TOCLIENT,
+    );
+}
@@ -87,6 +87,24 @@ fn x224_req_to_json(x224: &X224ConnectionRequest, js: &mut JsonBuilder) -> Resul
             }
             js.close()?;
         }
+        js.open_array("requested_protocols")?;
+        if req.protocols.is_empty() {
+            // standard RDP security only
+            js.append_string("rdp")?;
+        }
+        if req.protocols.contains(ProtocolFlags::PROTOCOL_SSL) {
+            js.append_string("ssl")?;
+        }
+        if req.protocols.contains(ProtocolFlags::PROTOCOL_HYBRID) {
+            js.append_string("hybrid")?;
+        }
+        if req.protocols.contains(ProtocolFlags::PROTOCOL_RDSTLS) {
+            js.append_string("rds_tls")?;
+        }
+        if req.protocols.contains(ProtocolFlags::PROTOCOL_HYBRID_EX) {
+            js.append_string("hybrid_ex")?;
+        }
+        js.close()?;
     }
 
     Ok(())
@@ -29,7 +29,14 @@ use std;
 use std::collections::VecDeque;
 use tls_parser::{parse_tls_plaintext, TlsMessage, TlsMessageHandshake, TlsRecordType};
 
-static mut ALPROTO_RDP: AppProto = ALPROTO_UNKNOWN;
+pub(super) static mut ALPROTO_RDP: AppProto = ALPROTO_UNKNOWN;
+
+#[derive(FromPrimitive, Debug, AppLayerEvent)]
+pub enum RdpEvent {
+    /// the client requested NLA, the server selected TLS or standard RDP
+    /// security
+    NlaDowngrade,
+}
 
 //
 // transactions
@@ -71,6 +78,10 @@ impl RdpTransaction {
             tx_data: AppLayerTxData::new(),
         }
     }
+
+    fn set_event(&mut self, event: RdpEvent) {
+        self.tx_data.set_event(event as u8);
+    }
 }
 
 unsafe extern "C" fn rdp_state_get_tx(
@@ -111,6 +122,8 @@ pub struct RdpState {
     transactions: VecDeque<RdpTransaction>,
     tls_parsing: bool,
     bypass_parsing: bool,
+    /// protocols of the client negotiation request
+    requested_protocols: Option<ProtocolFlags>,
 }
 
 impl State<RdpTransaction> for RdpState {
@@ -131,6 +144,7 @@ impl RdpState {
             transactions: VecDeque::new(),
             tls_parsing: false,
             bypass_parsing: false,
+            requested_protocols: None,
         }
     }
 
@@ -203,6 +217,9 @@ impl RdpState {
                         match t123.child {
                             // X.224 connection request
                             T123TpktChild::X224ConnectionRequest(x224) => {
+                                if let Some(ref req) = x224.negotiation_request {
+                                    self.requested_protocols = Some(req.protocols);
+                                }
                                 let tx =
                                     self.new_tx(RdpTransactionItem::X224ConnectionRequest(x224));
                                 self.transactions.push_back(tx);
@@ -317,8 +334,12 @@ impl RdpState {
                         match t123.child {
                             // X.224 connection confirm
                             T123TpktChild::X224ConnectionConfirm(x224) => {
-                                let tx =
+                                let downgrade = is_nla_downgrade(self.requested_protocols, &x224);
+                                let mut tx =
                                     self.new_tx(RdpTransactionItem::X224ConnectionConfirm(x224));
+                                if downgrade {
+                                    tx.set_event(RdpEvent::NlaDowngrade);
+                                }
                                 self.transactions.push_back(tx);
                             }
 
@@ -373,6 +394,19 @@ impl RdpState {
     }
 }
 
+/// Whether the server selected a protocol without Network Level
+/// Authentication (CredSSP) while the client requested it.
+fn is_nla_downgrade(requested: Option<ProtocolFlags>, x224: &X224ConnectionConfirm) -> bool {
+    let nla = ProtocolFlags::PROTOCOL_HYBRID | ProtocolFlags::PROTOCOL_HYBRID_EX;
+    match (requested, &x224.negotiation_from_server) {
+        (Some(requested), Some(NegotiationFromServer::Response(resp))) => {
+            requested.intersects(nla)
+                && matches!(resp.protocol, Protocol::ProtocolRdp | Protocol::ProtocolSsl)
+        }
+        _ => false,
+    }
+}
+
 extern "C" fn rdp_state_new(_orig_state: *mut std::os::raw::c_void, _orig_proto: AppProto) -> *mut std::os::raw::c_void {
     let state = RdpState::new();
     let boxed = Box::new(state);
@@ -476,8 +510,8 @@ pub unsafe extern "C" fn SCRegisterRdpParser() {
         tx_comp_st_ts: 1,
         tx_comp_st_tc: 1,
         tx_get_progress: rdp_tx_get_progress,
-        get_eventinfo: None,
-        get_eventinfo_byid: None,
+        get_eventinfo: Some(RdpEvent::get_event_info),
+        get_eventinfo_byid: Some(RdpEvent::get_event_info_by_id),
         localstorage_new: None,
         localstorage_free: None,
         get_tx_files: None,
@@ -592,6 +626,46 @@ mod tests {
         assert_eq!(AppLayerResult::err(), state.parse_tc(buf));
     }
 
+    #[test]
+    fn test_nla_downgrade() {
+        // negotiation request for SSL and HYBRID
+        let req: &[u8] = &[
+            0x03, 0x00, 0x00, 0x13, 0x0e, 0xe0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x08,
+            0x00, 0x03, 0x00, 0x00, 0x00,
+        ];
+        // negotiation response selecting SSL
+        let resp: &[u8] = &[
+            0x03, 0x00, 0x00, 0x13, 0x0e, 0xd0, 0x00, 0x00, 0x12, 0x34, 0x00, 0x02, 0x00, 0x08,
+            0x00, 0x01, 0x00, 0x00, 0x00,
+        ];
+        let mut state = RdpState::new();
+        assert_eq!(AppLayerResult::ok(), state.parse_ts(req));
+        assert_eq!(
+            Some(ProtocolFlags::PROTOCOL_SSL | ProtocolFlags::PROTOCOL_HYBRID),
+            state.requested_protocols
+        );
+        assert_eq!(AppLayerResult::ok(), state.parse_tc(resp));
+        assert_eq!(2, state.transactions.len());
+        let x224 = match &state.transactions[1].item {
+            RdpTransactionItem::X224ConnectionConfirm(x224) => x224,
+            _ => panic!("expected a connection confirm"),
+        };
+        assert!(is_nla_downgrade(state.requested_protocols, x224));
+        // no negotiation request, or no NLA requested
+        assert!(!is_nla_downgrade(None, x224));
+        assert!(!is_nla_downgrade(Some(ProtocolFlags::PROTOCOL_SSL), x224));
+
+        // the server selects HYBRID
+        let x224 = X224ConnectionConfirm {
+            negotiation_from_server: Some(NegotiationFromServer::Response(NegotiationResponse {
+                flags: NegotiationResponseFlags::empty(),
+                protocol: Protocol::ProtocolHybrid,
+            })),
+            ..x224.clone()
+        };
+        assert!(!is_nla_downgrade(state.requested_protocols, &x224));
+    }
+
     #[test]
     fn test_state_new_tx() {
         let mut state = RdpState::new();
@@ -21,70 +21,58 @@ use super::parser::RFBSecurityResultStatus;
 use super::rfb::{RFBTransaction, ALPROTO_RFB};
 use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
 use crate::detect::uint::{
-    detect_match_uint, detect_parse_uint_enum, DetectUintData, SCDetectU32Free, SCDetectU32Parse,
+    detect_match_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    SCDetectU32Free, SCDetectU32Parse,
 };
 use crate::detect::{
-    helper_keyword_register_sticky_buffer, DetectHelperBufferMpmRegister,
-    DetectHelperBufferRegister, DetectHelperGetData, DetectHelperKeywordRegister,
+    DetectEngineRegistrar, DetectHelperBufferRegister, DetectHelperKeywordRegister,
     DetectSignatureSetAppProto, SCSigTableAppLiteElmt, SigMatchAppendSMToList,
-    SigTableElmtStickyBuffer,
 };
 use std::ffi::CStr;
 use std::os::raw::{c_int, c_void};
-use std::ptr;
-use suricata_sys::sys::{DetectEngineCtx, SCDetectBufferSetActiveList, Signature};
+use suricata_sys::sys::{DetectEngineCtx, Signature};
 
-unsafe extern "C" fn rfb_name_get_data(
-    tx: *const c_void, _flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
-) -> bool {
-    let tx = cast_pointer!(tx, RFBTransaction);
-    if let Some(ref r) = tx.tc_server_init {
-        let p = &r.name;
-        if !p.is_empty() {
-            *buffer = p.as_ptr();
-            *buffer_len = p.len() as u32;
-            return true;
-        }
+detect_sticky_buffer! {
+    mod rfb_name {
+        name: "rfb.name",
+        desc: "sticky buffer to match on the RFB desktop name",
+        url: "/rules/rfb-keywords.html#rfb-name",
+        mpm_desc: "rfb name",
+        alproto: ALPROTO_RFB,
+        dir: STREAM_TOCLIENT,
+        tx: RFBTransaction,
+        get: |tx| {
+            tx.tc_server_init
+                .as_ref()
+                .map(|r| r.name.as_slice())
+                .filter(|p| !p.is_empty())
+        },
     }
-
-    *buffer = ptr::null();
-    *buffer_len = 0;
-    return false;
 }
 
-unsafe extern "C" fn rfb_name_get(
-    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
-    tx: *const c_void, list_id: c_int,
-) -> *mut c_void {
-    return DetectHelperGetData(
-        de,
-        transforms,
-        flow,
-        flow_flags,
-        tx,
-        list_id,
-        rfb_name_get_data,
-    );
+detect_sticky_buffer! {
+    mod rfb_failure_reason {
+        name: "rfb.failure_reason",
+        desc: "sticky buffer to match on the RFB security failure reason",
+        url: "/rules/rfb-keywords.html#rfb-failure-reason",
+        mpm_desc: "rfb failure reason",
+        alproto: ALPROTO_RFB,
+        dir: STREAM_TOCLIENT,
+        tx: RFBTransaction,
+        get: |tx| {
+            tx.tc_failure_reason
+                .as_ref()
+                .map(|r| r.reason_string.as_bytes())
+                .filter(|p| !p.is_empty())
+        },
+    }
 }
 
-static mut G_RFB_NAME_BUFFER_ID: c_int = 0;
 static mut G_RFB_SEC_TYPE_KW_ID: c_int = 0;
 static mut G_RFB_SEC_TYPE_BUFFER_ID: c_int = 0;
 static mut G_RFB_SEC_RESULT_KW_ID: c_int = 0;
 static mut G_RFB_SEC_RESULT_BUFFER_ID: c_int = 0;
 
-unsafe extern "C" fn rfb_name_setup(
-    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
-) -> c_int {
-    if DetectSignatureSetAppProto(s, ALPROTO_RFB) != 0 {
-        return -1;
-    }
-    if SCDetectBufferSetActiveList(de, s, G_RFB_NAME_BUFFER_ID) < 0 {
-        return -1;
-    }
-    return 0;
-}
-
 unsafe extern "C" fn rfb_sec_type_setup(
     de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
 ) -> c_int {
@@ -190,20 +178,8 @@ unsafe extern "C" fn rfb_sec_result_free(_de: *mut c_void, ctx: *mut c_void) {
 
 #[no_mangle]
 pub unsafe extern "C" fn SCDetectRfbRegister() {
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("rfb.name"),
-        desc: String::from("sticky buffer to match on the RFB desktop name"),
-        url: String::from("/rules/rfb-keywords.html#rfb-name"),
-        setup: rfb_name_setup,
-    };
-    let _g_rfb_name_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_RFB_NAME_BUFFER_ID = DetectHelperBufferMpmRegister(
-        b"rfb.name\0".as_ptr() as *const libc::c_char,
-        b"rfb name\0".as_ptr() as *const libc::c_char,
-        ALPROTO_RFB,
-        STREAM_TOCLIENT,
-        rfb_name_get,
-    );
+    rfb_name::register(&mut DetectEngineRegistrar);
+    rfb_failure_reason::register(&mut DetectEngineRegistrar);
     let kw = SCSigTableAppLiteElmt {
         name: b"rfb.sectype\0".as_ptr() as *const libc::c_char,
         desc: b"match RFB security type\0".as_ptr() as *const libc::c_char,
@@ -229,6 +205,7 @@ pub unsafe extern "C" fn SCDetectRfbRegister() {
         flags: 0,
     };
     G_RFB_SEC_RESULT_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u32, RFBSecurityResultStatus>(G_RFB_SEC_RESULT_KW_ID);
     G_RFB_SEC_RESULT_BUFFER_ID = DetectHelperBufferRegister(
         b"rfb.secresult\0".as_ptr() as *const libc::c_char,
         ALPROTO_RFB,
@@ -239,7 +216,9 @@ pub unsafe extern "C" fn SCDetectRfbRegister() {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::detect::sticky_buffer_tx_data;
     use crate::detect::uint::DetectUintMode;
+    use crate::rfb::parser::{parse_failure_reason, parse_server_init};
 
     #[test]
     fn test_rfb_parse_sec_result() {
@@ -248,4 +227,42 @@ mod test {
         assert_eq!(ctx.mode, DetectUintMode::DetectUintModeEqual);
         assert!(detect_parse_uint_enum::<u32, RFBSecurityResultStatus>("invalidopt").is_none());
     }
+
+    #[test]
+    fn test_rfb_sticky_buffers() {
+        let mut tx = RFBTransaction::new();
+        assert_eq!(sticky_buffer_tx_data(rfb_name::get_tx_data, &tx), None);
+        assert_eq!(
+            sticky_buffer_tx_data(rfb_failure_reason::get_tx_data, &tx),
+            None
+        );
+
+        let mut buf = vec![
+            0x05, 0x00, 0x03, 0x20, /* width, height */
+            0x20, 0x18, 0x00, 0x01, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x10, 0x08, 0x00, 0x00,
+            0x00, 0x00, /* pixel format */
+            0x00, 0x00, 0x00, 0x00, /* name length */
+        ];
+        tx.tc_server_init = Some(parse_server_init(&buf).unwrap().1);
+        // an empty name is no buffer
+        assert_eq!(sticky_buffer_tx_data(rfb_name::get_tx_data, &tx), None);
+        buf[23] = 4;
+        buf.extend_from_slice(b"desk");
+        tx.tc_server_init = Some(parse_server_init(&buf).unwrap().1);
+        assert_eq!(
+            sticky_buffer_tx_data(rfb_name::get_tx_data, &tx),
+            Some(&b"desk"[..])
+        );
+
+        tx.tc_failure_reason = Some(parse_failure_reason(b"\x00\x00\x00\x00").unwrap().1);
+        assert_eq!(
+            sticky_buffer_tx_data(rfb_failure_reason::get_tx_data, &tx),
+            None
+        );
+        tx.tc_failure_reason = Some(parse_failure_reason(b"\x00\x00\x00\x08too many").unwrap().1);
+        assert_eq!(
+            sticky_buffer_tx_data(rfb_failure_reason::get_tx_data, &tx),
+            Some(&b"too many"[..])
+        );
+    }
 }
@@ -92,8 +92,13 @@ pub struct SecurityResult {
     pub status: u32,
 }
 
+/// Longest failure reason kept, a larger declared length is not waited for
+pub const RFB_MAX_FAILURE_REASON_LEN: u32 = 4096;
+
 pub struct FailureReason {
     pub reason_string: String,
+    /// The declared length was above RFB_MAX_FAILURE_REASON_LEN
+    pub oversized: bool,
 }
 
 pub struct VncAuth {
@@ -179,11 +184,24 @@ pub fn parse_security_result(i: &[u8]) -> IResult<&[u8], SecurityResult> {
 
 pub fn parse_failure_reason(i: &[u8]) -> IResult<&[u8], FailureReason> {
     let (i, reason_length) = be_u32(i)?;
+    if reason_length > RFB_MAX_FAILURE_REASON_LEN {
+        // keep what we have so far instead of waiting for the whole string
+        let len = std::cmp::min(i.len(), RFB_MAX_FAILURE_REASON_LEN as usize);
+        let (reason_string, i) = i.split_at(len);
+        return Ok((
+            i,
+            FailureReason {
+                reason_string: String::from_utf8_lossy(reason_string).to_string(),
+                oversized: true,
+            },
+        ));
+    }
     let (i, reason_string) = map_res(take(reason_length as usize), str::from_utf8)(i)?;
     Ok((
         i,
         FailureReason {
             reason_string: reason_string.to_string(),
+            oversized: false,
         },
     ))
 }
@@ -405,4 +423,31 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parse_failure_reason() {
+        let buf = [
+            0x00, 0x00, 0x00, 0x04, /* Reason length: 4 */
+            0x66, 0x61, 0x69, 0x6c, /* Reason: "fail" */
+        ];
+        let (remainder, reason) = parse_failure_reason(&buf).unwrap();
+        assert!(remainder.is_empty());
+        assert_eq!(reason.reason_string, "fail");
+        assert!(!reason.oversized);
+
+        // a complete reason is still waited for
+        assert!(matches!(
+            parse_failure_reason(&buf[..6]),
+            Err(Err::Incomplete(_))
+        ));
+
+        let buf = [
+            0xff, 0xff, 0xff, 0xff, /* Reason length: 4GB */
+            0x66, 0x61, 0x69, 0x6c, /* Reason: "fail" */
+        ];
+        let (remainder, reason) = parse_failure_reason(&buf).unwrap();
+        assert!(remainder.is_empty());
+        assert_eq!(reason.reason_string, "fail");
+        assert!(reason.oversized);
+    }
 }
@@ -39,6 +39,7 @@ pub enum RFBEvent {
     UnknownSecurityResult,
     MalformedMessage,
     ConfusedState,
+    FailureReasonTooLong,
 }
 
 #[derive(AppLayerFrameType)]
@@ -670,10 +671,16 @@ impl RFBState {
                     match parser::parse_failure_reason(current) {
                         Ok((_rem, request)) => {
                             if let Some(current_transaction) = self.get_current_tx() {
+                                if request.oversized {
+                                    current_transaction.set_event(RFBEvent::FailureReasonTooLong);
+                                }
                                 current_transaction.tc_failure_reason = Some(request);
+                                // the server closes the connection after the reason
+                                current_transaction.complete = true;
                             } else {
                                 debug_validate_fail!("no transaction set at failure reason stage");
                             }
+                            self.state = parser::RFBGlobalState::Skip;
                             return AppLayerResult::ok();
                         }
                         Err(Err::Incomplete(_)) => {
@@ -1028,4 +1035,62 @@ mod test {
         ok_state = parser::RFBGlobalState::Skip;
         assert_eq!(init_state.state, ok_state);
     }
+
+    #[test]
+    fn test_rfb_failure_reason_oversized() {
+        let mut state = RFBState::new();
+
+        let buf: &[u8] = &[
+            0x52, 0x46, 0x42, 0x20, 0x30, 0x30, 0x33, 0x2e, 0x30, 0x30, 0x38, 0x0a,
+            0x01, /* Number of security types: 1 */
+            0x02, /* Security type: VNC (2) */
+            0x02, /* Security type selected: VNC (2) */
+            0x54, 0x7b, 0x7a, 0x6f, 0x36, 0xa1, 0x54, 0xdb, 0x03, 0xa2, 0x57, 0x5c, 0x6f, 0x2a,
+            0x4e, 0xc5, /* 16 byte Authentication challenge */
+            0x00, 0x00, 0x00, 0x01, /* Authentication result: failed */
+            0x80, 0x00, 0x00, 0x00, /* Reason length: 2GB */
+            0x61, 0x75, 0x74, 0x68, 0x20, 0x66, 0x61, 0x69, 0x6c, 0x65,
+            0x64, /* Reason: "auth failed" */
+        ];
+
+        state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[0..12], STREAM_START, 0),
+        );
+        state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[0..12], STREAM_START, 0),
+        );
+        state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[12..14], STREAM_START, 0),
+        );
+        state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[14..15], STREAM_START, 0),
+        );
+        state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[15..31], STREAM_START, 0),
+        );
+        state.parse_request(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[15..31], STREAM_START, 0),
+        );
+        assert_eq!(state.state, parser::RFBGlobalState::TCSecurityResult);
+
+        // the reason is not waited for, and the handshake is complete
+        let r = state.parse_response(
+            std::ptr::null(),
+            StreamSlice::from_slice(&buf[31..], STREAM_START, 0),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.state, parser::RFBGlobalState::Skip);
+
+        let tx = state.get_current_tx().unwrap();
+        assert!(tx.complete);
+        let reason = tx.tc_failure_reason.as_ref().unwrap();
+        assert!(reason.oversized);
+        assert_eq!(reason.reason_string, "auth failed");
+    }
 }
@@ -51,6 +51,10 @@ fn log(tx: &SIPTransaction, js: &mut JsonBuilder) -> Result<(), JsonError> {
         js.set_string("response_line", resp_line)?;
     }
 
+    if tx.retransmits > 0 {
+        js.set_uint("retransmits", tx.retransmits as u64)?;
+    }
+
     js.close()?;
 
     Ok(())
@@ -206,8 +206,36 @@ fn header_name(i: &[u8]) -> IResult<&[u8], &str> {
 }
 
 #[inline]
-fn header_value(i: &[u8]) -> IResult<&[u8], &str> {
-    map_res(parse_header_value, std::str::from_utf8)(i)
+fn header_value(i: &[u8]) -> IResult<&[u8], String> {
+    map(
+        map_res(parse_header_value, std::str::from_utf8),
+        unfold_header_value,
+    )(i)
+}
+
+/// Replace line folding (CRLF followed by linear white space) with a
+/// single space, as described in RFC3261 section 7.3.1.
+fn unfold_header_value(v: &str) -> String {
+    if !v.contains('\n') {
+        return String::from(v);
+    }
+    let mut unfolded = String::with_capacity(v.len());
+    for (idx, line) in v.split('\n').enumerate() {
+        let line = line.trim_end_matches('\r');
+        if idx == 0 {
+            unfolded.push_str(line.trim_end_matches([' ', '\t']));
+            continue;
+        }
+        let line = line.trim_matches([' ', '\t']);
+        if line.is_empty() {
+            continue;
+        }
+        if !unfolded.is_empty() {
+            unfolded.push(' ');
+        }
+        unfolded.push_str(line);
+    }
+    unfolded
 }
 
 #[inline]
@@ -224,7 +252,7 @@ fn message_header(i: &[u8]) -> IResult<&[u8], Header> {
         i,
         Header {
             name: String::from(n),
-            value: String::from(v),
+            value: v,
         },
     ))
 }
@@ -383,4 +411,47 @@ mod tests {
             "<sip:carol@chicago.com>"
         );
     }
+
+    #[test]
+    fn test_header_folded() {
+        let buf: &[u8] = "INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+                          Via: SIP/2.0/UDP pc33.atlanta.com\r\n\
+                          \t;branch=z9hG4bK776asdhds\r\n\
+                          Subject: I know you're there,\r\n\
+                          \x20\x20\x20\x20\x20pick up the phone\r\n\
+                          \x20and talk to me!\r\n\
+                          Content-Length: 0\r\n\
+                          \r\n"
+            .as_bytes();
+
+        let (_, req) = parse_request(buf).unwrap();
+        assert_eq!(req.method, "INVITE");
+        assert_eq!(
+            req.headers["Via"].first().unwrap(),
+            "SIP/2.0/UDP pc33.atlanta.com ;branch=z9hG4bK776asdhds"
+        );
+        assert_eq!(
+            req.headers["Subject"].first().unwrap(),
+            "I know you're there, pick up the phone and talk to me!"
+        );
+        assert_eq!(req.headers["Content-Length"].first().unwrap(), "0");
+    }
+
+    #[test]
+    fn test_header_folded_compact_form() {
+        let buf: &[u8] = "SIP/2.0 200 OK\r\n\
+                          v: SIP/2.0/UDP pc33.atlanta.com;\r\n\
+                          \x20\x20branch=z9hG4bK776asdhds\r\n\
+                          s:\r\n\
+                          \x20Lunch\r\n\
+                          \r\n"
+            .as_bytes();
+
+        let (_, resp) = parse_response(buf).unwrap();
+        assert_eq!(
+            resp.headers["Via"].first().unwrap(),
+            "SIP/2.0/UDP pc33.atlanta.com; branch=z9hG4bK776asdhds"
+        );
+        assert_eq!(resp.headers["Subject"].first().unwrap(), "Lunch");
+    }
 }
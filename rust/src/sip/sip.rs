@@ -18,6 +18,7 @@
 // written by Giuseppe Longo <giuseppe@glongo.it>
 
 use crate::applayer::{self, *};
+use crate::conf::conf_get;
 use crate::core;
 use crate::core::{ALPROTO_UNKNOWN, IPPROTO_TCP, IPPROTO_UDP};
 use crate::direction::Direction;
@@ -27,9 +28,14 @@ use crate::sip::parser::*;
 use nom7::Err;
 use suricata_sys::sys::AppProto;
 use std;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::ffi::CString;
 
+/// Number of recent UDP messages remembered to detect retransmissions
+const SIP_RETRANSMIT_TRACKED: usize = 64;
+
+static mut SIP_FOLD_RETRANSMISSIONS: bool = true;
+
 // app-layer-frame-documentation tag start: FrameType enum
 #[derive(AppLayerFrameType)]
 pub enum SIPFrameType {
@@ -56,6 +62,53 @@ pub struct SIPState {
     tx_id: u64,
     request_frame: Option<Frame>,
     response_frame: Option<Frame>,
+    // recent UDP messages and the id of their transaction
+    recent_messages: VecDeque<(SIPMessageKey, u64)>,
+}
+
+/// What identifies a message and its retransmissions: RFC 3261 timers
+/// resend the same start line with the same Call-ID, CSeq and Via branch.
+#[derive(Debug, PartialEq, Eq)]
+struct SIPMessageKey {
+    direction: Direction,
+    start_line: String,
+    call_id: String,
+    cseq: String,
+    branch: String,
+}
+
+fn sip_header_value<'a>(headers: &'a HashMap<String, Vec<String>>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .and_then(|(_, v)| v.first())
+        .map(|v| v.as_str())
+}
+
+fn sip_via_branch(via: &str) -> Option<&str> {
+    via.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("branch") {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+impl SIPMessageKey {
+    fn new(
+        direction: Direction, start_line: &Option<String>, headers: &HashMap<String, Vec<String>>,
+    ) -> Option<SIPMessageKey> {
+        let via = sip_header_value(headers, "Via")?;
+        Some(SIPMessageKey {
+            direction,
+            start_line: start_line.clone()?,
+            call_id: sip_header_value(headers, "Call-ID")?.to_string(),
+            cseq: sip_header_value(headers, "CSeq")?.to_string(),
+            branch: sip_via_branch(via)?.to_string(),
+        })
+    }
 }
 
 impl State<SIPTransaction> for SIPState {
@@ -74,6 +127,8 @@ pub struct SIPTransaction {
     pub response: Option<Response>,
     pub request_line: Option<String>,
     pub response_line: Option<String>,
+    /// number of retransmissions of the message folded into this transaction
+    pub retransmits: u32,
     tx_data: applayer::AppLayerTxData,
 }
 
@@ -109,6 +164,38 @@ impl SIPState {
         }
     }
 
+    /// Fold a retransmitted message into the transaction of the original
+    /// one. Returns false if the message was not seen recently.
+    fn fold_retransmission(&mut self, key: &Option<SIPMessageKey>) -> bool {
+        if unsafe { !SIP_FOLD_RETRANSMISSIONS } {
+            return false;
+        }
+        let key = if let Some(key) = key {
+            key
+        } else {
+            return false;
+        };
+        let tx_id = if let Some((_, id)) = self.recent_messages.iter().find(|(k, _)| k == key) {
+            *id
+        } else {
+            return false;
+        };
+        // the transaction may be gone already, once inspected and logged
+        if let Some(tx) = self.transactions.iter_mut().find(|tx| tx.id == tx_id) {
+            tx.retransmits += 1;
+        }
+        return true;
+    }
+
+    fn track_message(&mut self, key: Option<SIPMessageKey>, tx_id: u64) {
+        if let Some(key) = key {
+            if self.recent_messages.len() >= SIP_RETRANSMIT_TRACKED {
+                self.recent_messages.pop_front();
+            }
+            self.recent_messages.push_back((key, tx_id));
+        }
+    }
+
     fn set_event(&mut self, event: SIPEvent) {
         if let Some(tx) = self.transactions.back_mut() {
             tx.tx_data.set_event(event as u8);
@@ -130,12 +217,16 @@ impl SIPState {
 
         match parse_request(input) {
             Ok((_, request)) => {
+                let req_line = sip_take_line(input).ok().and_then(|(_, line)| line);
+                let key = SIPMessageKey::new(Direction::ToServer, &req_line, &request.headers);
+                if self.fold_retransmission(&key) {
+                    return true;
+                }
                 let mut tx = self.new_tx(Direction::ToServer);
                 sip_frames_ts(flow, &stream_slice, &request, tx.id);
                 tx.request = Some(request);
-                if let Ok((_, req_line)) = sip_take_line(input) {
-                    tx.request_line = req_line;
-                }
+                tx.request_line = req_line;
+                self.track_message(key, tx.id);
                 self.transactions.push_back(tx);
                 return true;
             }
@@ -226,12 +317,16 @@ impl SIPState {
 
         match parse_response(input) {
             Ok((_, response)) => {
+                let resp_line = sip_take_line(input).ok().and_then(|(_, line)| line);
+                let key = SIPMessageKey::new(Direction::ToClient, &resp_line, &response.headers);
+                if self.fold_retransmission(&key) {
+                    return true;
+                }
                 let mut tx = self.new_tx(Direction::ToClient);
                 sip_frames_tc(flow, &stream_slice, &response, tx.id);
                 tx.response = Some(response);
-                if let Ok((_, resp_line)) = sip_take_line(input) {
-                    tx.response_line = resp_line;
-                }
+                tx.response_line = resp_line;
+                self.track_message(key, tx.id);
                 self.transactions.push_back(tx);
                 return true;
             }
@@ -316,6 +411,7 @@ impl SIPTransaction {
             response: None,
             request_line: None,
             response_line: None,
+            retransmits: 0,
             tx_data: applayer::AppLayerTxData::for_direction(direction),
         }
     }
@@ -585,6 +681,11 @@ pub unsafe extern "C" fn SCRegisterSipParser() {
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
         }
+        if let Some("0" | "no" | "false" | "off") =
+            conf_get("app-layer.protocols.sip.fold-retransmissions")
+        {
+            SIP_FOLD_RETRANSMISSIONS = false;
+        }
         if register_pattern_probe(core::IPPROTO_UDP) < 0 {
             return;
         }
@@ -615,3 +716,40 @@ pub unsafe extern "C" fn SCRegisterSipParser() {
         SCLogDebug!("Protocol detection and parsing disabled for TCP SIP.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sip_message_key() {
+        let buf: &[u8] = "INVITE sip:bob@example.com SIP/2.0\r\n\
+                          Via: SIP/2.0/UDP 10.0.0.1:5060;rport;branch=z9hG4bK776asdhds\r\n\
+                          i: a84b4c76e66710@pc33.example.com\r\n\
+                          CSeq: 314159 INVITE\r\n\
+                          Content-Length: 0\r\n\
+                          \r\n"
+            .as_bytes();
+        let (_, request) = parse_request(buf).unwrap();
+        let line = Some("INVITE sip:bob@example.com SIP/2.0".to_string());
+        let key = SIPMessageKey::new(Direction::ToServer, &line, &request.headers).unwrap();
+        assert_eq!(key.call_id, "a84b4c76e66710@pc33.example.com");
+        assert_eq!(key.cseq, "314159 INVITE");
+        assert_eq!(key.branch, "z9hG4bK776asdhds");
+
+        let mut state = SIPState::new();
+        let key = Some(key);
+        assert!(!state.fold_retransmission(&key));
+        let mut tx = state.new_tx(Direction::ToServer);
+        state.track_message(
+            SIPMessageKey::new(Direction::ToServer, &line, &request.headers),
+            tx.id,
+        );
+        tx.request = Some(request);
+        state.transactions.push_back(tx);
+        assert!(state.fold_retransmission(&key));
+        assert!(state.fold_retransmission(&key));
+        assert_eq!(state.transactions.len(), 1);
+        assert_eq!(state.transactions[0].retransmits, 2);
+    }
+}
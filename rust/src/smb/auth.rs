@@ -108,7 +108,7 @@ fn parse_secblob_spnego(blob: &[u8]) -> Option<SpnegoRequest>
                     if let BerObjectContent::OID(ref oid) = se.content {
                         SCLogDebug!("OID {:?}", oid);
                         match oid.to_string().as_str() {
-                            "1.2.840.48018.1.2.2" => { SCLogDebug!("Microsoft Kerberos 5"); },
+                            "1.2.840.48018.1.2.2" => { SCLogDebug!("Microsoft Kerberos 5"); have_kerberos = true; },
                             "1.2.840.113554.1.2.2" => { SCLogDebug!("Kerberos 5"); have_kerberos = true; },
                             "1.2.840.113554.1.2.2.1" => { SCLogDebug!("krb5-name"); },
                             "1.2.840.113554.1.2.2.2" => { SCLogDebug!("krb5-principal"); },
@@ -232,6 +232,7 @@ pub fn parse_secblob(blob: &[u8]) -> Option<SpnegoRequest>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use kerberos_parser::krb5::{EncryptionType, NameType, PrincipalName, Realm};
     #[test]
     fn test_parse_secblob() {
         // smb2.security_blob
@@ -256,4 +257,27 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_parse_secblob_ms_kerberos() {
+        // SPNEGO NegTokenInit offering the Microsoft Kerberos OID first, then
+        // NTLMSSP, with an AP-REQ for cifs/dc1.example.com as mechToken
+        let blob = hex::decode("6081d506062b0601050502a081ca3081c7a019301706092a864882f712010202060a2b06010401823702020aa281a90481a66081a306092a864886f71201020201006e8193308190a003020105a10302010ea20703050020000000a35e615c305aa003020105a10d1b0b4558414d504c452e434f4da2223020a003020102a11930171b04636966731b0f6463312e6578616d706c652e636f6da320301ea003020117a103020102a212041011111111111111111111111111111111a41b3019a003020112a212041022222222222222222222222222222222").unwrap();
+        let result = parse_secblob(&blob);
+        assert_eq!(
+            result,
+            Some(SpnegoRequest {
+                krb: Some(Kerberos5Ticket {
+                    realm: Realm("EXAMPLE.COM".to_string()),
+                    sname: PrincipalName {
+                        name_type: NameType::KRB_NT_SRV_INST,
+                        name_string: vec!["cifs".to_string(), "dc1.example.com".to_string()],
+                    },
+                    sname_str: "cifs/dc1.example.com".to_string(),
+                    etype: EncryptionType::RC4_HMAC,
+                }),
+                ntlmssp: None,
+            })
+        );
+    }
 }
@@ -15,14 +15,25 @@
  * 02110-1301, USA.
  */
 
+use crate::core::{DetectEngineThreadCtx, STREAM_TOSERVER};
 use crate::dcerpc::dcerpc::DCERPC_TYPE_REQUEST;
 use crate::dcerpc::detect::{DCEIfaceData, DCEOpnumData, DETECT_DCE_OPNUM_RANGE_UNINITIALIZED};
-use crate::detect::uint::detect_match_uint;
+use crate::detect::uint::{
+    detect_match_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    SCDetectU32Free, SCDetectU32Parse,
+};
+use crate::detect::{
+    helper_keyword_register_sticky_buffer, DetectHelperBufferRegister, DetectHelperKeywordRegister,
+    DetectHelperMultiBufferMpmRegister, DetectSignatureSetAppProto, SCSigTableAppLiteElmt,
+    SigMatchAppendSMToList, SigTableElmtStickyBuffer,
+};
 use crate::direction::Direction;
+use crate::krb::detect::principal_name_get_buffer;
 use crate::smb::smb::*;
 use std::ffi::CStr;
-use std::os::raw::{c_char, c_void};
+use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
+use suricata_sys::sys::{DetectEngineCtx, SCDetectBufferSetActiveList, Signature};
 
 #[no_mangle]
 pub unsafe extern "C" fn SCSmbTxGetShare(
@@ -228,9 +239,240 @@ pub unsafe extern "C" fn SCSmbVersionFree(ptr: *mut c_void) {
     std::mem::drop(Box::from_raw(ptr as *mut u8));
 }
 
+static mut G_SMB_CREATE_DISPOSITION_KW_ID: c_int = 0;
+static mut G_SMB_CREATE_DISPOSITION_BUFFER_ID: c_int = 0;
+static mut G_SMB_CREATE_ACCESS_KW_ID: c_int = 0;
+static mut G_SMB_CREATE_ACCESS_BUFFER_ID: c_int = 0;
+
+fn smb_tx_get_create(tx: &SMBTransaction) -> Option<&SMBTransactionCreate> {
+    match tx.type_data {
+        Some(SMBTransactionTypeData::CREATE(ref x)) => Some(x),
+        _ => None,
+    }
+}
+
+unsafe extern "C" fn smb_create_disposition_parse(ustr: *const c_char) -> *mut DetectUintData<u32> {
+    let ft_name: &CStr = CStr::from_ptr(ustr); //unsafe
+    if let Ok(s) = ft_name.to_str() {
+        if let Some(ctx) = detect_parse_uint_enum::<u32, SMBCreateDisposition>(s) {
+            let boxed = Box::new(ctx);
+            return Box::into_raw(boxed) as *mut _;
+        }
+    }
+    return std::ptr::null_mut();
+}
+
+unsafe extern "C" fn smb_create_disposition_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_SMB) != 0 {
+        return -1;
+    }
+    let ctx = smb_create_disposition_parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_SMB_CREATE_DISPOSITION_KW_ID,
+        ctx,
+        G_SMB_CREATE_DISPOSITION_BUFFER_ID,
+    )
+    .is_null()
+    {
+        smb_create_uint_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn smb_create_disposition_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, SMBTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some(create) = smb_tx_get_create(tx) {
+        return detect_match_uint(ctx, create.disposition) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn smb_create_access_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_SMB) != 0 {
+        return -1;
+    }
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_SMB_CREATE_ACCESS_KW_ID,
+        ctx,
+        G_SMB_CREATE_ACCESS_BUFFER_ID,
+    )
+    .is_null()
+    {
+        smb_create_uint_free(std::ptr::null_mut(), ctx);
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn smb_create_access_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, _state: *mut c_void, tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let tx = cast_pointer!(tx, SMBTransaction);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    if let Some(create) = smb_tx_get_create(tx) {
+        return detect_match_uint(ctx, create.desired_access) as c_int;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn smb_create_uint_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
+static mut G_SMB_KERBEROS_SNAME_BUFFER_ID: c_int = 0;
+
+/// Server name of the Kerberos ticket of a session setup request, with the
+/// same buffers as krb5.sname.
+fn smb_tx_get_kerberos_sname(tx: &SMBTransaction, i: usize) -> Option<&str> {
+    if let Some(SMBTransactionTypeData::SESSIONSETUP(ref x)) = tx.type_data {
+        let ticket = x.krb_ticket.as_ref()?;
+        return principal_name_get_buffer(&ticket.sname, i);
+    }
+    None
+}
+
+unsafe extern "C" fn smb_kerberos_sname_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const c_char,
+) -> c_int {
+    if DetectSignatureSetAppProto(s, ALPROTO_SMB) != 0 {
+        return -1;
+    }
+    if SCDetectBufferSetActiveList(de, s, G_SMB_KERBEROS_SNAME_BUFFER_ID) < 0 {
+        return -1;
+    }
+    return 0;
+}
+
+unsafe extern "C" fn smb_kerberos_sname_get_data(
+    _de: *mut DetectEngineThreadCtx, tx: *const c_void, _flow_flags: u8, local_id: u32,
+    buffer: *mut *const u8, buffer_len: *mut u32,
+) -> bool {
+    let tx = cast_pointer!(tx, SMBTransaction);
+    if let Some(value) = smb_tx_get_kerberos_sname(tx, local_id as usize) {
+        *buffer = value.as_ptr();
+        *buffer_len = value.len() as u32;
+        return true;
+    }
+    return false;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn SCDetectSmbRegister() {
+    let kw = SCSigTableAppLiteElmt {
+        name: b"smb.create.disposition\0".as_ptr() as *const libc::c_char,
+        desc: b"match SMB create request disposition\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/smb-keywords.html#smb-create-disposition\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(smb_create_disposition_match),
+        Setup: smb_create_disposition_setup,
+        Free: Some(smb_create_uint_free),
+        flags: 0,
+    };
+    G_SMB_CREATE_DISPOSITION_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u32, SMBCreateDisposition>(G_SMB_CREATE_DISPOSITION_KW_ID);
+    G_SMB_CREATE_DISPOSITION_BUFFER_ID = DetectHelperBufferRegister(
+        b"smb.create.disposition\0".as_ptr() as *const libc::c_char,
+        ALPROTO_SMB,
+        STREAM_TOSERVER,
+    );
+    let kw = SCSigTableAppLiteElmt {
+        name: b"smb.create.access\0".as_ptr() as *const libc::c_char,
+        desc: b"match SMB create request desired access mask\0".as_ptr() as *const libc::c_char,
+        url: b"/rules/smb-keywords.html#smb-create-access\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(smb_create_access_match),
+        Setup: smb_create_access_setup,
+        Free: Some(smb_create_uint_free),
+        flags: 0,
+    };
+    G_SMB_CREATE_ACCESS_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_SMB_CREATE_ACCESS_BUFFER_ID = DetectHelperBufferRegister(
+        b"smb.create.access\0".as_ptr() as *const libc::c_char,
+        ALPROTO_SMB,
+        STREAM_TOSERVER,
+    );
+    let kw = SigTableElmtStickyBuffer {
+        name: String::from("smb.kerberos.sname"),
+        desc: String::from("sticky buffer to match on SMB Kerberos server name"),
+        url: String::from("/rules/smb-keywords.html#smb-kerberos-sname"),
+        setup: smb_kerberos_sname_setup,
+    };
+    let _g_smb_kerberos_sname_kw_id = helper_keyword_register_sticky_buffer(&kw);
+    G_SMB_KERBEROS_SNAME_BUFFER_ID = DetectHelperMultiBufferMpmRegister(
+        b"smb.kerberos.sname\0".as_ptr() as *const libc::c_char,
+        b"SMB Kerberos server name\0".as_ptr() as *const libc::c_char,
+        ALPROTO_SMB,
+        STREAM_TOSERVER,
+        smb_kerberos_sname_get_data,
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::detect::uint::{detect_parse_uint, DetectUintMode};
+    use crate::kerberos::Kerberos5Ticket;
+    use crate::smb::session::SMBTransactionSessionSetup;
+    use kerberos_parser::krb5::{EncryptionType, NameType, PrincipalName, Realm};
+
+    #[test]
+    fn test_parse_create_disposition() {
+        let ctx = detect_parse_uint_enum::<u32, SMBCreateDisposition>("file_overwrite_if").unwrap();
+        assert_eq!(ctx.arg1, 5);
+        assert_eq!(ctx.mode, DetectUintMode::DetectUintModeEqual);
+        let ctx = detect_parse_uint_enum::<u32, SMBCreateDisposition>("FILE_CREATE").unwrap();
+        assert_eq!(ctx.arg1, 2);
+        assert!(detect_parse_uint_enum::<u32, SMBCreateDisposition>("file_delete").is_none());
+    }
+
+    #[test]
+    fn test_match_create_access() {
+        // GENERIC_WRITE
+        let (_, ctx) = detect_parse_uint::<u32>("&0x40000000!=0").unwrap();
+        assert!(detect_match_uint(&ctx, 0x4012_0089));
+        assert!(!detect_match_uint(&ctx, 0x0012_0089));
+    }
+
+    #[test]
+    fn test_smb_kerberos_sname() {
+        let mut tx = SMBTransaction::new();
+        let mut sd = SMBTransactionSessionSetup::new();
+        assert!(smb_tx_get_kerberos_sname(&tx, 0).is_none());
+        sd.krb_ticket = Some(Kerberos5Ticket {
+            realm: Realm("EXAMPLE.COM".to_string()),
+            sname: PrincipalName {
+                name_type: NameType::KRB_NT_SRV_INST,
+                name_string: vec!["cifs".to_string(), "dc1.example.com".to_string()],
+            },
+            sname_str: "cifs/dc1.example.com".to_string(),
+            etype: EncryptionType::RC4_HMAC,
+        });
+        tx.type_data = Some(SMBTransactionTypeData::SESSIONSETUP(sd));
+        assert_eq!(smb_tx_get_kerberos_sname(&tx, 0), Some("cifs"));
+        assert_eq!(smb_tx_get_kerberos_sname(&tx, 1), Some("dc1.example.com"));
+        assert!(smb_tx_get_kerberos_sname(&tx, 2).is_none());
+    }
 
     #[test]
     fn test_parse_cmd_data() {
@@ -56,12 +56,13 @@ impl SMBTransaction {
     /// Set event.
     pub fn set_event(&mut self, e: SMBEvent) {
         self.tx_data.set_event(e as u8);
+        applayer_stats_incr!(SMB_STATS, events);
     }
 
     /// Set events from vector of events.
     pub fn set_events(&mut self, events: Vec<SMBEvent>) {
         for e in events {
-            self.tx_data.set_event(e as u8);
+            self.set_event(e);
         }
     }
 }
@@ -27,6 +27,7 @@ use crate::smb::smb2::*;
 use crate::dcerpc::dcerpc::*;
 use crate::smb::funcs::*;
 use crate::smb::smb_status::*;
+use crate::krb::krb5::test_weak_encryption;
 use std::error::Error;
 use std::fmt;
 
@@ -205,6 +206,8 @@ fn smb_common_header(
                     jsb.append_string(sname)?;
                 }
                 jsb.close()?;
+                jsb.set_string("encryption", &format!("{:?}", ticket.etype))?;
+                jsb.set_bool("weak_encryption", test_weak_encryption(ticket.etype))?;
                 jsb.close()?;
             }
 
@@ -251,6 +254,7 @@ fn smb_common_header(
                 5 => { jsb.set_string("disposition", "FILE_OVERWRITE_IF")?; },
                 _ => { jsb.set_string("disposition", "UNKNOWN")?; },
             }
+            jsb.set_uint("desired_access", x.desired_access as u64)?;
             if x.delete_on_close {
                 jsb.set_string("access", "delete on close")?;
             } else {
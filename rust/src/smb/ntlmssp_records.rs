@@ -84,78 +84,120 @@ fn parse_ntlm_auth_nego_flags(i: &[u8]) -> IResult<&[u8], NTLMSSPNegotiateFlags>
 
 const NTLMSSP_IDTYPE_LEN: usize = 12;
 
-fn extract_ntlm_substring(i: &[u8], offset: u32, length: u16) -> IResult<&[u8], &[u8]> {
-    if offset < NTLMSSP_IDTYPE_LEN as u32 {
+/// Security buffer descriptor: length, allocated space and offset of a
+/// field in the payload. Offset is relative to the start of the message.
+#[derive(Debug, PartialEq, Eq)]
+struct NTLMSSPSecBuf {
+    len: u16,
+    offset: u32,
+}
+
+fn parse_ntlm_secbuf(i: &[u8]) -> IResult<&[u8], NTLMSSPSecBuf> {
+    let (i, len) = le_u16(i)?;
+    let (i, _maxlen) = le_u16(i)?;
+    let (i, offset) = le_u32(i)?;
+    Ok((i, NTLMSSPSecBuf { len, offset }))
+}
+
+/// Parse a security buffer and make sure the data it points to is
+/// within the record.
+fn parse_ntlm_secbuf_checked(record_len: usize) -> impl Fn(&[u8]) -> IResult<&[u8], NTLMSSPSecBuf> {
+    move |i: &[u8]| {
+        verify(parse_ntlm_secbuf, |b: &NTLMSSPSecBuf| {
+            b.len == 0 || (b.offset as usize).saturating_add(b.len as usize) <= record_len
+        })(i)
+    }
+}
+
+fn extract_ntlm_substring<'a>(i: &'a [u8], buf: &NTLMSSPSecBuf) -> IResult<&'a [u8], &'a [u8]> {
+    // empty fields are often sent with a zero or bogus offset
+    if buf.len == 0 {
+        return Ok((i, &i[..0]));
+    }
+    if buf.offset < NTLMSSP_IDTYPE_LEN as u32 {
         return Err(Err::Error(make_error(i, ErrorKind::LengthValue)));
     }
-    let start = offset as usize - NTLMSSP_IDTYPE_LEN;
-    let end = offset as usize + length as usize - NTLMSSP_IDTYPE_LEN;
+    let start = buf.offset as usize - NTLMSSP_IDTYPE_LEN;
+    let end = start + buf.len as usize;
     if i.len() < end {
         return Err(Err::Error(make_error(i, ErrorKind::LengthValue)));
     }
     return Ok((i, &i[start..end]));
 }
 
+/// Lowest offset of the non-empty buffers, which is where the payload
+/// starts. Optional header fields can only be present before it.
+fn ntlm_payload_start(bufs: &[&NTLMSSPSecBuf], record_len: usize) -> usize {
+    bufs.iter()
+        .filter(|b| b.len > 0)
+        .map(|b| b.offset as usize)
+        .min()
+        .unwrap_or(record_len)
+}
+
 pub fn parse_ntlm_auth_record(i: &[u8]) -> IResult<&[u8], NTLMSSPAuthRecord> {
     let orig_i = i;
     let record_len = i.len() + NTLMSSP_IDTYPE_LEN; // identifier (8) and type (4) are cut before we are called
 
-    // track start of the data offset
-    let (i, _lm_blob_len) = verify(le_u16, |&v| (v as usize) < record_len)(i)?;
-    let (i, _lm_blob_maxlen) = le_u16(i)?;
-    let (i, lm_blob_offset) = verify(le_u32, |&v| (v as usize) < record_len)(i)?;
-    let mut data_start = lm_blob_offset;
-
-    let (i, _ntlmresp_blob_len) = verify(le_u16, |&v| (v as usize) < record_len)(i)?;
-    let (i, _ntlmresp_blob_maxlen) = le_u16(i)?;
-    let (i, ntlmresp_blob_offset) = verify(le_u32, |&v| (v as usize) < record_len)(i)?;
-    data_start = std::cmp::min(data_start, ntlmresp_blob_offset);
+    let (i, lm_blob) = parse_ntlm_secbuf_checked(record_len)(i)?;
+    let (i, ntlmresp_blob) = parse_ntlm_secbuf_checked(record_len)(i)?;
+    let (i, domain_blob) = parse_ntlm_secbuf_checked(record_len)(i)?;
+    let (i, user_blob) = parse_ntlm_secbuf_checked(record_len)(i)?;
+    let (i, host_blob) = parse_ntlm_secbuf_checked(record_len)(i)?;
 
-    let (i, domain_blob_len) = verify(le_u16, |&v| (v as usize) < record_len)(i)?;
-    let (i, _domain_blob_maxlen) = le_u16(i)?;
-    let (i, domain_blob_offset) = verify(le_u32, |&v| (v as usize) < record_len)(i)?;
-    data_start = std::cmp::min(data_start, domain_blob_offset);
+    let data_start = ntlm_payload_start(
+        &[
+            &lm_blob,
+            &ntlmresp_blob,
+            &domain_blob,
+            &user_blob,
+            &host_blob,
+        ],
+        record_len,
+    );
 
-    let (i, user_blob_len) = verify(le_u16, |&v| (v as usize) < record_len)(i)?;
-    let (i, _user_blob_maxlen) = le_u16(i)?;
-    let (i, user_blob_offset) = verify(le_u32, |&v| (v as usize) < record_len)(i)?;
-    data_start = std::cmp::min(data_start, user_blob_offset);
-
-    let (i, host_blob_len) = verify(le_u16, |&v| (v as usize) < record_len)(i)?;
-    let (i, _host_blob_maxlen) = le_u16(i)?;
-    let (i, host_blob_offset) = verify(le_u32, |&v| (v as usize) < record_len)(i)?;
-    data_start = std::cmp::min(data_start, host_blob_offset);
-
-    let (i, _ssnkey_blob_len) = verify(le_u16, |&v| (v as usize) < record_len)(i)?;
-    let (i, _ssnkey_blob_maxlen) = le_u16(i)?;
-    let (i, ssnkey_blob_offset) = verify(le_u32, |&v| (v as usize) < record_len)(i)?;
-    data_start = std::cmp::min(data_start, ssnkey_blob_offset);
-
-    let (i, nego_flags) = parse_ntlm_auth_nego_flags(i)?;
+    // The session key buffer and the negotiate flags are not sent by
+    // some older or embedded clients, in which case the payload starts
+    // right after the host buffer.
+    let consumed = orig_i.len() - i.len() + NTLMSSP_IDTYPE_LEN;
+    let has_ssnkey_and_flags = data_start >= consumed + 12;
+    let (i, ssnkey_blob) = cond(has_ssnkey_and_flags, parse_ntlm_secbuf_checked(record_len))(i)?;
+    let (i, nego_flags) = cond(has_ssnkey_and_flags, parse_ntlm_auth_nego_flags)(i)?;
+    let data_start = match ssnkey_blob {
+        Some(ref b) => std::cmp::min(ntlm_payload_start(&[b], record_len), data_start),
+        None => data_start,
+    };
 
     // Check if we have space for the version before the "data" starts.
     let consumed = orig_i.len() - i.len() + NTLMSSP_IDTYPE_LEN;
-    let has_space_for_version = data_start as usize >= consumed + 8 && nego_flags.version;
+    let has_space_for_version =
+        data_start >= consumed + 8 && nego_flags.map_or(false, |f| f.version);
 
+    // The MIC that may follow the version is not used, and we do not
+    // depend on it being there.
     let (_, version) = cond(has_space_for_version, parse_ntlm_auth_version)(i)?;
 
     // Caller does not care about remaining input...
-    let (_, domain_blob) = extract_ntlm_substring(orig_i, domain_blob_offset, domain_blob_len)?;
-    let (_, user_blob) = extract_ntlm_substring(orig_i, user_blob_offset, user_blob_len)?;
-    let (_, host_blob) = extract_ntlm_substring(orig_i, host_blob_offset, host_blob_len)?;
+    let (_, domain) = extract_ntlm_substring(orig_i, &domain_blob)?;
+    let (_, user) = extract_ntlm_substring(orig_i, &user_blob)?;
+    let (_, host) = extract_ntlm_substring(orig_i, &host_blob)?;
 
     let mut warning = false;
-    if (user_blob_offset > 0 && user_blob_offset < domain_blob_offset + domain_blob_len as u32)
-        || (host_blob_offset > 0 && host_blob_offset < user_blob_offset + user_blob_len as u32)
+    if (user_blob.len > 0
+        && domain_blob.len > 0
+        && user_blob.offset < domain_blob.offset + domain_blob.len as u32)
+        || (host_blob.len > 0
+            && user_blob.len > 0
+            && host_blob.offset < user_blob.offset + user_blob.len as u32)
     {
         // to set event in transaction
         warning = true;
     }
 
     let record = NTLMSSPAuthRecord {
-        domain: domain_blob,
-        user: user_blob,
-        host: host_blob,
+        domain,
+        user,
+        host,
         warning,
 
         version,
@@ -220,4 +262,113 @@ mod tests {
             }
         }
     }
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|c| c.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_parse_auth_record_windows() {
+        // NTLMv2 AUTH with version and MIC, payload order domain, user,
+        // host, lm, ntlm, session key.
+        let blob = [
+            0x18, 0x00, 0x18, 0x00, 0x7a, 0x00, 0x00, 0x00, 0x30, 0x00, 0x30, 0x00, 0x92, 0x00,
+            0x00, 0x00, 0x0e, 0x00, 0x0e, 0x00, 0x58, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x0a, 0x00,
+            0x66, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x0a, 0x00, 0x70, 0x00, 0x00, 0x00, 0x10, 0x00,
+            0x10, 0x00, 0xc2, 0x00, 0x00, 0x00, 0x15, 0x82, 0x88, 0xe2, 0x0a, 0x00, 0x61, 0x4a,
+            0x00, 0x00, 0x00, 0x0f, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+            0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x43, 0x00, 0x4f, 0x00, 0x4e, 0x00, 0x54, 0x00,
+            0x4f, 0x00, 0x53, 0x00, 0x4f, 0x00, 0x61, 0x00, 0x6c, 0x00, 0x69, 0x00, 0x63, 0x00,
+            0x65, 0x00, 0x57, 0x00, 0x4b, 0x00, 0x53, 0x00, 0x30, 0x00, 0x31, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35,
+            0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f, 0x40, 0x41, 0x42, 0x43,
+            0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51,
+            0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f,
+            0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x8d,
+            0x8e, 0x8f,
+        ];
+        let (_, record) = parse_ntlm_auth_record(&blob).unwrap();
+        assert_eq!(record.domain, utf16le("CONTOSO"));
+        assert_eq!(record.user, utf16le("alice"));
+        assert_eq!(record.host, utf16le("WKS01"));
+        assert_eq!(
+            record.version,
+            Some(NTLMSSPVersion {
+                ver_major: 10,
+                ver_minor: 0,
+                ver_build: 19041,
+                ver_ntlm_rev: 15,
+            })
+        );
+        assert!(!record.warning);
+    }
+
+    #[test]
+    fn test_parse_auth_record_samba() {
+        // AUTH with version but without MIC, payload order lm, ntlm,
+        // domain, user, host, session key.
+        let blob = [
+            0x18, 0x00, 0x18, 0x00, 0x48, 0x00, 0x00, 0x00, 0x28, 0x00, 0x28, 0x00, 0x60, 0x00,
+            0x00, 0x00, 0x0a, 0x00, 0x0a, 0x00, 0x88, 0x00, 0x00, 0x00, 0x06, 0x00, 0x06, 0x00,
+            0x92, 0x00, 0x00, 0x00, 0x10, 0x00, 0x10, 0x00, 0x98, 0x00, 0x00, 0x00, 0x10, 0x00,
+            0x10, 0x00, 0xa8, 0x00, 0x00, 0x00, 0x15, 0x82, 0x08, 0xe2, 0x06, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d,
+            0x4e, 0x4f, 0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x5b,
+            0x5c, 0x5d, 0x5e, 0x5f, 0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x53, 0x00,
+            0x41, 0x00, 0x4d, 0x00, 0x42, 0x00, 0x41, 0x00, 0x62, 0x00, 0x6f, 0x00, 0x62, 0x00,
+            0x4c, 0x00, 0x49, 0x00, 0x4e, 0x00, 0x55, 0x00, 0x58, 0x00, 0x42, 0x00, 0x4f, 0x00,
+            0x58, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xab,
+            0xac, 0xad, 0xae, 0xaf,
+        ];
+        let (_, record) = parse_ntlm_auth_record(&blob).unwrap();
+        assert_eq!(record.domain, utf16le("SAMBA"));
+        assert_eq!(record.user, utf16le("bob"));
+        assert_eq!(record.host, utf16le("LINUXBOX"));
+        assert_eq!(
+            record.version,
+            Some(NTLMSSPVersion {
+                ver_major: 6,
+                ver_minor: 1,
+                ver_build: 0,
+                ver_ntlm_rev: 15,
+            })
+        );
+        assert!(!record.warning);
+    }
+
+    #[test]
+    fn test_parse_auth_record_no_version_no_flags() {
+        // Embedded NAS client: OEM strings, no session key, flags,
+        // version or MIC. Empty domain with a zero offset.
+        let blob = [
+            0x18, 0x00, 0x18, 0x00, 0x3c, 0x00, 0x00, 0x00, 0x18, 0x00, 0x18, 0x00, 0x54, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x05, 0x00,
+            0x34, 0x00, 0x00, 0x00, 0x03, 0x00, 0x03, 0x00, 0x39, 0x00, 0x00, 0x00, 0x61, 0x64,
+            0x6d, 0x69, 0x6e, 0x4e, 0x41, 0x53, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+            0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15,
+            0x16, 0x17, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        ];
+        let (_, record) = parse_ntlm_auth_record(&blob).unwrap();
+        assert_eq!(record.domain, b"");
+        assert_eq!(record.user, b"admin");
+        assert_eq!(record.host, b"NAS");
+        assert_eq!(record.version, None);
+        assert!(!record.warning);
+    }
+
+    #[test]
+    fn test_parse_auth_record_out_of_bounds() {
+        // user buffer points past the end of the record
+        let blob = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x05, 0x00,
+            0x3c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x61, 0x64,
+            0x6d, 0x69,
+        ];
+        assert!(parse_ntlm_auth_record(&blob).is_err());
+    }
 }
@@ -45,6 +45,7 @@ use crate::flow::{Flow, FLOW_DIR_REVERSED};
 use crate::frames::*;
 use crate::conf::*;
 use crate::applayer::{AppLayerResult, AppLayerTxData, AppLayerEvent};
+use crate::applayerstats::{applayer_stats_register, AppLayerParserStats};
 
 use crate::smb::nbss_records::*;
 use crate::smb::smb1_records::*;
@@ -95,10 +96,12 @@ pub static mut SMB_CFG_MAX_FRAG_CACHE_SIZE: usize = 128;
 /// For SMBState::ssn2vec_cache
 pub static mut SMB_CFG_MAX_SSN2VEC_CACHE_SIZE: usize = 512;
 
-static mut ALPROTO_SMB: AppProto = ALPROTO_UNKNOWN;
+pub(crate) static mut ALPROTO_SMB: AppProto = ALPROTO_UNKNOWN;
 
 static mut SMB_MAX_TX: usize = 1024;
 
+pub(super) static SMB_STATS: AppLayerParserStats = AppLayerParserStats::new("smb");
+
 pub static mut SURICATA_SMB_FILE_CONFIG: Option<&'static SuricataFileContext> = None;
 
 #[no_mangle]
@@ -411,9 +414,22 @@ impl SMBState {
     }
 }
 
+/// CreateDisposition of the SMB1 NT_CREATE_ANDX and SMB2 CREATE requests
+#[derive(Clone, Debug, EnumStringU32)]
+#[repr(u32)]
+pub enum SMBCreateDisposition {
+    FileSupersede = 0,
+    FileOpen = 1,
+    FileCreate = 2,
+    FileOpenIf = 3,
+    FileOverwrite = 4,
+    FileOverwriteIf = 5,
+}
+
 #[derive(Default, Debug)]
 pub struct SMBTransactionCreate {
     pub disposition: u32,
+    pub desired_access: u32,
     pub delete_on_close: bool,
     pub directory: bool,
     pub filename: Vec<u8>,
@@ -428,9 +444,10 @@ pub struct SMBTransactionCreate {
 }
 
 impl SMBTransactionCreate {
-    pub fn new(filename: Vec<u8>, disp: u32, del: bool, dir: bool) -> Self {
+    pub fn new(filename: Vec<u8>, disp: u32, access: u32, del: bool, dir: bool) -> Self {
         return Self {
             disposition: disp,
+            desired_access: access,
             delete_on_close: del,
             directory: dir,
             filename,
@@ -831,6 +848,7 @@ impl SMBState {
         self.tx_id += 1;
         tx.id = self.tx_id;
         SCLogDebug!("TX {} created", tx.id);
+        applayer_stats_incr!(SMB_STATS, tx_created);
         if self.transactions.len() > unsafe { SMB_MAX_TX } {
             let mut index = self.tx_index_completed;
             for tx_old in &mut self.transactions.range_mut(self.tx_index_completed..) {
@@ -868,7 +886,9 @@ impl SMBState {
             SCLogDebug!("freeing TX with ID {} TX.ID {} at index {} left: {} max id: {}",
                     tx_id, tx_id+1, index, self.transactions.len(), self.tx_id);
             self.tx_index_completed = 0;
-            self.transactions.remove(index);
+            if let Some(tx) = self.transactions.remove(index) {
+                applayer_stats_tx_freed!(SMB_STATS, tx.request_done && tx.response_done);
+            }
         }
     }
 
@@ -1066,7 +1086,7 @@ impl SMBState {
     }
 
     pub fn new_create_tx(&mut self, file_name: &[u8],
-            disposition: u32, del: bool, dir: bool,
+            disposition: u32, desired_access: u32, del: bool, dir: bool,
             hdr: SMBCommonHdr)
         -> &mut SMBTransaction
     {
@@ -1075,7 +1095,7 @@ impl SMBState {
         tx.type_data = Some(SMBTransactionTypeData::CREATE(
                             SMBTransactionCreate::new(
                                 file_name.to_vec(), disposition,
-                                del, dir)));
+                                desired_access, del, dir)));
         tx.request_done = true;
         tx.response_done = self.tc_trunc; // no response expected if tc is truncated
 
@@ -1991,6 +2011,7 @@ extern "C" fn smb_state_new(_orig_state: *mut std::os::raw::c_void, _orig_proto:
     let state = SMBState::new();
     let boxed = Box::new(state);
     SCLogDebug!("allocating state");
+    applayer_stats_incr!(SMB_STATS, states_live);
     return Box::into_raw(boxed) as *mut _;
 }
 
@@ -2000,6 +2021,11 @@ extern "C" fn smb_state_free(state: *mut std::os::raw::c_void) {
     SCLogDebug!("freeing state");
     let mut smb_state = unsafe { Box::from_raw(state as *mut SMBState) };
     smb_state.free();
+    // the transactions left are freed with the state
+    for tx in &smb_state.transactions {
+        applayer_stats_tx_freed!(SMB_STATS, tx.request_done && tx.response_done);
+    }
+    applayer_stats_sub!(SMB_STATS, states_live, 1);
 }
 
 /// C binding parse a SMB request. Returns 1 on success, -1 on failure.
@@ -2026,7 +2052,7 @@ unsafe extern "C" fn smb_parse_request_tcp(flow: *const Flow,
     }
 
     state.update_ts(flow.get_last_time().as_secs());
-    state.parse_tcp_data_ts(flow, &stream_slice)
+    applayer_stats_result!(SMB_STATS, state.parse_tcp_data_ts(flow, &stream_slice))
 }
 
 extern "C" fn smb_parse_request_tcp_gap(
@@ -2059,7 +2085,7 @@ unsafe extern "C" fn smb_parse_response_tcp(flow: *const Flow,
     }
 
     state.update_ts(flow.get_last_time().as_secs());
-    state.parse_tcp_data_tc(flow, &stream_slice)
+    applayer_stats_result!(SMB_STATS, state.parse_tcp_data_tc(flow, &stream_slice))
 }
 
 extern "C" fn smb_parse_response_tcp_gap(
@@ -2392,6 +2418,7 @@ pub unsafe extern "C" fn SCRegisterSmbParser() {
         ) != 0
         {
             let _ = AppLayerRegisterParser(&parser, alproto);
+            applayer_stats_register(&SMB_STATS);
         }
         SCLogDebug!("Rust SMB parser registered.");
         let retval = conf_get("app-layer.protocols.smb.stream-depth");
@@ -482,7 +482,7 @@ fn smb1_request_record_one(state: &mut SMBState, r: &SmbRecord, command: u8, and
 
                     let tx_hdr = SMBCommonHdr::from1(r, SMBHDR_TYPE_GENERICTX);
                     let tx = state.new_create_tx(&cr.file_name,
-                            cr.disposition, del, dir, tx_hdr);
+                            cr.disposition, cr.desired_access, del, dir, tx_hdr);
                     tx.vercmd.set_smb1_cmd(command);
                     SCLogDebug!("TS CREATE TX {} created", tx.id);
                     true
@@ -665,7 +665,7 @@ fn smb1_response_record_one(state: &mut SMBState, r: &SmbRecord, command: u8, an
         },
         SMB1_COMMAND_TREE_CONNECT_ANDX => {
             if r.nt_status != SMB_NTSTATUS_SUCCESS {
-                let name_key = SMBCommonHdr::from1(r, SMBHDR_TYPE_TREE);
+                let name_key = smb1_treeconnect_key(state, r);
                 if let Some(tx) = state.get_treeconnect_tx(name_key) {
                     if let Some(SMBTransactionTypeData::TREECONNECT(ref mut tdn)) = tx.type_data {
                         tdn.tree_id = r.tree_id as u32;
@@ -679,7 +679,7 @@ fn smb1_response_record_one(state: &mut SMBState, r: &SmbRecord, command: u8, an
 
             match parse_smb_connect_tree_andx_response_record(&r.data[*andx_offset-SMB1_HEADER_SIZE..]) {
                 Ok((_, tr)) => {
-                    let name_key = SMBCommonHdr::from1(r, SMBHDR_TYPE_TREE);
+                    let name_key = smb1_treeconnect_key(state, r);
                     let is_pipe = tr.service == "IPC".as_bytes();
                     let mut share_name = Vec::new();
                     let found = match state.get_treeconnect_tx(name_key) {
@@ -799,8 +799,14 @@ fn smb1_response_record_one(state: &mut SMBState, r: &SmbRecord, command: u8, an
             tx.set_events(events);
         }
     } else if !have_tx && smb1_check_tx(command) {
-        let tx_key = SMBCommonHdr::new(SMBHDR_TYPE_GENERICTX,
+        let mut tx_key = SMBCommonHdr::new(SMBHDR_TYPE_GENERICTX,
                 key_ssn_id as u64, key_tree_id as u32, key_multiplex_id as u64);
+        if smb1_chained_after_setup(r, command) &&
+            state.get_generic_tx(1, command as u16, &tx_key).is_none()
+        {
+            tx_key = SMBCommonHdr::new(SMBHDR_TYPE_GENERICTX,
+                    0, key_tree_id as u32, key_multiplex_id as u64);
+        }
         let _have_tx2 = match state.get_generic_tx(1, command as u16, &tx_key) {
             Some(tx) => {
                 tx.request_done = true;
@@ -820,6 +826,24 @@ fn smb1_response_record_one(state: &mut SMBState, r: &SmbRecord, command: u8, an
     }
 }
 
+/// Commands chained to a SESSION_SETUP_ANDX request are sent before the
+/// server assigned the user id, so the request side stored them with
+/// ssn_id 0 while the response already carries the new user id.
+fn smb1_chained_after_setup(r: &SmbRecord, command: u8) -> bool {
+    r.command == SMB1_COMMAND_SESSION_SETUP_ANDX && command != r.command && r.ssn_id != 0
+}
+
+/// get the key of the tree connect tx matching this response
+fn smb1_treeconnect_key(state: &mut SMBState, r: &SmbRecord) -> SMBCommonHdr {
+    let name_key = SMBCommonHdr::from1(r, SMBHDR_TYPE_TREE);
+    if smb1_chained_after_setup(r, SMB1_COMMAND_TREE_CONNECT_ANDX) &&
+        state.get_treeconnect_tx(SMBCommonHdr::from1(r, SMBHDR_TYPE_TREE)).is_none()
+    {
+        return SMBCommonHdr::new(SMBHDR_TYPE_TREE, 0, 0, r.multiplex_id as u64);
+    }
+    name_key
+}
+
 pub fn smb1_response_record(state: &mut SMBState, r: &SmbRecord) -> u32 {
     let mut andx_offset = SMB1_HEADER_SIZE;
     let mut command = r.command;
@@ -1149,3 +1173,90 @@ fn smb1_response_record_generic(state: &mut SMBState, r: &SmbRecord, events: Vec
         tx.set_events(events);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SMB1 header with NT status codes and ASCII strings
+    fn smb1_header(command: u8, flags: u8, tree_id: u16, user_id: u16, multiplex_id: u16) -> Vec<u8> {
+        let mut hdr = b"\xffSMB".to_vec();
+        hdr.push(command);
+        hdr.extend_from_slice(&[0; 4]); // nt status
+        hdr.push(flags);
+        hdr.extend_from_slice(&0x4001_u16.to_le_bytes()); // flags2
+        hdr.extend_from_slice(&[0; 12]); // pid high, signature, reserved
+        hdr.extend_from_slice(&tree_id.to_le_bytes());
+        hdr.extend_from_slice(&0xfeff_u16.to_le_bytes()); // pid
+        hdr.extend_from_slice(&user_id.to_le_bytes());
+        hdr.extend_from_slice(&multiplex_id.to_le_bytes());
+        assert_eq!(hdr.len(), SMB1_HEADER_SIZE);
+        hdr
+    }
+
+    #[test]
+    fn test_smb1_tree_connect_chained_to_session_setup() {
+        // SESSION_SETUP_ANDX request with user id 0, chained to a
+        // TREE_CONNECT_ANDX for IPC$, like psexec does
+        let mut req = smb1_header(SMB1_COMMAND_SESSION_SETUP_ANDX, 0x18, 0, 0, 64);
+        let tree_connect_offset = (SMB1_HEADER_SIZE + 38) as u16;
+        req.extend_from_slice(&[12, SMB1_COMMAND_TREE_CONNECT_ANDX, 0]);
+        req.extend_from_slice(&tree_connect_offset.to_le_bytes());
+        req.extend_from_slice(&[0; 10]); // max buffer, max mpx, vc number, session key
+        req.extend_from_slice(&0_u16.to_le_bytes()); // security blob length
+        req.extend_from_slice(&[0; 8]); // reserved, capabilities
+        req.extend_from_slice(&11_u16.to_le_bytes());
+        req.extend_from_slice(b"Unix\0Samba\0");
+        assert_eq!(req.len(), tree_connect_offset as usize);
+        req.extend_from_slice(&[4, SMB1_COMMAND_NONE, 0, 0, 0, 0, 0]);
+        req.extend_from_slice(&1_u16.to_le_bytes()); // password length
+        req.extend_from_slice(&18_u16.to_le_bytes());
+        req.extend_from_slice(b"\0\\\\SRV\\IPC$\0?????\0");
+
+        // the response carries the user id assigned by the server and
+        // the id of the new tree
+        let mut resp = smb1_header(SMB1_COMMAND_SESSION_SETUP_ANDX, 0x98, 1, 2048, 64);
+        let tree_connect_offset = (SMB1_HEADER_SIZE + 20) as u16;
+        resp.extend_from_slice(&[3, SMB1_COMMAND_TREE_CONNECT_ANDX, 0]);
+        resp.extend_from_slice(&tree_connect_offset.to_le_bytes());
+        resp.extend_from_slice(&0_u16.to_le_bytes()); // action
+        resp.extend_from_slice(&11_u16.to_le_bytes());
+        resp.extend_from_slice(b"Unix\0Samba\0");
+        assert_eq!(resp.len(), tree_connect_offset as usize);
+        resp.extend_from_slice(&[3, SMB1_COMMAND_NONE, 0, 0, 0, 0, 0]);
+        resp.extend_from_slice(&5_u16.to_le_bytes());
+        resp.extend_from_slice(b"IPC\0\0");
+
+        let mut state = SMBState::new();
+        let (_, r) = parse_smb_record(&req).unwrap();
+        smb1_request_record(&mut state, &r);
+        assert_eq!(state.transactions.len(), 2);
+        assert!(state.get_treeconnect_tx(SMBCommonHdr::new(SMBHDR_TYPE_TREE, 0, 0, 64)).is_some());
+
+        let (_, r) = parse_smb_record(&resp).unwrap();
+        assert_eq!(r.ssn_id, 2048);
+        smb1_response_record(&mut state, &r);
+
+        // the response is matched with the tree connect tx stored with user id 0
+        let tx = &state.transactions[1];
+        assert!(tx.request_done && tx.response_done);
+        assert_eq!(tx.hdr, SMBCommonHdr::new(SMBHDR_TYPE_HEADER, 2048, 1, 64));
+        match tx.type_data {
+            Some(SMBTransactionTypeData::TREECONNECT(ref tdn)) => {
+                assert_eq!(tdn.tree_id, 1);
+                assert!(tdn.is_pipe);
+                assert_eq!(tdn.share_name, b"\\SRV\\IPC$");
+                assert_eq!(tdn.res_service.as_deref(), Some(&b"IPC"[..]));
+            },
+            _ => panic!("not a tree connect tx"),
+        }
+        assert!(state.transactions.iter().all(|tx| tx.response_done));
+        assert_eq!(state.transactions.len(), 2);
+
+        // so the tree is known for the commands that follow
+        let tree_key = SMBCommonHdr::new(SMBHDR_TYPE_SHARE, 2048, 1, 0);
+        let tree = state.ssn2tree_cache.get(&tree_key).unwrap();
+        assert_eq!(tree.name, b"\\SRV\\IPC$");
+        assert!(tree.is_pipe);
+    }
+}
@@ -582,6 +582,7 @@ pub fn parse_smb_rename_request_record(i: &[u8]) -> IResult<&[u8], SmbRequestRen
 
 #[derive(Debug,PartialEq, Eq)]
 pub struct SmbRequestCreateAndXRecord<> {
+    pub desired_access: u32,
     pub disposition: u32,
     pub create_options: u32,
     pub file_name: Vec<u8>,
@@ -592,7 +593,9 @@ pub fn parse_smb_create_andx_request_record<'a>(i: &'a[u8], r: &SmbRecord)
 {
     let (i, _skip1) = take(6_usize)(i)?;
     let (i, file_name_len) = le_u16(i)?;
-    let (i, _skip3) = take(28_usize)(i)?;
+    let (i, _skip3) = take(8_usize)(i)?;
+    let (i, desired_access) = le_u32(i)?;
+    let (i, _skip4) = take(16_usize)(i)?;
     let (i, disposition) = le_u32(i)?;
     let (i, create_options) = le_u32(i)?;
     let (i, _skip2) = take(5_usize)(i)?;
@@ -603,6 +606,7 @@ pub fn parse_smb_create_andx_request_record<'a>(i: &'a[u8], r: &SmbRecord)
     )(i)?;
     let (i, _skip3) = rest(i)?;
     let record = SmbRequestCreateAndXRecord {
+        desired_access,
         disposition,
         create_options,
         file_name: file_name.unwrap_or_default(),
@@ -560,7 +560,8 @@ pub fn smb2_request_record(state: &mut SMBState, r: &Smb2Record)
                 state.ssn2vec_cache.put(name_key, cr.data.to_vec());
 
                 let tx_hdr = SMBCommonHdr::from2(r, SMBHDR_TYPE_GENERICTX);
-                let tx = state.new_create_tx(cr.data, cr.disposition, del, dir, tx_hdr);
+                let tx = state.new_create_tx(cr.data, cr.disposition, cr.desired_access, del, dir,
+                        tx_hdr);
                 tx.vercmd.set_smb2_cmd(r.command);
                 SCLogDebug!("TS CREATE TX {} created", tx.id);
                 true
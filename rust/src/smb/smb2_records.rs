@@ -244,13 +244,16 @@ pub fn parse_smb2_response_tree_connect(i: &[u8]) -> IResult<&[u8], Smb2TreeConn
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Smb2CreateRequestRecord<'a> {
+    pub desired_access: u32,
     pub disposition: u32,
     pub create_options: u32,
     pub data: &'a [u8],
 }
 
 pub fn parse_smb2_request_create(i: &[u8]) -> IResult<&[u8], Smb2CreateRequestRecord> {
-    let (i, _skip1) = take(36_usize)(i)?;
+    let (i, _skip1) = take(24_usize)(i)?;
+    let (i, desired_access) = le_u32(i)?;
+    let (i, _skip4) = take(8_usize)(i)?;
     let (i, disposition) = le_u32(i)?;
     let (i, create_options) = le_u32(i)?;
     let (i, _file_name_offset) = le_u16(i)?;
@@ -259,6 +262,7 @@ pub fn parse_smb2_request_create(i: &[u8]) -> IResult<&[u8], Smb2CreateRequestRe
     let (i, data) = take(file_name_length)(i)?;
     let (i, _skip3) = rest(i)?;
     let record = Smb2CreateRequestRecord {
+        desired_access,
         disposition,
         create_options,
         data,
@@ -693,6 +697,7 @@ mod tests {
         let data = hex::decode("390000000200000000000000000000000000000000000000810010008000000003000000020000002100200078000000800000005800000000007200760073002800000010000400000018001000000044486e510000000000000000000000000000000000000000180000001000040000001800000000004d78416300000000000000001000040000001800000000005146696400000000").unwrap();
         let result = parse_smb2_request_create(&data).unwrap();
         let record: Smb2CreateRequestRecord = result.1;
+        assert_eq!(record.desired_access, 0x100081);
         assert_eq!(record.disposition, 2); // FILE_CREATE: 2
         assert_eq!(record.create_options, 0x200021);
         assert_eq!(record.data, &[] as &[u8]);
@@ -17,24 +17,24 @@
 
 // written by Pierre Chifflier  <chifflier@wzdftpd.net>
 
-use super::snmp::{SNMPTransaction, ALPROTO_SNMP};
+use super::snmp::{SNMPState, SNMPTransaction, ALPROTO_SNMP};
 use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
 use crate::detect::uint::{DetectUintData, SCDetectU32Free, SCDetectU32Match, SCDetectU32Parse};
 use crate::detect::{
-    helper_keyword_register_sticky_buffer, DetectHelperBufferMpmRegister,
-    DetectHelperBufferRegister, DetectHelperGetData, DetectHelperKeywordRegister,
+    DetectEngineRegistrar, DetectHelperBufferRegister, DetectHelperKeywordRegister,
     DetectSignatureSetAppProto, SCSigTableAppLiteElmt, SigMatchAppendSMToList,
-    SigTableElmtStickyBuffer,
 };
 use std::os::raw::{c_int, c_void};
-use suricata_sys::sys::{DetectEngineCtx, SCDetectBufferSetActiveList, Signature};
+use suricata_sys::sys::{DetectEngineCtx, Signature};
 
 static mut G_SNMP_VERSION_KW_ID: c_int = 0;
 static mut G_SNMP_VERSION_BUFFER_ID: c_int = 0;
 static mut G_SNMP_PDUTYPE_KW_ID: c_int = 0;
 static mut G_SNMP_PDUTYPE_BUFFER_ID: c_int = 0;
-static mut G_SNMP_USM_BUFFER_ID: c_int = 0;
-static mut G_SNMP_COMMUNITY_BUFFER_ID: c_int = 0;
+static mut G_SNMP_COMMUNITY_COUNT_KW_ID: c_int = 0;
+static mut G_SNMP_COMMUNITY_COUNT_BUFFER_ID: c_int = 0;
+static mut G_SNMP_UNANSWERED_REQUESTS_KW_ID: c_int = 0;
+static mut G_SNMP_UNANSWERED_REQUESTS_BUFFER_ID: c_int = 0;
 
 unsafe extern "C" fn snmp_detect_version_setup(
     de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
@@ -106,82 +106,104 @@ unsafe extern "C" fn snmp_detect_pdutype_free(_de: *mut c_void, ctx: *mut c_void
     SCDetectU32Free(ctx);
 }
 
-unsafe extern "C" fn snmp_detect_usm_setup(
-    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+unsafe extern "C" fn snmp_detect_community_count_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
 ) -> c_int {
     if DetectSignatureSetAppProto(s, ALPROTO_SNMP) != 0 {
         return -1;
     }
-    if SCDetectBufferSetActiveList(de, s, G_SNMP_USM_BUFFER_ID) < 0 {
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
         return -1;
     }
-    return 0;
-}
-
-unsafe extern "C" fn snmp_detect_usm_get(
-    tx: *const c_void, _flow_flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
-) -> bool {
-    let tx = cast_pointer!(tx, SNMPTransaction);
-    if let Some(ref c) = tx.usm {
-        *buffer = c.as_ptr();
-        *buffer_len = c.len() as u32;
-        return true;
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_SNMP_COMMUNITY_COUNT_KW_ID,
+        ctx,
+        G_SNMP_COMMUNITY_COUNT_BUFFER_ID,
+    )
+    .is_null()
+    {
+        snmp_detect_u32_free(std::ptr::null_mut(), ctx);
+        return -1;
     }
-    return false;
+    return 0;
 }
 
-unsafe extern "C" fn snmp_detect_usm_get_data(
-    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
-    tx: *const c_void, list_id: c_int,
-) -> *mut c_void {
-    return DetectHelperGetData(
-        de,
-        transforms,
-        flow,
-        flow_flags,
-        tx,
-        list_id,
-        snmp_detect_usm_get,
-    );
+unsafe extern "C" fn snmp_detect_community_count_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, state: *mut c_void, _tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let state = cast_pointer!(state, SNMPState);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    return SCDetectU32Match(state.community_count(), ctx);
 }
 
-unsafe extern "C" fn snmp_detect_community_setup(
-    de: *mut DetectEngineCtx, s: *mut Signature, _raw: *const std::os::raw::c_char,
+unsafe extern "C" fn snmp_detect_unanswered_requests_setup(
+    de: *mut DetectEngineCtx, s: *mut Signature, raw: *const libc::c_char,
 ) -> c_int {
     if DetectSignatureSetAppProto(s, ALPROTO_SNMP) != 0 {
         return -1;
     }
-    if SCDetectBufferSetActiveList(de, s, G_SNMP_COMMUNITY_BUFFER_ID) < 0 {
+    let ctx = SCDetectU32Parse(raw) as *mut c_void;
+    if ctx.is_null() {
+        return -1;
+    }
+    if SigMatchAppendSMToList(
+        de,
+        s,
+        G_SNMP_UNANSWERED_REQUESTS_KW_ID,
+        ctx,
+        G_SNMP_UNANSWERED_REQUESTS_BUFFER_ID,
+    )
+    .is_null()
+    {
+        snmp_detect_u32_free(std::ptr::null_mut(), ctx);
         return -1;
     }
     return 0;
 }
 
-unsafe extern "C" fn snmp_detect_community_get(
-    tx: *const c_void, _flow_flags: u8, buffer: *mut *const u8, buffer_len: *mut u32,
-) -> bool {
-    let tx = cast_pointer!(tx, SNMPTransaction);
-    if let Some(ref c) = tx.community {
-        *buffer = c.as_ptr();
-        *buffer_len = c.len() as u32;
-        return true;
+unsafe extern "C" fn snmp_detect_unanswered_requests_match(
+    _de: *mut c_void, _f: *mut c_void, _flags: u8, state: *mut c_void, _tx: *mut c_void,
+    _sig: *const c_void, ctx: *const c_void,
+) -> c_int {
+    let state = cast_pointer!(state, SNMPState);
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    return SCDetectU32Match(state.unanswered_requests(), ctx);
+}
+
+unsafe extern "C" fn snmp_detect_u32_free(_de: *mut c_void, ctx: *mut c_void) {
+    // Just unbox...
+    let ctx = cast_pointer!(ctx, DetectUintData<u32>);
+    SCDetectU32Free(ctx);
+}
+
+detect_sticky_buffer! {
+    mod snmp_usm {
+        name: "snmp.usm",
+        desc: "SNMP content modifier to match on the SNMP usm",
+        url: "/rules/snmp-keywords.html#snmp-usm",
+        mpm_desc: "SNMP USM",
+        alproto: ALPROTO_SNMP,
+        dir: STREAM_TOSERVER | STREAM_TOCLIENT,
+        tx: SNMPTransaction,
+        get: |tx| tx.usm.as_ref().map(|c| c.as_bytes()),
     }
-    return false;
 }
 
-unsafe extern "C" fn snmp_detect_community_get_data(
-    de: *mut c_void, transforms: *const c_void, flow: *const c_void, flow_flags: u8,
-    tx: *const c_void, list_id: c_int,
-) -> *mut c_void {
-    return DetectHelperGetData(
-        de,
-        transforms,
-        flow,
-        flow_flags,
-        tx,
-        list_id,
-        snmp_detect_community_get,
-    );
+detect_sticky_buffer! {
+    mod snmp_community {
+        name: "snmp.community",
+        desc: "SNMP content modifier to match on the SNMP community",
+        url: "/rules/snmp-keywords.html#snmp-community",
+        mpm_desc: "SNMP Community identifier",
+        alproto: ALPROTO_SNMP,
+        dir: STREAM_TOSERVER | STREAM_TOCLIENT,
+        tx: SNMPTransaction,
+        get: |tx| tx.community.as_ref().map(|c| c.as_bytes()),
+    }
 }
 
 pub(super) unsafe extern "C" fn detect_snmp_register() {
@@ -217,33 +239,76 @@ pub(super) unsafe extern "C" fn detect_snmp_register() {
         STREAM_TOSERVER | STREAM_TOCLIENT,
     );
 
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("snmp.usm"),
-        desc: String::from("SNMP content modifier to match on the SNMP usm"),
-        url: String::from("/rules/snmp-keywords.html#snmp-usm"),
-        setup: snmp_detect_usm_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"snmp.community_count\0".as_ptr() as *const libc::c_char,
+        desc: b"match the number of distinct SNMP communities of the flow\0".as_ptr()
+            as *const libc::c_char,
+        url: b"/rules/snmp-keywords.html#snmp-community-count\0".as_ptr() as *const libc::c_char,
+        AppLayerTxMatch: Some(snmp_detect_community_count_match),
+        Setup: snmp_detect_community_count_setup,
+        Free: Some(snmp_detect_u32_free),
+        flags: 0,
     };
-    let _g_snmp_usm_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_SNMP_USM_BUFFER_ID = DetectHelperBufferMpmRegister(
-        b"snmp.usm\0".as_ptr() as *const libc::c_char,
-        b"SNMP USM\0".as_ptr() as *const libc::c_char,
+    G_SNMP_COMMUNITY_COUNT_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_SNMP_COMMUNITY_COUNT_BUFFER_ID = DetectHelperBufferRegister(
+        b"snmp.community_count\0".as_ptr() as *const libc::c_char,
         ALPROTO_SNMP,
         STREAM_TOSERVER | STREAM_TOCLIENT,
-        snmp_detect_usm_get_data,
     );
 
-    let kw = SigTableElmtStickyBuffer {
-        name: String::from("snmp.community"),
-        desc: String::from("SNMP content modifier to match on the SNMP community"),
-        url: String::from("/rules/snmp-keywords.html#snmp-community"),
-        setup: snmp_detect_community_setup,
+    let kw = SCSigTableAppLiteElmt {
+        name: b"snmp.unanswered_requests\0".as_ptr() as *const libc::c_char,
+        desc: b"match the number of SNMP requests of the flow without a successful response\0"
+            .as_ptr() as *const libc::c_char,
+        url: b"/rules/snmp-keywords.html#snmp-unanswered-requests\0".as_ptr()
+            as *const libc::c_char,
+        AppLayerTxMatch: Some(snmp_detect_unanswered_requests_match),
+        Setup: snmp_detect_unanswered_requests_setup,
+        Free: Some(snmp_detect_u32_free),
+        flags: 0,
     };
-    let _g_snmp_community_kw_id = helper_keyword_register_sticky_buffer(&kw);
-    G_SNMP_COMMUNITY_BUFFER_ID = DetectHelperBufferMpmRegister(
-        b"snmp.community\0".as_ptr() as *const libc::c_char,
-        b"SNMP Community identifier\0".as_ptr() as *const libc::c_char,
+    G_SNMP_UNANSWERED_REQUESTS_KW_ID = DetectHelperKeywordRegister(&kw);
+    G_SNMP_UNANSWERED_REQUESTS_BUFFER_ID = DetectHelperBufferRegister(
+        b"snmp.unanswered_requests\0".as_ptr() as *const libc::c_char,
         ALPROTO_SNMP,
         STREAM_TOSERVER | STREAM_TOCLIENT,
-        snmp_detect_community_get_data,
     );
+
+    snmp_usm::register(&mut DetectEngineRegistrar);
+    snmp_community::register(&mut DetectEngineRegistrar);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::detect::sticky_buffer_tx_data;
+    use crate::direction::Direction;
+
+    #[test]
+    fn test_snmp_sticky_buffers() {
+        let mut tx = SNMPTransaction::new(Direction::ToServer, 2, 1);
+        assert_eq!(
+            sticky_buffer_tx_data(snmp_community::get_tx_data, &tx),
+            None
+        );
+        assert_eq!(sticky_buffer_tx_data(snmp_usm::get_tx_data, &tx), None);
+
+        tx.community = Some(String::from("public"));
+        assert_eq!(
+            sticky_buffer_tx_data(snmp_community::get_tx_data, &tx),
+            Some(&b"public"[..])
+        );
+        // an empty community is still a buffer
+        tx.community = Some(String::new());
+        assert_eq!(
+            sticky_buffer_tx_data(snmp_community::get_tx_data, &tx),
+            Some(&b""[..])
+        );
+
+        tx.usm = Some(String::from("admin"));
+        assert_eq!(
+            sticky_buffer_tx_data(snmp_usm::get_tx_data, &tx),
+            Some(&b"admin"[..])
+        );
+    }
 }
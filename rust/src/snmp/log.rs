@@ -18,7 +18,7 @@
 // written by Pierre Chifflier  <chifflier@wzdftpd.net>
 
 use crate::jsonbuilder::{JsonBuilder, JsonError};
-use crate::snmp::snmp::SNMPTransaction;
+use crate::snmp::snmp::{SNMPState, SNMPTransaction};
 use crate::snmp::snmp_parser::{NetworkAddress, PduType};
 use std::borrow::Cow;
 
@@ -64,6 +64,10 @@ fn snmp_log_response(jsb: &mut JsonBuilder, tx: &SNMPTransaction) -> Result<(),
                 }
                 jsb.close()?;
             }
+            if info.vars_truncated {
+                jsb.set_uint("vars_count", info.vars_count as u64)?;
+                jsb.set_bool("vars_truncated", true)?;
+            }
         }
         if let Some(community) = &tx.community {
             jsb.set_string("community", community)?;
@@ -77,6 +81,26 @@ fn snmp_log_response(jsb: &mut JsonBuilder, tx: &SNMPTransaction) -> Result<(),
     return Ok(());
 }
 
+fn snmp_log_flow(jsb: &mut JsonBuilder, state: &SNMPState) -> Result<(), JsonError> {
+    jsb.open_object("snmp")?;
+    jsb.set_uint("community_count", state.community_count() as u64)?;
+    if state.communities_overflow() {
+        jsb.set_bool("communities_overflow", true)?;
+    }
+    jsb.set_uint("unanswered_requests", state.unanswered_requests() as u64)?;
+    jsb.close()?;
+    return Ok(());
+}
+
+/// Log the SNMP counters of the flow in its flow record.
+pub(super) unsafe extern "C" fn snmp_log_json_flow(
+    state: *mut std::os::raw::c_void, jsb: *mut std::os::raw::c_void,
+) -> bool {
+    let state = cast_pointer!(state, SNMPState);
+    let jb = cast_pointer!(jsb, JsonBuilder);
+    snmp_log_flow(jb, state).is_ok()
+}
+
 pub(super) unsafe extern "C" fn snmp_log_json_response(
     tx: *const std::os::raw::c_void, jsb: *mut std::os::raw::c_void,
 ) -> bool {
@@ -17,15 +17,19 @@
 
 // written by Pierre Chifflier  <chifflier@wzdftpd.net>
 
+use crate::conf::conf_get;
 use crate::direction::Direction;
 use crate::flow::Flow;
 use crate::snmp::snmp_parser::*;
 use crate::core::{self, *};
 use crate::applayer::{self, *};
-use super::log::snmp_log_json_response;
+use super::log::{snmp_log_json_flow, snmp_log_json_response};
 use super::detect::detect_snmp_register;
 use std;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::ffi::CString;
+use std::hash::{Hash, Hasher};
 
 use asn1_rs::Oid;
 use der_parser::ber::BerObjectContent;
@@ -42,10 +46,17 @@ enum SNMPEvent {
     MalformedData,
     UnknownSecurityModel,
     VersionMismatch,
+    TooManyVars,
 }
 
+/// Maximum number of variable bindings stored per PDU
+static mut SNMP_MAX_VARS: usize = 256;
+
+/// Maximum number of distinct community strings counted per flow
+const SNMP_MAX_COMMUNITIES: usize = 1024;
+
 #[derive(Default)]
-struct SNMPState<'a> {
+pub(super) struct SNMPState<'a> {
     state_data: AppLayerStateData,
 
     /// SNMP protocol version
@@ -56,6 +67,16 @@ struct SNMPState<'a> {
 
     /// tx counter for assigning incrementing id's to tx's
     tx_id: u64,
+
+    /// Hashes of the distinct community strings of the requests, up to
+    /// SNMP_MAX_COMMUNITIES
+    communities: HashSet<u64>,
+
+    /// A new community was not counted because the set was full
+    communities_overflow: bool,
+
+    /// Requests not (yet) followed by a successful response
+    unanswered_requests: u32,
 }
 
 pub(super) struct SNMPPduInfo<'a> {
@@ -66,6 +87,12 @@ pub(super) struct SNMPPduInfo<'a> {
     pub trap_type: Option<(TrapType,Oid<'a>,NetworkAddress)>,
 
     pub vars: Vec<Oid<'a>>,
+
+    /// Number of variable bindings in the PDU, including the ones not stored
+    pub vars_count: usize,
+
+    /// True if more variable bindings were present than stored
+    pub vars_truncated: bool,
 }
 
 pub(super) struct SNMPTransaction<'a> {
@@ -87,6 +114,9 @@ pub(super) struct SNMPTransaction<'a> {
     /// The internal transaction id
     id: u64,
 
+    /// Events set on the transaction, as a bitmask of SNMPEvent
+    events: u32,
+
     tx_data: applayer::AppLayerTxData,
 }
 
@@ -100,6 +130,20 @@ impl<'a> SNMPState<'a> {
     fn new() -> SNMPState<'a> {
         Default::default()
     }
+
+    /// Number of distinct community strings seen in requests. Once the set
+    /// is full, new communities are not counted anymore.
+    pub(super) fn community_count(&self) -> u32 {
+        self.communities.len() as u32
+    }
+
+    pub(super) fn communities_overflow(&self) -> bool {
+        self.communities_overflow
+    }
+
+    pub(super) fn unanswered_requests(&self) -> u32 {
+        self.unanswered_requests
+    }
 }
 
 impl<'a> Default for SNMPPduInfo<'a> {
@@ -108,7 +152,9 @@ impl<'a> Default for SNMPPduInfo<'a> {
             pdu_type: PduType(0),
             err: ErrorStatus::NoError,
             trap_type: None,
-            vars: Vec::new()
+            vars: Vec::new(),
+            vars_count: 0,
+            vars_truncated: false,
         }
     }
 }
@@ -140,12 +186,61 @@ impl<'a> SNMPState<'a> {
             }
         }
 
-        for var in pdu.vars_iter() {
+        // snmp-parser has decoded all the bindings already: the cap bounds
+        // the OIDs copied into the transaction, logged and inspected
+        let mut vars = pdu.vars_iter();
+        for var in vars.by_ref().take(unsafe { SNMP_MAX_VARS }) {
             pdu_info.vars.push(var.oid.to_owned());
         }
+        let remaining = vars.len();
+        pdu_info.vars_count = pdu_info.vars.len() + remaining;
+        if remaining > 0 {
+            SCLogDebug!("SNMP PDU has {} variable bindings, storing {}", pdu_info.vars_count, pdu_info.vars.len());
+            pdu_info.vars_truncated = true;
+            self.set_event_tx(tx, SNMPEvent::TooManyVars);
+        }
         tx.info = Some(pdu_info);
     }
 
+    /// Update the flow counters used to spot community brute forcing:
+    /// many requests with different communities, answered by errors or
+    /// not at all.
+    fn update_counters(&mut self, tx: &SNMPTransaction<'a>) {
+        let info = match tx.info {
+            Some(ref info) => info,
+            None => return,
+        };
+        match info.pdu_type {
+            PduType::GetRequest
+            | PduType::GetNextRequest
+            | PduType::SetRequest
+            | PduType::GetBulkRequest => {
+                self.unanswered_requests = self.unanswered_requests.saturating_add(1);
+                if let Some(ref community) = tx.community {
+                    self.track_community(community);
+                }
+            }
+            PduType::Response if info.err == ErrorStatus::NoError => {
+                self.unanswered_requests = self.unanswered_requests.saturating_sub(1);
+            }
+            _ => (),
+        }
+    }
+
+    fn track_community(&mut self, community: &str) {
+        let mut hasher = DefaultHasher::new();
+        community.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.communities.contains(&hash) {
+            return;
+        }
+        if self.communities.len() < SNMP_MAX_COMMUNITIES {
+            self.communities.insert(hash);
+        } else {
+            self.communities_overflow = true;
+        }
+    }
+
     fn handle_snmp_v12(&mut self, msg: SnmpMessage<'a>, _direction: Direction) -> i32 {
         let mut tx = self.new_tx(_direction);
         // in the message, version is encoded as 0 (version 1) or 1 (version 2)
@@ -155,6 +250,7 @@ impl<'a> SNMPState<'a> {
         }
         self.add_pdu_info(&msg.pdu, &mut tx);
         tx.community = Some(msg.community);
+        self.update_counters(&tx);
         self.transactions.push(tx);
         0
     }
@@ -181,6 +277,7 @@ impl<'a> SNMPState<'a> {
                 self.set_event_tx(&mut tx, SNMPEvent::UnknownSecurityModel);
             }
         }
+        self.update_counters(&tx);
         self.transactions.push(tx);
         0
     }
@@ -232,18 +329,18 @@ impl<'a> SNMPState<'a> {
     /// Set an event. The event is set on the most recent transaction.
     fn set_event(&mut self, event: SNMPEvent) {
         if let Some(tx) = self.transactions.last_mut() {
-            tx.tx_data.set_event(event as u8);
+            tx.set_event(event);
         }
     }
 
     /// Set an event on a specific transaction.
     fn set_event_tx(&self, tx: &mut SNMPTransaction, event: SNMPEvent) {
-        tx.tx_data.set_event(event as u8);
+        tx.set_event(event);
     }
 }
 
 impl<'a> SNMPTransaction<'a> {
-    fn new(direction: Direction, version: u32, id: u64) -> SNMPTransaction<'a> {
+    pub(super) fn new(direction: Direction, version: u32, id: u64) -> SNMPTransaction<'a> {
         SNMPTransaction {
             version,
             info: None,
@@ -251,9 +348,21 @@ impl<'a> SNMPTransaction<'a> {
             usm: None,
             encrypted: false,
             id,
+            events: 0,
             tx_data: applayer::AppLayerTxData::for_direction(direction),
         }
     }
+
+    fn set_event(&mut self, event: SNMPEvent) {
+        let event = event as u8;
+        self.events |= 1 << event;
+        self.tx_data.set_event(event);
+    }
+
+    #[cfg(test)]
+    fn has_event(&self, event: SNMPEvent) -> bool {
+        self.events & (1 << (event as u32)) != 0
+    }
 }
 
 /// Returns *mut SNMPState
@@ -420,6 +529,13 @@ pub unsafe extern "C" fn SCRegisterSnmpParser() {
         LogTx: Some(snmp_log_json_response),
     };
     SCOutputEvePreRegisterLogger(reg_data);
+    if let Some(val) = conf_get("app-layer.protocols.snmp.max-vars") {
+        if let Ok(v) = val.parse::<usize>() {
+            SNMP_MAX_VARS = v;
+        } else {
+            SCLogError!("Invalid value for snmp.max-vars");
+        }
+    }
     SCSigTablePreRegister(Some(detect_snmp_register));
     if AppLayerProtoDetectConfProtoDetectionEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
         // port 161
@@ -435,7 +551,127 @@ pub unsafe extern "C" fn SCRegisterSnmpParser() {
             let _ = AppLayerRegisterParser(&parser, ALPROTO_SNMP);
         }
         AppLayerParserRegisterLogger(IPPROTO_UDP, ALPROTO_SNMP);
+        SCAppLayerParserRegisterFlowLogFunc(IPPROTO_UDP, ALPROTO_SNMP, Some(snmp_log_json_flow));
     } else {
         SCLogDebug!("Protocol detector and parser disabled for SNMP.");
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// BER encode a value, with a long form length if needed
+    fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut v = vec![tag];
+        match value.len() {
+            l if l < 0x80 => v.push(l as u8),
+            l if l < 0x100 => v.extend_from_slice(&[0x81, l as u8]),
+            l if l < 0x10000 => v.extend_from_slice(&[0x82, (l >> 8) as u8, l as u8]),
+            l => v.extend_from_slice(&[0x83, (l >> 16) as u8, (l >> 8) as u8, l as u8]),
+        }
+        v.extend_from_slice(value);
+        v
+    }
+
+    /// Build a SNMPv2c message with `nvars` variable bindings
+    fn message_vars(community: &str, pdu_type: u8, err: u8, nvars: usize) -> Vec<u8> {
+        // sysDescr.0, NULL
+        let varbind = [
+            0x30, 0x0c, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00,
+        ];
+        // request id 1, error status, error index 0
+        let mut pdu = vec![0x02, 0x01, 0x01, 0x02, 0x01, err, 0x02, 0x01, 0x00];
+        pdu.extend(tlv(0x30, &varbind.repeat(nvars)));
+        // version 2c
+        let mut msg = vec![0x02, 0x01, 0x01];
+        msg.extend(tlv(0x04, community.as_bytes()));
+        msg.extend(tlv(pdu_type, &pdu));
+        tlv(0x30, &msg)
+    }
+
+    /// Build a SNMPv2c message with a single variable binding
+    fn message(community: &str, pdu_type: u8, err: u8) -> Vec<u8> {
+        message_vars(community, pdu_type, err, 1)
+    }
+
+    #[test]
+    fn test_snmp_flow_counters() {
+        let communities: Vec<String> = (0..40).map(|i| format!("guess{}", i)).collect();
+        let requests: Vec<Vec<u8>> = communities.iter().map(|c| message(c, 0xa0, 0)).collect();
+        let public = message("public", 0xa1, 0);
+        let error = message("public", 0xa2, 2);
+        let response = message("public", 0xa2, 0);
+
+        let mut state = SNMPState::new();
+        assert_eq!(state.parse(&requests[0], Direction::ToServer), 0);
+        assert_eq!(state.parse(&requests[0], Direction::ToServer), 0);
+        assert_eq!(state.parse(&public, Direction::ToServer), 0);
+        assert_eq!(state.community_count(), 2);
+        assert_eq!(state.unanswered_requests(), 3);
+
+        // an error response does not answer the request
+        assert_eq!(state.parse(&error, Direction::ToClient), 0);
+        assert_eq!(state.unanswered_requests(), 3);
+        assert_eq!(state.parse(&response, Direction::ToClient), 0);
+        assert_eq!(state.unanswered_requests(), 2);
+        // responses do not add communities
+        assert_eq!(state.community_count(), 2);
+
+        for request in &requests[1..] {
+            assert_eq!(state.parse(request, Direction::ToServer), 0);
+        }
+        assert_eq!(state.community_count(), 41);
+        assert_eq!(state.unanswered_requests(), 41);
+
+        // known communities are not counted again
+        for request in &requests {
+            assert_eq!(state.parse(request, Direction::ToServer), 0);
+        }
+        assert_eq!(state.community_count(), 41);
+        assert!(!state.communities_overflow());
+        assert_eq!(state.unanswered_requests(), 81);
+    }
+
+    #[test]
+    fn test_snmp_communities_overflow() {
+        let mut state = SNMPState::new();
+        for i in 0..SNMP_MAX_COMMUNITIES {
+            state.track_community(&format!("guess{}", i));
+        }
+        assert_eq!(state.community_count(), SNMP_MAX_COMMUNITIES as u32);
+
+        // a known community does not overflow the full set
+        state.track_community("guess0");
+        assert!(!state.communities_overflow());
+
+        state.track_community("public");
+        assert!(state.communities_overflow());
+        assert_eq!(state.community_count(), SNMP_MAX_COMMUNITIES as u32);
+    }
+
+    #[test]
+    fn test_snmp_max_vars() {
+        let request = message_vars("public", 0xa0, 0, 2);
+        // a GETBULK sized response, with more bindings than the default
+        // cap of 256
+        let response = message_vars("public", 0xa2, 0, 10_000);
+
+        let mut state = SNMPState::new();
+        assert_eq!(state.parse(&request, Direction::ToServer), 0);
+        let tx = &state.transactions[0];
+        let info = tx.info.as_ref().unwrap();
+        assert_eq!(info.vars.len(), 2);
+        assert_eq!(info.vars_count, 2);
+        assert!(!info.vars_truncated);
+        assert!(!tx.has_event(SNMPEvent::TooManyVars));
+
+        assert_eq!(state.parse(&response, Direction::ToClient), 0);
+        let tx = &state.transactions[1];
+        let info = tx.info.as_ref().unwrap();
+        assert_eq!(info.vars.len(), 256);
+        assert_eq!(info.vars_count, 10_000);
+        assert!(info.vars_truncated);
+        assert!(tx.has_event(SNMPEvent::TooManyVars));
+    }
+}
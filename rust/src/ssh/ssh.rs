@@ -17,6 +17,7 @@
 
 use super::parser;
 use crate::applayer::*;
+use crate::conf::conf_get;
 use crate::core::*;
 use crate::direction::Direction;
 use crate::flow::Flow;
@@ -38,6 +39,9 @@ pub enum SshEncryptionHandling {
 static mut ALPROTO_SSH: AppProto = ALPROTO_UNKNOWN;
 static HASSH_ENABLED: AtomicBool = AtomicBool::new(false);
 
+/// Maximum number of lines the server may send before its banner.
+static mut SSH_MAX_PRE_BANNER_LINES: u32 = 16;
+
 static mut ENCRYPTION_BYPASS_ENABLED: SshEncryptionHandling =
     SshEncryptionHandling::SSH_HANDLE_ENCRYPTION_TRACK_ONLY;
 
@@ -62,6 +66,7 @@ pub enum SSHEvent {
     LongBanner,
     InvalidRecord,
     LongKexRecord,
+    TooManyPreBannerLines,
 }
 
 #[repr(u8)]
@@ -83,6 +88,7 @@ pub struct SshHeader {
     record_left_msg: parser::MessageCode,
 
     flags: SSHConnectionState,
+    pre_banner_lines: u32,
     pub protover: Vec<u8>,
     pub swver: Vec<u8>,
 
@@ -103,6 +109,7 @@ impl SshHeader {
             record_left_msg: parser::MessageCode::Undefined(0),
 
             flags: SSHConnectionState::SshStateInProgress,
+            pre_banner_lines: 0,
             protover: Vec::new(),
             swver: Vec::new(),
 
@@ -340,6 +347,7 @@ impl SSHState {
         if hdr.flags == SSHConnectionState::SshStateBannerWaitEol {
             match parser::ssh_parse_line(input) {
                 Ok((rem, _)) => {
+                    hdr.flags = SSHConnectionState::SshStateBannerDone;
                     let mut r = self.parse_record(rem, resp, pstate, flow, stream_slice);
                     if r.is_incomplete() {
                         //adds bytes consumed by banner to incomplete result
@@ -359,62 +367,94 @@ impl SSHState {
                 }
             }
         }
-        match parser::ssh_parse_line(input) {
-            Ok((rem, line)) => {
-                if let Ok((_, banner)) = parser::ssh_parse_banner(line) {
-                    hdr.protover.extend(banner.protover);
-                    if !banner.swver.is_empty() {
-                        hdr.swver.extend(banner.swver);
+        let mut line_input = input;
+        loop {
+            let hdr = if !resp {
+                &mut self.transaction.cli_hdr
+            } else {
+                &mut self.transaction.srv_hdr
+            };
+            match parser::ssh_parse_line(line_input) {
+                Ok((rem, line)) => {
+                    if resp && !line.starts_with(b"SSH-") {
+                        // RFC 4253 section 4.2: the server may send other
+                        // lines of data before the version string
+                        hdr.pre_banner_lines += 1;
+                        if hdr.pre_banner_lines > unsafe { SSH_MAX_PRE_BANNER_LINES } {
+                            SCLogDebug!("SSH too many lines before banner");
+                            self.set_event(SSHEvent::TooManyPreBannerLines);
+                            return AppLayerResult::err();
+                        }
+                        if line.len() >= SSH_MAX_BANNER_LEN {
+                            self.set_event(SSHEvent::LongBanner);
+                        }
+                        line_input = rem;
+                        continue;
                     }
-                    hdr.flags = SSHConnectionState::SshStateBannerDone;
-                } else {
-                    SCLogDebug!("SSH invalid banner");
-                    self.set_event(SSHEvent::InvalidBanner);
-                    return AppLayerResult::err();
-                }
-                if line.len() >= SSH_MAX_BANNER_LEN {
-                    SCLogDebug!(
-                        "SSH banner too long {} vs {}",
-                        line.len(),
-                        SSH_MAX_BANNER_LEN
-                    );
-                    self.set_event(SSHEvent::LongBanner);
-                }
-                let mut r = self.parse_record(rem, resp, pstate, flow, stream_slice);
-                if r.is_incomplete() {
-                    //adds bytes consumed by banner to incomplete result
-                    r.consumed += (input.len() - rem.len()) as u32;
-                }
-                return r;
-            }
-            Err(Err::Incomplete(_)) => {
-                if input.len() < SSH_MAX_BANNER_LEN {
-                    //0 consumed, needs at least one more byte
-                    return AppLayerResult::incomplete(0_u32, (input.len() + 1) as u32);
-                } else {
-                    SCLogDebug!(
-                        "SSH banner too long {} vs {} and waiting for eol",
-                        input.len(),
-                        SSH_MAX_BANNER_LEN
-                    );
-                    if let Ok((_, banner)) = parser::ssh_parse_banner(input) {
+                    if let Ok((_, banner)) = parser::ssh_parse_banner(line) {
                         hdr.protover.extend(banner.protover);
                         if !banner.swver.is_empty() {
                             hdr.swver.extend(banner.swver);
                         }
-                        hdr.flags = SSHConnectionState::SshStateBannerWaitEol;
-                        self.set_event(SSHEvent::LongBanner);
-                        return AppLayerResult::ok();
+                        hdr.flags = SSHConnectionState::SshStateBannerDone;
                     } else {
+                        SCLogDebug!("SSH invalid banner");
                         self.set_event(SSHEvent::InvalidBanner);
                         return AppLayerResult::err();
                     }
+                    if line.len() >= SSH_MAX_BANNER_LEN {
+                        SCLogDebug!(
+                            "SSH banner too long {} vs {}",
+                            line.len(),
+                            SSH_MAX_BANNER_LEN
+                        );
+                        self.set_event(SSHEvent::LongBanner);
+                    }
+                    let mut r = self.parse_record(rem, resp, pstate, flow, stream_slice);
+                    if r.is_incomplete() {
+                        //adds bytes consumed by banner to incomplete result
+                        r.consumed += (input.len() - rem.len()) as u32;
+                    }
+                    return r;
+                }
+                Err(Err::Incomplete(_)) => {
+                    if line_input.is_empty() {
+                        // only complete lines before the banner so far
+                        return AppLayerResult::ok();
+                    }
+                    if line_input.len() < SSH_MAX_BANNER_LEN {
+                        // keep the partial line, drop the complete lines
+                        // that were skipped before it
+                        let consumed = input.len() - line_input.len();
+                        return AppLayerResult::incomplete(
+                            consumed as u32,
+                            (line_input.len() + 1) as u32,
+                        );
+                    } else {
+                        SCLogDebug!(
+                            "SSH banner too long {} vs {} and waiting for eol",
+                            line_input.len(),
+                            SSH_MAX_BANNER_LEN
+                        );
+                        if let Ok((_, banner)) = parser::ssh_parse_banner(line_input) {
+                            hdr.protover.extend(banner.protover);
+                            if !banner.swver.is_empty() {
+                                hdr.swver.extend(banner.swver);
+                            }
+                            hdr.flags = SSHConnectionState::SshStateBannerWaitEol;
+                            self.set_event(SSHEvent::LongBanner);
+                            return AppLayerResult::ok();
+                        } else {
+                            self.set_event(SSHEvent::InvalidBanner);
+                            return AppLayerResult::err();
+                        }
+                    }
+                }
+                Err(_e) => {
+                    SCLogDebug!("SSH invalid banner {}", _e);
+                    self.set_event(SSHEvent::InvalidBanner);
+                    return AppLayerResult::err();
                 }
-            }
-            Err(_e) => {
-                SCLogDebug!("SSH invalid banner {}", _e);
-                self.set_event(SSHEvent::InvalidBanner);
-                return AppLayerResult::err();
             }
         }
     }
@@ -566,6 +606,13 @@ pub unsafe extern "C" fn SCRegisterSshParser() {
         if AppLayerParserConfParserEnabled(ip_proto_str.as_ptr(), parser.name) != 0 {
             let _ = AppLayerRegisterParser(&parser, alproto);
         }
+        if let Some(val) = conf_get("app-layer.protocols.ssh.max-pre-banner-lines") {
+            if let Ok(v) = val.parse::<u32>() {
+                SSH_MAX_PRE_BANNER_LINES = v;
+            } else {
+                SCLogError!("Invalid value for ssh.max-pre-banner-lines");
+            }
+        }
         AppLayerParserRegisterLogger(IPPROTO_TCP, ALPROTO_SSH);
         SCLogDebug!("Rust ssh parser registered.");
     } else {
@@ -607,3 +654,90 @@ pub unsafe extern "C" fn SCSshTxGetLogCondition(tx: *mut std::os::raw::c_void) -
     }
     return false;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `data` in chunks of `chunk_len` bytes, keeping the bytes not
+    /// consumed by the parser like the app-layer does for incomplete results.
+    fn feed_banner(state: &mut SSHState, data: &[u8], resp: bool, chunk_len: usize) -> i32 {
+        let mut buf: Vec<u8> = Vec::new();
+        for chunk in data.chunks(chunk_len) {
+            buf.extend_from_slice(chunk);
+            let stream_slice = StreamSlice::from_slice(&buf, 0, 0);
+            let r = state.parse_banner(
+                &buf,
+                resp,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &stream_slice,
+            );
+            if r.status < 0 {
+                return r.status;
+            }
+            if r.is_incomplete() {
+                assert!((r.consumed as usize) < buf.len());
+                buf.drain(..r.consumed as usize);
+            } else {
+                buf.clear();
+            }
+            assert!(buf.len() < SSH_MAX_BANNER_LEN);
+        }
+        return 0;
+    }
+
+    #[test]
+    fn test_ssh_banner_byte_by_byte() {
+        let mut state = SSHState::new();
+        let banner = b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13\r\n";
+        assert_eq!(feed_banner(&mut state, banner, false, 1), 0);
+        let hdr = &state.transaction.cli_hdr;
+        assert!(hdr.flags == SSHConnectionState::SshStateBannerDone);
+        assert_eq!(hdr.protover, b"2.0");
+        assert_eq!(hdr.swver, b"OpenSSH_9.6p1");
+    }
+
+    #[test]
+    fn test_ssh_banner_pre_banner_lines() {
+        let data = b"Welcome\r\nauthorized use only\r\nSSH-2.0-dropbear\r\n";
+        for chunk_len in [1, 5, data.len()] {
+            let mut state = SSHState::new();
+            assert_eq!(feed_banner(&mut state, data, true, chunk_len), 0);
+            let hdr = &state.transaction.srv_hdr;
+            assert!(hdr.flags == SSHConnectionState::SshStateBannerDone);
+            assert_eq!(hdr.pre_banner_lines, 2);
+            assert_eq!(hdr.protover, b"2.0");
+            assert_eq!(hdr.swver, b"dropbear");
+        }
+
+        // only the server may send lines before its banner
+        let mut state = SSHState::new();
+        assert_eq!(feed_banner(&mut state, data, false, 1), -1);
+    }
+
+    #[test]
+    fn test_ssh_banner_too_many_pre_banner_lines() {
+        let mut state = SSHState::new();
+        let mut data = Vec::new();
+        for _ in 0..=unsafe { SSH_MAX_PRE_BANNER_LINES } {
+            data.extend_from_slice(b"junk\r\n");
+        }
+        data.extend_from_slice(b"SSH-2.0-dropbear\r\n");
+        assert_eq!(feed_banner(&mut state, &data, true, 3), -1);
+        assert!(state.transaction.srv_hdr.flags == SSHConnectionState::SshStateInProgress);
+    }
+
+    #[test]
+    fn test_ssh_banner_long_byte_by_byte() {
+        let mut state = SSHState::new();
+        let mut data = b"SSH-2.0-".to_vec();
+        data.extend_from_slice(&[b'a'; 2 * SSH_MAX_BANNER_LEN]);
+        data.extend_from_slice(b"\r\n");
+        assert_eq!(feed_banner(&mut state, &data, false, 1), 0);
+        let hdr = &state.transaction.cli_hdr;
+        assert!(hdr.flags == SSHConnectionState::SshStateBannerDone);
+        assert_eq!(hdr.protover, b"2.0");
+        assert_eq!(hdr.swver.len(), SSH_MAX_BANNER_LEN - 8);
+    }
+}
@@ -29,6 +29,12 @@ fn tftp_log_request(tx: &TFTPTransaction, jb: &mut JsonBuilder) -> Result<(), Js
     };
     jb.set_string("file", tx.filename.as_str())?;
     jb.set_string("mode", tx.mode.as_str())?;
+    if let Some(blksize) = tx.blksize {
+        jb.set_uint("blksize", blksize)?;
+    }
+    if let Some(tsize) = tx.tsize {
+        jb.set_uint("tsize", tsize)?;
+    }
     jb.close()?;
     Ok(())
 }
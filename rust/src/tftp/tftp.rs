@@ -32,11 +32,19 @@ const DATA:         u8 = 3;
 const ACK:          u8 = 4;
 const ERROR:        u8 = 5;
 
+/// Block size range allowed by RFC 2348
+const BLKSIZE_MIN: u64 = 8;
+const BLKSIZE_MAX: u64 = 65464;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct TFTPTransaction {
     pub opcode : u8,
     pub filename : String,
     pub mode : String,
+    /// Block size requested with the blksize option (RFC 2348)
+    pub blksize : Option<u16>,
+    /// Transfer size given with the tsize option (RFC 2349)
+    pub tsize : Option<u64>,
     id: u64,
     tx_data: AppLayerTxData,
 }
@@ -68,6 +76,8 @@ impl TFTPTransaction {
             opcode,
             filename,
             mode : mode.to_lowercase(),
+            blksize : None,
+            tsize : None,
             id : 0,
             tx_data: AppLayerTxData::new(),
         }
@@ -84,6 +94,22 @@ impl TFTPTransaction {
             _ => false
         }
     }
+    /// Keep the value of the options (RFC 2347) we know about
+    fn set_options(&mut self, options: Vec<(&str, &str)>) {
+        for (name, value) in options {
+            match name.to_lowercase().as_str() {
+                "blksize" => {
+                    self.blksize = value.parse::<u64>().ok()
+                        .filter(|v| (BLKSIZE_MIN..=BLKSIZE_MAX).contains(v))
+                        .map(|v| v as u16);
+                }
+                "tsize" => {
+                    self.tsize = value.parse::<u64>().ok();
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 #[no_mangle]
@@ -125,16 +151,43 @@ fn getstr(i: &[u8]) -> IResult<&[u8], &str> {
     )(i)
 }
 
+fn tftp_option(i: &[u8]) -> IResult<&[u8], (&str, &str)> {
+    let (i, name) = getstr(i)?;
+    let (i, _) = tag([0])(i)?;
+    let (i, value) = getstr(i)?;
+    let (i, _) = tag([0])(i)?;
+    Ok((i, (name, value)))
+}
+
+/// Options following the mode, a truncated or invalid option ends the
+/// list as the request itself is still usable.
+fn tftp_options(i: &[u8]) -> Vec<(&str, &str)> {
+    let mut options = Vec::new();
+    let mut rem = match i.split_first() {
+        Some((0, rem)) => rem,
+        _ => return options,
+    };
+    while !rem.is_empty() {
+        match tftp_option(rem) {
+            Ok((i, (name, value))) if !name.is_empty() => {
+                options.push((name, value));
+                rem = i;
+            }
+            _ => break,
+        }
+    }
+    options
+}
+
 fn tftp_request(slice: &[u8]) -> IResult<&[u8], TFTPTransaction> {
     let (i, _) = tag([0])(slice)?;
     let (i, opcode) = be_u8(i)?;
     let (i, filename) = getstr(i)?;
     let (i, _) = tag([0])(i)?;
     let (i, mode) = getstr(i)?;
-    Ok((i,
-        TFTPTransaction::new(opcode, String::from(filename), String::from(mode))
-       )
-      )
+    let mut tx = TFTPTransaction::new(opcode, String::from(filename), String::from(mode));
+    tx.set_options(tftp_options(i));
+    Ok((i, tx))
 }
 
 fn parse_tftp_request(input: &[u8]) -> Option<TFTPTransaction> {
@@ -211,6 +264,11 @@ mod test {
     static INVALID_OPCODE: [u8; 20] = [
             0x00, 0x06, 0x72, 0x66, 0x63, 0x31, 0x33, 0x35, 0x30, 0x2e, 0x74, 0x78, 0x74, 0x6e, 0x6f, 0x63, 0x74, 0x65, 0x74, 0x00,
     ];
+    /* blksize 1428 option followed by a truncated tsize option */
+    static READ_REQUEST_OPTIONS: [u8; 38] = [
+            0x00, 0x01, 0x72, 0x66, 0x63, 0x31, 0x33, 0x35, 0x30, 0x2e, 0x74, 0x78, 0x74, 0x00, 0x6f, 0x63, 0x74, 0x65, 0x74, 0x00,
+            0x62, 0x6c, 0x6b, 0x73, 0x69, 0x7a, 0x65, 0x00, 0x31, 0x34, 0x32, 0x38, 0x00, 0x74, 0x73, 0x69, 0x7a, 0x65,
+    ];
     static INVALID_MODE: [u8; 20] = [
             0x00, 0x01, 0x72, 0x66, 0x63, 0x31, 0x33, 0x35, 0x30, 0x2e, 0x74, 0x78, 0x74, 0x00, 0x63, 0x63, 0x63, 0x63, 0x63, 0x00,
     ];
@@ -221,6 +279,8 @@ mod test {
             opcode: READREQUEST,
             filename: String::from("rfc1350.txt"),
             mode: String::from("octet"),
+            blksize: None,
+            tsize: None,
             id: 0,
             tx_data: AppLayerTxData::new(),
         };
@@ -235,6 +295,8 @@ mod test {
             opcode: WRITEREQUEST,
             filename: String::from("rfc1350.txt"),
             mode: String::from("octet"),
+            blksize: None,
+            tsize: None,
             id: 0,
             tx_data: AppLayerTxData::new(),
         };
@@ -260,6 +322,21 @@ mod test {
         assert_eq!(None, parse_tftp_request(&INVALID_OPCODE[..]));
     }
 
+    #[test]
+    pub fn test_parse_tftp_read_request_options() {
+        let txp = parse_tftp_request(&READ_REQUEST_OPTIONS[..]).unwrap();
+        assert_eq!(txp.filename, "rfc1350.txt");
+        assert_eq!(txp.blksize, Some(1428));
+        // tsize is truncated
+        assert_eq!(txp.tsize, None);
+
+        let mut buf = READ_REQUEST_OPTIONS.to_vec();
+        buf.extend_from_slice(&[0x00, 0x30, 0x00]);
+        let txp = parse_tftp_request(&buf).unwrap();
+        assert_eq!(txp.blksize, Some(1428));
+        assert_eq!(txp.tsize, Some(0));
+    }
+
     #[test]
     pub fn test_parse_tftp_invalid_mode() {
 
@@ -18,8 +18,9 @@
 use super::websocket::{WebSocketTransaction, ALPROTO_WEBSOCKET};
 use crate::core::{STREAM_TOCLIENT, STREAM_TOSERVER};
 use crate::detect::uint::{
-    detect_parse_uint, detect_parse_uint_enum, DetectUintData, DetectUintMode, SCDetectU32Free,
-    SCDetectU32Match, SCDetectU32Parse, SCDetectU8Free, SCDetectU8Match,
+    detect_parse_uint, detect_parse_uint_enum, detect_register_uint_enum, DetectUintData,
+    DetectUintMode, SCDetectU32Free, SCDetectU32Match, SCDetectU32Parse, SCDetectU8Free,
+    SCDetectU8Match,
 };
 use crate::detect::{
     helper_keyword_register_sticky_buffer, DetectHelperBufferMpmRegister,
@@ -291,6 +292,7 @@ pub unsafe extern "C" fn SCDetectWebsocketRegister() {
         flags: 0,
     };
     G_WEBSOCKET_OPCODE_KW_ID = DetectHelperKeywordRegister(&kw);
+    detect_register_uint_enum::<u8, WebSocketOpcode>(G_WEBSOCKET_OPCODE_KW_ID);
     G_WEBSOCKET_OPCODE_BUFFER_ID = DetectHelperBufferRegister(
         b"websocket.opcode\0".as_ptr() as *const libc::c_char,
         ALPROTO_WEBSOCKET,
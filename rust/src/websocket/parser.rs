@@ -42,10 +42,15 @@ pub struct WebSocketPdu {
     pub mask: Option<u32>,
     pub payload: Vec<u8>,
     pub to_skip: u64,
+    /// The payload length is over the maximum frame length: the payload is
+    /// neither taken nor skipped.
+    pub oversized: bool,
 }
 
 // cf rfc6455#section-5.2
-pub fn parse_message(i: &[u8], max_pl_size: u32) -> IResult<&[u8], WebSocketPdu> {
+pub fn parse_message(
+    i: &[u8], max_pl_size: u32, max_frame_len: u64,
+) -> IResult<&[u8], WebSocketPdu> {
     let (i, flags_op) = be_u8(i)?;
     let fin = (flags_op & 0x80) != 0;
     let compress = (flags_op & 0x40) != 0;
@@ -68,6 +73,20 @@ pub fn parse_message(i: &[u8], max_pl_size: u32) -> IResult<&[u8], WebSocketPdu>
     } else {
         None
     };
+    if payload_len > max_frame_len {
+        return Ok((
+            i,
+            WebSocketPdu {
+                flags,
+                fin,
+                compress,
+                opcode,
+                mask,
+                oversized: true,
+                ..Default::default()
+            },
+        ));
+    }
     // we limit payload_len to u32, so as to build on 32-bit system
     // where we cannot take(usize) with a u64
     let (to_skip, payload_len) = if payload_len < u64::from(max_pl_size) {
@@ -92,6 +111,54 @@ pub fn parse_message(i: &[u8], max_pl_size: u32) -> IResult<&[u8], WebSocketPdu>
             mask,
             payload,
             to_skip,
+            oversized: false,
         },
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // FIN, binary, masked, 64-bit payload length
+    fn frame_64(len: u64) -> Vec<u8> {
+        let mut v = vec![0x82, 0xFF];
+        v.extend_from_slice(&len.to_be_bytes());
+        v.extend_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+        v.extend_from_slice(&[0x75, 0x67, 0x70, 0x70]);
+        v
+    }
+
+    #[test]
+    fn test_parse_message_64bit_length() {
+        for len in [(1u64 << 31) - 1, 1 << 31, (1 << 32) + 2, (1 << 63) - 1] {
+            let frame = frame_64(len);
+            let (rem, pdu) = parse_message(&frame, 4, (1 << 63) - 1).unwrap();
+            assert!(rem.is_empty());
+            assert!(!pdu.oversized);
+            assert_eq!(pdu.mask, Some(0x01020304));
+            assert_eq!(pdu.payload, b"test");
+            assert_eq!(pdu.to_skip, len - 4);
+        }
+    }
+
+    #[test]
+    fn test_parse_message_oversized() {
+        for len in [1u64 << 63, u64::MAX] {
+            let frame = frame_64(len);
+            let (rem, pdu) = parse_message(&frame, 4, (1 << 63) - 1).unwrap();
+            assert!(pdu.oversized);
+            assert_eq!(pdu.to_skip, 0);
+            assert!(pdu.payload.is_empty());
+            // payload is left alone
+            assert_eq!(rem.len(), 4);
+        }
+
+        // configured cap
+        let frame = frame_64(1 << 31);
+        let (_, pdu) = parse_message(&frame, 4, (1 << 31) - 1).unwrap();
+        assert!(pdu.oversized);
+        let (_, pdu) = parse_message(&frame, 4, 1 << 31).unwrap();
+        assert!(!pdu.oversized);
+    }
+}
@@ -17,7 +17,7 @@
 
 use super::parser;
 use crate::applayer::{self, *};
-use crate::conf::conf_get;
+use crate::conf::{conf_get, get_memval};
 use crate::core::{ALPROTO_FAILED, ALPROTO_UNKNOWN, IPPROTO_TCP};
 use crate::direction::Direction;
 use crate::flow::Flow;
@@ -39,6 +39,9 @@ pub(super) static mut ALPROTO_WEBSOCKET: AppProto = ALPROTO_UNKNOWN;
 
 static mut WEBSOCKET_MAX_PAYLOAD_SIZE: u32 = 0xFFFF;
 
+// RFC 6455 section 5.2: the most significant bit of a 64-bit length must be 0
+static mut WEBSOCKET_MAX_FRAME_LENGTH: u64 = i64::MAX as u64;
+
 const WEBSOCKET_DECOMPRESS_BUF_SIZE: usize = 8192;
 
 #[derive(AppLayerFrameType)]
@@ -52,6 +55,7 @@ pub enum WebSocketFrameType {
 pub enum WebSocketEvent {
     SkipEndOfPayload,
     ReassemblyLimitReached,
+    FrameLengthTooLarge,
 }
 
 #[derive(Default)]
@@ -163,10 +167,20 @@ impl WebSocketState {
         }
 
         let max_pl_size = unsafe { WEBSOCKET_MAX_PAYLOAD_SIZE };
+        let max_frame_len = unsafe { WEBSOCKET_MAX_FRAME_LENGTH };
         while !start.is_empty() {
-            match parser::parse_message(start, max_pl_size) {
+            match parser::parse_message(start, max_pl_size, max_frame_len) {
                 Ok((rem, pdu)) => {
                     let mut tx = self.new_tx(direction);
+                    if pdu.oversized {
+                        // the end of the frame cannot be trusted, stop here
+                        // instead of skipping the rest of the flow
+                        tx.tx_data
+                            .set_event(WebSocketEvent::FrameLengthTooLarge as u8);
+                        tx.pdu = pdu;
+                        self.transactions.push_back(tx);
+                        return AppLayerResult::err();
+                    }
                     let _pdu = Frame::new(
                         flow,
                         &stream_slice,
@@ -439,8 +453,54 @@ pub unsafe extern "C" fn SCRegisterWebSocketParser() {
                 SCLogError!("Invalid value for websocket.max-payload-size");
             }
         }
+        if let Some(val) = conf_get("app-layer.protocols.websocket.max-frame-length") {
+            if let Ok(v) = get_memval(val) {
+                WEBSOCKET_MAX_FRAME_LENGTH = std::cmp::min(v, i64::MAX as u64);
+            } else {
+                SCLogError!("Invalid value for websocket.max-frame-length: {}", val);
+            }
+        }
         AppLayerParserRegisterLogger(IPPROTO_TCP, ALPROTO_WEBSOCKET);
     } else {
         SCLogDebug!("Protocol detector and parser disabled for WEBSOCKET.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::STREAM_TOSERVER;
+
+    #[test]
+    fn test_websocket_frame_length_too_large() {
+        let mut state = WebSocketState::new();
+        // 2^63 bytes of payload, unmasked, followed by a valid frame
+        let mut input = vec![0x82, 0x7F, 0x80, 0, 0, 0, 0, 0, 0, 0];
+        input.extend_from_slice(&[0x81, 0x02, b'h', b'i']);
+        let r = state.parse(
+            StreamSlice::from_slice(&input, STREAM_TOSERVER, 0),
+            Direction::ToServer,
+            std::ptr::null(),
+        );
+        assert_eq!(r, AppLayerResult::err());
+        assert_eq!(state.transactions.len(), 1);
+        assert!(state.transactions[0].pdu.oversized);
+        assert_eq!(state.to_skip_ts, 0);
+    }
+
+    #[test]
+    fn test_websocket_frame_length_2g() {
+        let mut state = WebSocketState::new();
+        // 2^31 bytes of payload, unmasked: all but the first 64 KiB are skipped
+        let mut input = vec![0x82, 0x7F, 0, 0, 0, 0, 0x80, 0, 0, 0];
+        input.extend_from_slice(&[0x41; 0xFFFF]);
+        let r = state.parse(
+            StreamSlice::from_slice(&input, STREAM_TOSERVER, 0),
+            Direction::ToServer,
+            std::ptr::null(),
+        );
+        assert_eq!(r, AppLayerResult::ok());
+        assert_eq!(state.transactions.len(), 1);
+        assert_eq!(state.to_skip_ts, (1 << 31) - 0xFFFF);
+    }
+}
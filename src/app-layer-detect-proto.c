@@ -1856,6 +1856,32 @@ bool AppLayerRequestProtocolTLSUpgrade(Flow *f)
     return AppLayerRequestProtocolChange(f, 443, ALPROTO_TLS);
 }
 
+/** \brief record an upgrade negotiated by the app-layer parser.
+ *
+ *  Parsers call this when the client asks for an upgrade and again when
+ *  the server accepts or refuses it, so that the upgrade can be matched
+ *  and logged even if no protocol change happens.
+ *
+ *  \param f flow to act on
+ *  \param alproto protocol the upgrade is for
+ *  \param flags FLOW_UPGRADE_* flags to add to the flow upgrade record
+ */
+void AppLayerSetProtocolUpgrade(Flow *f, AppProto alproto, uint8_t flags)
+{
+    f->upgrade_alproto = alproto;
+    f->upgrade_flags |= flags;
+}
+
+/** \brief record a STARTTLS upgrade negotiated by the app-layer parser.
+ *
+ *  \param f flow to act on
+ *  \param flags FLOW_UPGRADE_* flags to add to the flow upgrade record
+ */
+void AppLayerSetTLSUpgrade(Flow *f, uint8_t flags)
+{
+    AppLayerSetProtocolUpgrade(f, ALPROTO_TLS, flags);
+}
+
 /** \brief Forces a flow app-layer protocol change.
  *         Happens for instance when a HTTP2 flow is seen as DOH2
  *
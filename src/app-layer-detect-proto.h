@@ -120,6 +120,8 @@ void AppLayerProtoDetectReset(Flow *);
 
 bool AppLayerRequestProtocolChange(Flow *f, uint16_t dp, AppProto expect_proto);
 bool AppLayerRequestProtocolTLSUpgrade(Flow *f);
+void AppLayerSetProtocolUpgrade(Flow *f, AppProto alproto, uint8_t flags);
+void AppLayerSetTLSUpgrade(Flow *f, uint8_t flags);
 
 void AppLayerForceProtocolChange(Flow *f, AppProto new_proto);
 
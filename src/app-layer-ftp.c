@@ -475,6 +475,9 @@ static AppLayerResult FTPParseRequest(Flow *f, void *ftp_state, AppLayerParserSt
         }
 
         switch (state->command) {
+            case FTP_COMMAND_AUTH_TLS:
+                AppLayerSetTLSUpgrade(f, FLOW_UPGRADE_REQUESTED);
+                break;
             case FTP_COMMAND_EPRT:
                 // fallthrough
             case FTP_COMMAND_PORT:
@@ -654,7 +657,10 @@ static AppLayerResult FTPParseResponse(Flow *f, void *ftp_state, AppLayerParserS
         switch (state->command) {
             case FTP_COMMAND_AUTH_TLS:
                 if (line.len >= 4 && SCMemcmp("234 ", line.buf, 4) == 0) {
+                    AppLayerSetTLSUpgrade(f, FLOW_UPGRADE_ACCEPTED);
                     AppLayerRequestProtocolTLSUpgrade(f);
+                } else if (line.len >= 1 && (line.buf[0] == '4' || line.buf[0] == '5')) {
+                    AppLayerSetTLSUpgrade(f, FLOW_UPGRADE_REFUSED);
                 }
                 break;
 
@@ -0,0 +1,101 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+/**
+ * \file
+ *
+ * Exports learned by the NFS parsers, shared by the flows of a client and
+ * a server through the IP-pair storage. NFSv2/3 clients mount an export
+ * with the MOUNT protocol, usually on another port than NFS. The storage
+ * itself is owned by the rust parser.
+ */
+
+#include "suricata-common.h"
+#include "flow.h"
+#include "ippair.h"
+#include "ippair-storage.h"
+
+#include "app-layer-nfs-ippair.h"
+
+#include "rust.h"
+
+static IPPairStorageId g_ippair_nfs_exports_id = { .id = -1 };
+
+void NFSIPPairStorageRegister(void)
+{
+    if (g_ippair_nfs_exports_id.id != -1)
+        return;
+    g_ippair_nfs_exports_id =
+            IPPairStorageRegister("nfs.exports", sizeof(void *), NULL, SCNfsIPPairExportsFree);
+}
+
+static inline int GetFlowAddresses(const Flow *f, Address *ip_src, Address *ip_dst)
+{
+    memset(ip_src, 0, sizeof(*ip_src));
+    memset(ip_dst, 0, sizeof(*ip_dst));
+    if (FLOW_IS_IPV4(f)) {
+        FLOW_COPY_IPV4_ADDR_TO_PACKET(&f->src, ip_src);
+        FLOW_COPY_IPV4_ADDR_TO_PACKET(&f->dst, ip_dst);
+    } else if (FLOW_IS_IPV6(f)) {
+        FLOW_COPY_IPV6_ADDR_TO_PACKET(&f->src, ip_src);
+        FLOW_COPY_IPV6_ADDR_TO_PACKET(&f->dst, ip_dst);
+    } else {
+        return -1;
+    }
+    return 0;
+}
+
+/**
+ * \brief get the exports of the IP-pair of a flow
+ *
+ * \param f the flow
+ * \param create create the IP-pair and its exports if they don't exist
+ * \param ipp set to the IP-pair, locked, which the caller must release
+ *            with NFSIPPairRelease when not NULL
+ *
+ * \retval exports or NULL
+ */
+void *NFSIPPairExportsGet(const Flow *f, bool create, IPPair **ipp)
+{
+    Address ip_src, ip_dst;
+
+    *ipp = NULL;
+    if (g_ippair_nfs_exports_id.id == -1)
+        return NULL;
+    if (GetFlowAddresses(f, &ip_src, &ip_dst) == -1)
+        return NULL;
+
+    if (create) {
+        *ipp = IPPairGetIPPairFromHash(&ip_src, &ip_dst);
+    } else {
+        *ipp = IPPairLookupIPPairFromHash(&ip_src, &ip_dst);
+    }
+    if (*ipp == NULL)
+        return NULL;
+
+    void *exports = IPPairGetStorageById(*ipp, g_ippair_nfs_exports_id);
+    if (exports == NULL && create) {
+        exports = SCNfsIPPairExportsNew();
+        IPPairSetStorageById(*ipp, g_ippair_nfs_exports_id, exports);
+    }
+    return exports;
+}
+
+void NFSIPPairRelease(IPPair *ipp)
+{
+    IPPairRelease(ipp);
+}
@@ -0,0 +1,34 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+/**
+ * \file
+ *
+ * Exports learned by the NFS parsers, shared by the flows of a client and
+ * a server through the IP-pair storage.
+ */
+
+#ifndef SURICATA_APP_LAYER_NFS_IPPAIR_H
+#define SURICATA_APP_LAYER_NFS_IPPAIR_H
+
+#include "ippair.h"
+
+void NFSIPPairStorageRegister(void);
+void *NFSIPPairExportsGet(const Flow *f, bool create, IPPair **ipp);
+void NFSIPPairRelease(IPPair *ipp);
+
+#endif /* SURICATA_APP_LAYER_NFS_IPPAIR_H */
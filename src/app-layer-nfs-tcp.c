@@ -36,6 +36,7 @@
 #include "app-layer-parser.h"
 
 #include "app-layer-nfs-tcp.h"
+#include "app-layer-nfs-ippair.h"
 
 #include "rust.h"
 
@@ -53,5 +54,6 @@ void RegisterNFSTCPParsers(void)
 
         SCNfsInit(&sfc);
         SCRegisterNfsParser();
+        NFSIPPairStorageRegister();
     }
 }
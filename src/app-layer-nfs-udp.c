@@ -33,6 +33,7 @@
 #include "app-layer-parser.h"
 
 #include "app-layer-nfs-udp.h"
+#include "app-layer-nfs-ippair.h"
 #include "util-enum.h"
 
 #include "rust.h"
@@ -64,6 +65,7 @@ void RegisterNFSUDPParsers(void)
 {
     SCNfsInit(&sfc);
     SCRegisterNfsUdpParser();
+    NFSIPPairStorageRegister();
 
 #ifdef UNITTESTS
     AppLayerParserRegisterProtocolUnittests(IPPROTO_UDP, ALPROTO_NFS,
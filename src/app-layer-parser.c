@@ -104,6 +104,9 @@ typedef struct AppLayerParserProtoCtx_
 
     void (*SetStreamDepthFlag)(void *tx, uint8_t flags);
 
+    /** log the state of the whole flow in its flow record */
+    bool (*LogFlow)(void *alstate, SCJsonBuilder *jb);
+
     AppLayerParserGetFrameIdByNameFn GetFrameIdByName;
     AppLayerParserGetFrameNameByIdFn GetFrameNameById;
 
@@ -642,6 +645,16 @@ void AppLayerParserRegisterSetStreamDepthFlag(uint8_t ipproto, AppProto alproto,
     SCReturn;
 }
 
+void SCAppLayerParserRegisterFlowLogFunc(
+        uint8_t ipproto, AppProto alproto, bool (*LogFlow)(void *alstate, SCJsonBuilder *jb))
+{
+    SCEnter();
+
+    alp_ctx.ctxs[alproto][FlowGetProtoMapping(ipproto)].LogFlow = LogFlow;
+
+    SCReturn;
+}
+
 /***** Get and transaction functions *****/
 
 void *AppLayerParserGetProtocolParserLocalStorage(uint8_t ipproto, AppProto alproto)
@@ -947,6 +960,9 @@ void AppLayerParserTransactionsCleanup(Flow *f, const uint8_t pkt_dir)
 
         SCLogDebug("%p/%"PRIu64" checking", tx, i);
         AppLayerTxData *txd = AppLayerParserGetTxData(ipproto, alproto, tx);
+        if (txd->first_seen == 0) {
+            txd->first_seen = SCTIME_SECS(f->lastts);
+        }
         if (AppLayerParserHasFilesInDir(txd, pkt_dir)) {
             if (pkt_dir_trunc == -1)
                 pkt_dir_trunc = IS_DISRUPTED(
@@ -1186,6 +1202,24 @@ AppLayerTxData *AppLayerParserGetTxData(uint8_t ipproto, AppProto alproto, void
     SCReturnPtr(d, "AppLayerTxData");
 }
 
+/** \brief get the number of app-layer events set on a transaction */
+uint8_t SCAppLayerParserGetTxEventCount(const Flow *f, void *tx)
+{
+    const AppLayerTxData *txd = AppLayerParserGetTxData(f->proto, f->alproto, tx);
+    if (txd == NULL || txd->events == NULL)
+        return 0;
+    return txd->events->cnt;
+}
+
+/** \brief get the time in seconds the transaction was first seen, 0 if not set yet */
+uint64_t SCAppLayerParserGetTxFirstSeen(const Flow *f, void *tx)
+{
+    const AppLayerTxData *txd = AppLayerParserGetTxData(f->proto, f->alproto, tx);
+    if (txd == NULL)
+        return 0;
+    return txd->first_seen;
+}
+
 AppLayerStateData *AppLayerParserGetStateData(uint8_t ipproto, AppProto alproto, void *state)
 {
     SCEnter();
@@ -1594,6 +1628,19 @@ void AppLayerParserSetStreamDepthFlag(uint8_t ipproto, AppProto alproto, void *s
     SCReturn;
 }
 
+/** \brief log the app-layer state of the flow in its flow record
+ *  \retval false if the logging failed */
+bool SCAppLayerParserLogFlow(const Flow *f, SCJsonBuilder *jb)
+{
+    if (f->alstate == NULL || f->alproto == ALPROTO_UNKNOWN || f->alproto >= g_alproto_max)
+        return true;
+    bool (*LogFlow)(void *, SCJsonBuilder *) =
+            alp_ctx.ctxs[f->alproto][FlowGetProtoMapping(f->proto)].LogFlow;
+    if (LogFlow == NULL)
+        return true;
+    return LogFlow(f->alstate, jb);
+}
+
 /**
  *  \param id progress value id to get the name for
  *  \param direction STREAM_TOSERVER/STREAM_TOCLIENT
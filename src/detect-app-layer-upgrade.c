@@ -0,0 +1,193 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+/**
+ * \file
+ *
+ * Implements the app_layer_upgrade keyword, matching on the upgrade
+ * (e.g. STARTTLS) record set on the flow by the app-layer parsers.
+ */
+
+#include "suricata-common.h"
+#include "detect-engine.h"
+#include "detect-parse.h"
+#include "detect-app-layer-upgrade.h"
+#include "app-layer.h"
+#include "flow.h"
+#include "util-debug.h"
+#include "util-unittest.h"
+
+#ifdef UNITTESTS
+static void DetectAppLayerUpgradeRegisterTests(void);
+#endif
+
+typedef struct DetectAppLayerUpgradeData_ {
+    /** protocol of the upgrade, ALPROTO_UNKNOWN for any */
+    AppProto alproto;
+    /** FLOW_UPGRADE_* flags that all need to be set */
+    uint8_t flags;
+} DetectAppLayerUpgradeData;
+
+static int DetectAppLayerUpgradeMatch(
+        DetectEngineThreadCtx *det_ctx, Packet *p, const Signature *s, const SigMatchCtx *ctx)
+{
+    const Flow *f = p->flow;
+    if (f == NULL || f->upgrade_flags == 0) {
+        return 0;
+    }
+
+    const DetectAppLayerUpgradeData *data = (const DetectAppLayerUpgradeData *)ctx;
+    if ((f->upgrade_flags & data->flags) != data->flags) {
+        return 0;
+    }
+    if (data->alproto != ALPROTO_UNKNOWN && !AppProtoEquals(data->alproto, f->upgrade_alproto)) {
+        return 0;
+    }
+    return 1;
+}
+
+#define MAX_UPGRADE_ARG 64
+static DetectAppLayerUpgradeData *DetectAppLayerUpgradeParse(const char *arg)
+{
+    char copy[MAX_UPGRADE_ARG];
+    if (strlcpy(copy, arg, sizeof(copy)) >= sizeof(copy)) {
+        SCLogError("app_layer_upgrade argument too long");
+        return NULL;
+    }
+
+    AppProto alproto = ALPROTO_UNKNOWN;
+    uint8_t flags = 0;
+    char *saveptr = NULL;
+    for (char *token = strtok_r(copy, ",", &saveptr); token != NULL;
+            token = strtok_r(NULL, ",", &saveptr)) {
+        while (isspace((unsigned char)*token))
+            token++;
+        size_t len = strlen(token);
+        while (len > 0 && isspace((unsigned char)token[len - 1]))
+            token[--len] = '\0';
+
+        uint8_t flag = 0;
+        if (strcmp(token, "requested") == 0) {
+            flag = FLOW_UPGRADE_REQUESTED;
+        } else if (strcmp(token, "accepted") == 0) {
+            flag = FLOW_UPGRADE_ACCEPTED;
+        } else if (strcmp(token, "refused") == 0) {
+            flag = FLOW_UPGRADE_REFUSED;
+        }
+
+        if (flag != 0) {
+            if (flags != 0) {
+                SCLogError("app_layer_upgrade only takes one of requested, accepted "
+                           "or refused");
+                return NULL;
+            }
+            flags = flag;
+        } else {
+            if (alproto != ALPROTO_UNKNOWN) {
+                SCLogError("app_layer_upgrade only takes one protocol");
+                return NULL;
+            }
+            alproto = AppLayerGetProtoByName(token);
+            if (alproto == ALPROTO_UNKNOWN) {
+                SCLogError("app_layer_upgrade keyword supplied with unknown "
+                           "protocol or option \"%s\"",
+                        token);
+                return NULL;
+            }
+        }
+    }
+    if (flags == 0 && alproto == ALPROTO_UNKNOWN) {
+        SCLogError("app_layer_upgrade needs a protocol or one of requested, "
+                   "accepted or refused");
+        return NULL;
+    }
+
+    DetectAppLayerUpgradeData *data = SCCalloc(1, sizeof(*data));
+    if (unlikely(data == NULL))
+        return NULL;
+    data->alproto = alproto;
+    data->flags = flags;
+    return data;
+}
+
+static void DetectAppLayerUpgradeFree(DetectEngineCtx *de_ctx, void *ptr)
+{
+    SCFree(ptr);
+}
+
+static int DetectAppLayerUpgradeSetup(DetectEngineCtx *de_ctx, Signature *s, const char *arg)
+{
+    DetectAppLayerUpgradeData *data = DetectAppLayerUpgradeParse(arg);
+    if (data == NULL)
+        return -1;
+
+    if (SigMatchAppendSMToList(de_ctx, s, DETECT_APP_LAYER_UPGRADE, (SigMatchCtx *)data,
+                DETECT_SM_LIST_MATCH) == NULL) {
+        DetectAppLayerUpgradeFree(de_ctx, data);
+        return -1;
+    }
+    s->flags |= SIG_FLAG_REQUIRE_PACKET;
+    return 0;
+}
+
+void DetectAppLayerUpgradeRegister(void)
+{
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].name = "app_layer_upgrade";
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].desc =
+            "match on the app-layer protocol upgrade negotiated on the flow";
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].url = "/rules/app-layer.html#app-layer-upgrade";
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].Match = DetectAppLayerUpgradeMatch;
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].Setup = DetectAppLayerUpgradeSetup;
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].Free = DetectAppLayerUpgradeFree;
+#ifdef UNITTESTS
+    sigmatch_table[DETECT_APP_LAYER_UPGRADE].RegisterTests = DetectAppLayerUpgradeRegisterTests;
+#endif
+}
+
+#ifdef UNITTESTS
+
+static int DetectAppLayerUpgradeParseTest01(void)
+{
+    DetectAppLayerUpgradeData *data = DetectAppLayerUpgradeParse("refused");
+    FAIL_IF_NULL(data);
+    FAIL_IF(data->alproto != ALPROTO_UNKNOWN);
+    FAIL_IF(data->flags != FLOW_UPGRADE_REFUSED);
+    DetectAppLayerUpgradeFree(NULL, data);
+
+    data = DetectAppLayerUpgradeParse("tls, accepted");
+    FAIL_IF_NULL(data);
+    FAIL_IF(data->alproto != ALPROTO_TLS);
+    FAIL_IF(data->flags != FLOW_UPGRADE_ACCEPTED);
+    DetectAppLayerUpgradeFree(NULL, data);
+    PASS;
+}
+
+static int DetectAppLayerUpgradeParseTest02(void)
+{
+    FAIL_IF_NOT_NULL(DetectAppLayerUpgradeParse(""));
+    FAIL_IF_NOT_NULL(DetectAppLayerUpgradeParse("nosuchproto"));
+    FAIL_IF_NOT_NULL(DetectAppLayerUpgradeParse("accepted,refused"));
+    FAIL_IF_NOT_NULL(DetectAppLayerUpgradeParse("tls,http,requested"));
+    PASS;
+}
+
+static void DetectAppLayerUpgradeRegisterTests(void)
+{
+    UtRegisterTest("DetectAppLayerUpgradeParseTest01", DetectAppLayerUpgradeParseTest01);
+    UtRegisterTest("DetectAppLayerUpgradeParseTest02", DetectAppLayerUpgradeParseTest02);
+}
+#endif /* UNITTESTS */
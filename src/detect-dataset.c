@@ -50,6 +50,9 @@
 
 static int DetectDatasetSetup (DetectEngineCtx *, Signature *, const char *);
 void DetectDatasetFree (DetectEngineCtx *, void *);
+#ifdef UNITTESTS
+static void DetectDatasetRegisterTests(void);
+#endif
 
 void DetectDatasetRegister (void)
 {
@@ -58,6 +61,9 @@ void DetectDatasetRegister (void)
     sigmatch_table[DETECT_DATASET].url = "/rules/dataset-keywords.html#dataset";
     sigmatch_table[DETECT_DATASET].Setup = DetectDatasetSetup;
     sigmatch_table[DETECT_DATASET].Free  = DetectDatasetFree;
+#ifdef UNITTESTS
+    sigmatch_table[DETECT_DATASET].RegisterTests = DetectDatasetRegisterTests;
+#endif
 }
 
 /*
@@ -449,3 +455,7 @@ void DetectDatasetFree (DetectEngineCtx *de_ctx, void *ptr)
 
     SCFree(fd);
 }
+
+#ifdef UNITTESTS
+#include "tests/detect-dataset.c"
+#endif
@@ -33,6 +33,8 @@ static int g_mime_email_message_id_buffer_id = 0;
 static int g_mime_email_x_mailer_buffer_id = 0;
 static int g_mime_email_url_buffer_id = 0;
 static int g_mime_email_received_buffer_id = 0;
+static int g_mime_email_attachment_filename_buffer_id = 0;
+static int g_mime_email_attachment_content_type_buffer_id = 0;
 
 static int DetectMimeEmailFromSetup(DetectEngineCtx *de_ctx, Signature *s, const char *arg)
 {
@@ -328,6 +330,59 @@ static bool GetMimeEmailReceivedData(DetectEngineThreadCtx *det_ctx, const void
     return true;
 }
 
+static int DetectMimeEmailAttachmentFilenameSetup(
+        DetectEngineCtx *de_ctx, Signature *s, const char *arg)
+{
+    if (SCDetectBufferSetActiveList(de_ctx, s, g_mime_email_attachment_filename_buffer_id) < 0)
+        return -1;
+
+    if (DetectSignatureSetAppProto(s, ALPROTO_SMTP) < 0)
+        return -1;
+
+    return 0;
+}
+
+static bool GetMimeEmailAttachmentFilenameData(DetectEngineThreadCtx *det_ctx, const void *txv,
+        const uint8_t flags, uint32_t idx, const uint8_t **buf, uint32_t *buf_len)
+{
+    SMTPTransaction *tx = (SMTPTransaction *)txv;
+    if (tx->mime_state == NULL) {
+        return false;
+    }
+
+    if (SCDetectMimeEmailGetAttachmentFilename(tx->mime_state, buf, buf_len, idx) != 1) {
+        return false;
+    }
+    return true;
+}
+
+static int DetectMimeEmailAttachmentContentTypeSetup(
+        DetectEngineCtx *de_ctx, Signature *s, const char *arg)
+{
+    if (SCDetectBufferSetActiveList(de_ctx, s, g_mime_email_attachment_content_type_buffer_id) < 0)
+        return -1;
+
+    if (DetectSignatureSetAppProto(s, ALPROTO_SMTP) < 0)
+        return -1;
+
+    return 0;
+}
+
+static bool GetMimeEmailAttachmentContentTypeData(DetectEngineThreadCtx *det_ctx,
+        const void *txv, const uint8_t flags, uint32_t idx, const uint8_t **buf,
+        uint32_t *buf_len)
+{
+    SMTPTransaction *tx = (SMTPTransaction *)txv;
+    if (tx->mime_state == NULL) {
+        return false;
+    }
+
+    if (SCDetectMimeEmailGetAttachmentContentType(tx->mime_state, buf, buf_len, idx) != 1) {
+        return false;
+    }
+    return true;
+}
+
 void DetectEmailRegister(void)
 {
     SCSigTableAppLiteElmt kw = { 0 };
@@ -412,4 +467,24 @@ void DetectEmailRegister(void)
     DetectHelperKeywordRegister(&kw);
     g_mime_email_received_buffer_id = DetectHelperMultiBufferMpmRegister("email.received",
             "MIME EMAIL RECEIVED", ALPROTO_SMTP, STREAM_TOSERVER, GetMimeEmailReceivedData);
+
+    kw.name = "email.attachment.filename";
+    kw.desc = "file name of an email attachment";
+    kw.url = "/rules/email-keywords.html#email.attachment.filename";
+    kw.Setup = DetectMimeEmailAttachmentFilenameSetup;
+    kw.flags = SIGMATCH_NOOPT | SIGMATCH_INFO_STICKY_BUFFER;
+    DetectHelperKeywordRegister(&kw);
+    g_mime_email_attachment_filename_buffer_id = DetectHelperMultiBufferMpmRegister(
+            "email.attachment.filename", "MIME EMAIL ATTACHMENT FILENAME", ALPROTO_SMTP,
+            STREAM_TOSERVER, GetMimeEmailAttachmentFilenameData);
+
+    kw.name = "email.attachment.content_type";
+    kw.desc = "'Content-Type' of an email attachment";
+    kw.url = "/rules/email-keywords.html#email.attachment.content_type";
+    kw.Setup = DetectMimeEmailAttachmentContentTypeSetup;
+    kw.flags = SIGMATCH_NOOPT | SIGMATCH_INFO_STICKY_BUFFER;
+    DetectHelperKeywordRegister(&kw);
+    g_mime_email_attachment_content_type_buffer_id = DetectHelperMultiBufferMpmRegister(
+            "email.attachment.content_type", "MIME EMAIL ATTACHMENT CONTENT-TYPE", ALPROTO_SMTP,
+            STREAM_TOSERVER, GetMimeEmailAttachmentContentTypeData);
 }
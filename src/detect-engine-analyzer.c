@@ -732,6 +732,8 @@ static void DumpMatches(RuleAnalyzer *ctx, SCJsonBuilder *js, const SigMatchData
         SCJbStartObject(js);
         const char *mname = sigmatch_table[smd->type].name;
         SCJbSetString(js, "name", mname);
+        /* names accepted by enumeration based integer keywords */
+        SCDetectUintEnumToJson(smd->type, js);
 
         switch (smd->type) {
             case DETECT_CONTENT: {
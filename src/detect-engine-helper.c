@@ -100,6 +100,12 @@ int DetectHelperMultiBufferMpmRegister(const char *name, const char *desc, AppPr
     return DetectHelperMultiBufferProgressMpmRegister(name, desc, alproto, direction, GetData, 0);
 }
 
+/** \brief get the id of the transaction being inspected */
+uint64_t SCDetectHelperGetTxId(const DetectEngineThreadCtx *det_ctx)
+{
+    return det_ctx->tx_id;
+}
+
 int SCDetectHelperNewKeywordId(void)
 {
     if (DETECT_TBLSIZE_IDX >= DETECT_TBLSIZE) {
@@ -29,6 +29,7 @@
 #include "rust.h"
 
 int SCDetectHelperNewKeywordId(void);
+uint64_t SCDetectHelperGetTxId(const DetectEngineThreadCtx *det_ctx);
 
 int DetectHelperKeywordRegister(const SCSigTableAppLiteElmt *kw);
 void DetectHelperKeywordAliasRegister(int kwid, const char *alias);
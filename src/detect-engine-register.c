@@ -186,6 +186,7 @@
 #include "detect-iprep.h"
 #include "detect-geoip.h"
 #include "detect-app-layer-protocol.h"
+#include "detect-app-layer-upgrade.h"
 #include "detect-template.h"
 #include "detect-template2.h"
 #include "detect-tcphdr.h"
@@ -697,6 +698,9 @@ void SigTableSetup(void)
     DetectLuaRegister();
     DetectIPRepRegister();
     DetectAppLayerProtocolRegister();
+    DetectAppLayerUpgradeRegister();
+    SCDetectAppTxRegister();
+    SCDetectAppLayerEventCountRegister();
     DetectBase64DecodeRegister();
     DetectBase64DataRegister();
     DetectTemplateRegister();
@@ -722,6 +726,7 @@ void SigTableSetup(void)
     DetectQuicVersionRegister();
     DetectQuicCyuHashRegister();
     DetectQuicCyuStringRegister();
+    SCDetectQuicRegister();
     DetectJa4HashRegister();
     DetectFtpCommandRegister();
     DetectFtpCommandDataRegister();
@@ -745,6 +750,7 @@ void SigTableSetup(void)
     DetectTransformHeaderLowercaseRegister();
     DetectTransformFromBase64DecodeRegister();
     SCDetectTransformDomainRegister();
+    SCDetectTransformPunycodeDecodeRegister();
 
     DetectFileHandlerRegister();
 
@@ -764,6 +770,11 @@ void SigTableSetup(void)
     SCDetectLdapRegister();
     SCDetectSdpRegister();
     SCDetectDNSRegister();
+    SCDetectSmbRegister();
+    SCDetectKrb5Register();
+    SCDetectNfsRegister();
+    SCDetectPgsqlRegister();
+    SCDetectRdpRegister();
 
     for (size_t i = 0; i < preregistered_callbacks_nb; i++) {
         PreregisteredCallbacks[i]();
@@ -203,6 +203,7 @@ enum DetectKeywordId {
     DETECT_PKT_DATA,
     DETECT_APP_LAYER_EVENT,
     DETECT_APP_LAYER_STATE,
+    DETECT_APP_LAYER_UPGRADE,
 
     DETECT_HTTP2_FRAMETYPE,
     DETECT_HTTP2_ERRORCODE,
@@ -210,7 +211,12 @@ enum DetectKeywordId {
     DETECT_HTTP2_WINDOW,
     DETECT_HTTP2_SIZEUPDATE,
     DETECT_HTTP2_SETTINGS,
+    DETECT_HTTP2_STREAMID,
+    DETECT_HTTP2_MAX_CONCURRENT_STREAMS,
+    DETECT_HTTP2_RST_COUNT,
     DETECT_HTTP2_HEADERNAME,
+    DETECT_HTTP2_GOAWAY_LAST_STREAM_ID,
+    DETECT_HTTP2_GOAWAY_DEBUG_DATA,
     DETECT_HTTP_REQUEST_HEADER,
     DETECT_HTTP_RESPONSE_HEADER,
 
@@ -48,6 +48,7 @@ void DetectHTTP2priorityRegisterTests (void);
 void DetectHTTP2windowRegisterTests (void);
 void DetectHTTP2settingsRegisterTests (void);
 void DetectHTTP2sizeUpdateRegisterTests (void);
+void DetectHTTP2streamIdRegisterTests(void);
 #endif
 
 /* prototypes */
@@ -87,14 +88,34 @@ static int DetectHTTP2settingsMatch(DetectEngineThreadCtx *det_ctx,
 static int DetectHTTP2settingsSetup (DetectEngineCtx *, Signature *, const char *);
 void DetectHTTP2settingsFree (DetectEngineCtx *, void *);
 
+static int DetectHTTP2streamIdMatch(DetectEngineThreadCtx *det_ctx, Flow *f, uint8_t flags,
+        void *state, void *txv, const Signature *s, const SigMatchCtx *ctx);
+static int DetectHTTP2streamIdSetup(DetectEngineCtx *, Signature *, const char *);
+static void DetectHTTP2streamIdFree(DetectEngineCtx *, void *);
+
+static int DetectHTTP2maxConcurrentStreamsMatch(DetectEngineThreadCtx *det_ctx, Flow *f,
+        uint8_t flags, void *state, void *txv, const Signature *s, const SigMatchCtx *ctx);
+static int DetectHTTP2maxConcurrentStreamsSetup(DetectEngineCtx *, Signature *, const char *);
+
+static int DetectHTTP2rstCountMatch(DetectEngineThreadCtx *det_ctx, Flow *f, uint8_t flags,
+        void *state, void *txv, const Signature *s, const SigMatchCtx *ctx);
+static int DetectHTTP2rstCountSetup(DetectEngineCtx *, Signature *, const char *);
+
 static int DetectHTTP2headerNameSetup(DetectEngineCtx *de_ctx, Signature *s, const char *arg);
 
+static int DetectHTTP2goawayLastStreamIdMatch(DetectEngineThreadCtx *det_ctx, Flow *f,
+        uint8_t flags, void *state, void *txv, const Signature *s, const SigMatchCtx *ctx);
+static int DetectHTTP2goawayLastStreamIdSetup(DetectEngineCtx *, Signature *, const char *);
+
+static int DetectHTTP2goawayDebugDataSetup(DetectEngineCtx *de_ctx, Signature *s, const char *arg);
+
 #ifdef UNITTESTS
 void DetectHTTP2RegisterTests (void);
 #endif
 
 static int g_http2_match_buffer_id = 0;
 static int g_http2_header_name_buffer_id = 0;
+static int g_http2_goaway_debug_data_buffer_id = 0;
 
 /**
  * \brief Registration function for HTTP2 keywords
@@ -168,6 +189,35 @@ void DetectHttp2Register(void)
     sigmatch_table[DETECT_HTTP2_SETTINGS].RegisterTests = DetectHTTP2settingsRegisterTests;
 #endif
 
+    sigmatch_table[DETECT_HTTP2_STREAMID].name = "http2.stream_id";
+    sigmatch_table[DETECT_HTTP2_STREAMID].desc = "match on HTTP2 stream identifier";
+    sigmatch_table[DETECT_HTTP2_STREAMID].url = "/rules/http2-keywords.html#stream-id";
+    sigmatch_table[DETECT_HTTP2_STREAMID].AppLayerTxMatch = DetectHTTP2streamIdMatch;
+    sigmatch_table[DETECT_HTTP2_STREAMID].Setup = DetectHTTP2streamIdSetup;
+    sigmatch_table[DETECT_HTTP2_STREAMID].Free = DetectHTTP2streamIdFree;
+#ifdef UNITTESTS
+    sigmatch_table[DETECT_HTTP2_STREAMID].RegisterTests = DetectHTTP2streamIdRegisterTests;
+#endif
+
+    sigmatch_table[DETECT_HTTP2_MAX_CONCURRENT_STREAMS].name = "http2.max_concurrent_streams_seen";
+    sigmatch_table[DETECT_HTTP2_MAX_CONCURRENT_STREAMS].desc =
+            "match on the highest number of HTTP2 streams open at the same time on the connection";
+    sigmatch_table[DETECT_HTTP2_MAX_CONCURRENT_STREAMS].url =
+            "/rules/http2-keywords.html#max-concurrent-streams-seen";
+    sigmatch_table[DETECT_HTTP2_MAX_CONCURRENT_STREAMS].AppLayerTxMatch =
+            DetectHTTP2maxConcurrentStreamsMatch;
+    sigmatch_table[DETECT_HTTP2_MAX_CONCURRENT_STREAMS].Setup =
+            DetectHTTP2maxConcurrentStreamsSetup;
+    sigmatch_table[DETECT_HTTP2_MAX_CONCURRENT_STREAMS].Free = DetectHTTP2streamIdFree;
+
+    sigmatch_table[DETECT_HTTP2_RST_COUNT].name = "http2.rst_count";
+    sigmatch_table[DETECT_HTTP2_RST_COUNT].desc =
+            "match on the number of HTTP2 RST_STREAM frames on the connection";
+    sigmatch_table[DETECT_HTTP2_RST_COUNT].url = "/rules/http2-keywords.html#rst-count";
+    sigmatch_table[DETECT_HTTP2_RST_COUNT].AppLayerTxMatch = DetectHTTP2rstCountMatch;
+    sigmatch_table[DETECT_HTTP2_RST_COUNT].Setup = DetectHTTP2rstCountSetup;
+    sigmatch_table[DETECT_HTTP2_RST_COUNT].Free = DetectHTTP2streamIdFree;
+
     sigmatch_table[DETECT_HTTP2_HEADERNAME].name = "http2.header_name";
     sigmatch_table[DETECT_HTTP2_HEADERNAME].desc = "sticky buffer to match on one HTTP2 header name";
     sigmatch_table[DETECT_HTTP2_HEADERNAME].url = "/rules/http2-keywords.html#header_name";
@@ -184,6 +234,34 @@ void DetectHttp2Register(void)
                                          "HTTP2 header name");
     g_http2_header_name_buffer_id = DetectBufferTypeGetByName("http2_header_name");
 
+    sigmatch_table[DETECT_HTTP2_GOAWAY_LAST_STREAM_ID].name = "http2.goaway.last_stream_id";
+    sigmatch_table[DETECT_HTTP2_GOAWAY_LAST_STREAM_ID].desc =
+            "match on the last stream identifier of a HTTP2 GOAWAY frame";
+    sigmatch_table[DETECT_HTTP2_GOAWAY_LAST_STREAM_ID].url =
+            "/rules/http2-keywords.html#goaway-last-stream-id";
+    sigmatch_table[DETECT_HTTP2_GOAWAY_LAST_STREAM_ID].AppLayerTxMatch =
+            DetectHTTP2goawayLastStreamIdMatch;
+    sigmatch_table[DETECT_HTTP2_GOAWAY_LAST_STREAM_ID].Setup = DetectHTTP2goawayLastStreamIdSetup;
+    sigmatch_table[DETECT_HTTP2_GOAWAY_LAST_STREAM_ID].Free = DetectHTTP2streamIdFree;
+
+    sigmatch_table[DETECT_HTTP2_GOAWAY_DEBUG_DATA].name = "http2.goaway.debug_data";
+    sigmatch_table[DETECT_HTTP2_GOAWAY_DEBUG_DATA].desc =
+            "sticky buffer to match on the debug data of one HTTP2 GOAWAY frame";
+    sigmatch_table[DETECT_HTTP2_GOAWAY_DEBUG_DATA].url =
+            "/rules/http2-keywords.html#goaway-debug-data";
+    sigmatch_table[DETECT_HTTP2_GOAWAY_DEBUG_DATA].Setup = DetectHTTP2goawayDebugDataSetup;
+    sigmatch_table[DETECT_HTTP2_GOAWAY_DEBUG_DATA].flags |=
+            SIGMATCH_NOOPT | SIGMATCH_INFO_STICKY_BUFFER;
+
+    DetectAppLayerMultiRegister("http2_goaway_debug_data", ALPROTO_HTTP2, SIG_FLAG_TOCLIENT,
+            HTTP2StateOpen, SCHttp2TxGetGoAwayDebugData, 2);
+    DetectAppLayerMultiRegister("http2_goaway_debug_data", ALPROTO_HTTP2, SIG_FLAG_TOSERVER,
+            HTTP2StateOpen, SCHttp2TxGetGoAwayDebugData, 2);
+
+    DetectBufferTypeSupportsMultiInstance("http2_goaway_debug_data");
+    DetectBufferTypeSetDescriptionByName("http2_goaway_debug_data", "HTTP2 GOAWAY debug data");
+    g_http2_goaway_debug_data_buffer_id = DetectBufferTypeGetByName("http2_goaway_debug_data");
+
     DetectAppLayerInspectEngineRegister(
             "http2", ALPROTO_HTTP2, SIG_FLAG_TOSERVER, 0, DetectEngineInspectGenericList, NULL);
     DetectAppLayerInspectEngineRegister(
@@ -475,6 +553,100 @@ void DetectHTTP2windowFree(DetectEngineCtx *de_ctx, void *ptr)
     SCDetectU32Free(ptr);
 }
 
+/**
+ * \brief This function is used to match HTTP2 stream id rule option on a transaction
+ *
+ * \retval 0 no match
+ * \retval 1 match
+ */
+static int DetectHTTP2streamIdMatch(DetectEngineThreadCtx *det_ctx, Flow *f, uint8_t flags,
+        void *state, void *txv, const Signature *s, const SigMatchCtx *ctx)
+{
+    const DetectU32Data *du32 = (const DetectU32Data *)ctx;
+    return DetectU32Match(SCHttp2TxGetStreamId(txv), du32);
+}
+
+static int DetectHTTP2streamIdSetup(DetectEngineCtx *de_ctx, Signature *s, const char *str)
+{
+    if (DetectSignatureSetAppProto(s, ALPROTO_HTTP2) != 0)
+        return -1;
+
+    DetectU32Data *du32 = DetectU32Parse(str);
+    if (du32 == NULL)
+        return -1;
+
+    if (SigMatchAppendSMToList(de_ctx, s, DETECT_HTTP2_STREAMID, (SigMatchCtx *)du32,
+                g_http2_match_buffer_id) == NULL) {
+        SCDetectU32Free(du32);
+        return -1;
+    }
+
+    return 0;
+}
+
+static void DetectHTTP2streamIdFree(DetectEngineCtx *de_ctx, void *ptr)
+{
+    SCDetectU32Free(ptr);
+}
+
+static int DetectHTTP2ConnCounterSetup(
+        DetectEngineCtx *de_ctx, Signature *s, const char *str, uint16_t kw)
+{
+    if (DetectSignatureSetAppProto(s, ALPROTO_HTTP2) != 0)
+        return -1;
+
+    DetectU32Data *du32 = DetectU32Parse(str);
+    if (du32 == NULL)
+        return -1;
+
+    if (SigMatchAppendSMToList(de_ctx, s, kw, (SigMatchCtx *)du32, g_http2_match_buffer_id) ==
+            NULL) {
+        SCDetectU32Free(du32);
+        return -1;
+    }
+
+    return 0;
+}
+
+/**
+ * \brief This function is used to match the highest number of concurrent
+ *        streams of the connection, on any of its transactions
+ *
+ * \retval 0 no match
+ * \retval 1 match
+ */
+static int DetectHTTP2maxConcurrentStreamsMatch(DetectEngineThreadCtx *det_ctx, Flow *f,
+        uint8_t flags, void *state, void *txv, const Signature *s, const SigMatchCtx *ctx)
+{
+    const DetectU32Data *du32 = (const DetectU32Data *)ctx;
+    return DetectU32Match(SCHttp2StateGetMaxConcurrentStreams(state), du32);
+}
+
+static int DetectHTTP2maxConcurrentStreamsSetup(
+        DetectEngineCtx *de_ctx, Signature *s, const char *str)
+{
+    return DetectHTTP2ConnCounterSetup(de_ctx, s, str, DETECT_HTTP2_MAX_CONCURRENT_STREAMS);
+}
+
+/**
+ * \brief This function is used to match the number of RST_STREAM frames of
+ *        the connection, on any of its transactions
+ *
+ * \retval 0 no match
+ * \retval 1 match
+ */
+static int DetectHTTP2rstCountMatch(DetectEngineThreadCtx *det_ctx, Flow *f, uint8_t flags,
+        void *state, void *txv, const Signature *s, const SigMatchCtx *ctx)
+{
+    const DetectU32Data *du32 = (const DetectU32Data *)ctx;
+    return DetectU32Match(SCHttp2StateGetRstCount(state), du32);
+}
+
+static int DetectHTTP2rstCountSetup(DetectEngineCtx *de_ctx, Signature *s, const char *str)
+{
+    return DetectHTTP2ConnCounterSetup(de_ctx, s, str, DETECT_HTTP2_RST_COUNT);
+}
+
 /**
  * \brief This function is used to match HTTP2 size update rule option on a transaction with those passed via http2.size_update:
  *
@@ -590,6 +762,59 @@ static int DetectHTTP2headerNameSetup(DetectEngineCtx *de_ctx, Signature *s, con
     return 0;
 }
 
+/**
+ * \brief This function is used to match the last stream identifier of the
+ *        GOAWAY frames of a transaction
+ *
+ * \retval 0 no match
+ * \retval 1 match
+ */
+static int DetectHTTP2goawayLastStreamIdMatch(DetectEngineThreadCtx *det_ctx, Flow *f,
+        uint8_t flags, void *state, void *txv, const Signature *s, const SigMatchCtx *ctx)
+{
+    uint32_t nb = 0;
+    int value = SCHttp2TxGetNextGoAwayLastStreamId(txv, flags, nb);
+    const DetectU32Data *du32 = (const DetectU32Data *)ctx;
+    while (value >= 0) {
+        if (DetectU32Match(value, du32)) {
+            return 1;
+        }
+        nb++;
+        value = SCHttp2TxGetNextGoAwayLastStreamId(txv, flags, nb);
+    }
+    return 0;
+}
+
+static int DetectHTTP2goawayLastStreamIdSetup(
+        DetectEngineCtx *de_ctx, Signature *s, const char *str)
+{
+    if (DetectSignatureSetAppProto(s, ALPROTO_HTTP2) != 0)
+        return -1;
+
+    DetectU32Data *du32 = DetectU32Parse(str);
+    if (du32 == NULL)
+        return -1;
+
+    if (SigMatchAppendSMToList(de_ctx, s, DETECT_HTTP2_GOAWAY_LAST_STREAM_ID, (SigMatchCtx *)du32,
+                g_http2_match_buffer_id) == NULL) {
+        SCDetectU32Free(du32);
+        return -1;
+    }
+
+    return 0;
+}
+
+static int DetectHTTP2goawayDebugDataSetup(DetectEngineCtx *de_ctx, Signature *s, const char *arg)
+{
+    if (SCDetectBufferSetActiveList(de_ctx, s, g_http2_goaway_debug_data_buffer_id) < 0)
+        return -1;
+
+    if (DetectSignatureSetAppProto(s, ALPROTO_HTTP2) != 0)
+        return -1;
+
+    return 0;
+}
+
 #ifdef UNITTESTS
 #include "tests/detect-http2.c"
 #endif
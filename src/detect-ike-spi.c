@@ -51,15 +51,17 @@
 #include "app-layer-ike.h"
 #include "rust-bindings.h"
 
-#define KEYWORD_NAME_INITIATOR "ike.init_spi"
-#define KEYWORD_DOC_INITIATOR  "ike-keywords.html#ike-init_spi";
-#define BUFFER_NAME_INITIATOR  "ike.init_spi"
-#define BUFFER_DESC_INITIATOR  "ike init spi"
-
-#define KEYWORD_NAME_RESPONDER "ike.resp_spi"
-#define KEYWORD_DOC_RESPONDER  "ike-keywords.html#ike-resp_spi";
-#define BUFFER_NAME_RESPONDER  "ike.resp_spi"
-#define BUFFER_DESC_RESPONDER  "ike resp spi"
+#define KEYWORD_NAME_INITIATOR  "ike.init_spi"
+#define KEYWORD_ALIAS_INITIATOR "ike.spi_initiator"
+#define KEYWORD_DOC_INITIATOR   "ike-keywords.html#ike-init_spi";
+#define BUFFER_NAME_INITIATOR   "ike.init_spi"
+#define BUFFER_DESC_INITIATOR   "ike init spi"
+
+#define KEYWORD_NAME_RESPONDER  "ike.resp_spi"
+#define KEYWORD_ALIAS_RESPONDER "ike.spi_responder"
+#define KEYWORD_DOC_RESPONDER   "ike-keywords.html#ike-resp_spi";
+#define BUFFER_NAME_RESPONDER   "ike.resp_spi"
+#define BUFFER_DESC_RESPONDER   "ike resp spi"
 
 static int g_buffer_initiator_id = 0;
 static int g_buffer_responder_id = 0;
@@ -130,6 +132,7 @@ void DetectIkeSpiRegister(void)
 {
     // register initiator
     sigmatch_table[DETECT_IKE_SPI_INITIATOR].name = KEYWORD_NAME_INITIATOR;
+    sigmatch_table[DETECT_IKE_SPI_INITIATOR].alias = KEYWORD_ALIAS_INITIATOR;
     sigmatch_table[DETECT_IKE_SPI_INITIATOR].url =
             "/rules/" KEYWORD_DOC_INITIATOR sigmatch_table[DETECT_IKE_SPI_INITIATOR].desc =
                     "sticky buffer to match on the IKE spi initiator";
@@ -149,6 +152,7 @@ void DetectIkeSpiRegister(void)
 
     // register responder
     sigmatch_table[DETECT_IKE_SPI_RESPONDER].name = KEYWORD_NAME_RESPONDER;
+    sigmatch_table[DETECT_IKE_SPI_RESPONDER].alias = KEYWORD_ALIAS_RESPONDER;
     sigmatch_table[DETECT_IKE_SPI_RESPONDER].url =
             "/rules/" KEYWORD_DOC_RESPONDER sigmatch_table[DETECT_IKE_SPI_RESPONDER].desc =
                     "sticky buffer to match on the IKE spi responder";
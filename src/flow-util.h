@@ -61,6 +61,8 @@
         (f)->alproto_tc = 0;                                                                       \
         (f)->alproto_orig = 0;                                                                     \
         (f)->alproto_expect = 0;                                                                   \
+        (f)->upgrade_alproto = 0;                                                                  \
+        (f)->upgrade_flags = 0;                                                                    \
         (f)->de_ctx_version = 0;                                                                   \
         (f)->thread_id[0] = 0;                                                                     \
         (f)->thread_id[1] = 0;                                                                     \
@@ -106,6 +108,8 @@
         (f)->alproto_tc = 0;                                                                       \
         (f)->alproto_orig = 0;                                                                     \
         (f)->alproto_expect = 0;                                                                   \
+        (f)->upgrade_alproto = 0;                                                                  \
+        (f)->upgrade_flags = 0;                                                                    \
         (f)->de_ctx_version = 0;                                                                   \
         (f)->thread_id[0] = 0;                                                                     \
         (f)->thread_id[1] = 0;                                                                     \
@@ -1192,6 +1192,17 @@ void FlowGetLastTimeAsParts(Flow *flow, uint64_t *secs, uint64_t *usecs)
     *usecs = (uint64_t)SCTIME_USECS(flow->lastts);
 }
 
+/**
+ * \brief Get flow start time.
+ *
+ * Like FlowGetLastTimeAsParts, for the time of the first packet of the flow.
+ */
+void FlowGetStartTimeAsParts(Flow *flow, uint64_t *secs, uint64_t *usecs)
+{
+    *secs = (uint64_t)SCTIME_SECS(flow->startts);
+    *usecs = (uint64_t)SCTIME_USECS(flow->startts);
+}
+
 /**
  * \brief Get flow source port.
  *
@@ -245,6 +245,11 @@ typedef struct AppLayerParserState_ AppLayerParserState;
 #define FLOW_END_FLAG_SHUTDOWN  0x08
 #define FLOW_END_FLAG_TCPREUSE  0x10
 
+/* app-layer protocol upgrade record flags, see Flow::upgrade_flags */
+#define FLOW_UPGRADE_REQUESTED BIT_U8(0)
+#define FLOW_UPGRADE_ACCEPTED  BIT_U8(1)
+#define FLOW_UPGRADE_REFUSED   BIT_U8(2)
+
 /** Mutex or RWLocks for the flow. */
 //#define FLOWLOCK_RWLOCK
 #define FLOWLOCK_MUTEX
@@ -457,6 +462,11 @@ typedef struct Flow_
     /** expected app protocol: used in protocol change/upgrade like in
      *  STARTTLS. */
     AppProto alproto_expect;
+    /** protocol of the upgrade negotiated by the app-layer parser, e.g.
+     *  TLS for STARTTLS, whether it was accepted or not. */
+    AppProto upgrade_alproto;
+    uint8_t upgrade_flags;
+    /* coccinelle: Flow:upgrade_flags:FLOW_UPGRADE_ */
 
     /** detection engine ctx version used to inspect this flow. Set at initial
      *  inspection. If it doesn't match the currently in use de_ctx, the
@@ -583,6 +593,7 @@ FlowStorageId GetFlowBypassInfoID(void);
 void RegisterFlowBypassInfo(void);
 
 void FlowGetLastTimeAsParts(Flow *flow, uint64_t *secs, uint64_t *usecs);
+void FlowGetStartTimeAsParts(Flow *flow, uint64_t *secs, uint64_t *usecs);
 uint32_t FlowGetFlags(Flow *flow);
 uint16_t FlowGetSourcePort(Flow *flow);
 uint16_t FlowGetDestinationPort(Flow *flow);
@@ -358,11 +358,24 @@ static void EveFlowLogJSON(OutputJsonThreadCtx *aft, SCJsonBuilder *jb, Flow *f)
         }
     }
 
+    if (f->upgrade_flags != 0) {
+        SCJbOpenObject(jb, "upgrade");
+        SCJbSetString(jb, "app_proto", AppProtoToString(f->upgrade_alproto));
+        SCJbSetBool(jb, "requested", f->upgrade_flags & FLOW_UPGRADE_REQUESTED);
+        if (f->upgrade_flags & (FLOW_UPGRADE_ACCEPTED | FLOW_UPGRADE_REFUSED)) {
+            SCJbSetBool(jb, "accepted", f->upgrade_flags & FLOW_UPGRADE_ACCEPTED);
+        }
+        SCJbClose(jb);
+    }
+
     /* Close flow. */
     SCJbClose(jb);
 
     EveAddCommonOptions(&aft->ctx->cfg, NULL, f, jb, LOG_DIR_FLOW);
 
+    /* app-layer state kept for the whole flow */
+    SCAppLayerParserLogFlow(f, jb);
+
     /* TCP */
     if (f->proto == IPPROTO_TCP) {
         SCJbOpenObject(jb, "tcp");
@@ -0,0 +1,213 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+/**
+ * \file
+ *
+ * Implement JSON/eve logging app-layer LDAP, with the field selection
+ * of the eve-log.ldap configuration handled by the Rust logger.
+ */
+
+#include "suricata-common.h"
+#include "conf.h"
+
+#include "output.h"
+#include "output-json.h"
+
+#include "app-layer.h"
+#include "app-layer-parser.h"
+
+#include "output-json-ldap.h"
+#include "rust.h"
+
+typedef struct LogLdapFileCtx_ {
+    void *rs_logger;
+    OutputJsonCtx *eve_ctx;
+} LogLdapFileCtx;
+
+typedef struct LogLdapLogThread_ {
+    LogLdapFileCtx *ldaplog_ctx;
+    OutputJsonThreadCtx *thread;
+} LogLdapLogThread;
+
+/** \brief log the response side of a transaction, with split-records */
+static int JsonLdapLoggerToClient(ThreadVars *tv, LogLdapLogThread *thread, const Packet *p,
+        void *tx, uint64_t tx_id)
+{
+    LogLdapFileCtx *ctx = thread->ldaplog_ctx;
+
+    if (!SCLdapLogResponseEnabled(ctx->rs_logger, tx)) {
+        return TM_ECODE_OK;
+    }
+
+    SCJsonBuilder *js = CreateEveHeaderWithTxId(p, LOG_DIR_FLOW, "ldap", NULL, tx_id, ctx->eve_ctx);
+    if (unlikely(js == NULL)) {
+        return TM_ECODE_FAILED;
+    }
+
+    if (!SCLdapLoggerLogResponse(ctx->rs_logger, tx, js)) {
+        SCJbFree(js);
+        return TM_ECODE_FAILED;
+    }
+
+    OutputJsonBuilderBuffer(tv, p, p->flow, js, thread->thread);
+    SCJbFree(js);
+
+    return TM_ECODE_OK;
+}
+
+/** \brief log the request side of a transaction as soon as it is seen
+ *
+ *  Registered as a logger of its own, which only logs when split-records
+ *  is enabled. Otherwise the request is logged with the responses.
+ */
+static int JsonLdapLoggerToServer(ThreadVars *tv, void *thread_data, const Packet *p, Flow *f,
+        void *state, void *tx, uint64_t tx_id)
+{
+    LogLdapLogThread *thread = thread_data;
+    LogLdapFileCtx *ctx = thread->ldaplog_ctx;
+
+    if (!SCLdapLoggerSplitRecords(ctx->rs_logger) ||
+            !SCLdapLogRequestEnabled(ctx->rs_logger, tx)) {
+        return TM_ECODE_OK;
+    }
+
+    SCJsonBuilder *js = CreateEveHeaderWithTxId(p, LOG_DIR_FLOW, "ldap", NULL, tx_id, ctx->eve_ctx);
+    if (unlikely(js == NULL)) {
+        return TM_ECODE_FAILED;
+    }
+
+    if (!SCLdapLoggerLogRequest(ctx->rs_logger, tx, js)) {
+        SCJbFree(js);
+        return TM_ECODE_FAILED;
+    }
+
+    OutputJsonBuilderBuffer(tv, p, p->flow, js, thread->thread);
+    SCJbFree(js);
+
+    return TM_ECODE_OK;
+}
+
+static bool JsonLdapLoggerToServerCondition(
+        ThreadVars *tv, const Packet *p, void *state, void *tx, uint64_t tx_id)
+{
+    return SCLdapTxRequestDone(tx);
+}
+
+static int JsonLdapLogger(ThreadVars *tv, void *thread_data, const Packet *p, Flow *f,
+        void *state, void *tx, uint64_t tx_id)
+{
+    LogLdapLogThread *thread = thread_data;
+    LogLdapFileCtx *ctx = thread->ldaplog_ctx;
+
+    if (SCLdapLoggerSplitRecords(ctx->rs_logger)) {
+        return JsonLdapLoggerToClient(tv, thread, p, tx, tx_id);
+    }
+
+    SCJsonBuilder *js = CreateEveHeader(p, LOG_DIR_FLOW, "ldap", NULL, ctx->eve_ctx);
+    if (unlikely(js == NULL)) {
+        return TM_ECODE_FAILED;
+    }
+
+    if (!SCLdapLoggerLogWithConfig(ctx->rs_logger, tx, js)) {
+        SCJbFree(js);
+        return TM_ECODE_FAILED;
+    }
+
+    OutputJsonBuilderBuffer(tv, p, p->flow, js, thread->thread);
+    SCJbFree(js);
+
+    return TM_ECODE_OK;
+}
+
+static void OutputLdapLogDeInitCtxSub(OutputCtx *output_ctx)
+{
+    LogLdapFileCtx *ldaplog_ctx = (LogLdapFileCtx *)output_ctx->data;
+    SCLdapLoggerFree(ldaplog_ctx->rs_logger);
+    SCFree(ldaplog_ctx);
+    SCFree(output_ctx);
+}
+
+static OutputInitResult OutputLdapLogInitSub(SCConfNode *conf, OutputCtx *parent_ctx)
+{
+    OutputInitResult result = { NULL, false };
+
+    LogLdapFileCtx *ldaplog_ctx = SCCalloc(1, sizeof(*ldaplog_ctx));
+    if (unlikely(ldaplog_ctx == NULL)) {
+        return result;
+    }
+    ldaplog_ctx->eve_ctx = parent_ctx->data;
+
+    OutputCtx *output_ctx = SCCalloc(1, sizeof(*output_ctx));
+    if (unlikely(output_ctx == NULL)) {
+        SCFree(ldaplog_ctx);
+        return result;
+    }
+    output_ctx->data = ldaplog_ctx;
+    output_ctx->DeInit = OutputLdapLogDeInitCtxSub;
+
+    ldaplog_ctx->rs_logger = SCLdapLoggerNew(conf);
+
+    AppLayerParserRegisterLogger(IPPROTO_TCP, ALPROTO_LDAP);
+    AppLayerParserRegisterLogger(IPPROTO_UDP, ALPROTO_LDAP);
+
+    result.ctx = output_ctx;
+    result.ok = true;
+    return result;
+}
+
+static TmEcode JsonLdapLogThreadInit(ThreadVars *t, const void *initdata, void **data)
+{
+    LogLdapLogThread *thread = SCCalloc(1, sizeof(*thread));
+    if (unlikely(thread == NULL)) {
+        return TM_ECODE_FAILED;
+    }
+    LogLdapFileCtx *ctx = ((OutputCtx *)initdata)->data;
+    thread->ldaplog_ctx = ctx;
+    thread->thread = CreateEveThreadCtx(t, ctx->eve_ctx);
+    if (thread->thread == NULL) {
+        SCFree(thread);
+        return TM_ECODE_FAILED;
+    }
+
+    *data = (void *)thread;
+    return TM_ECODE_OK;
+}
+
+static TmEcode JsonLdapLogThreadDeinit(ThreadVars *t, void *data)
+{
+    LogLdapLogThread *thread = (LogLdapLogThread *)data;
+    if (thread == NULL) {
+        return TM_ECODE_OK;
+    }
+    FreeEveThreadCtx(thread->thread);
+    SCFree(thread);
+    return TM_ECODE_OK;
+}
+
+void JsonLdapLogRegister(void)
+{
+    /* Register as an eve sub-module. The request logger comes first so that
+     * with split-records the request is logged before the responses when
+     * both are done in the same packet. */
+    OutputRegisterTxSubModuleWithCondition(LOGGER_JSON_TX, "eve-log", "JsonLdapRequestLog",
+            "eve-log.ldap", OutputLdapLogInitSub, ALPROTO_LDAP, JsonLdapLoggerToServer,
+            JsonLdapLoggerToServerCondition, JsonLdapLogThreadInit, JsonLdapLogThreadDeinit);
+    OutputRegisterTxSubModule(LOGGER_JSON_TX, "eve-log", "JsonLdapLog", "eve-log.ldap",
+            OutputLdapLogInitSub, ALPROTO_LDAP, JsonLdapLogger, JsonLdapLogThreadInit,
+            JsonLdapLogThreadDeinit);
+}
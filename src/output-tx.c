@@ -53,6 +53,8 @@ typedef struct OutputTxLogger_ {
     struct OutputTxLogger_ *next;
     const char *name;
     LoggerId logger_id;
+    /** flag set in the tx logged flags once the logger is done with a tx */
+    uint32_t logged_flag;
     uint32_t id;
     int tc_log_progress;
     int ts_log_progress;
@@ -89,6 +91,7 @@ int SCOutputRegisterTxLogger(LoggerId id, const char *name, AppProto alproto, Tx
     op->initdata = initdata;
     op->name = name;
     op->logger_id = id;
+    op->logged_flag = BIT_U32(id);
     op->ThreadInit = ThreadInit;
     op->ThreadDeinit = ThreadDeinit;
 
@@ -116,9 +119,33 @@ int SCOutputRegisterTxLogger(LoggerId id, const char *name, AppProto alproto, Tx
         op->id = 1;
         list[alproto] = op;
     } else {
+        /* a protocol can have several loggers with the same id, like a
+         * request and a response eve logger. Each of them needs its own
+         * flag so that a tx is handed once to every one of them. These
+         * flags are taken from the bits above the last logger id, so
+         * they can't collide with the flag of any logger. */
+        uint32_t used = 0;
+        bool shared = false;
         OutputTxLogger *t = list[alproto];
-        while (t->next)
+        while (t) {
+            used |= t->logged_flag;
+            if (t->logger_id == id && t->LogFunc != LogFunc) {
+                shared = true;
+            }
+            if (t->next == NULL)
+                break;
             t = t->next;
+        }
+        if (shared) {
+            int bit = 31;
+            while (bit >= LOGGER_SIZE && (used & BIT_U32(bit)) != 0) {
+                bit--;
+            }
+            if (bit < LOGGER_SIZE) {
+                FatalError("Too many loggers registered for %s.", AppProtoToString(alproto));
+            }
+            op->logged_flag = BIT_U32(bit);
+        }
         if (t->id * 2ULL > UINT32_MAX) {
             FatalError("Too many loggers registered.");
         }
@@ -287,7 +314,7 @@ static void OutputTxLogCallLoggers(ThreadVars *tv, OutputTxLoggerThreadData *op_
                    "tc_log_progress %d",
                 logger, logger->alproto, logger->LogCondition, logger->ts_log_progress,
                 logger->tc_log_progress);
-        if ((ctx->tx_logged_old & BIT_U32(logger->logger_id)) == 0) {
+        if ((ctx->tx_logged_old & logger->logged_flag) == 0) {
             SCLogDebug("alproto match %d, logging tx_id %" PRIu64, logger->alproto, tx_id);
 
             SCLogDebug("pcap_cnt %" PRIu64 ", tx_id %" PRIu64 " logger %d. EOF %s", p->pcap_cnt,
@@ -319,7 +346,7 @@ static void OutputTxLogCallLoggers(ThreadVars *tv, OutputTxLoggerThreadData *op_
             logger->LogFunc(tv, store->thread_data, p, f, alstate, tx, tx_id);
             PACKET_PROFILING_LOGGER_END(p, logger->logger_id);
 
-            ctx->tx_logged |= BIT_U32(logger->logger_id);
+            ctx->tx_logged |= logger->logged_flag;
         }
 
     next_logger:
@@ -656,6 +683,19 @@ void OutputTxLoggerRegister (void)
             OutputTxLoggerGetActiveCount);
 }
 
+/** \brief get the tx logged flags of the loggers of a protocol */
+LoggerId OutputTxLoggerGetLoggedFlags(AppProto alproto)
+{
+    LoggerId flags = 0;
+    if (list == NULL) {
+        return flags;
+    }
+    for (const OutputTxLogger *t = list[alproto]; t != NULL; t = t->next) {
+        flags |= t->logged_flag;
+    }
+    return flags;
+}
+
 void OutputTxShutdown(void)
 {
     // called in different places because of unix socket mode, and engine-analysis mode
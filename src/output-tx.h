@@ -81,6 +81,9 @@ int SCOutputRegisterTxLogger(LoggerId id, const char *name, AppProto alproto, Tx
 /** Internal function: private API. */
 void OutputTxLoggerRegister (void);
 
+/** Internal function: private API. */
+LoggerId OutputTxLoggerGetLoggedFlags(AppProto alproto);
+
 /** Internal function: private API. */
 void OutputTxShutdown(void);
 
@@ -73,6 +73,7 @@
 #include "output-json-smb.h"
 #include "output-json-ike.h"
 #include "output-json-dhcp.h"
+#include "output-json-ldap.h"
 #include "output-json-mqtt.h"
 #include "output-json-pgsql.h"
 #include "output-lua.h"
@@ -1141,9 +1142,7 @@ void OutputRegisterLoggers(void)
             OutputJsonLogInitSub, ALPROTO_ENIP, JsonGenericDirFlowLogger, JsonLogThreadInit,
             JsonLogThreadDeinit);
     /* Ldap JSON logger. */
-    OutputRegisterTxSubModule(LOGGER_JSON_TX, "eve-log", "JsonLdapLog", "eve-log.ldap",
-            OutputJsonLogInitSub, ALPROTO_LDAP, JsonGenericDirFlowLogger, JsonLogThreadInit,
-            JsonLogThreadDeinit);
+    JsonLdapLogRegister();
     /* DoH2 JSON logger. */
     JsonDoh2LogRegister();
     /* POP3 JSON logger */
@@ -630,7 +630,7 @@ static void SetupOutput(
                 module->TxLogCondition, module->ThreadInit, module->ThreadDeinit);
         /* Not used with wild card loggers */
         if (module->alproto != ALPROTO_UNKNOWN) {
-            logger_bits[module->alproto] |= BIT_U32(module->logger_id);
+            logger_bits[module->alproto] |= OutputTxLoggerGetLoggedFlags(module->alproto);
         }
     } else if (module->FiledataLogFunc) {
         SCLogDebug("%s is a filedata logger", module->name);
@@ -0,0 +1,120 @@
+/* Copyright (C) 2025 Open Information Security Foundation
+ *
+ * You can copy, redistribute or modify this Program under the terms of
+ * the GNU General Public License version 2 as published by the Free
+ * Software Foundation.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * version 2 along with this program; if not, write to the Free Software
+ * Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA
+ * 02110-1301, USA.
+ */
+
+#include "detect-engine-build.h"
+#include "detect-engine-alert.h"
+#include "app-layer-parser.h"
+#include "util-unittest.h"
+#include "util-unittest-helper.h"
+#include "stream-tcp.h"
+
+/**
+ * \test Test dataset lookups of the values of a multi buffer: the search
+ *       filter values of a LDAP request
+ */
+static int DetectDatasetTest01(void)
+{
+    /* searchRequest, message id 2,
+     * (&(objectClass=user)(sAMAccountName=svc_backup)(!(cn=guest))(mail=*)) */
+    uint8_t search_request[] = {
+        0x30, 0x62, 0x02, 0x01, 0x02, 0x63, 0x5d, 0x04, 0x00, 0x0a, 0x01, 0x00, 0x0a, 0x01, 0x00,
+        0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x48, 0xa3, 0x13, 0x04, 0x0b,
+        'o', 'b', 'j', 'e', 'c', 't', 'C', 'l', 'a', 's', 's', 0x04, 0x04, 'u', 's', 'e', 'r',
+        0xa3, 0x1c, 0x04, 0x0e, 's', 'A', 'M', 'A', 'c', 'c', 'o', 'u', 'n', 't', 'N', 'a', 'm',
+        'e', 0x04, 0x0a, 's', 'v', 'c', '_', 'b', 'a', 'c', 'k', 'u', 'p', 0xa2, 0x0d, 0xa3, 0x0b,
+        0x04, 0x02, 'c', 'n', 0x04, 0x05, 'g', 'u', 'e', 's', 't', 0x87, 0x04, 'm', 'a', 'i', 'l',
+        0x30, 0x00
+    };
+
+    Flow f;
+    TcpSession ssn;
+    ThreadVars tv;
+    DetectEngineThreadCtx *det_ctx = NULL;
+    AppLayerParserThreadCtx *alp_tctx = AppLayerParserThreadCtxAlloc();
+
+    memset(&tv, 0, sizeof(ThreadVars));
+    memset(&f, 0, sizeof(Flow));
+    memset(&ssn, 0, sizeof(TcpSession));
+
+    Packet *p = UTHBuildPacketReal(search_request, sizeof(search_request), IPPROTO_TCP,
+            "192.168.1.5", "192.168.1.1", 51251, 389);
+    FAIL_IF_NULL(p);
+
+    FLOW_INITIALIZE(&f);
+    f.flags |= FLOW_IPV4;
+    f.proto = IPPROTO_TCP;
+    f.protoctx = &ssn;
+    f.protomap = FlowGetProtoMapping(f.proto);
+    f.alproto = ALPROTO_LDAP;
+
+    p->flow = &f;
+    p->flags |= PKT_HAS_FLOW | PKT_STREAM_EST;
+    p->flowflags |= FLOW_PKT_TOSERVER;
+    p->flowflags |= FLOW_PKT_ESTABLISHED;
+    p->pcap_cnt = 1;
+
+    StreamTcpInitConfig(true);
+
+    DetectEngineCtx *de_ctx = DetectEngineCtxInit();
+    FAIL_IF_NULL(de_ctx);
+    de_ctx->mpm_matcher = mpm_default_matcher;
+    de_ctx->flags |= DE_QUIET;
+
+    Signature *s = DetectEngineAppendSig(de_ctx,
+            "alert ldap any any -> any any (ldap.request.search.filter.value; "
+            "dataset:isset,ldap-watch-users,type string; sid:1;)");
+    FAIL_IF_NULL(s);
+    s = DetectEngineAppendSig(de_ctx,
+            "alert ldap any any -> any any (ldap.request.search.filter.value; "
+            "dataset:isset,ldap-watch-admins,type string; sid:2;)");
+    FAIL_IF_NULL(s);
+
+    /* the sets are set up by the rules, fill them like a load file would */
+    Dataset *users = DatasetFind("ldap-watch-users", DATASET_TYPE_STRING);
+    FAIL_IF_NULL(users);
+    FAIL_IF(DatasetAdd(users, (const uint8_t *)"svc_backup", 10) != 1);
+    Dataset *admins = DatasetFind("ldap-watch-admins", DATASET_TYPE_STRING);
+    FAIL_IF_NULL(admins);
+    FAIL_IF(DatasetAdd(admins, (const uint8_t *)"administrator", 13) != 1);
+
+    SigGroupBuild(de_ctx);
+    DetectEngineThreadCtxInit(&tv, (void *)de_ctx, (void *)&det_ctx);
+
+    int r = AppLayerParserParse(
+            NULL, alp_tctx, &f, ALPROTO_LDAP, STREAM_TOSERVER, search_request, sizeof(search_request));
+    FAIL_IF(r != 0);
+    FAIL_IF_NULL(f.alstate);
+
+    SigMatchSignatures(&tv, de_ctx, det_ctx, p);
+
+    /* svc_backup is the second value of the filter */
+    FAIL_IF_NOT(PacketAlertCheck(p, 1));
+    FAIL_IF(PacketAlertCheck(p, 2));
+
+    AppLayerParserThreadCtxFree(alp_tctx);
+    DetectEngineThreadCtxDeinit(&tv, det_ctx);
+    DetectEngineCtxFree(de_ctx);
+    StreamTcpFreeConfig(true);
+    FLOW_DESTROY(&f);
+    UTHFreePacket(p);
+    PASS;
+}
+
+static void DetectDatasetRegisterTests(void)
+{
+    UtRegisterTest("DetectDatasetTest01", DetectDatasetTest01);
+}
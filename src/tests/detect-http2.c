@@ -159,3 +159,29 @@ void DetectHTTP2sizeUpdateRegisterTests(void)
 {
     UtRegisterTest("DetectHTTP2sizeUpdateParseTest01", DetectHTTP2sizeUpdateParseTest01);
 }
+
+/**
+ * \test signature with a valid and an invalid http2.stream_id value.
+ */
+
+static int DetectHTTP2streamIdParseTest01(void)
+{
+    DetectEngineCtx *de_ctx = DetectEngineCtxInit();
+    FAIL_IF_NULL(de_ctx);
+
+    Signature *sig = DetectEngineAppendSig(
+            de_ctx, "alert http2 any any -> any any (http2.stream_id:>1; sid:1; rev:1;)");
+    FAIL_IF_NULL(sig);
+
+    sig = DetectEngineAppendSig(
+            de_ctx, "alert http2 any any -> any any (http2.stream_id:odd; sid:2; rev:1;)");
+    FAIL_IF_NOT_NULL(sig);
+
+    DetectEngineCtxFree(de_ctx);
+    PASS;
+}
+
+void DetectHTTP2streamIdRegisterTests(void)
+{
+    UtRegisterTest("DetectHTTP2streamIdParseTest01", DetectHTTP2streamIdParseTest01);
+}
@@ -49,6 +49,7 @@
 
 #include "output.h"
 #include "output-json.h"
+#include "rust.h"
 
 // MSG_NOSIGNAL does not exists on OS X
 #ifdef OS_DARWIN
@@ -780,6 +781,32 @@ static TmEcode UnixManagerRulesetStatsCommand(json_t *cmd,
     SCReturnInt(retval);
 }
 
+static TmEcode UnixManagerAppLayerParserStatsCommand(json_t *cmd, json_t *server_msg, void *data)
+{
+    SCEnter();
+    SCJsonBuilder *jb = SCJbNewObject();
+    if (jb == NULL) {
+        json_object_set_new(server_msg, "message", json_string("memory allocation failure"));
+        SCReturnInt(TM_ECODE_FAILED);
+    }
+    if (!SCAppLayerParserStatsLog(jb) || !SCJbClose(jb)) {
+        SCJbFree(jb);
+        json_object_set_new(server_msg, "message", json_string("failed to log parser stats"));
+        SCReturnInt(TM_ECODE_FAILED);
+    }
+
+    /* the counters are serialized by rust, hand them over as a json object */
+    json_error_t error;
+    json_t *jdata = json_loadb((const char *)SCJbPtr(jb), SCJbLen(jb), 0, &error);
+    SCJbFree(jb);
+    if (jdata == NULL) {
+        json_object_set_new(server_msg, "message", json_string(error.text));
+        SCReturnInt(TM_ECODE_FAILED);
+    }
+    json_object_set_new(server_msg, "message", jdata);
+    SCReturnInt(TM_ECODE_OK);
+}
+
 #ifdef PROFILE_RULES
 static TmEcode UnixManagerRulesetProfileCommand(json_t *cmd, json_t *server_msg, void *data)
 {
@@ -1082,6 +1109,8 @@ int UnixManagerInit(void)
     UnixManagerRegisterCommand("capture-mode", UnixManagerCaptureModeCommand, &command, 0);
     UnixManagerRegisterCommand("conf-get", UnixManagerConfGetCommand, &command, UNIX_CMD_TAKE_ARGS);
     UnixManagerRegisterCommand("dump-counters", StatsOutputCounterSocket, NULL, 0);
+    UnixManagerRegisterCommand(
+            "app-layer-parser-stats", UnixManagerAppLayerParserStatsCommand, NULL, 0);
     UnixManagerRegisterCommand("reload-rules", UnixManagerReloadRules, NULL, 0);
     UnixManagerRegisterCommand("ruleset-reload-rules", UnixManagerReloadRules, NULL, 0);
     UnixManagerRegisterCommand("ruleset-reload-nonblocking", UnixManagerNonBlockingReloadRules, NULL, 0);